@@ -0,0 +1,25 @@
+/* C interface of prop-simulator, built with `cargo build --release --features ffi`. */
+#ifndef PROP_SIMULATOR_H
+#define PROP_SIMULATOR_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Run a simulation. `config_json` is a JSON SimulationConfig (same fields as the
+ * web API). Returns the JSON SimulationResult, or {"error": "...", "kind": "..."}
+ * where kind is "invalid_config", "data", "limit_exceeded" or "other".
+ * Release the returned string with prop_sim_free_string. */
+char *prop_sim_run(const char *config_json);
+
+/* Crate version string. Release with prop_sim_free_string. */
+char *prop_sim_version(void);
+
+/* Release a string returned by this library. */
+void prop_sim_free_string(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PROP_SIMULATOR_H */
@@ -1,22 +1,87 @@
 use std::error::Error;
-use clap::Parser;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap::parser::{ArgMatches, ValueSource};
 use env_logger::Env;
-use prop_simulator::simulator;
-use simulator::{SimulationConfig, run_simulation, plot_histogram};
+use chrono::{NaiveDate, NaiveTime};
+use prop_simulator::prelude::{
+    account_catalog, account_diagram, DiagramFormat, plot_gross_payout_histogram, plot_histogram, ConsistencyWindow, DrawdownType, PayoutCadence, TradeSampling, RngBackend, run_simulation, Bundle, ConfigFile, Currency, EndOfGame, Locale, MoneyFormatter,
+    RunMetadata, SavedResult, SimulationConfig, SimulationError, SimulationResult, StatDiff, recommend_plans, RecommendOptions, allocate_budget, AllocationOptions, AllocationUtility, CVAR_LEVEL,
+    rate_difficulty, AccountInfo, HoldingAction, HoldingRules, NewsAction, NewsBlackout, Insurance, InsuranceCoverage, Convergence, ConvergenceMetric, EvalTimeLimit, CustomAccountSpec, ProfitSplit, ProfitableDays, measure_throughput, custom_account_factory, register_account, ExpiryAction, StatementPick, account_statement, comparison_configs, run_simulations_with, IterationSummary, SimulationHandle, anonymize_csv, OutcomeReweight, export_iterations, ExportFormat, resolve_account_type,
+};
+use serde::Serialize;
+use std::str::FromStr;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, IsTerminal, LineWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    simulate: SimulateArgs,
+}
+
+#[derive(Subcommand, Debug)]
+#[allow(clippy::large_enum_variant)] // parsed once at startup
+enum Command {
+    /// Run a simulation (the default when no subcommand is given)
+    Simulate(SimulateArgs),
+    /// Compare two saved results with confidence intervals on the differences
+    Diff(DiffArgs),
+    /// List supported accounts and their rule parameters
+    Accounts(AccountsArgs),
+    /// Re-execute a simulation bundle and check that it reproduces the bundled results
+    LoadBundle(LoadBundleArgs),
+    /// Rank account types and contract sizes within a monthly budget by risk-adjusted EV
+    Recommend(RecommendArgs),
+    /// Rank ways to split a monthly budget across several accounts by EV, payout probability or CVaR
+    Allocate(AllocateArgs),
+    /// Write a copy of a trade CSV with shifted dates and scaled P&L that can be shared publicly
+    Anonymize(AnonymizeArgs),
+    /// List saved results with their name and tags, optionally only those with given tags
+    Results(ResultsArgs),
+}
+
+#[derive(Args, Debug)]
+struct SimulateArgs {
     #[arg(short = 'f', long)]
     csv_file: Option<String>,
+    /// Exclude the first N trades of the CSV (e.g. a strategy development period) and compare with including them
+    #[arg(long)]
+    burn_in_trades: Option<usize>,
+    /// Exclude the first N trading days of the CSV and compare with including them
+    #[arg(long)]
+    burn_in_days: Option<usize>,
     #[arg(short, long, default_value_t = 10000)]
     iterations: usize,
+    /// Run as many iterations as fit in this time (e.g. "30s", "2m"), measured by a short calibration run
+    #[arg(long, value_parser = parse_duration, conflicts_with = "iterations")]
+    time_budget: Option<Duration>,
+    /// Measure the iterations per second with a short calibration run and report the expected runtime first
+    #[arg(long, default_value_t = false)]
+    estimate_runtime: bool,
     #[arg(short = 't', long)]
     max_trades_per_day: Option<u64>,
     #[arg(short = 'p', long)]
     daily_profit_target: Option<f64>,
     #[arg(short = 's', long)]
     daily_stop_loss: Option<f64>,
+    /// Personal daily stop as a percentage of the prior day's closing balance (requires --account-size)
+    #[arg(long)]
+    daily_stop_loss_percent: Option<f64>,
+    /// Nominal starting balance of the account, used by percentage-based loss limits
+    #[arg(long)]
+    account_size: Option<f64>,
+    /// Contracts the trades were taken with; accounts with a scaling plan scale them down
+    #[arg(long)]
+    traded_contracts: Option<u32>,
     #[arg(short = 'a', long)]
     avg_trades_per_day: Option<f64>,
     #[arg(long)]
@@ -29,51 +94,1421 @@ struct Cli {
     max_simulation_days: u64,
     #[arg(short = 'm', long, default_value_t = 12)]
     max_payouts: u8,
+    /// Keep trading and withdrawing after max payouts, reporting it as a milestone instead of ending the run
+    #[arg(long, default_value_t = false)]
+    continue_after_max_payouts: bool,
+    /// Account to simulate as company:account_type, e.g. "ftt:GT", "topstep:100k" or "topstep 50k"
     #[arg(short = 'c', long, default_value_t = String::from("ftt:GT"))]
     account_type: String,
+    /// JSON or TOML file with the rules of an account that is not built in, simulated instead of --account-type
+    #[arg(long)]
+    custom_account: Option<String>,
+    /// Override how the account's drawdown trails (options: "eod", "intraday", "static")
+    #[arg(long, value_parser = DrawdownType::from_str)]
+    drawdown_type: Option<DrawdownType>,
+    /// Override how often the firm pays out (options: "daily", "weekly", "biweekly", "monthly")
+    #[arg(long, value_parser = PayoutCadence::from_str)]
+    payout_cadence: Option<PayoutCadence>,
+    /// Trading days between requesting a payout and receiving it (default: the account's own, same day for built-in accounts)
+    #[arg(long)]
+    payout_delay_days: Option<u64>,
+    /// Chance between 0 and 1 that the firm denies each requested payout (default: the account's own, never for built-in accounts)
+    #[arg(long)]
+    payout_denial_probability: Option<f64>,
+    /// Require this many profitable days within each payout period (see --profitable-day-min)
+    #[arg(long)]
+    min_profitable_days: Option<u32>,
+    /// Smallest P&L of a profitable day for --min-profitable-days, or for the profitable days
+    /// of the config file
+    #[arg(long, default_value_t = 0.0)]
+    profitable_day_min: f64,
+    /// Override the percentage of each payout you keep, e.g. "80", or "100,90@10000" for all of
+    /// the first 10,000 paid out and 90% after
+    #[arg(long, value_parser = ProfitSplit::from_str)]
+    profit_split: Option<ProfitSplit>,
+    /// How each day's trades are drawn (options: "independent", "markov" for win/loss streaks within a day,
+    /// "block" for runs of consecutive trades, "day" or "historical" for whole historical days)
+    #[arg(long, alias = "day-sampling", value_parser = TradeSampling::from_str, default_value = "independent")]
+    trade_sampling: TradeSampling,
+    /// Consecutive trades per block of --trade-sampling block (default 5)
+    #[arg(long)]
+    block_length: Option<usize>,
+    /// Reject trades held past the session close (needs exit times in a fourth CSV column)
+    #[arg(long, default_value_t = false)]
+    no_overnight: bool,
+    /// Reject trades held over a weekend (needs exit times in a fourth CSV column)
+    #[arg(long, default_value_t = false)]
+    no_weekend: bool,
+    /// What to do with trades breaking --no-overnight/--no-weekend (options: "fail", "exclude", "truncate")
+    #[arg(long, value_parser = HoldingAction::from_str, default_value = "fail")]
+    holding_action: HoldingAction,
+    /// Session close for --no-overnight, in the time zone of the CSV timestamps
+    #[arg(long, default_value = "16:00:00")]
+    session_close: NaiveTime,
+    /// Percentage of a truncated trade's absolute return deducted for closing it at the session close
+    #[arg(long, default_value_t = 0.0)]
+    truncate_haircut_percent: f64,
+    /// Only resample CSV trades matching this expression over the CSV columns, dow, hour and date (e.g. 'setup == "ORB" && dow != "Fri"')
+    #[arg(long)]
+    trade_filter: Option<String>,
+    /// Change the chance of resampling trades by outcome, repeatable (e.g. "top1%=0.5", "bottom5%=2", "r3..=0" for 3R winners and up)
+    #[arg(long = "reweight", value_parser = OutcomeReweight::from_str)]
+    outcome_reweighting: Vec<OutcomeReweight>,
+    /// Random number generator (options: "std", "small" for speed, "chacha" for cryptographic quality, "thread" unseeded)
+    #[arg(long, value_parser = RngBackend::from_str, default_value = "std")]
+    rng_backend: RngBackend,
     #[arg(short = 'x', long, default_value_t = 1.0)]
     multiplier: f64,
     #[arg(long, default_value_t = false)]
     histogram: bool,
     #[arg(long, default_value = "final_balances_histogram.png")]
     histogram_file: String,
+    /// Write the outcome of every iteration (final balance, end state, length, payouts) to FILE for analysis elsewhere
+    #[arg(long = "export-results", value_name = "FILE")]
+    output_file: Option<String>,
+    /// Format of --export-results (options: csv, json); by default that of the file extension
+    #[arg(long = "export-format", value_parser = ExportFormat::from_str, requires = "output_file")]
+    output_format: Option<ExportFormat>,
+    /// Write a day by day account statement of one run: an iteration number, "typical" (median final balance), "unlucky" (10th percentile) or "lucky" (90th percentile)
+    #[arg(long, value_parser = StatementPick::from_str)]
+    statement: Option<StatementPick>,
+    /// File for --statement, HTML when it ends in .html, CSV otherwise
+    #[arg(long, default_value = "account_statement.csv")]
+    statement_file: String,
     #[arg(long)]
     round_trip_cost: Option<f64>,
-    /// Condition aggregate statistics based on end state (options: "Busted", "TimeOut", "MaxPayouts", "All")
+    /// Fixed fee charged on each payout (wire/processor fee)
+    #[arg(long)]
+    payout_fee_fixed: Option<f64>,
+    /// Percentage of each payout charged as a fee (e.g. 2.5 for 2.5%)
+    #[arg(long)]
+    payout_fee_percent: Option<f64>,
+    /// Fraction (0-1) of the withdrawable balance credited as a final payout when a run times out
+    #[arg(long)]
+    terminal_payout_fraction: Option<f64>,
+    /// Monthly platform or data feed cost in USD, paid outside the firm (e.g. 39 for a data subscription)
+    #[arg(long)]
+    platform_fee_monthly: Option<f64>,
+    /// Only pay the platform fee in months the account is funded
+    #[arg(long)]
+    platform_fee_funded_only: bool,
+    /// Personal money available for account purchases and fees; runs that cannot pay a fee end as OutOfFunds
+    #[arg(long)]
+    bankroll: Option<f64>,
+    /// Lowest balance the bankroll may be drawn down to (default 0)
+    #[arg(long)]
+    bankroll_floor: Option<f64>,
+    /// Price in USD of an insurance or reset-pass add-on that replaces covered busts with a new evaluation
+    #[arg(long)]
+    insurance_cost: Option<f64>,
+    /// Busts the insurance replaces
+    #[arg(long, default_value_t = 1)]
+    insurance_replacements: u32,
+    /// Busts the insurance covers (options: "evaluation", "funded", "any")
+    #[arg(long, value_parser = InsuranceCoverage::from_str, default_value = "evaluation")]
+    insurance_covers: InsuranceCoverage,
+    /// Price in USD of each replacement, e.g. a discounted reset
+    #[arg(long, default_value_t = 0.0)]
+    insurance_replacement_fee: f64,
+    /// Calendar days allowed to pass the evaluation; evaluations that run out of time are reset or end the run
+    #[arg(long)]
+    eval_time_limit_days: Option<u64>,
+    /// Paid extensions of the evaluation time limit
+    #[arg(long, default_value_t = 0)]
+    eval_extensions: u32,
+    /// Calendar days each extension adds
+    #[arg(long, default_value_t = 30)]
+    eval_extension_days: u64,
+    /// Price in USD of each extension
+    #[arg(long, default_value_t = 0.0)]
+    eval_extension_cost: f64,
+    /// What happens to an evaluation out of time and extensions (options: "reset", "end")
+    #[arg(long, value_parser = ExpiryAction::from_str, default_value = "reset")]
+    eval_expiry: ExpiryAction,
+    /// Price in USD of the new evaluation bought on expiry (default: the account price)
+    #[arg(long)]
+    eval_reset_cost: Option<f64>,
+    /// Buy up to this many new evaluations after busts, ending the run only when they are used up
+    #[arg(long)]
+    resets_allowed: Option<u8>,
+    /// Price in USD of each reset (default: the account price)
+    #[arg(long, requires = "resets_allowed")]
+    reset_cost: Option<f64>,
+    /// Date the account was bought (YYYY-MM-DD); the platform fee is billed monthly from it
+    #[arg(long)]
+    purchase_date: Option<NaiveDate>,
+    /// Price actually paid for the account in USD (e.g. with a promo code) instead of its list price
+    #[arg(long)]
+    purchase_price: Option<f64>,
+    /// High-impact news dates (YYYY-MM-DD), comma separated; needs --purchase-date
+    #[arg(long, value_delimiter = ',')]
+    news_dates: Vec<NaiveDate>,
+    /// Treat FOMC decision days and NFP release days as news days; needs --purchase-date
+    #[arg(long, default_value_t = false)]
+    news_calendar: bool,
+    /// What happens on news days (options: "sit_out", "inflate_variance")
+    #[arg(long, value_parser = NewsAction::from_str, default_value = "sit_out")]
+    news_action: NewsAction,
+    /// Factor the variance of news day trades is multiplied by with --news-action inflate_variance
+    #[arg(long, default_value_t = 2.0)]
+    news_variance_factor: f64,
+    /// Daily conversion rates (report currency per USD) by simulation day, comma separated
+    #[arg(long, value_delimiter = ',')]
+    conversion_rate_series: Option<Vec<f64>>,
+    /// FX fee charged on every conversion of account purchases and payouts (e.g. 1.5 for 1.5%)
+    #[arg(long)]
+    conversion_fee_percent: Option<f64>,
+    /// Monthly net income you need; reports how often each month meets it and the shortfall
+    #[arg(long)]
+    monthly_income_target: Option<f64>,
+    /// Report the state of all runs after this many trading days (repeatable)
+    #[arg(long = "snapshot-day")]
+    snapshot_days: Vec<u64>,
+    /// Report percentiles of the bank balance of all runs over time
+    #[arg(long)]
+    equity_curve: bool,
+    /// Gather the statistics run by run in constant memory, for millions of iterations
+    #[arg(long)]
+    streaming_stats: bool,
+    /// Stop once the standard error of the convergence metric is at most this, checked every 1,000 iterations; --iterations is then the most to run
+    #[arg(long)]
+    convergence_tolerance: Option<f64>,
+    /// Estimate the tolerance applies to (options: "mean_balance", "bust_percentage")
+    #[arg(long, value_parser = ConvergenceMetric::from_str, default_value = "mean_balance", requires = "convergence_tolerance")]
+    convergence_metric: ConvergenceMetric,
+    /// Percentiles of the final balance to report, comma separated (default 1,5,10,90,95,99)
+    #[arg(long, value_delimiter = ',')]
+    percentiles: Vec<f64>,
+    /// Confidence level in percent of the Value-at-Risk and Conditional VaR (default 95)
+    #[arg(long)]
+    var_confidence: Option<f64>,
+    /// Cohort mode: also simulate starting a new account every N trading days and report the combined monthly cash flow
+    #[arg(long)]
+    cohort_interval_days: Option<u64>,
+    /// Compare buying N evaluations at once (same trades, the first to pass is kept) with up to N sequential attempts
+    #[arg(long)]
+    parallel_evals: Option<u32>,
+    /// Hedge mode: also trade a second account of this type (e.g. at another firm) against the primary one
+    #[arg(long)]
+    hedge_account_type: Option<String>,
+    /// Correlation (-1 to 1) between the daily trades of the hedged accounts; -1 takes the opposite side of every trade
+    #[arg(long, allow_hyphen_values = true)]
+    hedge_correlation: Option<f64>,
+    /// Copy trading: also trade N copies of the account on the same trades and report their combined outcome
+    #[arg(long)]
+    copied_accounts: Option<u32>,
+    /// Share (0 to 1) of the days each copy trades the shared trades rather than its own draw; 1 copies every trade
+    #[arg(long)]
+    copy_correlation: Option<f64>,
+    /// Seed for the random number generator, making the run reproducible
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Condition aggregate statistics based on end state (options: "Busted", "TimeOut", "MaxPayouts", "OutOfFunds", "EvalExpired", "All"),
+    /// several separated by commas for their union, or prefixed with "!" to leave them out (e.g. "!Busted")
     #[arg(long, default_value = "All")]
     condition_end_state: String,
+    #[command(flatten)]
+    format: FormatArgs,
+    /// Suppress logging and human-readable output (errors still go to stderr)
+    #[arg(short = 'q', long, default_value_t = false)]
+    quiet: bool,
+    /// Output format for the results
+    #[arg(short = 'o', long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    /// Re-run the simulation whenever the input files change, printing the change in key statistics
+    #[arg(short = 'w', long, default_value_t = false)]
+    watch: bool,
+    /// TOML config file with [defaults] and named [scenarios.<name>] tables
+    #[arg(long)]
+    config: Option<String>,
+    /// Named scenario from the config file to run (repeatable)
+    #[arg(long)]
+    scenario: Vec<String>,
+    /// Run every scenario in the config file and print a comparison table
+    #[arg(long, default_value_t = false)]
+    all_scenarios: bool,
+    /// Run the same trades against each of these account types (comma separated) and print a comparison table
+    #[arg(long, value_delimiter = ',')]
+    compare_accounts: Vec<String>,
+    /// Save the result with its config and per-iteration data as JSON (input for `diff`)
+    #[arg(long)]
+    save_results: Option<String>,
+    /// Name of the run, kept in the result (scenarios default to their scenario name)
+    #[arg(long)]
+    name: Option<String>,
+    /// Label to find the run by with `results --tag`, repeatable
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Free-form notes kept in the result
+    #[arg(long)]
+    notes: Option<String>,
+    /// Write each iteration's outcome as a JSON line as soon as it completes, to FILE or to stdout ("-", the default)
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    stream_iterations: Option<String>,
+    /// Add every withdrawal, with the rule that set its amount, to the lines of --stream-iterations
+    #[arg(long, default_value_t = false, requires = "stream_iterations")]
+    audit_withdrawals: bool,
+    /// Package config, seed, version, input data and results into a zip archive to share the run
+    #[arg(long)]
+    bundle: Option<String>,
+    /// Store only the hash of the input data in the bundle, not the data itself
+    #[arg(long, default_value_t = false)]
+    bundle_without_data: bool,
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Saved result to compare against (from --save-results)
+    baseline: String,
+    /// Saved result to compare
+    candidate: String,
+    /// Output format for the differences
+    #[arg(short = 'o', long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    #[command(flatten)]
+    format: FormatArgs,
+}
+
+#[derive(Args, Debug)]
+struct LoadBundleArgs {
+    /// Bundle written with --bundle
+    bundle: String,
+    /// Input data for bundles created with --bundle-without-data (checked against the recorded hash)
+    #[arg(short = 'f', long)]
+    csv_file: Option<String>,
+    /// Output format for the results
+    #[arg(short = 'o', long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    #[command(flatten)]
+    format: FormatArgs,
+}
+
+#[derive(Args, Debug)]
+struct ResultsArgs {
+    /// Saved results (from --save-results) or directories holding them
+    #[arg(default_value = ".")]
+    paths: Vec<String>,
+    /// Only list results carrying this tag, repeatable (all must match)
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Output format of the list
+    #[arg(short = 'o', long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    #[command(flatten)]
+    format: FormatArgs,
+}
+
+#[derive(Args, Debug)]
+struct AnonymizeArgs {
+    /// Trade CSV to anonymize
+    csv_file: String,
+    /// Where to write the anonymized CSV
+    #[arg(short = 'o', long)]
+    output: String,
+    /// Seed for the date shift and P&L scale (random if not given)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Multiplier used with the original data, to print the one that reproduces its results
+    #[arg(long, default_value_t = 1.0)]
+    multiplier: f64,
+    /// Drop the metadata columns after the exit time (they are copied unchanged otherwise)
+    #[arg(long, default_value_t = false)]
+    drop_metadata: bool,
+}
+
+#[derive(Args, Debug)]
+struct AccountsArgs {
+    /// Only list accounts of this company (e.g. "ftt", "topstep")
+    #[arg(long)]
+    company: Option<String>,
+    /// Rate how hard each account is by simulating a reference strategy on it (takes a few seconds)
+    #[arg(long, default_value_t = false)]
+    difficulty: bool,
+    /// Print the state diagram of this account (e.g. "topstep:Fifty") instead of the catalog
+    #[arg(long, conflicts_with_all = ["company", "difficulty"])]
+    diagram: Option<String>,
+    /// Language of --diagram (options: "mermaid", "dot")
+    #[arg(long, value_parser = DiagramFormat::from_str, default_value = "mermaid", requires = "diagram")]
+    diagram_format: DiagramFormat,
+    /// JSON or TOML file with the rules of an account that is not built in, listed as custom:<name>
+    #[arg(long)]
+    custom_account: Option<String>,
+    /// Output format for the catalog
+    #[arg(short = 'o', long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    #[command(flatten)]
+    format: FormatArgs,
+}
+
+#[derive(Args, Debug)]
+struct RecommendArgs {
+    /// Most you can spend per month on the account purchase and platform fee (USD)
+    #[arg(long)]
+    monthly_budget: f64,
+    /// Contract counts to try, comma separated; each scales --multiplier
+    #[arg(long, value_delimiter = ',', default_value = "1,2,3")]
+    contracts: Vec<u32>,
+    /// Only consider accounts of this company (e.g. "ftt", "topstep")
+    #[arg(long)]
+    company: Option<String>,
+    /// Number of best plans to report in full
+    #[arg(long, default_value_t = 3)]
+    top: usize,
+    /// Trade data and simulation settings shared by all plans (the account type is ignored)
+    #[command(flatten)]
+    simulate: SimulateArgs,
+}
+
+#[derive(Args, Debug)]
+struct AllocateArgs {
+    /// Most you can spend per month on account purchases and platform fees (USD)
+    #[arg(long)]
+    monthly_budget: f64,
+    /// What to rank allocations by (options: "ev", "any_payout", "cvar")
+    #[arg(long, value_parser = AllocationUtility::from_str, default_value = "ev")]
+    utility: AllocationUtility,
+    /// Most accounts held at once
+    #[arg(long, default_value_t = 4)]
+    max_accounts: u32,
+    /// Only consider accounts of this company (e.g. "ftt", "topstep")
+    #[arg(long)]
+    company: Option<String>,
+    /// Number of best allocations to list
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    /// Trade data and simulation settings shared by all accounts (the account type is ignored)
+    #[command(flatten)]
+    simulate: SimulateArgs,
+}
+
+#[derive(Args, Debug)]
+struct FormatArgs {
+    /// Locale used to format money in the output (e.g. "en-US", "de-DE", "fr-FR")
+    #[arg(long, default_value = "en-US")]
+    locale: String,
+    /// Currency to report results in (options: "USD", "EUR", "GBP", "CHF")
+    #[arg(long, default_value = "USD")]
+    currency: String,
+    /// Units of the report currency per USD, used to convert costs, fees and payouts
+    #[arg(long)]
+    conversion_rate: Option<f64>,
+}
+
+impl FormatArgs {
+    fn money_formatter(&self) -> Result<MoneyFormatter, SimulationError> {
+        self.money_formatter_or(None)
+    }
+
+    // `fallback_rate` converts amounts when no --conversion-rate is given
+    fn money_formatter_or(&self, fallback_rate: Option<f64>) -> Result<MoneyFormatter, SimulationError> {
+        let invalid = |e: &str| SimulationError::InvalidConfig(e.to_string());
+        MoneyFormatter::new(
+            Locale::from_str(&self.locale).map_err(invalid)?,
+            Currency::from_str(&self.currency).map_err(invalid)?,
+            self.conversion_rate.or(fallback_rate),
+        )
+        .map_err(invalid)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+// Exit codes returned by the CLI on error (success exits with 0)
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_INVALID_CONFIG: i32 = 2;
+pub const EXIT_DATA_ERROR: i32 = 3;
+pub const EXIT_LIMIT_EXCEEDED: i32 = 4;
+pub const EXIT_CANCELLED: i32 = 5;
+
+/// Map an error returned by `main` to the process exit code
+pub fn exit_code(err: &(dyn Error + 'static)) -> i32 {
+    match err.downcast_ref::<SimulationError>() {
+        Some(SimulationError::InvalidConfig(_)) => EXIT_INVALID_CONFIG,
+        Some(SimulationError::Data(_)) => EXIT_DATA_ERROR,
+        Some(SimulationError::LimitExceeded(_)) => EXIT_LIMIT_EXCEEDED,
+        Some(SimulationError::Cancelled) => EXIT_CANCELLED,
+        None => EXIT_FAILURE,
+    }
 }
 
 // src/main.rs
 
 pub fn main() -> Result<(), Box<dyn Error>> {
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
 
-    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Simulate(args)) => {
+            let sub_matches = matches.subcommand_matches("simulate").unwrap_or(&matches);
+            simulate(&args, sub_matches)
+        }
+        Some(Command::Diff(args)) => diff(&args),
+        Some(Command::Accounts(args)) => accounts(&args),
+        Some(Command::LoadBundle(args)) => load_bundle(&args),
+        Some(Command::Recommend(args)) => recommend(&args),
+        Some(Command::Allocate(args)) => allocate(&args),
+        Some(Command::Anonymize(args)) => anonymize(&args),
+        Some(Command::Results(args)) => list_results(&args),
+        None => simulate(&cli.simulate, &matches),
+    }
+}
+
+fn simulate(args: &SimulateArgs, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let default_filter = if args.quiet { "error" } else { "info" };
+    env_logger::Builder::from_env(Env::default().default_filter_or(default_filter)).init();
+
+    // Amounts left in USD are shown at the first rate of a series
+    let first_rate = args.conversion_rate_series.as_ref().and_then(|series| series.first().copied());
+    let money = args.format.money_formatter_or(first_rate)?;
+
+    if args.watch {
+        return watch(args, matches, &money);
+    }
+
+    let mut runs = resolve_runs(args, matches)?;
+    // The statement replays its iteration, which needs the seed of the run
+    if args.statement.is_some() {
+        for (_, config) in runs.iter_mut() {
+            config.seed = Some(config.seed.unwrap_or_else(rand::random));
+        }
+    }
+    let bundle_path = match &args.bundle {
+        Some(path) => path,
+        None => {
+            run_and_report(args, &money, runs)?;
+            return Ok(());
+        }
+    };
+
+    if runs.len() > 1 {
+        return Err(SimulationError::InvalidConfig("--bundle requires a single run, select one scenario".into()).into());
+    }
+    // A bundle is only reproducible with a known seed
+    let config = &mut runs[0].1;
+    config.seed = Some(config.seed.unwrap_or_else(rand::random));
+    let config = config.clone();
+
+    let (_, result) = run_and_report(args, &money, runs)?.remove(0);
+    Bundle::new(config, result, !args.bundle_without_data)?.save(bundle_path)?;
+    if !args.quiet && args.output == OutputFormat::Text {
+        println!("Bundle saved to {}", bundle_path);
+    }
+    Ok(())
+}
 
-    let config = SimulationConfig {
-        csv_file: cli.csv_file,
+fn base_config(args: &SimulateArgs) -> Result<SimulationConfig, SimulationError> {
+    Ok(SimulationConfig {
+        csv_file: args.csv_file.clone(),
         csv_data: None,
-        iterations: cli.iterations,
-        max_trades_per_day: cli.max_trades_per_day,
-        daily_profit_target: cli.daily_profit_target,
-        daily_stop_loss: cli.daily_stop_loss,
-        avg_trades_per_day: cli.avg_trades_per_day,
-        round_trip_cost: cli.round_trip_cost,
-        stop_loss: cli.stop_loss,
-        take_profit: cli.take_profit,
-        win_percentage: cli.win_percentage,
-        max_simulation_days: cli.max_simulation_days,
-        max_payouts: cli.max_payouts,
-        account_type: cli.account_type,
-        multiplier: cli.multiplier,
-        histogram: cli.histogram,
-        histogram_file: Some(cli.histogram_file.clone()),
-        condition_end_state: cli.condition_end_state.clone(),
+        iterations: args.iterations,
+        max_trades_per_day: args.max_trades_per_day,
+        daily_profit_target: args.daily_profit_target,
+        daily_stop_loss: args.daily_stop_loss,
+        avg_trades_per_day: args.avg_trades_per_day,
+        round_trip_cost: args.round_trip_cost,
+        stop_loss: args.stop_loss,
+        take_profit: args.take_profit,
+        win_percentage: args.win_percentage,
+        max_simulation_days: args.max_simulation_days,
+        max_payouts: args.max_payouts,
+        account_type: args.account_type.clone(),
+        custom_account: args.custom_account.as_deref().map(CustomAccountSpec::load).transpose()?,
+        multiplier: args.multiplier,
+        histogram: args.histogram,
+        histogram_file: Some(args.histogram_file.clone()),
+        output_file: args.output_file.clone(),
+        output_format: args.output_format,
+        condition_end_state: args.condition_end_state.clone(),
+        histogram_bins: None,
+        balance_sample_size: None,
+        payout_fee_fixed: args.payout_fee_fixed,
+        payout_fee_percent: args.payout_fee_percent,
+        // Already validated by `money_formatter`
+        currency: Currency::from_str(&args.format.currency).ok(),
+        conversion_rate: args.format.conversion_rate,
+        conversion_rate_series: args.conversion_rate_series.clone(),
+        conversion_fee_percent: args.conversion_fee_percent,
+        monthly_income_target: args.monthly_income_target,
+        snapshot_days: Some(args.snapshot_days.clone()).filter(|days| !days.is_empty()),
+        equity_curve: args.equity_curve,
+        streaming_stats: args.streaming_stats,
+        convergence: args.convergence_tolerance.map(|tolerance| Convergence { tolerance, metric: args.convergence_metric }),
+        percentiles: Some(args.percentiles.clone()).filter(|percentiles| !percentiles.is_empty()),
+        var_confidence: args.var_confidence,
+        cohort_interval_days: args.cohort_interval_days,
+        parallel_evals: args.parallel_evals,
+        hedge_account_type: args.hedge_account_type.clone(),
+        hedge_correlation: args.hedge_correlation,
+        copied_accounts: args.copied_accounts,
+        copy_correlation: args.copy_correlation,
+        seed: args.seed,
+        // Only set through a config file
+        rule_timeline: None,
+        continue_after_max_payouts: args.continue_after_max_payouts,
+        audit_withdrawals: args.audit_withdrawals,
+        daily_stop_loss_percent: args.daily_stop_loss_percent,
+        account_size: args.account_size,
+        traded_contracts: args.traded_contracts,
+        drawdown_type: args.drawdown_type,
+        payout_cadence: args.payout_cadence,
+        payout_delay_days: args.payout_delay_days,
+        payout_denial_probability: args.payout_denial_probability,
+        min_profitable_days: args.min_profitable_days.map(|days| ProfitableDays { days, min_profit: args.profitable_day_min }),
+        profit_split: args.profit_split.clone(),
+        trade_sampling: args.trade_sampling,
+        block_length: args.block_length,
+        terminal_payout_fraction: args.terminal_payout_fraction,
+        platform_fee_monthly: args.platform_fee_monthly,
+        platform_fee_funded_only: args.platform_fee_funded_only,
+        bankroll: args.bankroll,
+        bankroll_floor: args.bankroll_floor,
+        insurance: args.insurance_cost.map(|cost| Insurance {
+            cost,
+            replacements: args.insurance_replacements,
+            covers: args.insurance_covers,
+            replacement_fee: args.insurance_replacement_fee,
+        }),
+        eval_time_limit: args.eval_time_limit_days.map(|calendar_days| EvalTimeLimit {
+            calendar_days,
+            extensions: args.eval_extensions,
+            extension_days: args.eval_extension_days,
+            extension_cost: args.eval_extension_cost,
+            on_expiry: args.eval_expiry,
+            reset_cost: args.eval_reset_cost,
+        }),
+        resets_allowed: args.resets_allowed,
+        reset_cost: args.reset_cost,
+        purchase_date: args.purchase_date,
+        purchase_price: args.purchase_price,
+        burn_in_trades: args.burn_in_trades,
+        burn_in_days: args.burn_in_days,
+        rng_backend: args.rng_backend,
+        name: args.name.clone(),
+        tags: args.tags.clone(),
+        notes: args.notes.clone(),
+        holding_rules: (args.no_overnight || args.no_weekend).then_some(HoldingRules {
+            no_overnight: args.no_overnight,
+            no_weekend: args.no_weekend,
+            action: args.holding_action,
+            session_close: args.session_close,
+            truncate_haircut_percent: args.truncate_haircut_percent,
+        }),
+        trade_filter: args.trade_filter.clone(),
+        outcome_reweighting: Some(args.outcome_reweighting.clone()).filter(|reweight| !reweight.is_empty()),
+        news_blackout: (args.news_calendar || !args.news_dates.is_empty()).then(|| NewsBlackout {
+            dates: args.news_dates.clone(),
+            builtin_calendar: args.news_calendar,
+            action: args.news_action,
+            variance_factor: args.news_variance_factor,
+        }),
+    })
+}
+
+// Build the labelled configs to run, sized to --time-budget when given
+fn resolve_runs(args: &SimulateArgs, matches: &ArgMatches) -> Result<Vec<(String, SimulationConfig)>, Box<dyn Error>> {
+    let mut runs = resolve_configs(args, matches)?;
+    if matches.value_source("profitable_day_min") == Some(ValueSource::CommandLine) {
+        // Also given alone, to change the minimum of the profitable days the config file sets
+        for (_, config) in &mut runs {
+            let profitable_days = config.min_profitable_days.as_mut().ok_or_else(|| {
+                SimulationError::InvalidConfig("--profitable-day-min requires --min-profitable-days or min_profitable_days".into())
+            })?;
+            profitable_days.min_profit = args.profitable_day_min;
+        }
+    }
+    if !args.compare_accounts.is_empty() {
+        // Label the runs by the registered names, whichever aliases were given
+        let account_types = args.compare_accounts.iter().map(|name| resolve_account_type(name)).collect::<Result<Vec<_>, _>>()?;
+        runs = compare_accounts(runs, &account_types);
+    }
+    tune_iterations(args, &mut runs)?;
+    Ok(runs)
+}
+
+// The plain CLI config, or the selected scenarios of the config file with options given
+// explicitly on the command line taking precedence
+fn resolve_configs(args: &SimulateArgs, matches: &ArgMatches) -> Result<Vec<(String, SimulationConfig)>, Box<dyn Error>> {
+    let base = base_config(args)?;
+    let config_path = match &args.config {
+        Some(path) => path,
+        None => {
+            if args.all_scenarios || !args.scenario.is_empty() {
+                return Err(SimulationError::InvalidConfig("--scenario and --all-scenarios require --config".into()).into());
+            }
+            return Ok(vec![(String::from("default"), base)]);
+        }
+    };
+
+    let config_file = ConfigFile::load(config_path)?;
+    let mut overrides = toml::Table::try_from(&base)?;
+    overrides.retain(|key, _| {
+        override_arg_ids(key)
+            .iter()
+            .any(|id| matches.value_source(id) == Some(ValueSource::CommandLine))
+    });
+
+    let names = if args.all_scenarios {
+        config_file.scenario_names()
+    } else {
+        args.scenario.clone()
+    };
+    if names.is_empty() {
+        return Ok(vec![(String::from("defaults"), config_file.resolve(&base, None, &overrides)?)]);
+    }
+
+    let mut runs = Vec::new();
+    for name in names {
+        let mut config = config_file.resolve(&base, Some(&name), &overrides)?;
+        // Keep one histogram per scenario instead of overwriting the same file
+        config.histogram_file = config.histogram_file.map(|file| suffixed_path(&file, &name));
+        config.output_file = config.output_file.map(|file| suffixed_path(&file, &name));
+        config.name.get_or_insert_with(|| name.clone());
+        runs.push((name, config));
+    }
+    Ok(runs)
+}
+
+// Args setting the config field `key`: those of fields built from several args, otherwise
+// the arg of the same id. `value_source` panics on an id that is no arg.
+fn override_arg_ids(key: &str) -> Vec<&str> {
+    match key {
+        "holding_rules" => vec!["no_overnight", "no_weekend", "holding_action"],
+        "news_blackout" => vec!["news_dates", "news_calendar", "news_action", "news_variance_factor"],
+        "insurance" => vec!["insurance_cost", "insurance_replacements", "insurance_covers", "insurance_replacement_fee"],
+        "eval_time_limit" => vec![
+            "eval_time_limit_days",
+            "eval_extensions",
+            "eval_extension_days",
+            "eval_extension_cost",
+            "eval_expiry",
+            "eval_reset_cost",
+        ],
+        "convergence" => vec!["convergence_tolerance", "convergence_metric"],
+        "min_profitable_days" => vec!["min_profitable_days", "profitable_day_min"],
+        _ => vec![key],
+    }
+}
+
+// One run per account type for each run, labelled with the account type (and the scenario
+// when there are several)
+fn compare_accounts(runs: Vec<(String, SimulationConfig)>, account_types: &[String]) -> Vec<(String, SimulationConfig)> {
+    let multiple = runs.len() > 1;
+    let mut compared = Vec::new();
+    for (name, config) in runs {
+        for mut config in comparison_configs(&config, account_types) {
+            let account_type = config.account_type.clone();
+            config.histogram_file = config.histogram_file.map(|file| suffixed_path(&file, &account_type.replace(':', "_")));
+            config.output_file = config.output_file.map(|file| suffixed_path(&file, &account_type.replace(':', "_")));
+            let label = if multiple { format!("{}/{}", name, account_type) } else { account_type };
+            compared.push((label, config));
+        }
+    }
+    compared
+}
+
+// Calibrate each run for --estimate-runtime and --time-budget. Runs execute in parallel
+// and share the CPU, so each gets an equal share of the budget.
+fn tune_iterations(args: &SimulateArgs, runs: &mut [(String, SimulationConfig)]) -> Result<(), Box<dyn Error>> {
+    if args.time_budget.is_none() && !args.estimate_runtime {
+        return Ok(());
+    }
+    let share = runs.len().max(1) as u32;
+    let multiple = runs.len() > 1;
+    for (name, config) in runs.iter_mut() {
+        let throughput = measure_throughput(config)?;
+        if let Some(budget) = args.time_budget {
+            config.iterations = throughput.iterations_for(budget / share);
+        }
+        if !args.quiet {
+            let label = if multiple { format!("{}: ", name) } else { String::new() };
+            eprintln!(
+                "{}{:.0} iterations/s, {} iterations expected to take {}",
+                label,
+                throughput.iterations_per_second,
+                config.iterations,
+                format_seconds(throughput.expected_seconds(config.iterations))
+            );
+        }
+    }
+    Ok(())
+}
+
+// "30s", "2m", "1h" or a number of seconds
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len()));
+    let unit_seconds = match unit.to_lowercase().as_str() {
+        "" | "s" | "sec" => 1.0,
+        "m" | "min" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("Unknown time unit '{}' (options: s, m, h)", unit)),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(Duration::from_secs_f64(value * unit_seconds)),
+        _ => Err(format!("Invalid duration '{}'", s)),
+    }
+}
+
+fn format_seconds(seconds: f64) -> String {
+    if seconds < 60.0 {
+        format!("{:.1}s", seconds)
+    } else {
+        let seconds = seconds.round() as u64;
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+// "dir/results.json" + "name" -> "dir/results_name.json"
+fn suffixed_path(file: &str, suffix: &str) -> String {
+    let path = Path::new(file);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+    let file_name = match path.extension().and_then(|s| s.to_str()) {
+        Some(ext) => format!("{}_{}.{}", stem, suffix, ext),
+        None => format!("{}_{}", stem, suffix),
+    };
+    path.with_file_name(file_name).to_string_lossy().into_owned()
+}
+
+fn run_and_report(
+    args: &SimulateArgs,
+    money: &MoneyFormatter,
+    runs: Vec<(String, SimulationConfig)>,
+) -> Result<Vec<(String, SimulationResult)>, Box<dyn Error>> {
+    let multiple = runs.len() > 1;
+    // Run all simulations in parallel, sharing the parsed trade data
+    let configs = runs.iter().map(|(_, config)| config.clone()).collect();
+    let show_progress = !args.quiet && io::stderr().is_terminal();
+    // Final balance of every iteration, to pick the run of --statement
+    let final_balances: Vec<Mutex<Vec<f64>>> = runs
+        .iter()
+        .map(|(_, config)| Mutex::new(vec![0.0; if args.statement.is_some() { config.iterations } else { 0 }]))
+        .collect();
+    let record = |run: usize, summary: &IterationSummary| {
+        if let Some(balance) = final_balances[run].lock().unwrap().get_mut(summary.iteration) {
+            *balance = summary.final_balance;
+        }
+    };
+    let simulated = with_progress(show_progress, |handle| match &args.stream_iterations {
+        Some(target) => {
+            let names: Vec<&str> = runs.iter().map(|(name, _)| name.as_str()).collect();
+            run_streaming(target, &names, configs, handle, &record)
+        }
+        None => run_simulations_with(configs, handle, &record),
+    })?;
+    let mut results = Vec::new();
+    for (((name, config), result), final_balances) in runs.into_iter().zip(simulated).zip(final_balances) {
+        let histogram_file = config.histogram_file.clone().filter(|_| config.histogram);
+        let export = config.output_file.clone().map(|file| (file, config.output_format));
+        let condition_end_state = config.condition_end_state.clone();
+        let statement = match args.statement {
+            Some(pick) => {
+                let file = if multiple { suffixed_path(&args.statement_file, &name) } else { args.statement_file.clone() };
+                let iteration = pick
+                    .select(&final_balances.into_inner().unwrap())
+                    .ok_or_else(|| SimulationError::InvalidConfig(format!("--statement: no iteration {:?}", pick)))?;
+                account_statement(&config, iteration)?.save(&file)?;
+                Some((iteration, file))
+            }
+            None => None,
+        };
+
+        let result = match &args.save_results {
+            Some(path) => {
+                let path = if multiple { suffixed_path(path, &name) } else { path.clone() };
+                let saved = SavedResult::new(config, result);
+                saved.save(&path)?;
+                saved.result
+            }
+            None => result,
+        };
+
+        if !multiple && args.output == OutputFormat::Text && !args.quiet {
+            print_text_report(&condition_end_state, &money.for_currency(result.currency), &result);
+        }
+
+        // Handle histogram if requested
+        if let Some(histogram_file) = histogram_file {
+            plot_histogram(&result.final_balances, &histogram_file)?;
+            let gross_histogram_file = suffixed_path(&histogram_file, "gross");
+            plot_gross_payout_histogram(&result.final_gross_payouts, &gross_histogram_file)?;
+            if !args.quiet && args.output == OutputFormat::Text {
+                println!("Histogram saved to {} (gross payouts: {})", histogram_file, gross_histogram_file);
+            }
+        }
+        if let Some((file, format)) = export {
+            export_iterations(&result.iterations, &file, format)?;
+            if !args.quiet && args.output == OutputFormat::Text {
+                println!("Iteration results saved to {}", file);
+            }
+        }
+        if let Some((iteration, file)) = statement {
+            if !args.quiet && args.output == OutputFormat::Text {
+                println!("Statement of iteration {} saved to {}", iteration, file);
+            }
+        }
+        results.push((name, result));
+    }
+
+    match args.output {
+        OutputFormat::Json if multiple => {
+            let by_name: BTreeMap<&String, &SimulationResult> = results.iter().map(|(n, r)| (n, r)).collect();
+            println!("{}", serde_json::to_string_pretty(&by_name)?);
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&results[0].1)?),
+        OutputFormat::Text if multiple && !args.quiet => print_comparison_table(money, &results),
+        OutputFormat::Text => (),
+    }
+
+    Ok(results)
+}
+
+// Run `run` with a handle, showing the share of iterations completed on stderr meanwhile
+fn with_progress<T>(show: bool, run: impl FnOnce(&SimulationHandle) -> T) -> T {
+    let handle = SimulationHandle::new();
+    if !show {
+        return run(&handle);
+    }
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let progress_line = scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                let progress = handle.progress();
+                // Nothing to show while the trade data loads
+                if progress.total_iterations > 0 {
+                    eprint!(
+                        "\rSimulating: {:>3.0}% ({}/{} iterations)",
+                        progress.fraction() * 100.0,
+                        progress.completed_iterations,
+                        progress.total_iterations
+                    );
+                }
+                thread::park_timeout(Duration::from_millis(200));
+            }
+            eprint!("\r\x1b[K");
+        });
+        let result = run(&handle);
+        done.store(true, Ordering::Relaxed);
+        progress_line.thread().unpark();
+        result
+    })
+}
+
+// One line of --stream-iterations; lines of several scenarios carry the scenario name
+#[derive(Serialize)]
+struct StreamedIteration<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scenario: Option<&'a str>,
+    #[serde(flatten)]
+    summary: &'a IterationSummary,
+}
+
+// Run the configs, writing every iteration to `target` ("-" for stdout) as it completes
+// and passing it on to `on_iteration`
+fn run_streaming(
+    target: &str,
+    names: &[&str],
+    configs: Vec<SimulationConfig>,
+    handle: &SimulationHandle,
+    on_iteration: &(dyn Fn(usize, &IterationSummary) + Sync),
+) -> Result<Vec<SimulationResult>, Box<dyn Error>> {
+    let out: Box<dyn Write + Send> = if target == "-" {
+        Box::new(io::stdout())
+    } else {
+        Box::new(LineWriter::new(fs::File::create(target).map_err(|e| format!("{}: {}", target, e))?))
+    };
+    let out = Mutex::new(out);
+    let write_error: Mutex<Option<io::Error>> = Mutex::new(None);
+    let multiple = names.len() > 1;
+
+    let results = run_simulations_with(configs, handle, &|run, summary| {
+        on_iteration(run, summary);
+        let line = StreamedIteration { scenario: multiple.then(|| names[run]), summary };
+        let written = serde_json::to_string(&line)
+            .map_err(io::Error::from)
+            .and_then(|line| writeln!(out.lock().unwrap(), "{}", line));
+        if let Err(e) = written {
+            write_error.lock().unwrap().get_or_insert(e);
+        }
+    })?;
+    if let Some(e) = write_error.into_inner().unwrap() {
+        return Err(format!("{}: {}", target, e).into());
+    }
+    Ok(results)
+}
+
+fn print_comparison_table(money: &MoneyFormatter, results: &[(String, SimulationResult)]) {
+    let name_width = results.iter().map(|(n, _)| n.len()).max().unwrap_or(0).max("Scenario".len());
+    println!(
+        "\n{:<nw$}  {:>14}  {:>14}  {:>14}  {:>9}  {:>8}  {:>8}  {:>10}  {:>9}",
+        "Scenario", "Mean", "Median", "Std Dev", "Positive", "Busted", "TimeOut", "MaxPayouts", "Mean Days",
+        nw = name_width
+    );
+    for (name, result) in results {
+        let money = money.for_currency(result.currency);
+        let pct = |state: EndOfGame| result.end_state_percentages.get(&state).copied().unwrap_or(0.0);
+        println!(
+            "{:<nw$}  {:>14}  {:>14}  {:>14}  {:>8.2}%  {:>7.2}%  {:>7.2}%  {:>9.2}%  {:>9.2}",
+            name,
+            money.format(result.mean_balance),
+            money.format(result.median_balance),
+            money.format(result.std_dev),
+            result.positive_balance_percentage,
+            pct(EndOfGame::Busted),
+            pct(EndOfGame::TimeOut),
+            pct(EndOfGame::MaxPayouts),
+            result.mean_days,
+            nw = name_width
+        );
+    }
+}
+
+// Poll the watched files and re-run the simulation whenever one of them is modified
+fn watch(args: &SimulateArgs, matches: &ArgMatches, money: &MoneyFormatter) -> Result<(), Box<dyn Error>> {
+    let mut previous: HashMap<String, SimulationResult> = HashMap::new();
+    loop {
+        // Re-resolve every time so edits to the config file (including its csv_file) are picked up
+        let runs = resolve_runs(args, matches);
+        let mut watched: Vec<String> = args.config.iter().cloned().collect();
+        if let Ok(runs) = &runs {
+            watched.extend(runs.iter().filter_map(|(_, config)| config.csv_file.clone()));
+        }
+        watched.dedup();
+        if watched.is_empty() {
+            return Err(SimulationError::InvalidConfig("--watch requires an input file (--csv-file or --config) to watch".into()).into());
+        }
+
+        let modified_times = || -> Vec<Option<SystemTime>> {
+            watched
+                .iter()
+                .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+                .collect()
+        };
+        let last_seen = modified_times();
+
+        match runs.and_then(|runs| run_and_report(args, money, runs)) {
+            Ok(results) => {
+                for (name, result) in results {
+                    if let Some(prev) = previous.get(&name) {
+                        print_watch_diff(&name, &money.for_currency(result.currency), prev, &result);
+                    }
+                    previous.insert(name, result);
+                }
+            }
+            // Keep watching: the file may be mid-edit
+            Err(e) => eprintln!("Error: {}", e),
+        }
+        eprintln!("\nWatching {} for changes (Ctrl+C to stop)...", watched.join(", "));
+
+        while modified_times() == last_seen {
+            thread::sleep(Duration::from_millis(500));
+        }
+    }
+}
+
+fn diff(args: &DiffArgs) -> Result<(), Box<dyn Error>> {
+    let baseline = SavedResult::load(&args.baseline)?;
+    let candidate = SavedResult::load(&args.candidate)?;
+    let money = args.format.money_formatter()?.for_currency(baseline.result.currency);
+    let diff = baseline.diff(&candidate);
+
+    if args.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    println!("Baseline:  {} (version {})", args.baseline, baseline.crate_version);
+    println!("Candidate: {} (version {})", args.candidate, candidate.crate_version);
+    for (label, metadata) in [("Baseline", &baseline.result.metadata), ("Candidate", &candidate.result.metadata)] {
+        if !metadata.is_empty() {
+            println!("{:<10} {}", format!("{}:", label), metadata);
+        }
+    }
+    if baseline.final_balances.is_empty() || candidate.final_balances.is_empty() {
+        println!("Note: per-iteration data missing, confidence intervals are unavailable (save with --save-results)");
+    }
+    println!(
+        "\n{:<32}  {:>14}  {:>14}  {:>14}  {:>30}",
+        "Statistic", "Baseline", "Candidate", "Delta", "95% CI of Delta"
+    );
+
+    let money_row = |label: &str, stat: &StatDiff| {
+        let ci = stat
+            .ci95
+            .map(|(low, high)| format!("[{}, {}]", money.format(low), money.format(high)))
+            .unwrap_or_else(|| String::from("n/a"));
+        println!(
+            "{:<32}  {:>14}  {:>14}  {:>14}  {:>30}{}",
+            label,
+            money.format(stat.baseline),
+            money.format(stat.candidate),
+            money.format_signed(stat.delta),
+            ci,
+            if stat.is_significant() { " *" } else { "" }
+        );
+    };
+    let pct_row = |label: &str, stat: &StatDiff, unit: &str| {
+        let ci = stat
+            .ci95
+            .map(|(low, high)| format!("[{:+.2}, {:+.2}]", low, high))
+            .unwrap_or_else(|| String::from("n/a"));
+        println!(
+            "{:<32}  {:>13.2}{}  {:>13.2}{}  {:>+13.2}{}  {:>30}{}",
+            label, stat.baseline, unit, stat.candidate, unit, stat.delta, unit, ci,
+            if stat.is_significant() { " *" } else { "" }
+        );
     };
 
-    // Run the simulation
+    money_row("Mean Final Bank Balance", &diff.mean_balance);
+    money_row("Median Final Bank Balance", &diff.median_balance);
+    money_row("Standard Deviation", &diff.std_dev);
+    pct_row("Percent Positive", &diff.positive_balance_percentage, "%");
+    pct_row("Mean Simulation Length (days)", &diff.mean_days, " ");
+    for (end_state, stat) in &diff.end_state_percentages {
+        pct_row(&format!("{} Rate", end_state), stat, "%");
+    }
+    println!("\n* confidence interval excludes zero");
+
+    Ok(())
+}
+
+fn load_bundle(args: &LoadBundleArgs) -> Result<(), Box<dyn Error>> {
+    let bundle = Bundle::load(&args.bundle)?;
+    let config = bundle.replay_config(args.csv_file.as_deref())?;
+    let version = env!("CARGO_PKG_VERSION");
+    if bundle.manifest.crate_version != version {
+        eprintln!(
+            "Warning: bundle was created with version {}, running version {}",
+            bundle.manifest.crate_version, version
+        );
+    }
+
+    let condition_end_state = config.condition_end_state.clone();
     let result = run_simulation(config)?;
+    let reproduced = result.final_balances == bundle.saved.final_balances;
+
+    match args.output {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "reproduced": reproduced, "result": result }))?
+        ),
+        OutputFormat::Text => {
+            let money = args.format.money_formatter()?.for_currency(result.currency);
+            print_text_report(&condition_end_state, &money, &result);
+            println!("\nBundle: {} (version {}, seed {})", args.bundle, bundle.manifest.crate_version, bundle.manifest.seed);
+            if reproduced {
+                println!("Reproduced: all {} iterations match the bundled results", result.final_balances.len());
+            } else {
+                println!(
+                    "Not reproduced: mean balance {} (bundle {})",
+                    money.format(result.mean_balance),
+                    money.format(bundle.saved.result.mean_balance)
+                );
+            }
+        }
+    }
+
+    if !reproduced {
+        return Err(SimulationError::Data("results differ from the bundle".into()).into());
+    }
+    Ok(())
+}
+
+fn accounts(args: &AccountsArgs) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &args.custom_account {
+        let spec = CustomAccountSpec::load(path)?;
+        let factory = custom_account_factory(spec.clone())?;
+        register_account(&spec.account_type(), move || factory())?;
+    }
+    if let Some(account_type) = &args.diagram {
+        print!("{}", account_diagram(account_type, args.diagram_format)?);
+        return Ok(());
+    }
+    let money = args.format.money_formatter()?;
+    let mut catalog = account_catalog(args.company.as_deref());
+    if catalog.is_empty() {
+        return Err(SimulationError::InvalidConfig(format!("Unknown company '{}'", args.company.clone().unwrap_or_default())).into());
+    }
+    if args.difficulty {
+        rate_difficulty(&mut catalog)?;
+        // Easiest first
+        catalog.sort_by(|a, b| {
+            let score = |info: &AccountInfo| info.difficulty.as_ref().map_or(0.0, |d| d.score);
+            score(a).total_cmp(&score(b))
+        });
+    }
+
+    if args.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&catalog)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<18}  {:>10}  {:>10}  {:>11}  {:<17}  {:>11}  {:>13}  {:>11}  {:>8}  {:<8}  {:<42}  {:<22}  Qualifying Day",
+        "Account", "Difficulty", "Cost", "Funded Fee", "Drawdown Type", "Drawdown", "Profit Target", "Consistency", "Min Days", "Cadence", "Payout Cap", "Profit Split"
+    );
+    for info in &catalog {
+        println!(
+            "{:<18}  {:>10}  {:>10}  {:>11}  {:<17}  {:>11}  {:>13}  {:>11}  {:>8}  {:<8}  {:<42}  {:<22}  {}",
+            info.account_type,
+            info.difficulty.as_ref().map(|d| format!("{:.1}", d.score)).unwrap_or_else(|| String::from("-")),
+            money.format(info.cost),
+            money.format(info.funded_account_cost),
+            format!("{:?}", info.drawdown_type),
+            money.format(info.drawdown),
+            info.profit_target.map(|t| money.format(t)).unwrap_or_else(|| String::from("-")),
+            info.consistency_fraction
+                .map(|c| match info.consistency_window {
+                    ConsistencyWindow::Account => format!("{:.0}%", c * 100.0),
+                    ConsistencyWindow::SinceLastPayout => format!("{:.0}%/payout", c * 100.0),
+                    ConsistencyWindow::TrailingDays(days) => format!("{:.0}%/{}d", c * 100.0, days),
+                })
+                .unwrap_or_else(|| String::from("-")),
+            info.min_days_for_payout,
+            format!("{:?}", info.payout_cadence),
+            info.payout_cap,
+            info.profit_split.to_string(),
+            info.qualifying_day
+        );
+    }
+
+    Ok(())
+}
+
+fn anonymize(args: &AnonymizeArgs) -> Result<(), Box<dyn Error>> {
+    let data = fs::read(&args.csv_file).map_err(|e| format!("{}: {}", args.csv_file, e))?;
+    let anonymized = anonymize_csv(&data, args.seed, args.drop_metadata)
+        .map_err(|e| format!("{}: {}", args.csv_file, e))?;
+    fs::write(&args.output, &anonymized.csv).map_err(|e| format!("{}: {}", args.output, e))?;
+
+    println!("Wrote {} trades to {}", anonymized.trades, args.output);
+    println!("P&L scaled by {}, dates shifted by whole weeks", anonymized.scale);
+    if anonymized.metadata_dropped {
+        println!("Metadata columns dropped");
+    }
+    println!(
+        "Simulate it with --multiplier {} to reproduce the results of the original data",
+        anonymized.equivalent_multiplier(args.multiplier)
+    );
+    Ok(())
+}
+
+// A saved result found by `results`
+#[derive(Serialize)]
+struct ListedResult {
+    file: String,
+    #[serde(flatten)]
+    metadata: RunMetadata,
+    account_type: String,
+    iterations: Option<usize>,
+    mean_balance: f64,
+    currency: Currency,
+}
+
+fn list_results(args: &ResultsArgs) -> Result<(), Box<dyn Error>> {
+    let mut files = Vec::new();
+    for path in &args.paths {
+        if Path::new(path).is_dir() {
+            for entry in fs::read_dir(path).map_err(|e| format!("{}: {}", path, e))? {
+                let file = entry?.path();
+                if file.extension().is_some_and(|extension| extension == "json") {
+                    files.push(file.to_string_lossy().into_owned());
+                }
+            }
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+
+    // Other JSON files in the directories are not results and are passed over
+    let listed: Vec<ListedResult> = files
+        .into_iter()
+        .filter_map(|file| SavedResult::load(&file).ok().map(|saved| (file, saved)))
+        .filter(|(_, saved)| saved.result.metadata.has_tags(&args.tags))
+        .map(|(file, saved)| ListedResult {
+            file,
+            metadata: saved.result.metadata.clone(),
+            account_type: saved.result.account_type.clone(),
+            iterations: saved.config.as_ref().map(|config| config.iterations),
+            mean_balance: saved.result.mean_balance,
+            currency: saved.result.currency,
+        })
+        .collect();
+
+    if args.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&listed)?);
+        return Ok(());
+    }
+    if listed.is_empty() {
+        println!("No saved results found");
+        return Ok(());
+    }
+    let money = args.format.money_formatter()?;
+    println!("{:<32}  {:<24}  {:<24}  {:<18}  {:>10}  {:>14}", "File", "Name", "Tags", "Account", "Iterations", "Mean Balance");
+    for result in &listed {
+        println!(
+            "{:<32}  {:<24}  {:<24}  {:<18}  {:>10}  {:>14}",
+            result.file,
+            result.metadata.name.as_deref().unwrap_or("-"),
+            result.metadata.tags.join(", "),
+            result.account_type,
+            result.iterations.map_or_else(|| String::from("-"), |iterations| iterations.to_string()),
+            money.for_currency(result.currency).format(result.mean_balance)
+        );
+        if let Some(notes) = &result.metadata.notes {
+            println!("  {}", notes);
+        }
+    }
+    Ok(())
+}
+
+fn recommend(args: &RecommendArgs) -> Result<(), Box<dyn Error>> {
+    let default_filter = if args.simulate.quiet { "error" } else { "warn" };
+    env_logger::Builder::from_env(Env::default().default_filter_or(default_filter)).init();
+    let money = args.simulate.format.money_formatter()?;
+
+    let options = RecommendOptions {
+        monthly_budget: args.monthly_budget,
+        contracts: args.contracts.clone(),
+        company: args.company.clone(),
+        top: args.top,
+    };
+    let plans = recommend_plans(&base_config(&args.simulate)?, &options)?;
+
+    if args.simulate.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&plans)?);
+        return Ok(());
+    }
+    if args.simulate.quiet {
+        return Ok(());
+    }
+
+    println!(
+        "{:>4}  {:<18}  {:>9}  {:>12}  {:>14}  {:>14}  {:>14}  {:>10}  {:>8}  {:>13}",
+        "Rank", "Account", "Contracts", "Monthly Cost", "Mean", "Median", "Std Dev", "Positive", "Busted", "Risk-Adj. EV"
+    );
+    for (rank, plan) in plans.iter().enumerate() {
+        let balances = money.for_currency(plan.currency);
+        println!(
+            "{:>4}  {:<18}  {:>9}  {:>12}  {:>14}  {:>14}  {:>14}  {:>9.2}%  {:>7.2}%  {:>13.3}",
+            rank + 1,
+            plan.account_type,
+            plan.contracts,
+            money.format(plan.monthly_cost),
+            balances.format(plan.mean_balance),
+            balances.format(plan.median_balance),
+            balances.format(plan.std_dev),
+            plan.positive_balance_percentage,
+            plan.busted_percentage,
+            plan.risk_adjusted_ev
+        );
+    }
+
+    for (rank, plan) in plans.iter().enumerate() {
+        if let Some(result) = &plan.result {
+            println!("\n#{}: {} with {} contract(s)", rank + 1, plan.account_type, plan.contracts);
+            print_text_report(&args.simulate.condition_end_state, &money.for_currency(result.currency), result);
+        }
+    }
+    Ok(())
+}
+
+fn allocate(args: &AllocateArgs) -> Result<(), Box<dyn Error>> {
+    let default_filter = if args.simulate.quiet { "error" } else { "warn" };
+    env_logger::Builder::from_env(Env::default().default_filter_or(default_filter)).init();
+    let money = args.simulate.format.money_formatter()?;
+
+    let options = AllocationOptions {
+        monthly_budget: args.monthly_budget,
+        utility: args.utility,
+        max_accounts: args.max_accounts,
+        company: args.company.clone(),
+    };
+    let mut allocations = allocate_budget(&base_config(&args.simulate)?, &options)?;
+    allocations.truncate(args.top);
+
+    if args.simulate.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&allocations)?);
+        return Ok(());
+    }
+    if args.simulate.quiet {
+        return Ok(());
+    }
+
+    let cvar_label = format!("CVaR {:.0}%", CVAR_LEVEL * 100.0);
+    println!(
+        "{:>4}  {:<40}  {:>12}  {:>14}  {:>14}  {:>14}  {:>10}",
+        "Rank", "Accounts", "Monthly Cost", "Mean", "Median", cvar_label, "Any Payout"
+    );
+    for (rank, allocation) in allocations.iter().enumerate() {
+        let accounts: Vec<String> = allocation
+            .accounts
+            .iter()
+            .map(|account| format!("{}x {}", account.count, account.account_type))
+            .collect();
+        let balances = money.for_currency(allocation.currency);
+        println!(
+            "{:>4}  {:<40}  {:>12}  {:>14}  {:>14}  {:>14}  {:>9.2}%",
+            rank + 1,
+            accounts.join(" + "),
+            money.format(allocation.monthly_cost),
+            balances.format(allocation.mean_balance),
+            balances.format(allocation.median_balance),
+            balances.format(allocation.cvar),
+            allocation.any_payout_percentage
+        );
+    }
+    Ok(())
+}
+
+fn print_watch_diff(name: &str, money: &MoneyFormatter, prev: &SimulationResult, curr: &SimulationResult) {
+    println!("\nChange vs previous run ({}):", name);
+    println!("  Mean Final Bank Balance: {} ({})", money.format(curr.mean_balance), money.format_signed(curr.mean_balance - prev.mean_balance));
+    println!("  Median Final Bank Balance: {} ({})", money.format(curr.median_balance), money.format_signed(curr.median_balance - prev.median_balance));
+    println!("  Standard Deviation: {} ({})", money.format(curr.std_dev), money.format_signed(curr.std_dev - prev.std_dev));
+    println!(
+        "  Percent Positive: {:.2}% ({:+.2})",
+        curr.positive_balance_percentage,
+        curr.positive_balance_percentage - prev.positive_balance_percentage
+    );
+    println!("  Mean Simulation Length: {:.2} days ({:+.2})", curr.mean_days, curr.mean_days - prev.mean_days);
+    for end_state in EndOfGame::ALL {
+        let now = curr.end_state_percentages.get(&end_state).copied().unwrap_or(0.0);
+        let before = prev.end_state_percentages.get(&end_state).copied().unwrap_or(0.0);
+        println!("  {:?}: {:.2}% ({:+.2})", end_state, now, now - before);
+    }
+}
+
+fn print_text_report(condition_end_state: &str, money: &MoneyFormatter, result: &SimulationResult) {
+    if !result.metadata.is_empty() {
+        println!("\nRun: {}", result.metadata);
+        if let Some(notes) = &result.metadata.notes {
+            println!("Notes: {}", notes);
+        }
+    }
+    println!("\nAccount: {}", result.account_type);
+    if let Some(convergence) = &result.convergence {
+        let (metric, standard_error, tolerance) = match convergence.metric {
+            ConvergenceMetric::MeanBalance => {
+                ("mean balance", money.format(convergence.standard_error), money.format(convergence.tolerance))
+            }
+            ConvergenceMetric::BustPercentage => {
+                ("bust percentage", format!("{:.3}%", convergence.standard_error), format!("{:.3}%", convergence.tolerance))
+            }
+        };
+        println!(
+            "Iterations: {} ({}, standard error of the {} {} against a tolerance of {})",
+            convergence.iterations,
+            if convergence.converged { "converged" } else { "not converged" },
+            metric,
+            standard_error,
+            tolerance
+        );
+    }
 
     // Display the end state percentages
     println!("\nEnd State Percentages:");
@@ -81,24 +1516,417 @@ pub fn main() -> Result<(), Box<dyn Error>> {
         println!("  {:?}: {:.2}%", end_state, percentage);
     }
 
+    println!("\nStatistics by End State:");
+    println!(
+        "  {:<12}  {:>8}  {:>14}  {:>14}  {:>14}  {:>10}",
+        "End State", "Runs", "Mean", "Median", "Std Dev", "Mean Days"
+    );
+    for end_state in EndOfGame::ALL {
+        let Some(stats) = result.stats_by_end_state.get(&end_state) else {
+            continue;
+        };
+        println!(
+            "  {:<12}  {:>8}  {:>14}  {:>14}  {:>14}  {:>10.2}",
+            format!("{:?}", end_state),
+            stats.runs,
+            money.format(stats.mean_balance),
+            money.format(stats.median_balance),
+            money.format(stats.std_dev),
+            stats.mean_days
+        );
+        if !stats.percentiles.is_empty() {
+            let percentiles: Vec<String> = stats
+                .percentiles
+                .iter()
+                .map(|p| format!("P{}: {}", p.percentile, money.format(p.balance)))
+                .collect();
+            println!("  {:<12}  {}", "", percentiles.join(", "));
+        }
+    }
 
     // Display the results
-    println!("\nStatistics Conditioned on End State '{}':", cli.condition_end_state);
+    println!("\nStatistics Conditioned on End State '{}':", condition_end_state);
     println!("Percent Positive Final Bank Balance: {:.2}", result.positive_balance_percentage);
     println!("Mean Simulation Length: {:.2} days", result.mean_days);
-    println!("Median Final Bank Balance: {:.2}", result.median_balance);
-    println!("Mean Final Bank Balance: {:.2}", result.mean_balance);
-    println!("Standard Deviation of Final Bank Balances: {:.2}", result.std_dev);
-    println!("Mean Absolute Deviation: {:.2}", result.mad);
-    println!("Interquartile Range: {:.2}", result.iqr);
-    println!("Median Absolute Deviation: {:.2}", result.mad_median);
+    println!("Median Final Bank Balance: {}", money.format(result.median_balance));
+    println!("Mean Final Bank Balance: {}", money.format(result.mean_balance));
+    println!("Standard Deviation of Final Bank Balances: {}", money.format(result.std_dev));
+    println!("Mean Absolute Deviation: {}", money.format(result.mad));
+    println!("Interquartile Range: {}", money.format(result.iqr));
+    println!("Median Absolute Deviation: {}", money.format(result.mad_median));
+    if !result.percentiles.is_empty() {
+        println!("Final Bank Balance Percentiles:");
+        for p in &result.percentiles {
+            println!("  P{}: {}", p.percentile, money.format(p.balance));
+        }
+    }
+    let tail = &result.tail_risk;
+    println!("Value at Risk ({}%): {}", tail.confidence, money.format(tail.value_at_risk));
+    println!("Conditional Value at Risk ({}%): {}", tail.confidence, money.format(tail.conditional_value_at_risk));
 
-    // Handle histogram if requested
-    if cli.histogram {
-        plot_histogram(&result.final_balances, &cli.histogram_file)?;
-        println!("Histogram saved to {}", cli.histogram_file);
+    let gross = &result.gross_payouts;
+    println!("\nGross Payouts vs Net Bank Balance (per run):");
+    println!("  {:<16}  {:>14}  {:>14}", "", "Gross Payouts", "Net Balance");
+    for (label, gross, net) in [
+        ("Mean", gross.mean, result.mean_balance),
+        ("Median", gross.median, result.median_balance),
+        ("Std Dev", gross.std_dev, result.std_dev),
+    ] {
+        println!("  {:<16}  {:>14}  {:>14}", label, money.format(gross), money.format(net));
     }
+    println!(
+        "  {:<16}  {:>13.2}%  {:>13.2}%",
+        "Paid / Positive", gross.paid_percentage, result.positive_balance_percentage
+    );
 
-    Ok(())
+    // Empty when no run traded a period
+    if result.period_pnl.weekly.periods > 0 {
+        println!("\nTrading P&L per Period:");
+        println!(
+            "  {:<8}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}  {:>8}",
+            "", "Mean", "Volatility", "Median", "5th Pct", "Worst", "Mean Worst", "Losing"
+        );
+        for (label, stats) in [("Weekly", &result.period_pnl.weekly), ("Monthly", &result.period_pnl.monthly)] {
+            println!(
+                "  {:<8}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}  {:>7.2}%",
+                label,
+                money.format(stats.mean),
+                money.format(stats.volatility),
+                money.format(stats.median),
+                money.format(stats.p5),
+                money.format(stats.worst),
+                money.format(stats.mean_worst),
+                stats.losing_percentage
+            );
+        }
+    }
+
+    if result.worst_outcomes.worst_trade.runs > 0 {
+        println!("\nWorst per Run (what a run sits through):");
+        println!(
+            "  {:<8}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}",
+            "", "Mean", "Median", "10th Pct", "5th Pct", "1st Pct", "Worst"
+        );
+        for (label, stats) in [("Trade", &result.worst_outcomes.worst_trade), ("Day", &result.worst_outcomes.worst_day)] {
+            println!(
+                "  {:<8}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}",
+                label,
+                money.format(stats.mean),
+                money.format(stats.median),
+                money.format(stats.p10),
+                money.format(stats.p5),
+                money.format(stats.p1),
+                money.format(stats.worst)
+            );
+        }
+    }
+
+    let time_to_money = [("Funded", &result.time_to_money.funded), ("Payout", &result.time_to_money.first_payout)];
+    if time_to_money.iter().any(|(_, stats)| stats.is_some()) {
+        println!("\nDays Until Funded and First Payout:");
+        println!(
+            "  {:<8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}",
+            "", "Reached", "Mean", "10th Pct", "25th Pct", "Median", "75th Pct", "90th Pct"
+        );
+        for (label, stats) in time_to_money {
+            if let Some(stats) = stats {
+                println!(
+                    "  {:<8}  {:>7.2}%  {:>8.1}  {:>8.0}  {:>8.0}  {:>8.1}  {:>8.0}  {:>8.0}",
+                    label, stats.reached_percentage, stats.mean, stats.p10, stats.p25, stats.median, stats.p75, stats.p90
+                );
+            }
+        }
+    }
+
+    if let Some(holding) = &result.holding {
+        let action = match holding.action {
+            HoldingAction::Exclude => "excluded",
+            _ => "truncated at the session close",
+        };
+        println!(
+            "\nHolding Rules: {} trades held overnight and {} over a weekend were {}",
+            holding.overnight_trades, holding.weekend_trades, action
+        );
+        if holding.unchecked_trades > 0 {
+            println!("  {} trades had no exit time and were not checked", holding.unchecked_trades);
+        }
+    }
+
+    if let Some(filter) = &result.trade_filter {
+        println!(
+            "\nTrade Filter `{}`: {} trades kept, {} left out",
+            filter.expression, filter.kept_trades, filter.excluded_trades
+        );
+    }
+
+    if let Some(reweighting) = &result.outcome_reweighting {
+        println!("\nOutcome Reweighting:");
+        for bucket in &reweighting.buckets {
+            println!("  {}: {} trades weighted x{}", bucket.bucket, bucket.trades, bucket.weight);
+        }
+        if let Some(r_unit) = reweighting.r_unit {
+            println!("  1R (mean loss): {}", money.format(r_unit));
+        }
+        println!(
+            "  Mean Trade Return: {} (unweighted: {})",
+            money.format(reweighting.weighted_mean_return),
+            money.format(reweighting.mean_return)
+        );
+    }
+
+    if let Some(burn_in) = &result.burn_in {
+        println!(
+            "\nBurn-In Excluded: {} trades ({} remain in the pool)",
+            burn_in.excluded_trades, burn_in.remaining_trades
+        );
+        println!(
+            "  Mean Final Bank Balance: {} (included: {}, change {})",
+            money.format(result.mean_balance),
+            money.format(burn_in.included_mean_balance),
+            money.format(burn_in.mean_balance_change)
+        );
+        println!(
+            "  Percent Positive: {:.2} (included: {:.2}, change {:+.2})",
+            result.positive_balance_percentage,
+            burn_in.included_positive_balance_percentage,
+            burn_in.positive_balance_percentage_change
+        );
+    }
+    if let Some(chain) = &result.outcome_chain {
+        println!(
+            "Trade Outcome Chain: first trade wins {:.2}%, win after win {:.2}%, win after loss {:.2}%",
+            chain.first_win * 100.0, chain.win_after_win * 100.0, chain.win_after_loss * 100.0
+        );
+    }
+    if let Some(reached) = result.max_payouts_reached_percentage {
+        print!("Reached Max Payouts (trading continued): {:.2}%", reached);
+        match result.mean_days_to_max_payouts {
+            Some(days) => println!(", after {:.2} days on average", days),
+            None => println!(),
+        }
+    }
+
+    let ev = &result.ev_decomposition;
+    println!("\nWhere the Money Goes (mean per run):");
+    println!("  Gross Payouts: {}", money.format(ev.gross_payouts));
+    if ev.terminal_payouts != 0.0 {
+        println!("  Terminal Payouts (balance left at timeout): {}", money.format(ev.terminal_payouts));
+    }
+    for (label, amount) in [
+        ("Profit Split Retained by Firm", ev.profit_split),
+        ("Payout Fees", ev.payout_fees),
+        ("Currency Conversion Fees", ev.conversion_fees),
+        ("Evaluation Fees", ev.eval_fees),
+        ("Reset Fees", ev.reset_fees),
+        ("Activation/Monthly Fees", ev.activation_fees),
+        ("Platform/Data Fees", ev.platform_fees),
+        ("Insurance", ev.insurance_fees),
+    ] {
+        if amount != 0.0 {
+            println!("  {}: {}", label, money.format(-amount));
+        }
+    }
+    println!("  Net (Mean Final Bank Balance): {}", money.format(ev.net()));
+
+    let triggers = &result.rule_triggers;
+    println!("\nRule Triggers (per 100 days):");
+    for (label, rate) in [
+        ("Personal Daily Stop", triggers.daily_stop),
+        ("Personal Daily Target", triggers.daily_target),
+        ("Max Trades per Day", triggers.max_trades),
+        ("Firm Daily Loss Limit", triggers.firm_daily_loss_limit),
+        ("Firm Drawdown", triggers.max_drawdown),
+        ("News Days", triggers.news_days),
+        ("Trades Scaled Down", triggers.scaled_trades),
+        ("Payouts Denied", triggers.payout_denials),
+    ] {
+        println!("  {}: {:.2}", label, rate);
+    }
+    if let Some(soft) = &result.soft_breaches {
+        println!(
+            "  Soft Breaches (day ended, account kept): {:.2}; {:.2} per run on average, at most {}, in {:.2}% of runs",
+            triggers.soft_breaches, soft.mean_per_run, soft.max_per_run, soft.breached_percentage
+        );
+    }
+
+    for snapshot in &result.snapshots {
+        println!("\nState After Day {}:", snapshot.day);
+        println!("  In Evaluation: {:.2}%", snapshot.evaluation_percentage);
+        println!("  Funded: {:.2}%", snapshot.funded_percentage);
+        println!("  Busted: {:.2}%", snapshot.busted_percentage);
+        println!("  MaxPayouts: {:.2}%", snapshot.max_payouts_percentage);
+        println!("  TimeOut: {:.2}%", snapshot.timeout_percentage);
+        if let Some(balance) = snapshot.median_account_balance {
+            println!("  Median Account Balance (active runs): {}", money.format(balance));
+        }
+        println!("  Median Bank Balance: {}", money.format(snapshot.median_bank_balance));
+        println!("  Mean Bank Balance: {}", money.format(snapshot.mean_bank_balance));
+        println!("  Positive Bank Balance: {:.2}%", snapshot.positive_bank_percentage);
+    }
+
+    if let Some(curve) = &result.equity_curve {
+        println!("\nBank Balance Over Time (percentiles of all runs):");
+        println!("  {:>5} {:>14} {:>14} {:>14} {:>14} {:>14}", "Day", "P5", "P25", "P50", "P75", "P95");
+        let last = curve.days.len().saturating_sub(1);
+        // One row per month of 21 trading days, plus the last day
+        for i in (0..curve.days.len()).filter(|&i| i % 21 == 0 || i == last) {
+            println!(
+                "  {:>5} {:>14} {:>14} {:>14} {:>14} {:>14}",
+                curve.days[i],
+                money.format(curve.p5[i]),
+                money.format(curve.p25[i]),
+                money.format(curve.p50[i]),
+                money.format(curve.p75[i]),
+                money.format(curve.p95[i])
+            );
+        }
+    }
+
+    if let Some(cohort) = &result.cohort {
+        println!(
+            "\nCohort: new account every {} trading days ({} accounts per run)",
+            cohort.interval_days, cohort.accounts_per_run
+        );
+        println!("  {:<8}  {:>14}  {:>14}  {:>14}  {:>14}", "Month", "Mean", "Median", "10th Pct", "90th Pct");
+        for month in 0..cohort.mean_monthly_cash_flow.len() {
+            println!(
+                "  {:<8}  {:>14}  {:>14}  {:>14}  {:>14}",
+                month + 1,
+                money.format(cohort.mean_monthly_cash_flow[month]),
+                money.format(cohort.median_monthly_cash_flow[month]),
+                money.format(cohort.p10_monthly_cash_flow[month]),
+                money.format(cohort.p90_monthly_cash_flow[month])
+            );
+        }
+        println!("  Mean Total Cash Flow: {}", money.format(cohort.mean_total_cash_flow));
+        println!("  Positive Total Cash Flow: {:.2}%", cohort.positive_total_percentage);
+    }
+
+    if let Some(report) = &result.parallel_evals {
+        println!("\nBuying {} Evaluations at Once vs One After Another:", report.evals);
+        println!("  {:<12}  {:>8}  {:>14}  {:>14}  {:>14}", "Strategy", "Passed", "Eval Fees", "Days to Pass", "Mean Balance");
+        for (name, stats) in [("Parallel", &report.parallel), ("Sequential", &report.sequential)] {
+            println!(
+                "  {:<12}  {:>7.2}%  {:>14}  {:>14}  {:>14}",
+                name,
+                stats.pass_percentage,
+                money.format(stats.mean_eval_fees),
+                stats.mean_days_to_pass.map(|days| format!("{:.1}", days)).unwrap_or_else(|| String::from("-")),
+                money.format(stats.mean_final_balance)
+            );
+        }
+    }
+
+    if let Some(hedge) = &result.hedge {
+        println!("\nHedge with {} (trade correlation {:.2}):", hedge.hedge_account_type, hedge.correlation);
+        println!("  Mean Combined Final Bank Balance: {}", money.format(hedge.mean_combined_balance));
+        println!("  Median Combined Final Bank Balance: {}", money.format(hedge.median_combined_balance));
+        println!("  Positive Combined Balance: {:.2}%", hedge.positive_combined_percentage);
+        println!("  Mean Payouts: {}", money.format(hedge.mean_gross_payouts));
+        println!("  Mean Costs (purchases, fees, splits): {}", money.format(hedge.mean_costs));
+        println!(
+            "  Busted: primary {:.2}%, hedge {:.2}%, both {:.2}%",
+            hedge.primary_busted_percentage, hedge.hedge_busted_percentage, hedge.both_busted_percentage
+        );
+    }
+
+    if let Some(copies) = &result.copy_trading {
+        println!("\nCopy Trading {} Accounts (shared days {:.2}):", copies.accounts, copies.correlation);
+        println!("  Mean Combined Final Bank Balance: {}", money.format(copies.mean_combined_balance));
+        println!("  Median Combined Final Bank Balance: {}", money.format(copies.median_combined_balance));
+        println!(
+            "  Combined Final Bank Balance P10 / P90: {} / {}",
+            money.format(copies.p10_combined_balance),
+            money.format(copies.p90_combined_balance)
+        );
+        println!(
+            "  Standard Deviation: combined {}, single account {}",
+            money.format(copies.combined_balance_std_dev),
+            money.format(copies.account_balance_std_dev)
+        );
+        println!("  Positive Combined Balance: {:.2}%", copies.positive_combined_percentage);
+        println!("  Mean Lowest Combined Balance: {}", money.format(copies.mean_lowest_combined_balance));
+        println!("  Mean Payouts: {}", money.format(copies.mean_gross_payouts));
+        println!("  Mean Costs (purchases, fees, splits): {}", money.format(copies.mean_costs));
+        println!(
+            "  Busted: {:.2} accounts per run, all of them in {:.2}% of runs",
+            copies.mean_busted_accounts, copies.all_busted_percentage
+        );
+    }
+
+    if let Some(insurance) = &result.insurance {
+        println!("\nInsurance (costing {}):", money.format(insurance.cost));
+        println!(
+            "  Used in {:.2}% of runs ({:.2} replacements per run)",
+            insurance.used_percentage, insurance.mean_replacements_used
+        );
+        println!(
+            "  Mean Final Bank Balance: {} insured, {} uninsured ({})",
+            money.format(insurance.mean_balance_insured),
+            money.format(insurance.mean_balance_uninsured),
+            money.format(insurance.mean_balance_change)
+        );
+        println!(
+            "  Busted: {:.2}% insured, {:.2}% uninsured",
+            insurance.busted_percentage_insured, insurance.busted_percentage_uninsured
+        );
+    }
+
+    if let Some(time_limit) = &result.eval_time_limit {
+        println!("\nEvaluation Time Limit:");
+        println!(
+            "  Extended in {:.2}% of runs ({:.2} extensions per run)",
+            time_limit.extended_percentage, time_limit.mean_extensions
+        );
+        println!(
+            "  Expired in {:.2}% of runs ({:.2} expired evaluations per run)",
+            time_limit.expired_percentage, time_limit.mean_expiries
+        );
+    }
+
+    if let Some(resets) = &result.resets {
+        println!("\nResets (up to {} per run):", resets.resets_allowed);
+        println!("  Reset in {:.2}% of runs ({:.2} resets per run)", resets.reset_percentage, resets.mean_resets);
+        println!("  Mean Evaluations Bought: {:.2}", resets.mean_evaluations);
+        println!("  Busted with every reset used: {:.2}% of runs", resets.exhausted_percentage);
+    }
+
+    if let Some(buffer) = &result.drawdown_buffer {
+        println!("\nDrawdown Buffer in Surviving Runs ({} runs):", buffer.surviving_runs);
+        println!("  Mean Minimum Buffer: {}", money.format(buffer.mean_min_buffer));
+        println!("  Median Minimum Buffer: {}", money.format(buffer.median_min_buffer));
+        println!("  10th-90th Percentile Minimum Buffer: {} to {}", money.format(buffer.p10_min_buffer), money.format(buffer.p90_min_buffer));
+        if let Some(at_payout) = buffer.mean_buffer_at_first_payout {
+            println!("  Mean Buffer After First Payout: {}", money.format(at_payout));
+        }
+    }
+
+    if let Some(income) = &result.income {
+        println!("\nMonthly Income Target: {}", money.format(income.monthly_target));
+        println!("Months Meeting Target:");
+        for (month, percentage) in income.met_percentage_by_month.iter().enumerate() {
+            println!("  Month {:>2}: {:.2}%", month + 1, percentage);
+        }
+        println!("Target Met Every Month: {:.2}%", income.met_all_months_percentage);
+        println!("Mean Monthly Shortfall: {}", money.format(income.mean_shortfall));
+        println!("Median Monthly Shortfall: {}", money.format(income.median_shortfall));
+        println!("90th Percentile Monthly Shortfall: {}", money.format(income.p90_shortfall));
+        println!("Mean Total Shortfall: {}", money.format(income.mean_total_shortfall));
+    }
+
+    if let Some(stationary) = &result.stationary_income {
+        println!("\nLong-Run Income (a new account whenever one ends):");
+        println!("  Mean Monthly Income: {}", money.format(stationary.mean_monthly_income));
+        println!("  Monthly Standard Deviation: {}", money.format(stationary.monthly_std_dev));
+        println!(
+            "  90% Band: {} to {}",
+            money.format(stationary.p5_monthly_income),
+            money.format(stationary.p95_monthly_income)
+        );
+        println!(
+            "  Mean Account Lifetime: {:.2} days ({:.2} accounts per month)",
+            stationary.mean_account_lifetime_days, stationary.accounts_per_month
+        );
+    }
 }
 
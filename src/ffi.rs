@@ -0,0 +1,82 @@
+// src/ffi.rs
+//! C ABI for embedding the simulator (enabled with the `ffi` feature).
+//! Configs and results are exchanged as JSON strings; see `include/prop_simulator.h`.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic;
+
+use serde_json::json;
+
+use crate::simulator::{run_simulation, SimulationConfig, SimulationError};
+
+fn error_json(kind: &str, message: &str) -> String {
+    json!({ "error": message, "kind": kind }).to_string()
+}
+
+fn run_json(config_json: &str) -> String {
+    let config: SimulationConfig = match serde_json::from_str(config_json) {
+        Ok(config) => config,
+        Err(e) => return error_json("invalid_config", &e.to_string()),
+    };
+
+    match run_simulation(config) {
+        Ok(result) => serde_json::to_string(&result)
+            .unwrap_or_else(|e| error_json("other", &e.to_string())),
+        Err(e) => {
+            let kind = match e.downcast_ref::<SimulationError>() {
+                Some(SimulationError::InvalidConfig(_)) => "invalid_config",
+                Some(SimulationError::Data(_)) => "data",
+                Some(SimulationError::LimitExceeded(_)) => "limit_exceeded",
+                Some(SimulationError::Cancelled) => "cancelled",
+                None => "other",
+            };
+            error_json(kind, &e.to_string())
+        }
+    }
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // JSON never contains interior NUL bytes, but don't panic across the FFI boundary if it did
+    CString::new(s)
+        .unwrap_or_else(|_| CString::new(error_json("other", "result contained a NUL byte")).unwrap())
+        .into_raw()
+}
+
+/// Run a simulation described by a JSON `SimulationConfig` and return the JSON
+/// `SimulationResult`, or `{"error": ..., "kind": ...}` on failure.
+/// The returned string must be released with `prop_sim_free_string`.
+///
+/// # Safety
+/// `config_json` must be a valid pointer to a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn prop_sim_run(config_json: *const c_char) -> *mut c_char {
+    if config_json.is_null() {
+        return into_c_string(error_json("invalid_config", "config_json is null"));
+    }
+    let config_json = match CStr::from_ptr(config_json).to_str() {
+        Ok(s) => s.to_owned(),
+        Err(e) => return into_c_string(error_json("invalid_config", &e.to_string())),
+    };
+
+    let output = panic::catch_unwind(|| run_json(&config_json))
+        .unwrap_or_else(|_| error_json("other", "simulation panicked"));
+    into_c_string(output)
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+/// `s` must have been returned by `prop_sim_run` or `prop_sim_version` and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn prop_sim_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Crate version as a string; release it with `prop_sim_free_string`.
+#[no_mangle]
+pub extern "C" fn prop_sim_version() -> *mut c_char {
+    into_c_string(env!("CARGO_PKG_VERSION").to_string())
+}
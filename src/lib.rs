@@ -1,3 +1,7 @@
 // src/lib.rs
 
-pub mod simulator;
+mod simulator;
+pub mod prelude;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
@@ -8,7 +8,10 @@ mod web;
 
 #[cfg(feature = "cli")]
 fn main() {
-    cli::main().unwrap();
+    if let Err(e) = cli::main() {
+        eprintln!("Error: {}", e);
+        std::process::exit(cli::exit_code(e.as_ref()));
+    }
 }
 
 #[cfg(feature = "web")]
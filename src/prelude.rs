@@ -0,0 +1,40 @@
+// src/prelude.rs
+//! Stable public API of the simulator. Downstream crates should import from here
+//! (`use prop_simulator::prelude::*;`) rather than from internal module paths.
+
+pub use crate::simulator::{
+    run_simulation, run_simulation_with, run_simulation_cached, run_simulations, run_simulations_streaming, run_simulations_with, run_simulations_cached, IterationSummary, SimulationHandle, Progress, preview_trades, PreviewTrade, TradePreview, simulation_options, SimulationOptions,
+    set_simulation_limits, simulation_limits, SimulationLimits, measure_throughput, Throughput, recommend_plans, Plan, RecommendOptions,
+    rate_difficulty, Difficulty,
+    SimulationConfig, SimulationConfigBuilder, SimulationError, SimulationResult, EndOfGame, RunMetadata,
+    GrossPayoutStats, PeriodPnlReport, PeriodPnlStats, CohortReport, ParallelEvalReport, EvalAttemptStats, HedgeReport, CopyTradingReport, DaySnapshot, EquityCurve, BalancePercentile, TailRisk, DrawdownBufferStats, IncomeReport, StationaryIncome, ResetReport, Ledger, WithdrawalAudit, RuleTriggerReport, SoftBreachStats, BurnInComparison, RngBackend,
+};
+pub use crate::simulator::prop_account::{
+    account_catalog, account_diagram, account_factory, account_info, custom_account_factory, state_diagram, create_account, register_account, registered_accounts, resolve_account_type,
+    AccountFactory, AccountInfo, AccountStatus, BreachSeverity, BulenoxAccountType, DiagramFormat, ConsistencyTracker, ConsistencyWindow, DailyLoss, DailyLossLimit, Drawdown, DrawdownType, PayoutCadence, AccountType, CustomAccountSpec, FttAccountType, GenericPropAccount, Milestone, MyFundedFuturesAccountType, ProfitSplit, ProfitableDays, PropAccount, RuleChange,
+    RuleOverrides, ScalingPlan, ScalingTier, SplitTier, TakeProfitTraderAccountType, TopstepAccountType, WithdrawalLimit,
+};
+pub use crate::simulator::trade_data::{OutcomeChain, Trade, TradeSampling, DEFAULT_BLOCK_LENGTH};
+pub use crate::simulator::stats;
+pub use crate::simulator::trade_cache::{TradeCache, TradeCacheStats};
+pub use crate::simulator::end_states::EndStateStats;
+pub use crate::simulator::export::{export_iterations, ExportFormat, IterationRecord};
+pub use crate::simulator::worst::{WorstOutcomeReport, WorstOutcomeStats};
+pub use crate::simulator::time_to_money::{DaysToStats, TimeToMoneyReport};
+pub use crate::simulator::reweight::{OutcomeBucket, OutcomeReweight, ReweightReport, ReweightedBucket};
+pub use crate::simulator::holding::{HoldingAction, HoldingReport, HoldingRules};
+pub use crate::simulator::news::{NewsAction, NewsBlackout};
+pub use crate::simulator::insurance::{Insurance, InsuranceCoverage, InsuranceReport};
+pub use crate::simulator::allocate::{allocate_budget, AllocatedAccount, Allocation, AllocationOptions, AllocationUtility, CVAR_LEVEL};
+pub use crate::simulator::compare::{comparison_configs, run_comparison, AccountComparison};
+pub use crate::simulator::statement::{account_statement, AccountPhase, AccountStatement, StatementDay, StatementPick};
+pub use crate::simulator::convergence::{Convergence, ConvergenceMetric, ConvergenceReport, CONVERGENCE_BATCH};
+pub use crate::simulator::time_limit::{EvalTimeLimit, EvalTimeLimitReport, ExpiryAction};
+pub use crate::simulator::config_file::ConfigFile;
+pub use crate::simulator::bundle::{Bundle, BundleData, BundleManifest};
+pub use crate::simulator::anonymize::{anonymize_csv, AnonymizedCsv};
+pub use crate::simulator::saved_result::{ResultDiff, SavedResult, StatDiff};
+pub use crate::simulator::formatting::{Currency, Locale, MoneyFormatter};
+pub use crate::simulator::plotting::{compute_histogram, plot_gross_payout_histogram, plot_histogram, HistogramBins};
+#[cfg(feature = "web")]
+pub use crate::simulator::plotting::{generate_plotly_gross_payout_histogram_json, generate_plotly_histogram_json};
@@ -0,0 +1,213 @@
+// src/simulator/allocate.rs
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use super::formatting::Currency;
+use super::prop_account::account_catalog;
+use super::stats;
+use super::{run_simulations_streaming, SimulationConfig, SimulationError};
+
+/// Share of the worst outcomes the CVaR utility averages
+pub const CVAR_LEVEL: f64 = 0.05;
+
+/// What an allocation is ranked by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationUtility {
+    /// Mean combined final balance
+    #[default]
+    Ev,
+    /// Probability that at least one account pays out
+    AnyPayout,
+    /// Mean combined final balance of the worst `CVAR_LEVEL` of runs
+    Cvar,
+}
+
+impl AllocationUtility {
+    pub const ALL: [AllocationUtility; 3] = [AllocationUtility::Ev, AllocationUtility::AnyPayout, AllocationUtility::Cvar];
+}
+
+impl FromStr for AllocationUtility {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ev" => Ok(AllocationUtility::Ev),
+            "any_payout" | "anypayout" => Ok(AllocationUtility::AnyPayout),
+            "cvar" => Ok(AllocationUtility::Cvar),
+            _ => Err("Unknown allocation utility (options: ev, any_payout, cvar)"),
+        }
+    }
+}
+
+/// How much the trader can spend and what an allocation of it is ranked by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationOptions {
+    /// Most the trader can spend per month on account purchases and platform fees (USD)
+    pub monthly_budget: f64,
+    pub utility: AllocationUtility,
+    /// Most accounts held at once
+    pub max_accounts: u32,
+    /// Only consider accounts of this company
+    pub company: Option<String>,
+}
+
+impl AllocationOptions {
+    pub fn new(monthly_budget: f64) -> Self {
+        AllocationOptions {
+            monthly_budget,
+            utility: AllocationUtility::default(),
+            max_accounts: 4,
+            company: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocatedAccount {
+    pub account_type: String,
+    pub count: u32,
+}
+
+/// One way to split the budget, with the statistics of the combined bank balance of its accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Allocation {
+    pub accounts: Vec<AllocatedAccount>,
+    /// Account purchases plus one month of platform fee each, checked against the budget
+    pub monthly_cost: f64,
+    /// Currency of the balances and the CVaR; the monthly cost stays in USD
+    #[serde(default)]
+    pub currency: Currency,
+    pub mean_balance: f64,
+    pub median_balance: f64,
+    pub std_dev: f64,
+    /// Runs in which at least one account paid out
+    pub any_payout_percentage: f64,
+    /// Mean combined final balance of the worst `CVAR_LEVEL` of runs
+    pub cvar: f64,
+    /// Value of the chosen utility, the ranking key
+    pub utility: f64,
+}
+
+// Every multiset of at most `max_accounts` accounts (by index into `costs`) within `budget`
+fn affordable_allocations(costs: &[f64], budget: f64, max_accounts: u32) -> Vec<Vec<u32>> {
+    fn extend(costs: &[f64], from: usize, budget: f64, left: u32, counts: &mut Vec<u32>, allocations: &mut Vec<Vec<u32>>) {
+        for account in from..costs.len() {
+            if left == 0 || costs[account] > budget {
+                continue;
+            }
+            counts[account] += 1;
+            allocations.push(counts.clone());
+            extend(costs, account, budget - costs[account], left - 1, counts, allocations);
+            counts[account] -= 1;
+        }
+    }
+    let mut allocations = Vec::new();
+    extend(costs, 0, budget, max_accounts, &mut vec![0; costs.len()], &mut allocations);
+    allocations
+}
+
+/// Simulate every way to spend `options.monthly_budget` on up to `options.max_accounts`
+/// accounts on the trades of `base`, and rank the allocations by `options.utility`, best
+/// first. The accounts of an allocation copy the same trades: each account type is
+/// simulated once with a shared seed, and the outcomes of iteration `i` of all its
+/// accounts are added up. Without a seed in `base`, a random one is used.
+pub fn allocate_budget(base: &SimulationConfig, options: &AllocationOptions) -> Result<Vec<Allocation>, Box<dyn Error>> {
+    if options.max_accounts == 0 {
+        return Err(SimulationError::InvalidConfig("max_accounts must be greater than zero".into()).into());
+    }
+    if !base.rng_backend.seedable() {
+        return Err(SimulationError::InvalidConfig("allocations need a seedable RNG backend".into()).into());
+    }
+    let platform_fee = base.platform_fee_monthly.unwrap_or(0.0);
+    let accounts: Vec<(String, f64)> = account_catalog(options.company.as_deref())
+        .into_iter()
+        .map(|info| (info.account_type, info.cost + platform_fee))
+        .filter(|(_, monthly_cost)| *monthly_cost <= options.monthly_budget)
+        .collect();
+    if accounts.is_empty() {
+        return Err(SimulationError::InvalidConfig(format!(
+            "no account fits a monthly budget of {:.2}",
+            options.monthly_budget
+        ))
+        .into());
+    }
+
+    // Copied trades: every account type draws the same random days
+    let seed = base.seed.unwrap_or_else(rand::random);
+    let configs = accounts
+        .iter()
+        .map(|(account_type, _)| {
+            let mut config = base.clone();
+            config.account_type = account_type.clone();
+            config.custom_account = None;
+            // A purchase price belongs to one particular account
+            config.purchase_price = None;
+            config.histogram = false;
+            config.seed = Some(seed);
+            config
+        })
+        .collect();
+    // Final balance and whether it paid out, by account type and iteration
+    let outcomes: Vec<Mutex<Vec<(f64, bool)>>> = accounts.iter().map(|_| Mutex::new(vec![(0.0, false); base.iterations])).collect();
+    run_simulations_streaming(configs, &|run, summary| {
+        outcomes[run].lock().unwrap()[summary.iteration] = (summary.final_balance, summary.ledger.gross_payouts > 0.0);
+    })?;
+    let outcomes: Vec<Vec<(f64, bool)>> = outcomes.into_iter().map(|o| o.into_inner().unwrap()).collect();
+
+    let costs: Vec<f64> = accounts.iter().map(|(_, cost)| *cost).collect();
+    // Balances are in the home currency the runs converted them to, costs stay in USD
+    let currency = base.currency.unwrap_or_default();
+    let mut allocations: Vec<Allocation> = affordable_allocations(&costs, options.monthly_budget, options.max_accounts)
+        .into_iter()
+        .map(|counts| {
+            let held: Vec<(usize, u32)> = counts.iter().copied().enumerate().filter(|&(_, count)| count > 0).collect();
+            let mut totals: Vec<f64> = (0..base.iterations)
+                .map(|i| held.iter().map(|&(account, count)| outcomes[account][i].0 * count as f64).sum())
+                .collect();
+            let paid = (0..base.iterations).filter(|&i| held.iter().any(|&(account, _)| outcomes[account][i].1)).count();
+            allocation(&accounts, &held, &mut totals, paid, options.utility, currency)
+        })
+        .collect();
+    allocations.sort_by(|a, b| b.utility.total_cmp(&a.utility));
+    Ok(allocations)
+}
+
+fn allocation(
+    accounts: &[(String, f64)],
+    held: &[(usize, u32)],
+    totals: &mut [f64],
+    paid: usize,
+    utility: AllocationUtility,
+    currency: Currency,
+) -> Allocation {
+    totals.sort_by(|a, b| a.total_cmp(b));
+    let runs = totals.len().max(1) as f64;
+    let mean_balance = stats::mean(totals).unwrap_or_default();
+    let std_dev = stats::std_dev(totals).unwrap_or_default();
+    let median_balance = stats::median(totals).unwrap_or_default();
+    let tail = ((totals.len() as f64 * CVAR_LEVEL).ceil() as usize).clamp(1, totals.len().max(1));
+    let cvar = totals.iter().take(tail).sum::<f64>() / tail as f64;
+    let any_payout_percentage = paid as f64 / runs * 100.0;
+
+    Allocation {
+        accounts: held
+            .iter()
+            .map(|&(account, count)| AllocatedAccount { account_type: accounts[account].0.clone(), count })
+            .collect(),
+        monthly_cost: held.iter().map(|&(account, count)| accounts[account].1 * count as f64).sum(),
+        currency,
+        mean_balance,
+        median_balance,
+        std_dev,
+        any_payout_percentage,
+        cvar,
+        utility: match utility {
+            AllocationUtility::Ev => mean_balance,
+            AllocationUtility::AnyPayout => any_payout_percentage,
+            AllocationUtility::Cvar => cvar,
+        },
+    }
+}
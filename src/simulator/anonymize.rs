@@ -0,0 +1,113 @@
+// src/simulator/anonymize.rs
+use chrono::{Duration, NaiveDateTime};
+use csv::{ByteRecord, Reader, Writer};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Serialize, Deserialize};
+
+use super::trade_data::DATETIME_FORMAT;
+use super::SimulationError;
+
+// Dates move by a whole number of weeks, up to this many either way, so every trade keeps
+// its weekday and time of day
+const MAX_SHIFT_WEEKS: i64 = 520;
+const MIN_SHIFT_WEEKS: i64 = 52;
+
+// P&L is scaled by a factor between these bounds, rounded to SCALE_DECIMALS decimals
+const MIN_SCALE: f64 = 0.2;
+const MAX_SCALE: f64 = 5.0;
+const SCALE_DECIMALS: i32 = 4;
+
+/// A trade CSV with its dates shifted and its P&L scaled, safe to share in place of the
+/// original. Simulating it with the multiplier divided by `scale` reproduces the results
+/// of the original data: trades keep their order, weekday, time of day and daily grouping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnonymizedCsv {
+    pub csv: String,
+    pub trades: usize,
+    /// Factor every return and excursion was multiplied by
+    pub scale: f64,
+    /// Days every entry and exit time was moved by (a multiple of 7)
+    pub shift_days: i64,
+    /// Whether the metadata columns after the exit time were dropped
+    pub metadata_dropped: bool,
+}
+
+impl AnonymizedCsv {
+    /// Multiplier to simulate the anonymized data with, given the one used with the original
+    pub fn equivalent_multiplier(&self, multiplier: f64) -> f64 {
+        multiplier / self.scale
+    }
+}
+
+/// Anonymize "datetime,return,max opposite excursion[,exit datetime][,metadata...]" rows.
+/// The same seed gives the same shift and scale.
+pub fn anonymize_csv(data: &[u8], seed: Option<u64>, drop_metadata: bool) -> Result<AnonymizedCsv, SimulationError> {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let weeks = rng.gen_range(MIN_SHIFT_WEEKS..=MAX_SHIFT_WEEKS) * if rng.gen_bool(0.5) { 1 } else { -1 };
+    let shift = Duration::weeks(weeks);
+    let precision = 10f64.powi(SCALE_DECIMALS);
+    let scale = (rng.gen_range(MIN_SCALE.ln()..MAX_SCALE.ln()).exp() * precision).round() / precision;
+
+    let data_error = |line: u64, e: &dyn std::fmt::Display| SimulationError::Data(format!("line {}: {}", line, e));
+    let mut rdr = Reader::from_reader(data);
+    let mut wtr = Writer::from_writer(Vec::new());
+    let kept_columns = |record: &ByteRecord| if drop_metadata { record.len().min(4) } else { record.len() };
+
+    let headers = rdr.byte_headers().map_err(|e| data_error(1, &e))?.clone();
+    wtr.write_record(headers.iter().take(kept_columns(&headers))).map_err(|e| data_error(1, &e))?;
+
+    let mut record = ByteRecord::new();
+    let mut output = ByteRecord::new();
+    let mut trades = 0;
+    loop {
+        let line = rdr.position().line();
+        if !rdr.read_byte_record(&mut record).map_err(|e| data_error(line, &e))? {
+            break;
+        }
+        let line = record.position().map_or(line, |position| position.line());
+        let field = |column: usize| -> Result<&str, SimulationError> {
+            let bytes = record.get(column).ok_or_else(|| data_error(line, &format!("missing column {}", column + 1)))?;
+            std::str::from_utf8(bytes).map(str::trim).map_err(|e| data_error(line, &e))
+        };
+        let shift_time = |text: &str| -> Result<String, SimulationError> {
+            let time = NaiveDateTime::parse_from_str(text, DATETIME_FORMAT).map_err(|e| data_error(line, &e))?;
+            let shifted = time.checked_add_signed(shift).ok_or_else(|| data_error(line, &"date out of range"))?;
+            Ok(shifted.format(DATETIME_FORMAT).to_string())
+        };
+        let scale_value = |text: &str| -> Result<String, SimulationError> {
+            let value: f64 = text.parse().map_err(|e| data_error(line, &e))?;
+            Ok((value * scale).to_string())
+        };
+
+        output.clear();
+        output.push_field(shift_time(field(0)?)?.as_bytes());
+        output.push_field(scale_value(field(1)?)?.as_bytes());
+        output.push_field(scale_value(field(2)?)?.as_bytes());
+        if record.len() > 3 {
+            let exit = field(3)?;
+            if exit.is_empty() {
+                output.push_field(b"");
+            } else {
+                output.push_field(shift_time(exit)?.as_bytes());
+            }
+        }
+        for metadata in record.iter().take(kept_columns(&record)).skip(4) {
+            output.push_field(metadata);
+        }
+        wtr.write_byte_record(&output).map_err(|e| data_error(line, &e))?;
+        trades += 1;
+    }
+
+    let bytes = wtr.into_inner().map_err(|e| SimulationError::Data(e.to_string()))?;
+    Ok(AnonymizedCsv {
+        csv: String::from_utf8(bytes).map_err(|e| SimulationError::Data(e.to_string()))?,
+        trades,
+        scale,
+        shift_days: shift.num_days(),
+        metadata_dropped: drop_metadata,
+    })
+}
@@ -0,0 +1,45 @@
+// src/simulator/buffer.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+
+/// How close surviving (not busted) runs came to the loss balance. The buffer is the
+/// distance between the prop account balance and the balance at which it is blown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawdownBufferStats {
+    pub surviving_runs: usize,
+    /// Minimum buffer reached per surviving run
+    pub mean_min_buffer: f64,
+    pub median_min_buffer: f64,
+    pub p10_min_buffer: f64,
+    pub p90_min_buffer: f64,
+    /// Mean buffer left right after the first payout, over surviving runs with a payout
+    pub mean_buffer_at_first_payout: Option<f64>,
+}
+
+/// Build the stats from `(minimum buffer, buffer at first payout)` of each surviving run,
+/// or `None` when every run was busted
+pub fn drawdown_buffer_stats(runs: &[(f64, Option<f64>)]) -> Option<DrawdownBufferStats> {
+    if runs.is_empty() {
+        return None;
+    }
+
+    let min_buffers: Vec<f64> = runs.iter().map(|(min_buffer, _)| *min_buffer).collect();
+    let min_buffers = stats::sorted(&min_buffers);
+
+    let first_payout_buffers: Vec<f64> = runs.iter().filter_map(|(_, at_payout)| *at_payout).collect();
+    let mean_buffer_at_first_payout = if first_payout_buffers.is_empty() {
+        None
+    } else {
+        Some(first_payout_buffers.iter().sum::<f64>() / first_payout_buffers.len() as f64)
+    };
+
+    Some(DrawdownBufferStats {
+        surviving_runs: runs.len(),
+        mean_min_buffer: min_buffers.iter().sum::<f64>() / min_buffers.len() as f64,
+        median_min_buffer: stats::median(&min_buffers)?,
+        p10_min_buffer: stats::percentile(&min_buffers, 10.0)?,
+        p90_min_buffer: stats::percentile(&min_buffers, 90.0)?,
+        mean_buffer_at_first_payout,
+    })
+}
@@ -0,0 +1,241 @@
+// src/simulator/builder.rs
+use chrono::NaiveDate;
+use super::formatting::Currency;
+use super::prop_account::{CustomAccountSpec, DrawdownType, PayoutCadence, ProfitSplit, ProfitableDays, RuleChange};
+use super::trade_data::TradeSampling;
+use super::reweight::OutcomeReweight;
+use super::export::ExportFormat;
+use super::rng::RngBackend;
+use super::holding::HoldingRules;
+use super::news::NewsBlackout;
+use super::insurance::Insurance;
+use super::convergence::Convergence;
+use super::time_limit::EvalTimeLimit;
+use super::{SimulationConfig, SimulationError};
+
+impl Default for SimulationConfig {
+    // Same defaults as the CLI
+    fn default() -> Self {
+        SimulationConfig {
+            csv_file: None,
+            csv_data: None,
+            iterations: 10_000,
+            max_trades_per_day: None,
+            daily_profit_target: None,
+            daily_stop_loss: None,
+            round_trip_cost: None,
+            avg_trades_per_day: None,
+            stop_loss: None,
+            take_profit: None,
+            win_percentage: None,
+            max_simulation_days: 365,
+            max_payouts: 12,
+            account_type: String::from("ftt:GT"),
+            custom_account: None,
+            multiplier: 1.0,
+            histogram: false,
+            histogram_file: None,
+            output_file: None,
+            output_format: None,
+            condition_end_state: String::from("All"),
+            histogram_bins: None,
+            balance_sample_size: None,
+            payout_fee_fixed: None,
+            payout_fee_percent: None,
+            currency: None,
+            conversion_rate: None,
+            conversion_rate_series: None,
+            conversion_fee_percent: None,
+            monthly_income_target: None,
+            snapshot_days: None,
+            equity_curve: false,
+            streaming_stats: false,
+            convergence: None,
+            percentiles: None,
+            var_confidence: None,
+            cohort_interval_days: None,
+            parallel_evals: None,
+            hedge_account_type: None,
+            hedge_correlation: None,
+            copied_accounts: None,
+            copy_correlation: None,
+            holding_rules: None,
+            trade_filter: None,
+            outcome_reweighting: None,
+            news_blackout: None,
+            seed: None,
+            rule_timeline: None,
+            continue_after_max_payouts: false,
+            audit_withdrawals: false,
+            daily_stop_loss_percent: None,
+            account_size: None,
+            traded_contracts: None,
+            drawdown_type: None,
+            payout_cadence: None,
+            payout_delay_days: None,
+            payout_denial_probability: None,
+            min_profitable_days: None,
+            profit_split: None,
+            trade_sampling: TradeSampling::Independent,
+            block_length: None,
+            terminal_payout_fraction: None,
+            platform_fee_monthly: None,
+            platform_fee_funded_only: false,
+            bankroll: None,
+            bankroll_floor: None,
+            insurance: None,
+            eval_time_limit: None,
+            resets_allowed: None,
+            reset_cost: None,
+            purchase_date: None,
+            purchase_price: None,
+            burn_in_trades: None,
+            burn_in_days: None,
+            rng_backend: RngBackend::Std,
+            name: None,
+            tags: Vec::new(),
+            notes: None,
+        }
+    }
+}
+
+// Setters for required fields take the value, setters for optional fields wrap it in `Some`
+macro_rules! setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, value: $ty) -> Self {
+                self.config.$field = value;
+                self
+            }
+        )*
+    };
+}
+
+macro_rules! optional_setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, value: $ty) -> Self {
+                self.config.$field = Some(value);
+                self
+            }
+        )*
+    };
+}
+
+/// Builder for `SimulationConfig`, starting from the defaults
+#[derive(Debug, Clone, Default)]
+pub struct SimulationConfigBuilder {
+    config: SimulationConfig,
+}
+
+impl SimulationConfig {
+    pub fn builder() -> SimulationConfigBuilder {
+        SimulationConfigBuilder::default()
+    }
+}
+
+impl SimulationConfigBuilder {
+    setters! {
+        iterations: usize,
+        max_simulation_days: u64,
+        max_payouts: u8,
+        multiplier: f64,
+        histogram: bool,
+        equity_curve: bool,
+        streaming_stats: bool,
+        continue_after_max_payouts: bool,
+        audit_withdrawals: bool,
+        trade_sampling: TradeSampling,
+        rng_backend: RngBackend,
+        platform_fee_funded_only: bool,
+        tags: Vec<String>,
+    }
+
+    optional_setters! {
+        csv_file: String,
+        csv_data: String,
+        max_trades_per_day: u64,
+        daily_profit_target: f64,
+        daily_stop_loss: f64,
+        round_trip_cost: f64,
+        avg_trades_per_day: f64,
+        stop_loss: f64,
+        take_profit: f64,
+        win_percentage: f64,
+        histogram_file: String,
+        output_file: String,
+        output_format: ExportFormat,
+        histogram_bins: usize,
+        block_length: usize,
+        balance_sample_size: usize,
+        payout_fee_fixed: f64,
+        payout_fee_percent: f64,
+        currency: Currency,
+        conversion_rate: f64,
+        conversion_rate_series: Vec<f64>,
+        conversion_fee_percent: f64,
+        monthly_income_target: f64,
+        snapshot_days: Vec<u64>,
+        percentiles: Vec<f64>,
+        var_confidence: f64,
+        cohort_interval_days: u64,
+        parallel_evals: u32,
+        hedge_account_type: String,
+        hedge_correlation: f64,
+        copied_accounts: u32,
+        copy_correlation: f64,
+        holding_rules: HoldingRules,
+        trade_filter: String,
+        outcome_reweighting: Vec<OutcomeReweight>,
+        news_blackout: NewsBlackout,
+        seed: u64,
+        rule_timeline: Vec<RuleChange>,
+        daily_stop_loss_percent: f64,
+        account_size: f64,
+        traded_contracts: u32,
+        drawdown_type: DrawdownType,
+        payout_cadence: PayoutCadence,
+        payout_delay_days: u64,
+        payout_denial_probability: f64,
+        min_profitable_days: ProfitableDays,
+        profit_split: ProfitSplit,
+        terminal_payout_fraction: f64,
+        platform_fee_monthly: f64,
+        bankroll: f64,
+        bankroll_floor: f64,
+        custom_account: CustomAccountSpec,
+        insurance: Insurance,
+        convergence: Convergence,
+        eval_time_limit: EvalTimeLimit,
+        resets_allowed: u8,
+        reset_cost: f64,
+        purchase_date: NaiveDate,
+        purchase_price: f64,
+        burn_in_trades: usize,
+        burn_in_days: usize,
+        name: String,
+        notes: String,
+    }
+
+    pub fn account_type(mut self, account_type: impl Into<String>) -> Self {
+        self.config.account_type = account_type.into();
+        self
+    }
+
+    pub fn condition_end_state(mut self, condition_end_state: impl Into<String>) -> Self {
+        self.config.condition_end_state = condition_end_state.into();
+        self
+    }
+
+    /// Validate and return the config
+    pub fn build(self) -> Result<SimulationConfig, SimulationError> {
+        super::config_account_factory(&self.config)?;
+        if self.config.iterations == 0 {
+            return Err(SimulationError::InvalidConfig("iterations must be greater than zero".into()));
+        }
+        super::currency_conversion(&self.config)?;
+        super::trader::conditioned_end_states(&self.config.condition_end_state)
+            .map_err(|e| SimulationError::InvalidConfig(format!("condition_end_state: {}", e)))?;
+        Ok(self.config)
+    }
+}
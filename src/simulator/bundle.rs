@@ -0,0 +1,165 @@
+// src/simulator/bundle.rs
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::saved_result::SavedResult;
+use super::{SimulationConfig, SimulationError, SimulationResult};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const CONFIG_FILE: &str = "config.json";
+const RESULTS_FILE: &str = "results.json";
+const DATA_FILE: &str = "data.csv";
+
+/// Everything needed to re-run a simulation: written with `--bundle` and re-executed with `load-bundle`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub crate_version: String,
+    pub seed: u64,
+    /// Input trade data, `None` for simulated bracket trades
+    pub data: Option<BundleData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleData {
+    /// Name of the original CSV file, if the data came from a file
+    pub file_name: Option<String>,
+    pub sha256: String,
+    pub bytes: u64,
+    /// Whether the data itself is stored in the bundle (otherwise only its hash)
+    pub included: bool,
+}
+
+#[derive(Debug)]
+pub struct Bundle {
+    pub manifest: BundleManifest,
+    pub config: SimulationConfig,
+    pub saved: SavedResult,
+    data: Option<Vec<u8>>,
+}
+
+/// Hex encoded SHA-256 of the input data
+pub fn data_hash(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn bundle_error(path: &str, e: impl std::fmt::Display) -> SimulationError {
+    SimulationError::Data(format!("{}: {}", path, e))
+}
+
+impl Bundle {
+    /// Bundle a finished run. The config must carry the seed the run used.
+    pub fn new(config: SimulationConfig, result: SimulationResult, include_data: bool) -> Result<Self, SimulationError> {
+        let seed = config
+            .seed
+            .ok_or_else(|| SimulationError::InvalidConfig("a bundle requires a seeded run".into()))?;
+
+        let data = match (&config.csv_data, &config.csv_file) {
+            (Some(csv_data), _) => Some((None, csv_data.as_bytes().to_vec())),
+            (None, Some(csv_file)) => {
+                let bytes = fs::read(csv_file).map_err(|e| bundle_error(csv_file, e))?;
+                Some((Some(csv_file.clone()), bytes))
+            }
+            (None, None) => None,
+        };
+        let manifest = BundleManifest {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            seed,
+            data: data.as_ref().map(|(file_name, bytes)| BundleData {
+                file_name: file_name.clone(),
+                sha256: data_hash(bytes),
+                bytes: bytes.len() as u64,
+                included: include_data,
+            }),
+        };
+
+        // The data is stored next to the config, not inside it
+        let mut config = config;
+        config.csv_data = None;
+        Ok(Bundle {
+            manifest,
+            saved: SavedResult::new(config.clone(), result),
+            config,
+            data: data.filter(|_| include_data).map(|(_, bytes)| bytes),
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), SimulationError> {
+        let file = File::create(path).map_err(|e| bundle_error(path, e))?;
+        let mut zip = ZipWriter::new(file);
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        let mut entries = vec![
+            (MANIFEST_FILE, serde_json::to_vec_pretty(&self.manifest).map_err(|e| bundle_error(path, e))?),
+            (CONFIG_FILE, serde_json::to_vec_pretty(&self.config).map_err(|e| bundle_error(path, e))?),
+            (RESULTS_FILE, serde_json::to_vec(&self.saved).map_err(|e| bundle_error(path, e))?),
+        ];
+        if let Some(data) = &self.data {
+            entries.push((DATA_FILE, data.clone()));
+        }
+        for (name, contents) in entries {
+            zip.start_file(name, options).map_err(|e| bundle_error(path, e))?;
+            zip.write_all(&contents).map_err(|e| bundle_error(path, e))?;
+        }
+        zip.finish().map_err(|e| bundle_error(path, e))?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, SimulationError> {
+        let file = File::open(path).map_err(|e| bundle_error(path, e))?;
+        let mut zip = ZipArchive::new(file).map_err(|e| bundle_error(path, e))?;
+        let mut read_entry = |name: &str| -> Result<Option<Vec<u8>>, SimulationError> {
+            let mut entry = match zip.by_name(name) {
+                Ok(entry) => entry,
+                Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                Err(e) => return Err(bundle_error(path, e)),
+            };
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).map_err(|e| bundle_error(path, e))?;
+            Ok(Some(contents))
+        };
+        let mut required = |name: &str| -> Result<Vec<u8>, SimulationError> {
+            read_entry(name)?.ok_or_else(|| bundle_error(path, format!("{} missing, not a simulation bundle", name)))
+        };
+
+        let manifest: BundleManifest = serde_json::from_slice(&required(MANIFEST_FILE)?).map_err(|e| bundle_error(path, e))?;
+        let config: SimulationConfig = serde_json::from_slice(&required(CONFIG_FILE)?).map_err(|e| bundle_error(path, e))?;
+        let saved: SavedResult = serde_json::from_slice(&required(RESULTS_FILE)?).map_err(|e| bundle_error(path, e))?;
+        let data = read_entry(DATA_FILE)?;
+        Ok(Bundle { manifest, config, saved, data })
+    }
+
+    /// Config to re-execute the bundled run. Bundled data is used when present, otherwise
+    /// `csv_file` (or the original file) must match the recorded hash.
+    pub fn replay_config(&self, csv_file: Option<&str>) -> Result<SimulationConfig, SimulationError> {
+        let mut config = self.config.clone();
+        config.seed = Some(self.manifest.seed);
+        // Histogram bins are not needed to reproduce the statistics
+        config.histogram = false;
+
+        let expected = match &self.manifest.data {
+            Some(expected) => expected,
+            None => return Ok(config),
+        };
+        let data = match (&self.data, csv_file.or(config.csv_file.as_deref())) {
+            (Some(data), _) => data.clone(),
+            (None, Some(file)) => fs::read(file).map_err(|e| bundle_error(file, e))?,
+            (None, None) => {
+                return Err(SimulationError::InvalidConfig("the bundle does not include its data, pass the CSV file".into()));
+            }
+        };
+        let hash = data_hash(&data);
+        if hash != expected.sha256 {
+            return Err(SimulationError::Data(format!(
+                "input data hash {} does not match the bundle ({})",
+                hash, expected.sha256
+            )));
+        }
+        config.csv_file = None;
+        config.csv_data = Some(String::from_utf8(data).map_err(|e| SimulationError::Data(e.to_string()))?);
+        Ok(config)
+    }
+}
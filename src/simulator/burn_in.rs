@@ -0,0 +1,39 @@
+// src/simulator/burn_in.rs
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use super::{EndOfGame, SimulationResult};
+
+/// How excluding the burn-in period (the first trades or days of the data) from the
+/// resampling pool changed the results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnInComparison {
+    pub excluded_trades: usize,
+    pub remaining_trades: usize,
+    /// Results with the burn-in period kept in the pool
+    pub included_mean_balance: f64,
+    pub included_median_balance: f64,
+    pub included_positive_balance_percentage: f64,
+    pub included_end_state_percentages: HashMap<EndOfGame, f64>,
+    /// Change from excluding the burn-in period (excluded minus included)
+    pub mean_balance_change: f64,
+    pub positive_balance_percentage_change: f64,
+}
+
+pub fn burn_in_comparison(
+    excluded_trades: usize,
+    remaining_trades: usize,
+    excluded: &SimulationResult,
+    included: &SimulationResult,
+) -> BurnInComparison {
+    BurnInComparison {
+        excluded_trades,
+        remaining_trades,
+        included_mean_balance: included.mean_balance,
+        included_median_balance: included.median_balance,
+        included_positive_balance_percentage: included.positive_balance_percentage,
+        included_end_state_percentages: included.end_state_percentages.clone(),
+        mean_balance_change: excluded.mean_balance - included.mean_balance,
+        positive_balance_percentage_change: excluded.positive_balance_percentage - included.positive_balance_percentage,
+    }
+}
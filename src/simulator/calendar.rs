@@ -0,0 +1,38 @@
+// src/simulator/calendar.rs
+// Maps simulation days onto calendar dates for accounts with a known purchase date
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+
+fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Date of trading day `day` (0-based) for an account bought on `purchase_date`; trading
+/// starts on the first weekday on or after the purchase
+pub fn trading_date(purchase_date: NaiveDate, day: u64) -> NaiveDate {
+    let mut date = next_trading_day(purchase_date);
+    date += Duration::weeks((day / 5) as i64);
+    for _ in 0..day % 5 {
+        date = next_trading_day(date + Duration::days(1));
+    }
+    date
+}
+
+fn next_trading_day(mut date: NaiveDate) -> NaiveDate {
+    while !is_trading_day(date) {
+        date += Duration::days(1);
+    }
+    date
+}
+
+/// Billing month `date` falls in, counting from 0 on `purchase_date`. Each month starts on
+/// the monthly anniversary of the purchase (the last day of shorter months).
+pub fn billing_month(purchase_date: NaiveDate, date: NaiveDate) -> u64 {
+    if date < purchase_date {
+        return 0;
+    }
+    let mut months = (date.year() - purchase_date.year()) as u32 * 12 + date.month() - purchase_date.month();
+    if purchase_date.checked_add_months(Months::new(months)).is_some_and(|anniversary| anniversary > date) {
+        months -= 1;
+    }
+    months as u64
+}
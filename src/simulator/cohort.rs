@@ -0,0 +1,58 @@
+// src/simulator/cohort.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+
+/// Combined cash flow of a trader who buys a new account every `interval_days` trading days
+/// (while earlier accounts keep trading) over the whole simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CohortReport {
+    pub interval_days: u64,
+    /// Accounts started in each run
+    pub accounts_per_run: usize,
+    /// Net cash flow of all accounts together, for each complete month of the simulation
+    pub mean_monthly_cash_flow: Vec<f64>,
+    pub median_monthly_cash_flow: Vec<f64>,
+    pub p10_monthly_cash_flow: Vec<f64>,
+    pub p90_monthly_cash_flow: Vec<f64>,
+    /// Final bank balance of the whole cohort
+    pub mean_total_cash_flow: f64,
+    pub positive_total_percentage: f64,
+}
+
+/// Build the report from the monthly cash flow and final bank balance of each run's cohort
+pub fn cohort_report(
+    interval_days: u64,
+    accounts_per_run: usize,
+    runs: &[(Vec<f64>, f64)],
+    months: usize,
+) -> CohortReport {
+    let run_count = runs.len().max(1) as f64;
+    let mut mean_monthly_cash_flow = Vec::with_capacity(months);
+    let mut median_monthly_cash_flow = Vec::with_capacity(months);
+    let mut p10_monthly_cash_flow = Vec::with_capacity(months);
+    let mut p90_monthly_cash_flow = Vec::with_capacity(months);
+
+    for month in 0..months {
+        let flows: Vec<f64> = runs
+            .iter()
+            .map(|(monthly, _)| monthly.get(month).copied().unwrap_or(0.0))
+            .collect();
+        let flows = stats::sorted(&flows);
+        mean_monthly_cash_flow.push(flows.iter().sum::<f64>() / run_count);
+        median_monthly_cash_flow.push(stats::median(&flows).unwrap_or(0.0));
+        p10_monthly_cash_flow.push(stats::percentile(&flows, 10.0).unwrap_or(0.0));
+        p90_monthly_cash_flow.push(stats::percentile(&flows, 90.0).unwrap_or(0.0));
+    }
+
+    CohortReport {
+        interval_days,
+        accounts_per_run,
+        mean_monthly_cash_flow,
+        median_monthly_cash_flow,
+        p10_monthly_cash_flow,
+        p90_monthly_cash_flow,
+        mean_total_cash_flow: runs.iter().map(|(_, total)| total).sum::<f64>() / run_count,
+        positive_total_percentage: runs.iter().filter(|(_, total)| *total > 0.0).count() as f64 / run_count * 100.0,
+    }
+}
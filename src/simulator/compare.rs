@@ -0,0 +1,40 @@
+// src/simulator/compare.rs
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+
+use super::{run_simulations, SimulationConfig, SimulationError, SimulationResult};
+
+/// Result of one account type of a comparison
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountComparison {
+    pub account_type: String,
+    pub result: SimulationResult,
+}
+
+/// `config` once for each of `account_types`, with everything else unchanged. A
+/// `custom_account` of `config` is left out, the account types replace it.
+pub fn comparison_configs(config: &SimulationConfig, account_types: &[String]) -> Vec<SimulationConfig> {
+    account_types
+        .iter()
+        .map(|account_type| SimulationConfig {
+            account_type: account_type.clone(),
+            custom_account: None,
+            ..config.clone()
+        })
+        .collect()
+}
+
+/// Run `config` against each of `account_types` in parallel on the same trades, returning
+/// the results in the order of `account_types`. With a seed every account trades the same
+/// random days for as long as its run lasts, so differences come from the rules alone.
+pub fn run_comparison(config: SimulationConfig, account_types: &[String]) -> Result<Vec<AccountComparison>, Box<dyn Error>> {
+    if account_types.is_empty() {
+        return Err(SimulationError::InvalidConfig("a comparison requires at least one account type".into()).into());
+    }
+    let results = run_simulations(comparison_configs(&config, account_types))?;
+    Ok(account_types
+        .iter()
+        .zip(results)
+        .map(|(account_type, result)| AccountComparison { account_type: account_type.clone(), result })
+        .collect())
+}
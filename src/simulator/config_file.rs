@@ -0,0 +1,77 @@
+// src/simulator/config_file.rs
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+
+use super::{SimulationConfig, SimulationError};
+
+// Other names `SimulationConfig` accepts for its fields, by field
+const FIELD_ALIASES: [(&str, &str); 1] = [("day_sampling", "trade_sampling")];
+
+// The table with aliased keys under their field names, so that layers given under
+// different names of a field still override each other
+fn canonical_keys(mut table: toml::Table) -> toml::Table {
+    for (alias, field) in FIELD_ALIASES {
+        if let Some(value) = table.remove(alias) {
+            table.insert(field.to_string(), value);
+        }
+    }
+    table
+}
+
+/// A TOML config file holding shared `[defaults]` and any number of named
+/// `[scenarios.<name>]` tables. Keys are the `SimulationConfig` field names.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub defaults: toml::Table,
+    #[serde(default)]
+    pub scenarios: BTreeMap<String, toml::Table>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &str) -> Result<Self, SimulationError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| SimulationError::InvalidConfig(format!("{}: {}", path, e)))?;
+        Self::from_toml_str(&contents)
+            .map_err(|e| SimulationError::InvalidConfig(format!("{}: {}", path, e)))
+    }
+
+    pub fn from_toml_str(contents: &str) -> Result<Self, SimulationError> {
+        toml::from_str(contents).map_err(|e| SimulationError::InvalidConfig(e.to_string()))
+    }
+
+    pub fn scenario_names(&self) -> Vec<String> {
+        self.scenarios.keys().cloned().collect()
+    }
+
+    /// Build the config for `scenario` (or just the defaults when `None`).
+    /// Values are layered: `base` < `[defaults]` < `[scenarios.<name>]` < `overrides`.
+    pub fn resolve(
+        &self,
+        base: &SimulationConfig,
+        scenario: Option<&str>,
+        overrides: &toml::Table,
+    ) -> Result<SimulationConfig, SimulationError> {
+        let mut table = toml::Table::try_from(base)
+            .map_err(|e| SimulationError::InvalidConfig(e.to_string()))?;
+
+        table.extend(canonical_keys(self.defaults.clone()));
+        if let Some(name) = scenario {
+            let scenario_table = self.scenarios.get(name).ok_or_else(|| {
+                SimulationError::InvalidConfig(format!(
+                    "Unknown scenario '{}'. Available scenarios: {}",
+                    name,
+                    self.scenario_names().join(", ")
+                ))
+            })?;
+            table.extend(canonical_keys(scenario_table.clone()));
+        }
+        table.extend(overrides.clone());
+
+        let label = scenario.unwrap_or("defaults");
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| SimulationError::InvalidConfig(format!("scenario '{}': {}", label, e)))
+    }
+}
@@ -0,0 +1,124 @@
+// src/simulator/convergence.rs
+use serde::{Serialize, Deserialize};
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::stats::RunningMoments;
+
+/// Iterations run between two checks of the standard error; the first check also waits
+/// for this many, so that a handful of runs cannot look converged by chance
+pub const CONVERGENCE_BATCH: usize = 1_000;
+
+/// Estimate whose Monte Carlo standard error decides when the runs have converged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConvergenceMetric {
+    /// Mean final balance of the runs `condition_end_state` selects, in the result currency
+    #[default]
+    MeanBalance,
+    /// Percentage of all runs that busted, in percentage points
+    BustPercentage,
+}
+
+impl ConvergenceMetric {
+    pub const ALL: [ConvergenceMetric; 2] = [ConvergenceMetric::MeanBalance, ConvergenceMetric::BustPercentage];
+}
+
+impl FromStr for ConvergenceMetric {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "mean_balance" => Ok(ConvergenceMetric::MeanBalance),
+            "bust_percentage" => Ok(ConvergenceMetric::BustPercentage),
+            _ => Err("Unknown convergence metric (options: mean_balance, bust_percentage)"),
+        }
+    }
+}
+
+/// Run iterations until the standard error of `metric` is at most `tolerance`, with
+/// `iterations` as the most to run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Convergence {
+    pub tolerance: f64,
+    #[serde(default)]
+    pub metric: ConvergenceMetric,
+}
+
+/// How many iterations a run with `convergence` used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvergenceReport {
+    pub metric: ConvergenceMetric,
+    pub tolerance: f64,
+    /// Iterations run, at most `iterations`
+    pub iterations: usize,
+    /// Standard error of the metric after the last iteration
+    pub standard_error: f64,
+    /// Whether the standard error reached the tolerance; `false` when the runs stopped at
+    /// `iterations` before it did
+    pub converged: bool,
+}
+
+/// Running standard error of the metrics over the runs so far
+#[derive(Debug, Clone, Default)]
+pub struct ConvergenceTracker {
+    balances: RunningMoments, // of the conditioned runs
+    runs: usize,
+    busts: usize,
+}
+
+impl ConvergenceTracker {
+    /// Count a run, with its final balance when `condition_end_state` selects it
+    pub fn add(&mut self, final_balance: Option<f64>, busted: bool) {
+        if let Some(balance) = final_balance {
+            self.balances.add(balance);
+        }
+        self.runs += 1;
+        self.busts += usize::from(busted);
+    }
+
+    pub fn merge(&mut self, other: &ConvergenceTracker) {
+        self.balances.merge(&other.balances);
+        self.runs += other.runs;
+        self.busts += other.busts;
+    }
+
+    /// `None` before there are runs to estimate from. A bust percentage of 0 or 100 has a
+    /// standard error of 0.
+    pub fn standard_error(&self, metric: ConvergenceMetric) -> Option<f64> {
+        match metric {
+            ConvergenceMetric::MeanBalance => {
+                let n = (self.balances.count() > 1).then_some(self.balances.count() as f64)?;
+                Some(self.balances.std_dev()? / n.sqrt())
+            }
+            ConvergenceMetric::BustPercentage => {
+                let n = (self.runs > 0).then_some(self.runs as f64)?;
+                let p = self.busts as f64 / n;
+                Some((p * (1.0 - p) / n).sqrt() * 100.0)
+            }
+        }
+    }
+
+    pub fn converged(&self, convergence: &Convergence) -> bool {
+        self.standard_error(convergence.metric).is_some_and(|standard_error| standard_error <= convergence.tolerance)
+    }
+
+    /// The report of `convergence` on the runs so far, `None` when there is none
+    pub fn report(&self, convergence: &Convergence) -> Option<ConvergenceReport> {
+        let standard_error = self.standard_error(convergence.metric)?;
+        Some(ConvergenceReport {
+            metric: convergence.metric,
+            tolerance: convergence.tolerance,
+            iterations: self.runs,
+            standard_error,
+            converged: standard_error <= convergence.tolerance,
+        })
+    }
+}
+
+/// Iteration numbers to run one after another: all `iterations` at once, or batches of
+/// `CONVERGENCE_BATCH` with `convergence` set
+pub fn iteration_batches(iterations: usize, convergence: Option<&Convergence>) -> impl Iterator<Item = Range<usize>> {
+    let batch = if convergence.is_some() { CONVERGENCE_BATCH } else { iterations.max(1) };
+    (0..iterations).step_by(batch).map(move |start| start..(start + batch).min(iterations))
+}
@@ -0,0 +1,72 @@
+// src/simulator/copy_trading.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+
+/// Several copies of the account trading the same trades (copy trading), or trades sharing
+/// only part of their days, each with its own drawdown and payouts, with the bank accounts
+/// of all copies combined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyTradingReport {
+    pub accounts: u32,
+    /// Share of the days on which each copy trades the shared trades rather than its own draw
+    pub correlation: f64,
+    /// Combined final bank balance of all copies
+    pub mean_combined_balance: f64,
+    pub median_combined_balance: f64,
+    pub p10_combined_balance: f64,
+    pub p90_combined_balance: f64,
+    pub combined_balance_std_dev: f64,
+    /// Standard deviation of the final bank balance of a single copy; the combined one is
+    /// `accounts` times it for copies in lockstep and shrinks towards the square root of
+    /// `accounts` times it as they become independent
+    pub account_balance_std_dev: f64,
+    pub positive_combined_percentage: f64,
+    /// Lowest combined bank balance during the run, mean over runs: the money the copies
+    /// need up front
+    pub mean_lowest_combined_balance: f64,
+    /// Gross (and terminal) payouts of all copies together, mean per run
+    pub mean_gross_payouts: f64,
+    /// Everything the payouts did not cover: account purchases, fees and profit splits
+    pub mean_costs: f64,
+    /// Copies busted per run, on average
+    pub mean_busted_accounts: f64,
+    /// Runs in which every copy busted
+    pub all_busted_percentage: f64,
+}
+
+/// Outcome of one run of the copies
+#[derive(Debug, Clone)]
+pub struct CopyTradingRun {
+    /// Final bank balance of each copy
+    pub account_balances: Vec<f64>,
+    pub combined_balance: f64,
+    pub lowest_combined_balance: f64,
+    pub gross_payouts: f64,
+    pub busted_accounts: u32,
+}
+
+pub fn copy_trading_report(accounts: u32, correlation: f64, runs: &[CopyTradingRun]) -> CopyTradingReport {
+    let count = runs.len().max(1) as f64;
+    let balances = stats::sorted(&runs.iter().map(|run| run.combined_balance).collect::<Vec<_>>());
+    let account_balances: Vec<f64> = runs.iter().flat_map(|run| run.account_balances.iter().copied()).collect();
+    let mean_combined_balance = stats::mean(&balances).unwrap_or(0.0);
+    let mean_gross_payouts = runs.iter().map(|run| run.gross_payouts).sum::<f64>() / count;
+
+    CopyTradingReport {
+        accounts,
+        correlation,
+        mean_combined_balance,
+        median_combined_balance: stats::median(&balances).unwrap_or(0.0),
+        p10_combined_balance: stats::percentile(&balances, 10.0).unwrap_or(0.0),
+        p90_combined_balance: stats::percentile(&balances, 90.0).unwrap_or(0.0),
+        combined_balance_std_dev: stats::std_dev(&balances).unwrap_or(0.0),
+        account_balance_std_dev: stats::std_dev(&account_balances).unwrap_or(0.0),
+        positive_combined_percentage: balances.iter().filter(|&&balance| balance > 0.0).count() as f64 / count * 100.0,
+        mean_lowest_combined_balance: runs.iter().map(|run| run.lowest_combined_balance).sum::<f64>() / count,
+        mean_gross_payouts,
+        mean_costs: mean_gross_payouts - mean_combined_balance,
+        mean_busted_accounts: runs.iter().map(|run| run.busted_accounts as f64).sum::<f64>() / count,
+        all_busted_percentage: runs.iter().filter(|run| run.busted_accounts == accounts).count() as f64 / count * 100.0,
+    }
+}
@@ -0,0 +1,60 @@
+// src/simulator/difficulty.rs
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+
+use super::prop_account::AccountInfo;
+use super::trader::EndOfGame;
+use super::{run_simulations, SimulationConfig};
+
+// Reference strategy: a strategy without edge, whose brackets are scaled to each account's
+// starting drawdown buffer so that accounts of different sizes are comparable
+const REFERENCE_BRACKET_FRACTION: f64 = 0.1;
+const REFERENCE_WIN_PERCENTAGE: f64 = 50.0;
+const REFERENCE_TRADES_PER_DAY: f64 = 3.0;
+const REFERENCE_DAYS: u64 = 252;
+const REFERENCE_ITERATIONS: usize = 5_000;
+const REFERENCE_SEED: u64 = 0x0D1F_F1C0;
+
+/// How hard an account is to get paid from, comparable across firms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Difficulty {
+    /// Profit target divided by the starting drawdown buffer, for accounts with a target
+    pub target_to_buffer: Option<f64>,
+    /// Percentage of reference strategy runs reaching a first payout within a year of
+    /// trading days, under all of the account's rules (target, consistency, minimum days)
+    pub reference_payout_rate: f64,
+    /// 0 (every reference run gets paid) to 100 (none do)
+    pub score: f64,
+}
+
+fn reference_config(info: &AccountInfo) -> SimulationConfig {
+    let bracket = info.starting_buffer * REFERENCE_BRACKET_FRACTION;
+    SimulationConfig {
+        account_type: info.account_type.clone(),
+        iterations: REFERENCE_ITERATIONS,
+        max_simulation_days: REFERENCE_DAYS,
+        max_payouts: 1,
+        stop_loss: Some(bracket),
+        take_profit: Some(bracket),
+        win_percentage: Some(REFERENCE_WIN_PERCENTAGE),
+        avg_trades_per_day: Some(REFERENCE_TRADES_PER_DAY),
+        round_trip_cost: Some(0.0),
+        seed: Some(REFERENCE_SEED),
+        ..SimulationConfig::default()
+    }
+}
+
+/// Fill in the difficulty of every account in `catalog` by simulating the reference strategy
+/// on each of them
+pub fn rate_difficulty(catalog: &mut [AccountInfo]) -> Result<(), Box<dyn Error>> {
+    let results = run_simulations(catalog.iter().map(reference_config).collect())?;
+    for (info, result) in catalog.iter_mut().zip(results) {
+        let reference_payout_rate = result.end_state_percentages.get(&EndOfGame::MaxPayouts).copied().unwrap_or(0.0);
+        info.difficulty = Some(Difficulty {
+            target_to_buffer: info.profit_target.map(|target| target / info.starting_buffer),
+            reference_payout_rate,
+            score: 100.0 - reference_payout_rate,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// src/simulator/end_states.rs
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+use super::stats;
+use super::tail::{self, BalancePercentile};
+use super::trader::EndOfGame;
+
+/// Statistics of the runs that ended in one end state, as `condition_end_state` would
+/// report them for that state alone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndStateStats {
+    pub runs: usize,
+    /// Share of all runs ending in this state
+    pub percentage: f64,
+    pub mean_balance: f64,
+    pub median_balance: f64,
+    pub std_dev: f64,
+    pub mean_days: f64,
+    /// Configured percentiles of the final balances of these runs
+    pub percentiles: Vec<BalancePercentile>,
+}
+
+/// Statistics of each end state reached by `runs`, given as (end state, final balance,
+/// simulation length in days)
+pub fn end_state_stats(runs: &[(EndOfGame, f64, u64)], percentiles: &[f64]) -> HashMap<EndOfGame, EndStateStats> {
+    EndOfGame::ALL
+        .into_iter()
+        .filter_map(|end_state| {
+            let mut balances: Vec<f64> = Vec::new();
+            let mut total_days = 0;
+            for (_, balance, days) in runs.iter().filter(|(state, _, _)| *state == end_state) {
+                balances.push(*balance);
+                total_days += days;
+            }
+            let balances = stats::sorted(&balances);
+            let summary = stats::Summary::of_sorted(&balances)?;
+            let n = summary.count;
+            let end_state_stats = EndStateStats {
+                runs: n,
+                percentage: n as f64 / runs.len() as f64 * 100.0,
+                mean_balance: summary.mean,
+                median_balance: summary.median,
+                std_dev: summary.std_dev,
+                mean_days: total_days as f64 / n as f64,
+                percentiles: tail::balance_percentiles(&balances, percentiles),
+            };
+            Some((end_state, end_state_stats))
+        })
+        .collect()
+}
@@ -0,0 +1,36 @@
+// src/simulator/equity.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats::{self, percentile};
+
+/// Percentiles of the bank balance of all runs by trading day, day 0 being right after
+/// the account purchase ("how the spread of outcomes evolves over time")
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EquityCurve {
+    pub days: Vec<u64>,
+    pub p5: Vec<f64>,
+    pub p25: Vec<f64>,
+    pub p50: Vec<f64>,
+    pub p75: Vec<f64>,
+    pub p95: Vec<f64>,
+}
+
+/// Aggregate the daily bank balances of all runs over days `0..=max_days`. A run that
+/// ended early keeps its last balance for the remaining days.
+pub fn equity_curve(paths: &[&[f64]], max_days: u64) -> EquityCurve {
+    let mut curve = EquityCurve::default();
+    if paths.is_empty() {
+        return curve;
+    }
+    for day in 0..=max_days {
+        let balances: Vec<f64> = paths.iter().filter_map(|path| path.get(day as usize).or(path.last()).copied()).collect();
+        let balances = stats::sorted(&balances);
+        curve.days.push(day);
+        curve.p5.push(percentile(&balances, 5.0).unwrap_or_default());
+        curve.p25.push(percentile(&balances, 25.0).unwrap_or_default());
+        curve.p50.push(percentile(&balances, 50.0).unwrap_or_default());
+        curve.p75.push(percentile(&balances, 75.0).unwrap_or_default());
+        curve.p95.push(percentile(&balances, 95.0).unwrap_or_default());
+    }
+    curve
+}
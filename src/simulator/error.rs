@@ -0,0 +1,28 @@
+// src/simulator/error.rs
+use std::error::Error;
+use std::fmt;
+
+/// Errors surfaced by `run_simulation`, split by cause so frontends can react
+/// differently (e.g. the CLI maps them to distinct exit codes).
+#[derive(Debug)]
+pub enum SimulationError {
+    InvalidConfig(String),
+    Data(String),
+    /// The run would exceed a limit set with `set_simulation_limits`
+    LimitExceeded(String),
+    /// The run was stopped with `SimulationHandle::cancel`
+    Cancelled,
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::InvalidConfig(msg) => write!(f, "Invalid configuration: {}", msg),
+            SimulationError::Data(msg) => write!(f, "Data error: {}", msg),
+            SimulationError::LimitExceeded(msg) => write!(f, "Limit exceeded: {}", msg),
+            SimulationError::Cancelled => write!(f, "Simulation cancelled"),
+        }
+    }
+}
+
+impl Error for SimulationError {}
@@ -0,0 +1,76 @@
+// src/simulator/export.rs
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::trader::EndOfGame;
+
+/// File format of the per-iteration export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One row per iteration with a header
+    #[default]
+    Csv,
+    /// A JSON array of one object per iteration
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Csv, ExportFormat::Json];
+
+    /// Format given by the extension of `path`: JSON for `.json`, CSV otherwise
+    pub fn for_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err("Unknown export format (options: csv, json)"),
+        }
+    }
+}
+
+/// Outcome of one iteration, as exported with `output_file`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IterationRecord {
+    pub iteration: usize,
+    pub final_balance: f64,
+    pub end_state: EndOfGame,
+    pub simulation_length: u64,
+    /// Payouts received over the run
+    pub payouts: u32,
+    /// Their total before the profit split and fees
+    pub gross_payouts: f64,
+    /// Simulation day the first evaluation was passed, empty if it never was
+    pub funded_day: Option<u64>,
+    /// Simulation day of the first payout, empty without one
+    pub first_payout_day: Option<u64>,
+}
+
+/// Write `iterations` to `path`, in `format` or else the format of its extension
+pub fn export_iterations(iterations: &[IterationRecord], path: &str, format: Option<ExportFormat>) -> Result<(), Box<dyn Error>> {
+    let contents = match format.unwrap_or_else(|| ExportFormat::for_path(path)) {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for record in iterations {
+                writer.serialize(record)?;
+            }
+            String::from_utf8(writer.into_inner()?)?
+        }
+        ExportFormat::Json => serde_json::to_string_pretty(iterations)?,
+    };
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(())
+}
@@ -0,0 +1,170 @@
+// src/simulator/formatting.rs
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+// Locales supported for number formatting in reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    EnUs,
+    EnGb,
+    DeDe,
+    FrFr,
+    EsEs,
+    ItIt,
+    NlNl,
+    ChDe,
+}
+
+impl Locale {
+    // (thousands separator, decimal separator)
+    fn separators(&self) -> (&'static str, &'static str) {
+        match self {
+            Locale::EnUs | Locale::EnGb => (",", "."),
+            Locale::DeDe | Locale::EsEs | Locale::ItIt | Locale::NlNl => (".", ","),
+            Locale::FrFr => ("\u{202f}", ","),
+            Locale::ChDe => ("'", "."),
+        }
+    }
+
+    // English locales put the currency symbol in front of the amount
+    fn symbol_first(&self) -> bool {
+        matches!(self, Locale::EnUs | Locale::EnGb | Locale::ChDe)
+    }
+}
+
+impl FromStr for Locale {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "en-us" | "en" => Ok(Locale::EnUs),
+            "en-gb" => Ok(Locale::EnGb),
+            "de-de" | "de" => Ok(Locale::DeDe),
+            "fr-fr" | "fr" => Ok(Locale::FrFr),
+            "es-es" | "es" => Ok(Locale::EsEs),
+            "it-it" | "it" => Ok(Locale::ItIt),
+            "nl-nl" | "nl" => Ok(Locale::NlNl),
+            "de-ch" | "ch" => Ok(Locale::ChDe),
+            _ => Err("Unknown locale"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    #[default]
+    Usd,
+    Eur,
+    Gbp,
+    Chf,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 4] = [Currency::Usd, Currency::Eur, Currency::Gbp, Currency::Chf];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+            Currency::Chf => "CHF",
+        }
+    }
+}
+
+impl FromStr for Currency {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "usd" | "$" => Ok(Currency::Usd),
+            "eur" | "€" => Ok(Currency::Eur),
+            "gbp" | "£" => Ok(Currency::Gbp),
+            "chf" => Ok(Currency::Chf),
+            _ => Err("Unknown currency"),
+        }
+    }
+}
+
+/// Formats USD amounts produced by the simulation for display, converting to
+/// the target currency with `conversion_rate` (units of target currency per USD).
+#[derive(Debug, Clone)]
+pub struct MoneyFormatter {
+    pub locale: Locale,
+    pub currency: Currency,
+    pub conversion_rate: f64,
+}
+
+impl Default for MoneyFormatter {
+    fn default() -> Self {
+        MoneyFormatter {
+            locale: Locale::EnUs,
+            currency: Currency::Usd,
+            conversion_rate: 1.0,
+        }
+    }
+}
+
+impl MoneyFormatter {
+    /// Currencies other than USD need a `conversion_rate`; USD amounts are shown as they are
+    /// without one
+    pub fn new(locale: Locale, currency: Currency, conversion_rate: Option<f64>) -> Result<Self, &'static str> {
+        let conversion_rate = match (currency, conversion_rate) {
+            (_, Some(rate)) if rate.is_nan() || rate <= 0.0 => return Err("conversion rate must be positive"),
+            (_, Some(rate)) => rate,
+            (Currency::Usd, None) => 1.0,
+            (_, None) => return Err("a conversion rate is required to report money in a currency other than USD"),
+        };
+        Ok(MoneyFormatter { locale, currency, conversion_rate })
+    }
+
+    /// Format a plain number with the locale's separators and two decimals
+    pub fn format_number(&self, value: f64) -> String {
+        let (thousands_sep, decimal_sep) = self.locale.separators();
+        let formatted = format!("{:.2}", value.abs());
+        let (int_part, frac_part) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+
+        let mut grouped = String::new();
+        for (i, c) in int_part.chars().enumerate() {
+            if i > 0 && (int_part.len() - i) % 3 == 0 {
+                grouped.push_str(thousands_sep);
+            }
+            grouped.push(c);
+        }
+
+        let sign = if value < 0.0 && formatted != "0.00" { "-" } else { "" };
+        format!("{}{}{}{}", sign, grouped, decimal_sep, frac_part)
+    }
+
+    /// Format a USD amount as money in the configured currency and locale
+    pub fn format(&self, usd_amount: f64) -> String {
+        let amount = usd_amount * self.conversion_rate;
+        let number = self.format_number(amount.abs());
+        let sign = if amount < 0.0 && format!("{:.2}", amount.abs()) != "0.00" { "-" } else { "" };
+        if self.locale.symbol_first() {
+            format!("{}{}{}", sign, self.currency.symbol(), number)
+        } else {
+            format!("{}{} {}", sign, number, self.currency.symbol())
+        }
+    }
+
+    /// Formatter for amounts the simulation already converted into `currency`
+    /// (USD results are still converted for display with `conversion_rate`)
+    pub fn for_currency(&self, currency: Currency) -> Self {
+        if currency == Currency::Usd {
+            return self.clone();
+        }
+        MoneyFormatter {
+            locale: self.locale,
+            currency,
+            conversion_rate: 1.0,
+        }
+    }
+
+    /// Like `format`, but always shows the sign (for deltas)
+    pub fn format_signed(&self, usd_amount: f64) -> String {
+        let formatted = self.format(usd_amount);
+        if formatted.starts_with('-') { formatted } else { format!("+{}", formatted) }
+    }
+}
@@ -0,0 +1,35 @@
+// src/simulator/gross.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+
+/// Distribution of the total gross payouts of a run (withdrawn from the prop account, before
+/// the profit split, fees and account costs), to set beside the net final bank balance when
+/// comparing with the gross payout figures firms publish
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrossPayoutStats {
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub p10: f64,
+    pub p90: f64,
+    /// Percentage of runs with at least one payout
+    pub paid_percentage: f64,
+}
+
+/// Build the stats from the total gross payouts of each run
+pub fn gross_payout_stats(gross_payouts: &[f64]) -> GrossPayoutStats {
+    let sorted = stats::sorted(gross_payouts);
+    let (Some(mean), Some(std_dev), Some(median)) = (stats::mean(gross_payouts), stats::std_dev(gross_payouts), stats::median(&sorted)) else {
+        return GrossPayoutStats::default();
+    };
+
+    GrossPayoutStats {
+        mean,
+        median,
+        std_dev,
+        p10: stats::percentile(&sorted, 10.0).unwrap_or_default(),
+        p90: stats::percentile(&sorted, 90.0).unwrap_or_default(),
+        paid_percentage: gross_payouts.iter().filter(|&&payouts| payouts > 0.0).count() as f64 / sorted.len() as f64 * 100.0,
+    }
+}
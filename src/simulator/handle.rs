@@ -0,0 +1,74 @@
+// src/simulator/handle.rs
+use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Control of a simulation running on another thread: ask how far it got with `progress`
+/// or stop it with `cancel`. Clones control the same run.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationHandle {
+    state: Arc<HandleState>,
+}
+
+#[derive(Debug, Default)]
+struct HandleState {
+    cancelled: AtomicBool,
+    completed_iterations: AtomicUsize,
+    total_iterations: AtomicUsize,
+}
+
+/// Monte Carlo iterations a run has completed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    pub completed_iterations: usize,
+    /// 0 until the run has started
+    pub total_iterations: usize,
+}
+
+impl Progress {
+    /// Share of the iterations completed, from 0 to 1
+    pub fn fraction(&self) -> f64 {
+        if self.total_iterations == 0 {
+            0.0
+        } else {
+            self.completed_iterations as f64 / self.total_iterations as f64
+        }
+    }
+}
+
+impl SimulationHandle {
+    pub fn new() -> Self {
+        SimulationHandle::default()
+    }
+
+    /// Stop the run: iterations not started yet are skipped and the run returns
+    /// `SimulationError::Cancelled`
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            completed_iterations: self.state.completed_iterations.load(Ordering::Relaxed),
+            total_iterations: self.state.total_iterations.load(Ordering::Relaxed),
+        }
+    }
+
+    // Count the iterations of a run about to start
+    pub(crate) fn start(&self, iterations: usize) {
+        self.state.total_iterations.fetch_add(iterations, Ordering::Relaxed);
+    }
+
+    // Uncount iterations of a run that stopped before running them
+    pub(crate) fn skip(&self, iterations: usize) {
+        self.state.total_iterations.fetch_sub(iterations, Ordering::Relaxed);
+    }
+
+    pub(crate) fn complete_iteration(&self) {
+        self.state.completed_iterations.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,86 @@
+// src/simulator/hedge.rs
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+use super::trade_data::Trade;
+
+/// Two accounts at different firms trading opposite (or otherwise correlated) trade
+/// streams, with both bank accounts combined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgeReport {
+    pub hedge_account_type: String,
+    /// Correlation between the daily trades of the two accounts
+    pub correlation: f64,
+    /// Combined final bank balance of both accounts
+    pub mean_combined_balance: f64,
+    pub median_combined_balance: f64,
+    pub positive_combined_percentage: f64,
+    /// Gross (and terminal) payouts of both accounts together, mean per run
+    pub mean_gross_payouts: f64,
+    /// Everything the payouts did not cover: account purchases, fees and profit splits
+    pub mean_costs: f64,
+    /// Runs in which each account, or both, busted
+    pub primary_busted_percentage: f64,
+    pub hedge_busted_percentage: f64,
+    pub both_busted_percentage: f64,
+}
+
+/// Outcome of one run of the pair
+#[derive(Debug, Clone)]
+pub struct HedgeRun {
+    pub combined_balance: f64,
+    pub gross_payouts: f64,
+    pub primary_busted: bool,
+    pub hedge_busted: bool,
+}
+
+/// Trades of the hedge account for a day on which the primary account trades `trades`.
+/// A negative correlation takes the opposite side of the primary's trades on that share
+/// of the days, a positive one copies them, and the other days trade `independent`.
+/// Both sides pay `round_trip_cost` on every trade.
+pub fn hedge_trades(
+    trades: &[Trade],
+    independent: Vec<Trade>,
+    correlation: f64,
+    round_trip_cost: f64,
+    rng: &mut impl Rng,
+) -> Vec<Trade> {
+    if !rng.gen_bool(correlation.abs()) {
+        return independent;
+    }
+    if correlation > 0.0 {
+        return trades.to_vec();
+    }
+    trades
+        .iter()
+        .map(|trade| {
+            let return_value = -(trade.return_value + round_trip_cost) - round_trip_cost;
+            // The favorable excursion of the primary's trade is not in the data, so the
+            // opposite trade's worst point is its own loss, if any
+            Trade { return_value, max_opposite_excursion: return_value.min(0.0) }
+        })
+        .collect()
+}
+
+pub fn hedge_report(hedge_account_type: &str, correlation: f64, runs: &[HedgeRun]) -> HedgeReport {
+    let count = runs.len().max(1) as f64;
+    let percentage = |matches: usize| matches as f64 / count * 100.0;
+    let balances: Vec<f64> = runs.iter().map(|run| run.combined_balance).collect();
+    let balances = stats::sorted(&balances);
+    let mean_combined_balance = balances.iter().sum::<f64>() / count;
+    let mean_gross_payouts = runs.iter().map(|run| run.gross_payouts).sum::<f64>() / count;
+
+    HedgeReport {
+        hedge_account_type: hedge_account_type.to_string(),
+        correlation,
+        mean_combined_balance,
+        median_combined_balance: stats::median(&balances).unwrap_or(0.0),
+        positive_combined_percentage: percentage(balances.iter().filter(|&&balance| balance > 0.0).count()),
+        mean_gross_payouts,
+        mean_costs: mean_gross_payouts - mean_combined_balance,
+        primary_busted_percentage: percentage(runs.iter().filter(|run| run.primary_busted).count()),
+        hedge_busted_percentage: percentage(runs.iter().filter(|run| run.hedge_busted).count()),
+        both_busted_percentage: percentage(runs.iter().filter(|run| run.primary_busted && run.hedge_busted).count()),
+    }
+}
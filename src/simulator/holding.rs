@@ -0,0 +1,135 @@
+// src/simulator/holding.rs
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::trade_data::Trade;
+
+/// What to do with CSV trades that break the holding rules
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldingAction {
+    /// Refuse to load the data, listing the offending trades
+    #[default]
+    Fail,
+    /// Leave the trades out of the resampling pool
+    Exclude,
+    /// Keep the trades as if closed at the session close, less a haircut
+    Truncate,
+}
+
+impl HoldingAction {
+    pub const ALL: [HoldingAction; 3] = [HoldingAction::Fail, HoldingAction::Exclude, HoldingAction::Truncate];
+}
+
+impl FromStr for HoldingAction {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fail" => Ok(HoldingAction::Fail),
+            "exclude" => Ok(HoldingAction::Exclude),
+            "truncate" => Ok(HoldingAction::Truncate),
+            _ => Err("Unknown holding action (options: fail, exclude, truncate)"),
+        }
+    }
+}
+
+/// Holding restrictions of intraday-only accounts, checked against the exit timestamps of
+/// CSV trades (an optional fourth column)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HoldingRules {
+    /// Trades must be closed by the session close of the day they were opened
+    pub no_overnight: bool,
+    /// Trades must not be held over a Saturday or Sunday
+    pub no_weekend: bool,
+    pub action: HoldingAction,
+    /// Daily session close, in the time zone of the CSV timestamps
+    pub session_close: NaiveTime,
+    /// Percentage of a truncated trade's absolute return deducted from it, since its P&L at
+    /// the session close is not in the data
+    pub truncate_haircut_percent: f64,
+}
+
+impl Default for HoldingRules {
+    fn default() -> Self {
+        HoldingRules {
+            no_overnight: false,
+            no_weekend: false,
+            action: HoldingAction::default(),
+            session_close: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+            truncate_haircut_percent: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldingViolation {
+    Overnight,
+    Weekend,
+}
+
+impl fmt::Display for HoldingViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoldingViolation::Overnight => write!(f, "held past the session close"),
+            HoldingViolation::Weekend => write!(f, "held over a weekend"),
+        }
+    }
+}
+
+impl HoldingRules {
+    /// The rule a trade held from `entry` to `exit` breaks, if any
+    pub fn violation(&self, entry: NaiveDateTime, exit: NaiveDateTime) -> Option<HoldingViolation> {
+        let mut day = entry.date();
+        while day < exit.date() {
+            day += Duration::days(1);
+            if self.no_weekend && matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                return Some(HoldingViolation::Weekend);
+            }
+        }
+        if self.no_overnight {
+            let close = entry.date().and_time(self.session_close);
+            let first_close = if entry < close { close } else { close + Duration::days(1) };
+            if exit > first_close {
+                return Some(HoldingViolation::Overnight);
+            }
+        }
+        None
+    }
+
+    /// A trade closed early at the session close
+    pub fn truncate(&self, trade: Trade) -> Trade {
+        let return_value = trade.return_value - trade.return_value.abs() * self.truncate_haircut_percent / 100.0;
+        Trade { return_value, max_opposite_excursion: trade.max_opposite_excursion.min(return_value) }
+    }
+}
+
+/// Trades found to break the holding rules while loading the CSV
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HoldingReport {
+    pub action: HoldingAction,
+    pub overnight_trades: usize,
+    pub weekend_trades: usize,
+    /// Trades without an exit timestamp, which could not be checked
+    pub unchecked_trades: usize,
+}
+
+impl HoldingReport {
+    pub fn new(action: HoldingAction) -> Self {
+        HoldingReport { action, ..Default::default() }
+    }
+
+    pub fn record(&mut self, violation: HoldingViolation) {
+        match violation {
+            HoldingViolation::Overnight => self.overnight_trades += 1,
+            HoldingViolation::Weekend => self.weekend_trades += 1,
+        }
+    }
+
+    pub fn violations(&self) -> usize {
+        self.overnight_trades + self.weekend_trades
+    }
+}
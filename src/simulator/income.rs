@@ -0,0 +1,165 @@
+// src/simulator/income.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+use super::trader::TRADING_DAYS_PER_MONTH;
+
+/// How well the simulated net cash flow (payouts minus account purchases) covers a
+/// monthly income requirement. Months are blocks of `TRADING_DAYS_PER_MONTH` trading
+/// days; a run that has ended earns nothing in the remaining months.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomeReport {
+    pub monthly_target: f64,
+    /// Percentage of runs netting at least the target, for each complete month of the simulation
+    pub met_percentage_by_month: Vec<f64>,
+    /// Percentage of runs netting at least the target in every month
+    pub met_all_months_percentage: f64,
+    /// Shortfall per run and month (target minus net income, zero when the target is met)
+    pub mean_shortfall: f64,
+    pub median_shortfall: f64,
+    pub p90_shortfall: f64,
+    /// Mean of the summed shortfall over all months of a run
+    pub mean_total_shortfall: f64,
+}
+
+/// Number of complete months in a simulation of `max_simulation_days` trading days (at least one)
+pub fn simulated_months(max_simulation_days: u64) -> usize {
+    ((max_simulation_days / TRADING_DAYS_PER_MONTH) as usize).max(1)
+}
+
+/// Build the report from the monthly net income of each run
+pub fn income_report(monthly_incomes: &[&[f64]], monthly_target: f64, months: usize) -> IncomeReport {
+    let runs = monthly_incomes.len().max(1) as f64;
+    let mut met_by_month = vec![0usize; months];
+    let mut met_all_months = 0usize;
+    let mut shortfalls = Vec::with_capacity(monthly_incomes.len() * months);
+    let mut total_shortfall = 0.0;
+
+    for incomes in monthly_incomes {
+        let mut met_every_month = true;
+        for (month, met) in met_by_month.iter_mut().enumerate() {
+            let income = incomes.get(month).copied().unwrap_or(0.0);
+            let shortfall = (monthly_target - income).max(0.0);
+            if income >= monthly_target {
+                *met += 1;
+            } else {
+                met_every_month = false;
+            }
+            total_shortfall += shortfall;
+            shortfalls.push(shortfall);
+        }
+        if met_every_month {
+            met_all_months += 1;
+        }
+    }
+
+    let shortfalls = stats::sorted(&shortfalls);
+
+    IncomeReport {
+        monthly_target,
+        met_percentage_by_month: met_by_month.iter().map(|&met| met as f64 / runs * 100.0).collect(),
+        met_all_months_percentage: met_all_months as f64 / runs * 100.0,
+        mean_shortfall: shortfalls.iter().sum::<f64>() / shortfalls.len().max(1) as f64,
+        median_shortfall: stats::median(&shortfalls).unwrap_or(0.0),
+        p90_shortfall: stats::percentile(&shortfalls, 90.0).unwrap_or(0.0),
+        mean_total_shortfall: total_shortfall / runs,
+    }
+}
+
+/// Long-run income of buying a new account whenever the previous one ends, once the
+/// purchase, bust and payout cycle has reached equilibrium. Each run is one cycle; by the
+/// renewal-reward theorem the income rate is the mean net result of a cycle over its mean
+/// length, and the monthly income is approximately normal around it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StationaryIncome {
+    pub mean_monthly_income: f64,
+    pub monthly_std_dev: f64,
+    /// 90% band of the monthly income (5th and 95th percentiles of the normal approximation)
+    pub p5_monthly_income: f64,
+    pub p95_monthly_income: f64,
+    /// Mean trading days an account lasts, including the day it was lost
+    pub mean_account_lifetime_days: f64,
+    /// Accounts bought per month in equilibrium
+    pub accounts_per_month: f64,
+}
+
+// 95th percentile of the standard normal distribution
+const Z_95: f64 = 1.644_853_626_951_472_2;
+
+/// Estimate from the net result and length in trading days of every run
+pub fn stationary_income(cycles: &[(f64, u64)]) -> Option<StationaryIncome> {
+    let total_days: u64 = cycles.iter().map(|&(_, days)| days).sum();
+    if total_days == 0 {
+        return None;
+    }
+    let runs = cycles.len() as f64;
+    let mean_days = total_days as f64 / runs;
+    let daily_rate = cycles.iter().map(|&(net, _)| net).sum::<f64>() / total_days as f64;
+    // Variance of the income over `t` days tends to t * Var(R - rate * L) / E[L]
+    let residual_variance = cycles
+        .iter()
+        .map(|&(net, days)| (net - daily_rate * days as f64).powi(2))
+        .sum::<f64>()
+        / runs;
+    Some(equilibrium_income(daily_rate, residual_variance, mean_days))
+}
+
+/// Sums over the cycles that `stationary_income` needs, for runs that are not kept
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CycleSums {
+    runs: usize,
+    net: f64,
+    days: u64,
+    net_squared: f64,
+    net_days: f64,
+    days_squared: f64,
+}
+
+impl CycleSums {
+    pub fn add(&mut self, net: f64, days: u64) {
+        self.runs += 1;
+        self.net += net;
+        self.days += days;
+        let days = days as f64;
+        self.net_squared += net * net;
+        self.net_days += net * days;
+        self.days_squared += days * days;
+    }
+
+    pub fn merge(&mut self, other: &CycleSums) {
+        self.runs += other.runs;
+        self.net += other.net;
+        self.days += other.days;
+        self.net_squared += other.net_squared;
+        self.net_days += other.net_days;
+        self.days_squared += other.days_squared;
+    }
+
+    /// `stationary_income` of the cycles added
+    pub fn stationary_income(&self) -> Option<StationaryIncome> {
+        if self.days == 0 {
+            return None;
+        }
+        let runs = self.runs as f64;
+        let daily_rate = self.net / self.days as f64;
+        // The mean of (R - rate * L)^2, expanded
+        let residual_variance =
+            (self.net_squared - 2.0 * daily_rate * self.net_days + daily_rate * daily_rate * self.days_squared) / runs;
+        Some(equilibrium_income(daily_rate, residual_variance.max(0.0), self.days as f64 / runs))
+    }
+}
+
+fn equilibrium_income(daily_rate: f64, residual_variance: f64, mean_days: f64) -> StationaryIncome {
+    let month = TRADING_DAYS_PER_MONTH as f64;
+    let mean_monthly_income = daily_rate * month;
+    let monthly_std_dev = (month * residual_variance / mean_days).sqrt();
+
+    StationaryIncome {
+        mean_monthly_income,
+        monthly_std_dev,
+        p5_monthly_income: mean_monthly_income - Z_95 * monthly_std_dev,
+        p95_monthly_income: mean_monthly_income + Z_95 * monthly_std_dev,
+        mean_account_lifetime_days: mean_days,
+        accounts_per_month: month / mean_days,
+    }
+}
@@ -0,0 +1,109 @@
+// src/simulator/insurance.rs
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+/// Busts an insurance product covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsuranceCoverage {
+    /// Busting the evaluation
+    #[default]
+    Evaluation,
+    /// Busting the funded account
+    Funded,
+    /// Either
+    Any,
+}
+
+impl InsuranceCoverage {
+    pub const ALL: [InsuranceCoverage; 3] = [InsuranceCoverage::Evaluation, InsuranceCoverage::Funded, InsuranceCoverage::Any];
+
+    pub fn covers(&self, funded: bool) -> bool {
+        match self {
+            InsuranceCoverage::Evaluation => !funded,
+            InsuranceCoverage::Funded => funded,
+            InsuranceCoverage::Any => true,
+        }
+    }
+}
+
+impl FromStr for InsuranceCoverage {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "evaluation" => Ok(InsuranceCoverage::Evaluation),
+            "funded" => Ok(InsuranceCoverage::Funded),
+            "any" => Ok(InsuranceCoverage::Any),
+            _ => Err("Unknown insurance coverage (options: evaluation, funded, any)"),
+        }
+    }
+}
+
+/// Add-on sold with the account (drawdown insurance, reset pass): after a covered bust
+/// the trader continues on a new evaluation of the same account type instead of the run
+/// ending
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Insurance {
+    /// Upfront price in USD, paid with the account purchase
+    pub cost: f64,
+    /// Busts replaced by a new evaluation
+    pub replacements: u32,
+    pub covers: InsuranceCoverage,
+    /// Price in USD of each replacement, e.g. a discounted reset (0 if fully covered)
+    pub replacement_fee: f64,
+}
+
+impl Default for Insurance {
+    fn default() -> Self {
+        Insurance {
+            cost: 0.0,
+            replacements: 1,
+            covers: InsuranceCoverage::default(),
+            replacement_fee: 0.0,
+        }
+    }
+}
+
+/// Runs with the insurance compared with the same runs without it (same seed and trades)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsuranceReport {
+    /// Upfront price of the insurance, in the result currency
+    pub cost: f64,
+    /// Runs that used at least one replacement
+    pub used_percentage: f64,
+    pub mean_replacements_used: f64,
+    pub mean_balance_insured: f64,
+    pub mean_balance_uninsured: f64,
+    /// Mean final balance gained by buying the insurance; positive if it is worth its price
+    pub mean_balance_change: f64,
+    pub busted_percentage_insured: f64,
+    pub busted_percentage_uninsured: f64,
+}
+
+/// Outcome of one run, with or without the insurance
+#[derive(Debug, Clone)]
+pub struct InsuranceRun {
+    pub final_balance: f64,
+    pub busted: bool,
+    pub replacements_used: u32,
+}
+
+pub fn insurance_report(insurance: &Insurance, insured: &[InsuranceRun], uninsured: &[InsuranceRun]) -> InsuranceReport {
+    let mean = |runs: &[InsuranceRun], value: fn(&InsuranceRun) -> f64| {
+        runs.iter().map(value).sum::<f64>() / runs.len().max(1) as f64
+    };
+    let mean_balance_insured = mean(insured, |run| run.final_balance);
+    let mean_balance_uninsured = mean(uninsured, |run| run.final_balance);
+    InsuranceReport {
+        cost: insurance.cost,
+        used_percentage: mean(insured, |run| if run.replacements_used > 0 { 100.0 } else { 0.0 }),
+        mean_replacements_used: mean(insured, |run| run.replacements_used as f64),
+        mean_balance_insured,
+        mean_balance_uninsured,
+        mean_balance_change: mean_balance_insured - mean_balance_uninsured,
+        busted_percentage_insured: mean(insured, |run| if run.busted { 100.0 } else { 0.0 }),
+        busted_percentage_uninsured: mean(uninsured, |run| if run.busted { 100.0 } else { 0.0 }),
+    }
+}
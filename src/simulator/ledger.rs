@@ -0,0 +1,87 @@
+// src/simulator/ledger.rs
+use serde::{Serialize, Deserialize};
+
+/// Where the money of a run went, in the result currency. The final bank balance is
+/// `gross_payouts + terminal_payouts - profit_split - payout_fees - conversion_fees - eval_fees - reset_fees - activation_fees - platform_fees - insurance_fees`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    /// Evaluation (combine) purchases
+    pub eval_fees: f64,
+    /// Evaluation resets and repurchases
+    pub reset_fees: f64,
+    /// Funded account activation and monthly fees
+    pub activation_fees: f64,
+    /// Payouts withdrawn from the prop account, before any deductions
+    pub gross_payouts: f64,
+    /// Balance credited as withdrawn when a run times out (see `terminal_payout_fraction`)
+    #[serde(default)]
+    pub terminal_payouts: f64,
+    /// Share of the payouts retained by the firm
+    pub profit_split: f64,
+    /// Wire and payment processor fees on payouts
+    pub payout_fees: f64,
+    /// FX fees on currency conversions
+    pub conversion_fees: f64,
+    /// Platform and data feed subscriptions paid outside the firm
+    #[serde(default)]
+    pub platform_fees: f64,
+    /// Insurance and reset-pass add-ons bought with the account
+    #[serde(default)]
+    pub insurance_fees: f64,
+}
+
+impl Ledger {
+    pub fn net(&self) -> f64 {
+        self.gross_payouts
+            + self.terminal_payouts
+            - self.profit_split
+            - self.payout_fees
+            - self.conversion_fees
+            - self.eval_fees
+            - self.reset_fees
+            - self.activation_fees
+            - self.platform_fees
+            - self.insurance_fees
+    }
+
+    /// Add the amounts of another run, component by component
+    pub fn add(&mut self, other: &Ledger) {
+        self.eval_fees += other.eval_fees;
+        self.reset_fees += other.reset_fees;
+        self.activation_fees += other.activation_fees;
+        self.gross_payouts += other.gross_payouts;
+        self.terminal_payouts += other.terminal_payouts;
+        self.profit_split += other.profit_split;
+        self.payout_fees += other.payout_fees;
+        self.conversion_fees += other.conversion_fees;
+        self.platform_fees += other.platform_fees;
+        self.insurance_fees += other.insurance_fees;
+    }
+
+    /// Component-wise mean over a set of runs
+    pub fn mean<'a>(ledgers: impl Iterator<Item = &'a Ledger>) -> Ledger {
+        let mut total = Ledger::default();
+        let mut count = 0usize;
+        for ledger in ledgers {
+            total.add(ledger);
+            count += 1;
+        }
+        total.divided(count.max(1) as f64)
+    }
+
+    /// Every amount divided by `n`, e.g. the mean of a total over `n` runs
+    pub fn divided(&self, n: f64) -> Ledger {
+        Ledger {
+            eval_fees: self.eval_fees / n,
+            reset_fees: self.reset_fees / n,
+            activation_fees: self.activation_fees / n,
+            gross_payouts: self.gross_payouts / n,
+            terminal_payouts: self.terminal_payouts / n,
+            profit_split: self.profit_split / n,
+            payout_fees: self.payout_fees / n,
+            conversion_fees: self.conversion_fees / n,
+            platform_fees: self.platform_fees / n,
+            insurance_fees: self.insurance_fees / n,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// src/simulator/limits.rs
+use serde::{Serialize, Deserialize};
+use std::sync::RwLock;
+
+use super::{SimulationConfig, SimulationError};
+
+/// Resource limits enforced on every run, set once by the embedding application (e.g. a
+/// hosted web service) so that a single extreme request cannot exhaust its memory.
+/// Limits left at `None` are unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationLimits {
+    pub max_iterations: Option<usize>,
+    /// Trades read from CSV data or generated from bracket parameters
+    pub max_trades: Option<usize>,
+    pub max_simulation_days: Option<u64>,
+    /// Snapshot days, each kept for every iteration
+    pub max_snapshot_days: Option<usize>,
+}
+
+static LIMITS: RwLock<SimulationLimits> = RwLock::new(SimulationLimits {
+    max_iterations: None,
+    max_trades: None,
+    max_simulation_days: None,
+    max_snapshot_days: None,
+});
+
+/// Replace the limits applied to all subsequent runs
+pub fn set_simulation_limits(limits: SimulationLimits) {
+    *LIMITS.write().unwrap() = limits;
+}
+
+pub fn simulation_limits() -> SimulationLimits {
+    LIMITS.read().unwrap().clone()
+}
+
+fn exceeded(what: &str, requested: impl std::fmt::Display, limit: impl std::fmt::Display) -> SimulationError {
+    SimulationError::LimitExceeded(format!("{} {} exceeds the limit of {}", what, requested, limit))
+}
+
+impl SimulationLimits {
+    /// Check a config before any data is loaded
+    pub fn check_config(&self, config: &SimulationConfig) -> Result<(), SimulationError> {
+        if let Some(limit) = self.max_iterations.filter(|&limit| config.iterations > limit) {
+            return Err(exceeded("iterations", config.iterations, limit));
+        }
+        if let Some(limit) = self.max_simulation_days.filter(|&limit| config.max_simulation_days > limit) {
+            return Err(exceeded("max_simulation_days", config.max_simulation_days, limit));
+        }
+        let snapshot_days = config.snapshot_days.as_ref().map_or(0, Vec::len);
+        if let Some(limit) = self.max_snapshot_days.filter(|&limit| snapshot_days > limit) {
+            return Err(exceeded("snapshot days", snapshot_days, limit));
+        }
+        Ok(())
+    }
+
+    pub fn check_trades(&self, trades: usize) -> Result<(), SimulationError> {
+        match self.max_trades {
+            Some(limit) if trades > limit => Err(exceeded("trades", trades, limit)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// src/simulator/metadata.rs
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use super::SimulationConfig;
+
+/// Free-form labels of a run (`name`, `tags` and `notes` of its config), carried into its
+/// result so saved and stored results can be told apart and filtered by tag
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl RunMetadata {
+    pub fn of(config: &SimulationConfig) -> Self {
+        RunMetadata {
+            name: config.name.clone(),
+            tags: config.tags.clone(),
+            notes: config.notes.clone(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.tags.is_empty() && self.notes.is_none()
+    }
+
+    /// True when the run carries every one of `tags`, compared without regard to case
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|wanted| self.tags.iter().any(|tag| tag.trim().eq_ignore_ascii_case(wanted.trim())))
+    }
+}
+
+impl fmt::Display for RunMetadata {
+    // `name [tag, tag]`, without the notes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name.as_deref().unwrap_or("(unnamed)"))?;
+        if !self.tags.is_empty() {
+            write!(f, " [{}]", self.tags.join(", "))?;
+        }
+        Ok(())
+    }
+}
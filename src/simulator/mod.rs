@@ -3,23 +3,122 @@ pub mod trade_data;
 pub mod prop_account;
 pub mod trader;
 pub mod plotting;
+pub mod formatting;
+pub mod error;
+pub mod config_file;
+pub mod saved_result;
+pub mod builder;
+pub mod income;
+pub mod ledger;
+pub mod buffer;
+pub mod snapshot;
+pub mod equity;
+pub mod cohort;
+pub mod bundle;
+pub mod triggers;
+pub mod burn_in;
+pub mod rng;
+pub mod preview;
+pub mod options;
+pub mod limits;
+pub mod calendar;
+pub mod recommend;
+pub mod difficulty;
+pub mod parallel_evals;
+pub mod hedge;
+pub mod copy_trading;
+pub mod holding;
+pub mod news;
+pub mod insurance;
+pub mod trade_filter;
+pub mod gross;
+pub mod periods;
+pub mod handle;
+pub mod anonymize;
+pub mod time_limit;
+pub mod throughput;
+pub mod statement;
+pub mod compare;
+pub mod allocate;
+pub mod tail;
+pub mod reweight;
+pub mod end_states;
+pub mod worst;
+pub mod time_to_money;
+pub mod streaming;
+pub mod convergence;
+pub mod export;
+pub mod stats;
+pub mod trade_cache;
+pub mod metadata;
+pub mod resets;
 
-#[allow(unused_imports)]
-use prop_account::AccountType;
+use prop_account::{account_factory, custom_account_factory, AccountFactory, CustomAccountSpec, DrawdownType, PayoutCadence, ProfitSplit, ProfitableDays, RuleChange, RuleOverrides};
 use serde::{Serialize, Deserialize};
+use chrono::NaiveDate;
 use trade_data::read_csv_from_string;
-pub use trade_data::{read_csv, calculate_trades_per_day, generate_simulated_trades, TradeRecord};
-pub use prop_account::ftt_account::FttAccountType;
-pub use trader::{Trader, EndOfGame};
-pub use plotting::plot_histogram;
-use rand::seq::SliceRandom;
+pub use trade_data::{read_csv, OutcomeChain, TradePool, TradeSampling};
+use trade_data::{generate_simulated_trades_with_rng, BlockCursor, Trade, DEFAULT_BLOCK_LENGTH, SIMULATED_DAYS};
+pub use trader::{Trader, EndOfGame, PayoutFees, PlatformFee, Bankroll, CurrencyConversion, WithdrawalAudit};
+use formatting::Currency;
+pub use error::SimulationError;
+pub use builder::SimulationConfigBuilder;
+use plotting::{compute_histogram, DEFAULT_HISTOGRAM_BINS};
+pub use plotting::HistogramBins;
+pub use income::{IncomeReport, StationaryIncome};
+pub use periods::{PeriodPnlReport, PeriodPnlStats};
+pub use ledger::Ledger;
+pub use buffer::DrawdownBufferStats;
+pub use snapshot::DaySnapshot;
+pub use equity::EquityCurve;
+pub use tail::{BalancePercentile, TailRisk};
+pub use cohort::CohortReport;
+pub use parallel_evals::{EvalAttemptStats, ParallelEvalReport};
+use parallel_evals::EvalAttempts;
+pub use hedge::HedgeReport;
+use hedge::HedgeRun;
+pub use copy_trading::CopyTradingReport;
+use copy_trading::CopyTradingRun;
+pub use holding::{HoldingReport, HoldingRules};
+pub use trade_filter::TradeFilterReport;
+pub use reweight::{OutcomeReweight, ReweightReport};
+pub use end_states::EndStateStats;
+pub use worst::WorstOutcomeReport;
+pub use time_to_money::TimeToMoneyReport;
+use streaming::StreamingStats;
+pub use convergence::{Convergence, ConvergenceReport};
+use convergence::ConvergenceTracker;
+pub use export::{ExportFormat, IterationRecord};
+pub use metadata::RunMetadata;
+pub use gross::GrossPayoutStats;
+pub use handle::{Progress, SimulationHandle};
+pub use news::NewsBlackout;
+pub use insurance::{Insurance, InsuranceReport};
+pub use time_limit::{EvalTimeLimit, EvalTimeLimitReport};
+pub use resets::ResetReport;
+use insurance::InsuranceRun;
+pub use triggers::{RuleTriggerReport, SoftBreachStats};
+pub use burn_in::BurnInComparison;
+pub use rng::RngBackend;
+pub use preview::{preview_trades, PreviewTrade, TradePreview};
+pub use options::{simulation_options, SimulationOptions};
+pub use limits::{set_simulation_limits, simulation_limits, SimulationLimits};
+pub use throughput::{measure_throughput, Throughput};
+pub use recommend::{recommend_plans, Plan, RecommendOptions};
+pub use difficulty::{rate_difficulty, Difficulty};
+use rng::{RngStream, SimulationRng};
+use trade_cache::TradeCache;
+use triggers::RuleTriggers;
+use snapshot::AccountSnapshot;
+use rand::Rng;
 use rayon::prelude::*;
 use std::collections::HashMap;
 use std::error::Error;
-use std::str::FromStr;
+use std::sync::Arc;
 use log::info;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimulationConfig {
     pub csv_file: Option<String>,
     pub csv_data: Option<String>,
@@ -35,16 +134,179 @@ pub struct SimulationConfig {
     pub max_simulation_days: u64,
     pub max_payouts: u8,
     pub account_type: String,
+    /// Rules of an account that is not built in, simulated instead of `account_type`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub custom_account: Option<CustomAccountSpec>,
     pub multiplier: f64,
     pub histogram: bool,
     pub histogram_file: Option<String>,
+    /// File for the outcome of every iteration, kept in `SimulationResult::iterations`.
+    /// Like `histogram_file`, the frontends write it (see `export_iterations`).
+    pub output_file: Option<String>,
+    /// Format of `output_file`, by default that of its extension
+    pub output_format: Option<ExportFormat>,
+    /// End states the conditioned statistics cover: `All`, one state, a list of states
+    /// (`["Busted", "TimeOut"]` or `"Busted,TimeOut"`) or exclusions (`"!Busted"`)
+    #[serde(deserialize_with = "trader::deserialize_condition_end_state")]
     pub condition_end_state: String,
+    /// Number of bins for the raw histogram returned in the result (default 50)
+    pub histogram_bins: Option<usize>,
+    /// Return this many representative final balances (evenly spaced quantiles) for client-side charting
+    pub balance_sample_size: Option<usize>,
+    /// Fixed fee charged on each payout (wire/processor fee)
+    pub payout_fee_fixed: Option<f64>,
+    /// Percentage of each payout charged as a fee, e.g. 2.5 for 2.5%
+    pub payout_fee_percent: Option<f64>,
+    /// Home currency of the trader; costs, fees and payouts are converted into it (default USD)
+    pub currency: Option<Currency>,
+    /// Units of `currency` per USD
+    pub conversion_rate: Option<f64>,
+    /// Daily conversion rates by simulation day, overriding `conversion_rate` (the last rate holds afterwards)
+    pub conversion_rate_series: Option<Vec<f64>>,
+    /// FX fee charged on every conversion (account purchases and payouts), e.g. 1.5 for 1.5%
+    pub conversion_fee_percent: Option<f64>,
+    /// Monthly net income the trader needs; enables the `income` report
+    pub monthly_income_target: Option<f64>,
+    /// Trading days at which to snapshot the state of all runs, e.g. [30, 90]
+    pub snapshot_days: Option<Vec<u64>>,
+    /// Track the bank balance of every run per trading day and return its percentiles
+    /// in `equity_curve` (memory grows with iterations times simulation days)
+    pub equity_curve: bool,
+    /// Gather the statistics run by run instead of keeping every run, so that millions of
+    /// iterations run in constant memory (see `streaming::StreamingStats`)
+    pub streaming_stats: bool,
+    /// Stop running iterations once the standard error of the mean balance or of the bust
+    /// percentage is within a tolerance, checked every `convergence::CONVERGENCE_BATCH`
+    /// iterations; `iterations` is then the most to run
+    pub convergence: Option<Convergence>,
+    /// Percentiles of the final balance to report, e.g. [1, 5, 95, 99] (default 1, 5, 10, 90, 95, 99)
+    pub percentiles: Option<Vec<f64>>,
+    /// Confidence level in percent of the Value-at-Risk and Conditional VaR (default 95)
+    pub var_confidence: Option<f64>,
+    /// Cohort mode: also simulate buying a new account every this many trading days
+    pub cohort_interval_days: Option<u64>,
+    /// Also compare buying this many evaluations at once, all trading the same trades and
+    /// keeping the first to pass, with buying up to as many one after another
+    pub parallel_evals: Option<u32>,
+    /// Hedge mode: also simulate a second account of this type trading against the primary one
+    pub hedge_account_type: Option<String>,
+    /// Correlation (-1 to 1) between the daily trades of the two hedge accounts; -1 (the
+    /// default) takes the opposite side of every trade
+    pub hedge_correlation: Option<f64>,
+    /// Copy trading: also simulate this many copies of the account trading the same trades,
+    /// each with its own drawdown and payouts, and combine their bank balances
+    pub copied_accounts: Option<u32>,
+    /// Share (0 to 1) of the days on which each copy trades the shared trades rather than
+    /// its own draw; 1 (the default) copies every trade
+    pub copy_correlation: Option<f64>,
+    /// No-overnight/no-weekend rules checked against the exit times of CSV trades
+    pub holding_rules: Option<HoldingRules>,
+    /// Expression selecting the CSV trades that enter the resampling pool, over the CSV
+    /// columns and the entry time (e.g. `setup == "ORB" && dow != "Fri"`)
+    pub trade_filter: Option<String>,
+    /// Change the chance of resampling trades by their outcome, e.g. halve it for the top 1%
+    /// of winners to see what happens if the biggest winners do not repeat
+    pub outcome_reweighting: Option<Vec<OutcomeReweight>>,
+    /// High-impact news days on which no trades are taken or trade variance is inflated;
+    /// needs `purchase_date` to place the simulated days on the calendar
+    pub news_blackout: Option<NewsBlackout>,
+    /// Seed for the random number generator; runs with the same seed and inputs give identical results
+    pub seed: Option<u64>,
+    /// Account rule changes at given simulation days or payout counts
+    pub rule_timeline: Option<Vec<RuleChange>>,
+    /// Keep trading after `max_payouts` instead of ending the run; `max_payouts` becomes a reported milestone
+    pub continue_after_max_payouts: bool,
+    /// Record every withdrawal with the rule that set its amount in the iteration summaries
+    /// of `run_simulations_streaming`
+    pub audit_withdrawals: bool,
+    /// Personal daily stop as a percentage of the prior day's closing balance, e.g. 2.0 for 2%
+    pub daily_stop_loss_percent: Option<f64>,
+    /// Nominal starting balance of the account (e.g. 50000), required by percentage-based loss limits
+    pub account_size: Option<f64>,
+    /// Contracts the trades of the data were taken with. Accounts with a scaling plan scale
+    /// each trade down to the share of them the plan allows at the current balance.
+    pub traded_contracts: Option<u32>,
+    /// Override how the account's drawdown trails: end-of-day balances, intraday highs or static
+    pub drawdown_type: Option<DrawdownType>,
+    /// Override how often the firm pays out; built-in accounts default to the firm's real cadence
+    pub payout_cadence: Option<PayoutCadence>,
+    /// Override the trading days between requesting a payout and receiving it; built-in
+    /// accounts pay out the same day
+    pub payout_delay_days: Option<u64>,
+    /// Override the chance between 0 and 1 that the firm denies a requested payout; built-in
+    /// accounts never do. A denied payout stays on the account and the payout counters start over.
+    pub payout_denial_probability: Option<f64>,
+    /// Require this many profitable days within each payout period, e.g.
+    /// `{ days = 5, min_profit = 100.0 }`, on top of the account's own payout rules
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub min_profitable_days: Option<ProfitableDays>,
+    /// Override the share of each payout the trader keeps, e.g. `{ trader_percent = 80.0 }`;
+    /// built-in accounts default to the firm's real split
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub profit_split: Option<ProfitSplit>,
+    /// How the trades of each simulated day are drawn from the trade data; also read as
+    /// `day_sampling`, with `"historical"` for whole historical days
+    #[serde(alias = "day_sampling")]
+    pub trade_sampling: TradeSampling,
+    /// Trades per block of block trade sampling, `DEFAULT_BLOCK_LENGTH` if not set
+    pub block_length: Option<usize>,
+    /// Fraction (0 to 1) of the withdrawable prop balance credited as a final payout when a
+    /// run times out, assuming the trader would eventually withdraw it
+    pub terminal_payout_fraction: Option<f64>,
+    /// Fixed monthly platform or data feed cost in USD, paid from the bank account
+    pub platform_fee_monthly: Option<f64>,
+    /// Only pay the platform fee in months the account is funded
+    pub platform_fee_funded_only: bool,
+    /// Personal money available for account purchases and fees, in the home currency. A
+    /// fee that would take it (plus the cash flow so far) below `bankroll_floor` ends the
+    /// run as `OutOfFunds`.
+    pub bankroll: Option<f64>,
+    /// Lowest balance the bankroll may be drawn down to (default 0)
+    pub bankroll_floor: Option<f64>,
+    /// Insurance or reset-pass add-on bought with the account; the result compares the
+    /// runs with and without it
+    pub insurance: Option<Insurance>,
+    /// Calendar days allowed to pass the evaluation, with optional paid extensions; an
+    /// evaluation that runs out of time is reset or ends the run as `EvalExpired`
+    pub eval_time_limit: Option<EvalTimeLimit>,
+    /// New evaluations the trader buys after busts (that the insurance does not cover)
+    /// before the run ends as `Busted`
+    pub resets_allowed: Option<u8>,
+    /// USD paid for each reset (the account price if not set)
+    pub reset_cost: Option<f64>,
+    /// Date the account was bought. Trading starts on the first weekday from it and the
+    /// platform fee is billed on each monthly anniversary of it.
+    pub purchase_date: Option<NaiveDate>,
+    /// USD actually paid for the account (e.g. a promo price) instead of its list price.
+    /// In cohort mode only the first account is bought at this price.
+    pub purchase_price: Option<f64>,
+    /// Exclude the first N trades of the data from the resampling pool
+    pub burn_in_trades: Option<usize>,
+    /// Exclude the first N trading days of the data from the resampling pool
+    pub burn_in_days: Option<usize>,
+    /// Random number generator backend; all but `thread` can be seeded
+    pub rng_backend: RngBackend,
+    /// Name of the run, e.g. the experiment it belongs to; copied into the result
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+    /// Labels to find the run by later, e.g. `["orb", "v2"]`; copied into the result
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    /// Free-form notes on the run; copied into the result
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SimulationResult {
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub final_balances: Vec<f64>,
+    /// Total gross payouts of the same runs as `final_balances`, in the same order
+    #[serde(skip)]
+    pub final_gross_payouts: Vec<f64>,
+    /// Outcome of every iteration in iteration order, when `output_file` is set
+    #[serde(skip)]
+    pub iterations: Vec<IterationRecord>,
     pub mean_balance: f64,
     pub median_balance: f64,
     pub std_dev: f64,
@@ -53,258 +315,1431 @@ pub struct SimulationResult {
     pub mad_median: f64,
     pub mean_days: f64,
     pub end_state_percentages: HashMap<EndOfGame, f64>,
+    /// Statistics of every end state reached, whatever `condition_end_state` selects
+    #[serde(default)]
+    pub stats_by_end_state: HashMap<EndOfGame, EndStateStats>,
     pub positive_balance_percentage: f64, 
-    #[cfg(feature = "web")]
-    pub histogram_plotly_json: Option<String>,
+    /// Configured percentiles of the final balances
+    #[serde(default)]
+    pub percentiles: Vec<BalancePercentile>,
+    /// Value-at-Risk and Conditional VaR of the final balances
+    #[serde(default)]
+    pub tail_risk: TailRisk,
+    /// Account simulated, by its registered name (`company:account_type`), whichever alias
+    /// `account_type` used
+    #[serde(default)]
+    pub account_type: String,
+    /// Currency of all amounts in the result
+    #[serde(default)]
+    pub currency: Currency,
+    /// Mean total payout fees paid per iteration
+    #[serde(default)]
+    pub mean_payout_fees: f64,
+    /// Mean total FX conversion fees paid per iteration
+    #[serde(default)]
+    pub mean_conversion_fees: f64,
+    /// Mean final balance broken down into fees, payouts and deductions
+    #[serde(default)]
+    pub ev_decomposition: Ledger,
+    /// Distribution of the gross payouts per run, beside the net final balances above
+    #[serde(default)]
+    pub gross_payouts: GrossPayoutStats,
+    /// How often each personal and firm rule fired, per 100 simulated days over all runs
+    #[serde(default)]
+    pub rule_triggers: RuleTriggerReport,
+    /// Soft breaches of the firm daily loss limit per run over all runs, when any run had one
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub soft_breaches: Option<SoftBreachStats>,
+    /// Trading P&L per week and month of trading days
+    #[serde(default)]
+    pub period_pnl: PeriodPnlReport,
+    /// Distribution of the worst trade and worst day of each run
+    #[serde(default)]
+    pub worst_outcomes: WorstOutcomeReport,
+    /// Distribution of the day each run was funded and received its first payout
+    #[serde(default)]
+    pub time_to_money: TimeToMoneyReport,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub histogram: Option<HistogramBins>,
+    /// Histogram of the gross payouts per run, when `histogram` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub gross_histogram: Option<HistogramBins>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub balance_sample: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub income: Option<IncomeReport>,
+    /// Long-run monthly income of buying a new account whenever one ends
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stationary_income: Option<StationaryIncome>,
+    /// Minimum drawdown buffer reached by runs that were not busted
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub drawdown_buffer: Option<DrawdownBufferStats>,
+    /// State of all runs at each of the configured `snapshot_days`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub snapshots: Vec<DaySnapshot>,
+    /// Percentiles of the bank balance by trading day, when `equity_curve` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub equity_curve: Option<EquityCurve>,
+    /// Cash flow of staggered accounts when `cohort_interval_days` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cohort: Option<CohortReport>,
+    /// Parallel versus sequential evaluation attempts when `parallel_evals` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub parallel_evals: Option<ParallelEvalReport>,
+    /// Combined outcome of the primary and hedge accounts when `hedge_account_type` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub hedge: Option<HedgeReport>,
+    /// Combined outcome of the copies when `copied_accounts` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub copy_trading: Option<CopyTradingReport>,
+    /// CSV trades found to break the holding rules and what was done with them
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub holding: Option<HoldingReport>,
+    /// CSV trades kept and left out by `trade_filter`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub trade_filter: Option<TradeFilterReport>,
+    /// Trades in each bucket of `outcome_reweighting` and the mean return it leaves
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub outcome_reweighting: Option<ReweightReport>,
+    /// Value of the insurance add-on when `insurance` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub insurance: Option<InsuranceReport>,
+    /// Extensions bought and evaluations expired over all runs, when `eval_time_limit` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub eval_time_limit: Option<EvalTimeLimitReport>,
+    /// Resets bought over all runs, when `resets_allowed` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub resets: Option<ResetReport>,
+    /// Runs that reached `max_payouts`, when trading continues past it
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_payouts_reached_percentage: Option<f64>,
+    /// Mean simulation day on which those runs reached `max_payouts`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mean_days_to_max_payouts: Option<f64>,
+    /// Win/loss transition probabilities used by Markov trade sampling
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub outcome_chain: Option<OutcomeChain>,
+    /// Effect of excluding the burn-in period, when one is configured
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub burn_in: Option<BurnInComparison>,
+    /// Iterations used and the standard error reached, when `convergence` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub convergence: Option<ConvergenceReport>,
+    /// Name, tags and notes of the config
+    #[serde(skip_serializing_if = "RunMetadata::is_empty", default)]
+    pub metadata: RunMetadata,
+}
+
+/// Outcome of one Monte Carlo iteration, passed to the observer of
+/// `run_simulations_streaming` as soon as the iteration completes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IterationSummary {
+    pub iteration: usize,
+    pub end_state: EndOfGame,
+    pub final_balance: f64,
+    pub simulation_length: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_payouts_day: Option<u64>,
+    /// Simulation day the first evaluation was passed
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub funded_day: Option<u64>,
+    /// Simulation day of the first payout
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub first_payout_day: Option<u64>,
+    pub ledger: Ledger,
+    /// Firm daily loss limits reached as soft breaches, which ended the day without losing the account
+    #[serde(default)]
+    pub soft_breaches: u64,
+    /// Withdrawals of the run, with `audit_withdrawals`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub withdrawals: Vec<WithdrawalAudit>,
 }
 
+// Called with each completed iteration, from the thread that ran it
+type IterationObserver<'a> = &'a (dyn Fn(&IterationSummary) + Sync);
+
 #[derive(Debug)]
-struct IterationResult {
+pub(crate) struct IterationResult {
     final_balance: f64,
     end_state: EndOfGame,
     simulation_length: u64,
+    ledger: Ledger,
+    payouts: u32,
+    monthly_income: Vec<f64>,
+    weekly_pnl: Vec<f64>,
+    monthly_pnl: Vec<f64>,
+    worst_trade: Option<f64>,
+    worst_day: Option<f64>,
+    min_drawdown_buffer: f64,
+    buffer_at_first_payout: Option<f64>,
+    funded_day: Option<u64>,
+    first_payout_day: Option<u64>,
+    snapshots: Vec<AccountSnapshot>,
+    balance_path: Vec<f64>, // bank balance after each trading day, from day 0, with `equity_curve`
+    max_payouts_day: Option<u64>,
+    rule_triggers: RuleTriggers,
+    replacements_used: u32,
+    resets_used: u32,
+    eval_extensions: u32,
+    eval_expiries: u32,
 }
 
+/// Run one simulation.
+///
+/// With `config.seed` set the result is fully determined by the config and the trade data,
+/// whatever the number of threads rayon runs on: each iteration draws from its own RNG
+/// stream, derived from the seed and the iteration number rather than from shared state,
+/// and iteration results are combined in iteration order.
 pub fn run_simulation(config: SimulationConfig) -> Result<SimulationResult, Box<dyn Error>> {
-    // Initialize logging if not already initialized (optional)
-    // env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
-    info!("Starting the Prop Simulator with simulation config: {:?}", config.clone());
-    // Clone the account type for use in the simulation
-    let account_type = AccountType::from_str(&config.account_type)
-        .map_err(|_| "Invalid account type format")?;
+    run_simulation_with(config, &SimulationHandle::new())
+}
+
+/// `run_simulation`, reporting its progress to `handle` and stopping with
+/// `SimulationError::Cancelled` once `handle` is cancelled, e.g. from another thread
+pub fn run_simulation_with(config: SimulationConfig, handle: &SimulationHandle) -> Result<SimulationResult, Box<dyn Error>> {
+    simulation_limits().check_config(&config)?;
+    let trades = load_trades(&config)?;
+    handle.start(config.iterations);
+    simulate_trades(config, &trades, handle, &|_| {}).map_err(|e| -> Box<dyn Error> { e })
+}
+
+/// `run_simulation_with`, taking the trade data from `cache` when it holds the data of
+/// `config` and adding it otherwise, e.g. for a server running many simulations on the
+/// same uploads
+pub fn run_simulation_cached(
+    config: SimulationConfig,
+    cache: &TradeCache,
+    handle: &SimulationHandle,
+) -> Result<SimulationResult, Box<dyn Error>> {
+    simulation_limits().check_config(&config)?;
+    let trades = cache.get_or_load(&config)?;
+    handle.start(config.iterations);
+    simulate_trades(config, &trades, handle, &|_| {}).map_err(|e| -> Box<dyn Error> { e })
+}
+
+/// Run several configs in parallel, returning the results in the order of `configs`.
+/// Trade data is loaded once per distinct source (CSV file or inline data, multiplier
+/// and round trip cost) and shared between the runs using it. Seeded configs give the
+/// same results as running them one by one with `run_simulation`.
+pub fn run_simulations(configs: Vec<SimulationConfig>) -> Result<Vec<SimulationResult>, Box<dyn Error>> {
+    run_simulations_streaming(configs, &|_, _| {})
+}
+
+/// `run_simulations`, also passing every Monte Carlo iteration to `on_iteration` with the
+/// index of its config as soon as it completes, e.g. to write progress during long runs.
+/// Iterations complete in parallel, so they arrive out of order and from several threads.
+pub fn run_simulations_streaming(
+    configs: Vec<SimulationConfig>,
+    on_iteration: &(dyn Fn(usize, &IterationSummary) + Sync),
+) -> Result<Vec<SimulationResult>, Box<dyn Error>> {
+    run_simulations_with(configs, &SimulationHandle::new(), on_iteration)
+}
 
-    info!("Running simulation with account type: {:?}", account_type);
+/// `run_simulations_streaming`, reporting the progress of all runs together to `handle`
+/// and stopping with `SimulationError::Cancelled` once `handle` is cancelled
+pub fn run_simulations_with(
+    configs: Vec<SimulationConfig>,
+    handle: &SimulationHandle,
+    on_iteration: &(dyn Fn(usize, &IterationSummary) + Sync),
+) -> Result<Vec<SimulationResult>, Box<dyn Error>> {
+    run_simulations_cached(configs, &TradeCache::new(usize::MAX), handle, on_iteration)
+}
+
+/// `run_simulations_with`, taking the trade data from `cache` like `run_simulation_cached`
+pub fn run_simulations_cached(
+    configs: Vec<SimulationConfig>,
+    cache: &TradeCache,
+    handle: &SimulationHandle,
+    on_iteration: &(dyn Fn(usize, &IterationSummary) + Sync),
+) -> Result<Vec<SimulationResult>, Box<dyn Error>> {
+    let limits = simulation_limits();
+    for config in &configs {
+        limits.check_config(config)?;
+    }
+    // Configs on the same data share its trade pool; simulated bracket trades are random,
+    // every config generates its own
+    let runs = configs
+        .into_iter()
+        .map(|config| {
+            let trades = cache.get_or_load(&config)?;
+            Ok((config, trades))
+        })
+        .collect::<Result<Vec<_>, SimulationError>>()?;
+
+    handle.start(runs.iter().map(|(config, _)| config.iterations).sum());
+    runs.into_par_iter()
+        .enumerate()
+        .map(|(run, (config, trades))| simulate_trades(config, &trades, handle, &|summary| on_iteration(run, summary)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| -> Box<dyn Error> { e })
+}
 
-    // Load or generate trades based on the provided configuration
-    let trades = if let Some(csv_data) = &config.csv_data {
+// Load or generate trades based on the provided configuration
+fn load_trades(config: &SimulationConfig) -> Result<TradePool, SimulationError> {
+    if !config.multiplier.is_finite() || config.round_trip_cost.is_some_and(|cost| !cost.is_finite()) {
+        return Err(SimulationError::InvalidConfig("multiplier and round_trip_cost must be finite numbers".into()));
+    }
+    let holding_rules = config.holding_rules.as_ref();
+    let trade_filter = config.trade_filter.as_deref();
+    let (trades, holding, filter) = if let Some(csv_data) = &config.csv_data {
         // Read trades from CSV data
-        read_csv_from_string(csv_data, config.multiplier, config.round_trip_cost)?
+        read_csv_from_string(csv_data, config.multiplier, config.round_trip_cost, holding_rules, trade_filter)
+            .map_err(|e| read_error(e, None))?
     } else if let Some(csv_file) = &config.csv_file {
-        read_csv(csv_file, config.multiplier, config.round_trip_cost)?
+        read_csv(csv_file, config.multiplier, config.round_trip_cost, holding_rules, trade_filter)
+            .map_err(|e| read_error(e, Some(csv_file)))?
     } else {
-        let stop_loss = config.stop_loss.ok_or("Stop loss required")?;
-        let take_profit = config.take_profit.ok_or("Take profit required")?;
-        let win_percentage = config.win_percentage.ok_or("Win percentage required")?;
-        let avg_trades_per_day = config.avg_trades_per_day.ok_or("Avg trades per day required")?;
+        if trade_filter.is_some() {
+            return Err(SimulationError::InvalidConfig("trade_filter requires CSV data".into()));
+        }
+        let missing = |what: &str| SimulationError::InvalidConfig(format!("{} required", what));
+        let stop_loss = config.stop_loss.ok_or_else(|| missing("Stop loss"))?;
+        let take_profit = config.take_profit.ok_or_else(|| missing("Take profit"))?;
+        let win_percentage = config.win_percentage.ok_or_else(|| missing("Win percentage"))?;
+        let avg_trades_per_day = config.avg_trades_per_day.ok_or_else(|| missing("Avg trades per day"))?;
+        // The distributions the trades are drawn from are undefined outside these ranges
+        if !(stop_loss.is_finite() && stop_loss >= 0.0 && take_profit.is_finite() && take_profit >= 0.0) {
+            return Err(SimulationError::InvalidConfig("stop_loss and take_profit must be finite and not negative".into()));
+        }
+        if !(0.0..=100.0).contains(&win_percentage) {
+            return Err(SimulationError::InvalidConfig("win_percentage must be between 0 and 100".into()));
+        }
+        if !(avg_trades_per_day.is_finite() && avg_trades_per_day > 0.0) {
+            return Err(SimulationError::InvalidConfig("avg_trades_per_day must be a finite number greater than zero".into()));
+        }
+        simulation_limits().check_trades((avg_trades_per_day * SIMULATED_DAYS as f64).ceil() as usize)?;
 
-        generate_simulated_trades(
+        let trades = generate_simulated_trades_with_rng(
             avg_trades_per_day,
             stop_loss,
             take_profit,
             win_percentage,
             config.multiplier,
             config.round_trip_cost,
-        )
+            &mut SimulationRng::new(config.rng_backend, config.seed, RngStream::TradeGeneration, 0),
+        );
+        // Generated trades never hold past the session
+        (trades, None, None)
+    };
+    TradePool::new(trades)
+        .with_holding_report(holding)
+        .with_filter_report(filter)
+        .with_reweighting(config.outcome_reweighting.as_deref().unwrap_or_default())
+}
+
+// Keep limit errors raised while reading, anything else is a data error
+fn read_error(e: Box<dyn Error>, file: Option<&str>) -> SimulationError {
+    match e.downcast::<SimulationError>() {
+        Ok(e) => *e,
+        Err(e) => SimulationError::Data(match file {
+            Some(file) => format!("{}: {}", file, e),
+            None => e.to_string(),
+        }),
+    }
+}
+
+// Run the simulation on loaded trades. The error type is `Send` so that runs can execute in parallel.
+fn simulate_trades(
+    config: SimulationConfig,
+    trades: &TradePool,
+    handle: &SimulationHandle,
+    on_iteration: IterationObserver,
+) -> Result<SimulationResult, Box<dyn Error + Send + Sync>> {
+    // Progress counts the iterations of the run itself, not of its comparison runs
+    let on_iteration = |summary: &IterationSummary| {
+        handle.complete_iteration();
+        on_iteration(summary);
+    };
+    // Iterations a converged run did not need no longer count towards the progress
+    let iterations = config.iterations;
+    let finish = |result: SimulationResult| {
+        if let Some(convergence) = &result.convergence {
+            handle.skip(iterations - convergence.iterations);
+        }
+        result
     };
+    let burn_in_trades = config.burn_in_trades.unwrap_or(0);
+    let burn_in_days = config.burn_in_days.unwrap_or(0);
+    if burn_in_trades == 0 && burn_in_days == 0 {
+        return simulate_pool(config, trades, handle, &on_iteration).map(finish);
+    }
+
+    let pool = trades.without_burn_in(burn_in_trades, burn_in_days)?;
+    if pool.is_empty() {
+        return Err(SimulationError::Data("The burn-in period excludes all trades".into()).into());
+    }
+    // Rerun with the burn-in period kept for comparison; with a seed both runs draw the same
+    // random numbers, so the difference reflects the data rather than noise
+    let mut included_config = config.clone();
+    included_config.burn_in_trades = None;
+    included_config.burn_in_days = None;
+    included_config.histogram = false;
+    included_config.cohort_interval_days = None;
+    included_config.parallel_evals = None;
+    included_config.hedge_account_type = None;
+    included_config.copied_accounts = None;
+    included_config.snapshot_days = None;
+    included_config.equity_curve = false;
+    included_config.output_file = None;
+    included_config.monthly_income_target = None;
+    let included = simulate_pool(included_config, trades, handle, &|_| {})?;
+
+    let mut result = simulate_pool(config, &pool, handle, &on_iteration)?;
+    result.burn_in = Some(burn_in::burn_in_comparison(trades.len() - pool.len(), pool.len(), &result, &included));
+    Ok(finish(result))
+}
+
+// Simulate on one trade pool
+fn simulate_pool(
+    config: SimulationConfig,
+    trades: &TradePool,
+    handle: &SimulationHandle,
+    on_iteration: IterationObserver,
+) -> Result<SimulationResult, Box<dyn Error + Send + Sync>> {
+    info!("Starting the Prop Simulator with simulation config: {:?}", config.clone());
+    let account_factory = config_account_factory(&config)?;
+    let currency_conversion = currency_conversion(&config)?;
+    validate_loss_limits(&config)?;
+    if config.iterations == 0 {
+        return Err(SimulationError::InvalidConfig("iterations must be greater than zero".into()).into());
+    }
+    if config.purchase_price.is_some_and(|price| price < 0.0) {
+        return Err(SimulationError::InvalidConfig("purchase_price must not be negative".into()).into());
+    }
+    if let Some(news_blackout) = &config.news_blackout {
+        if config.purchase_date.is_none() {
+            return Err(SimulationError::InvalidConfig("news_blackout requires purchase_date".into()).into());
+        }
+        if news_blackout.variance_factor <= 0.0 {
+            return Err(SimulationError::InvalidConfig("news_blackout.variance_factor must be greater than zero".into()).into());
+        }
+    }
+    if config.insurance.as_ref().is_some_and(|insurance| insurance.cost < 0.0 || insurance.replacement_fee < 0.0) {
+        return Err(SimulationError::InvalidConfig("insurance cost and replacement_fee must not be negative".into()).into());
+    }
+    if let Some(profit_split) = &config.profit_split {
+        profit_split.validate().map_err(SimulationError::InvalidConfig)?;
+    }
+    if config.traded_contracts == Some(0) {
+        return Err(SimulationError::InvalidConfig("traded_contracts must be greater than zero".into()).into());
+    }
+    if config.block_length == Some(0) {
+        return Err(SimulationError::InvalidConfig("block_length must be greater than zero".into()).into());
+    }
+    if config.min_profitable_days.is_some_and(|rule| rule.days == 0) {
+        return Err(SimulationError::InvalidConfig("min_profitable_days.days must be greater than zero".into()).into());
+    }
+    if let Some(eval_time_limit) = &config.eval_time_limit {
+        eval_time_limit.validate().map_err(SimulationError::InvalidConfig)?;
+    }
+    if config.reset_cost.is_some_and(|cost| cost < 0.0) {
+        return Err(SimulationError::InvalidConfig("reset_cost must not be negative".into()).into());
+    }
+    if config.reset_cost.is_some() && config.resets_allowed.is_none() {
+        return Err(SimulationError::InvalidConfig("reset_cost requires resets_allowed".into()).into());
+    }
+    if config.bankroll_floor.is_some() && config.bankroll.is_none() {
+        return Err(SimulationError::InvalidConfig("bankroll_floor requires bankroll".into()).into());
+    }
+    if config.payout_denial_probability.is_some_and(|probability| !(0.0..=1.0).contains(&probability)) {
+        return Err(SimulationError::InvalidConfig("payout_denial_probability must be between 0 and 1".into()).into());
+    }
+    if config.terminal_payout_fraction.is_some_and(|fraction| !(0.0..=1.0).contains(&fraction)) {
+        return Err(SimulationError::InvalidConfig("terminal_payout_fraction must be between 0 and 1".into()).into());
+    }
+    if config.percentiles.iter().flatten().any(|p| !(0.0..=100.0).contains(p)) {
+        return Err(SimulationError::InvalidConfig("percentiles must be between 0 and 100".into()).into());
+    }
+    if config.var_confidence.is_some_and(|confidence| !(confidence > 0.0 && confidence < 100.0)) {
+        return Err(SimulationError::InvalidConfig("var_confidence must be between 0 and 100".into()).into());
+    }
+    if config.seed.is_some() && !config.rng_backend.seedable() {
+        return Err(SimulationError::InvalidConfig("the thread RNG backend cannot be seeded".into()).into());
+    }
+    if config.cohort_interval_days == Some(0) {
+        return Err(SimulationError::InvalidConfig("cohort_interval_days must be greater than zero".into()).into());
+    }
+    if config.parallel_evals == Some(0) {
+        return Err(SimulationError::InvalidConfig("parallel_evals must be greater than zero".into()).into());
+    }
+    if config.hedge_correlation.is_some_and(|correlation| !(-1.0..=1.0).contains(&correlation)) {
+        return Err(SimulationError::InvalidConfig("hedge_correlation must be between -1 and 1".into()).into());
+    }
+    // Resolved here so that an unknown hedge account fails before the runs
+    let hedge_factory = config.hedge_account_type.as_deref().map(prop_account::account_factory).transpose()?;
+    if config.copied_accounts == Some(0) {
+        return Err(SimulationError::InvalidConfig("copied_accounts must be greater than zero".into()).into());
+    }
+    if config.copy_correlation.is_some_and(|correlation| !(0.0..=1.0).contains(&correlation)) {
+        return Err(SimulationError::InvalidConfig("copy_correlation must be between 0 and 1".into()).into());
+    }
+    if config.convergence.is_some_and(|convergence| convergence.tolerance.is_nan() || convergence.tolerance <= 0.0) {
+        return Err(SimulationError::InvalidConfig("convergence.tolerance must be greater than zero".into()).into());
+    }
+    if config.streaming_stats {
+        // Options that need every run kept
+        let needs_runs = [
+            ("output_file", config.output_file.is_some()),
+            ("histogram", config.histogram),
+            ("equity_curve", config.equity_curve),
+            ("snapshot_days", config.snapshot_days.is_some()),
+            ("balance_sample_size", config.balance_sample_size.is_some()),
+            ("insurance", config.insurance.is_some()),
+            ("monthly_income_target", config.monthly_income_target.is_some()),
+            ("eval_time_limit", config.eval_time_limit.is_some()),
+            ("resets_allowed", config.resets_allowed.is_some_and(|resets| resets > 0)),
+            ("continue_after_max_payouts", config.continue_after_max_payouts),
+            ("cohort_interval_days", config.cohort_interval_days.is_some()),
+            ("parallel_evals", config.parallel_evals.is_some()),
+            ("hedge_account_type", config.hedge_account_type.is_some()),
+            ("copied_accounts", config.copied_accounts.is_some()),
+        ];
+        if let Some((option, _)) = needs_runs.iter().find(|(_, set)| *set) {
+            return Err(SimulationError::InvalidConfig(format!("{} cannot be combined with streaming_stats", option)).into());
+        }
+    }
+
+    let target_end_states = trader::conditioned_end_states(&config.condition_end_state)
+        .map_err(|e| SimulationError::InvalidConfig(format!("condition_end_state: {}", e)))?;
+
+    // The canonical name, whichever alias the config used
+    let account_type = match &config.custom_account {
+        Some(spec) => spec.account_type(),
+        None => prop_account::resolve_account_type(&config.account_type)?,
+    };
+    info!("Running simulation with account type: {}", account_type);
 
-    // Calculate the number of trades per day
-    let trades_per_day_map = calculate_trades_per_day(&trades);
-    let trades_per_day: Vec<usize> = trades_per_day_map.values().cloned().collect();
 
     // Run the Monte Carlo simulation
-    let simulation_results = monte_carlo_simulation(
-        &trades,
-        &trades_per_day,
-        config.iterations,
-        account_type,
-        config.max_trades_per_day,
-        config.daily_profit_target,
-        config.daily_stop_loss,
-        config.max_simulation_days,
-        config.max_payouts,
-    );
+    let (simulation_results, streaming_stats, convergence) = if config.streaming_stats {
+        let (stats, convergence) = streaming_monte_carlo_simulation(
+            trades,
+            &account_factory,
+            currency_conversion.as_ref(),
+            &config,
+            &target_end_states,
+            handle,
+            on_iteration,
+        )?;
+        (Vec::new(), Some(stats), convergence)
+    } else {
+        let (results, convergence) = monte_carlo_simulation(
+            trades,
+            &account_factory,
+            currency_conversion.as_ref(),
+            &config,
+            &target_end_states,
+            handle,
+            on_iteration,
+        )?;
+        (results, None, convergence)
+    };
 
-    // Process the simulation results
-    let mut final_balances = Vec::new();
-    let mut aggregate_days = Vec::new();
-    let mut balances_by_end_state = HashMap::new();
-    let mut days_by_end_state = HashMap::new();
-    let mut end_state_counts = HashMap::new();
+    // The side analyses run as many iterations as the main runs, which stop early once converged
+    let side_config = SimulationConfig {
+        iterations: convergence.as_ref().map_or(config.iterations, |report| report.iterations),
+        convergence: None,
+        ..config.clone()
+    };
+
+    let cohort = config.cohort_interval_days.map(|interval_days| {
+        cohort_simulation(trades, &account_factory, currency_conversion.as_ref(), &side_config, interval_days)
+    });
+
+    let parallel_evals = config
+        .parallel_evals
+        .map(|evals| parallel_eval_simulation(trades, &account_factory, currency_conversion.as_ref(), &side_config, evals));
+
+    let hedge = config.hedge_account_type.as_deref().zip(hedge_factory.as_ref()).map(|(hedge_account_type, hedge_factory)| {
+        hedge_simulation(trades, &account_factory, currency_conversion.as_ref(), &side_config, hedge_account_type, hedge_factory)
+    });
+
+    let copy_trading = config
+        .copied_accounts
+        .map(|accounts| copy_trading_simulation(trades, &account_factory, currency_conversion.as_ref(), &side_config, accounts));
+
+    if let Some(stats) = streaming_stats {
+        if handle.is_cancelled() {
+            return Err(SimulationError::Cancelled.into());
+        }
+        let percentiles = config.percentiles.as_deref().unwrap_or(&tail::DEFAULT_PERCENTILES);
+        let summary = stats
+            .summary(&target_end_states, percentiles, config.var_confidence.unwrap_or(tail::DEFAULT_VAR_CONFIDENCE))
+            .ok_or_else(|| SimulationError::Data("No data available for the specified condition_end_state.".into()))?;
+        // The analyses that need every run are left out
+        return Ok(SimulationResult {
+            mean_balance: summary.mean,
+            median_balance: summary.median,
+            std_dev: summary.std_dev,
+            mad: summary.mad,
+            iqr: summary.iqr,
+            mad_median: summary.mad_median,
+            mean_days: summary.mean_days,
+            end_state_percentages: stats.end_state_percentages(),
+            stats_by_end_state: stats.stats_by_end_state(percentiles),
+            positive_balance_percentage: summary.positive_percentage,
+            percentiles: summary.percentiles,
+            tail_risk: summary.tail_risk,
+            account_type,
+            currency: if currency_conversion.is_some() { config.currency.unwrap_or_default() } else { Currency::Usd },
+            mean_payout_fees: summary.ev_decomposition.payout_fees,
+            mean_conversion_fees: summary.ev_decomposition.conversion_fees,
+            ev_decomposition: summary.ev_decomposition,
+            gross_payouts: summary.gross_payouts,
+            rule_triggers: stats.rule_trigger_report(),
+            period_pnl: summary.period_pnl,
+            worst_outcomes: summary.worst_outcomes,
+            time_to_money: summary.time_to_money,
+            drawdown_buffer: stats.drawdown_buffer(),
+            soft_breaches: stats.soft_breaches(),
+            stationary_income: stats.stationary_income(),
+            cohort,
+            parallel_evals,
+            hedge,
+            copy_trading,
+            holding: trades.holding_report().cloned(),
+            trade_filter: trades.filter_report().cloned(),
+            outcome_reweighting: trades.reweight_report().cloned(),
+            outcome_chain: (config.trade_sampling == TradeSampling::Markov).then(|| trades.outcome_chain()),
+            convergence,
+            metadata: RunMetadata::of(&config),
+            ..SimulationResult::default()
+        });
+    }
+
+    let insurance = config.insurance.as_ref().map(|insurance| {
+        // The same runs without the insurance; with a seed they draw the same trades up to
+        // the first covered bust
+        let uninsured_config = SimulationConfig {
+            insurance: None,
+            iterations: simulation_results.len(),
+            convergence: None,
+            ..config.clone()
+        };
+        let (uninsured, _) = monte_carlo_simulation(
+            trades,
+            &account_factory,
+            currency_conversion.as_ref(),
+            &uninsured_config,
+            &target_end_states,
+            handle,
+            &|_| {},
+        )?;
+        let runs = |results: &[IterationResult]| -> Vec<InsuranceRun> {
+            results
+                .iter()
+                .map(|result| InsuranceRun {
+                    final_balance: result.final_balance,
+                    busted: result.end_state == EndOfGame::Busted,
+                    replacements_used: result.replacements_used,
+                })
+                .collect()
+        };
+        let mut report = insurance::insurance_report(insurance, &runs(&simulation_results), &runs(&uninsured));
+        // Priced in the result currency like the balances, at the rate of the purchase day
+        if let Some(conversion) = &currency_conversion {
+            report.cost *= conversion.rate_on(0);
+        }
+        Ok::<_, SimulationError>(report)
+    }).transpose()?;
+    // Comparison runs above do not stop early, but their result is dropped
+    if handle.is_cancelled() {
+        return Err(SimulationError::Cancelled.into());
+    }
 
+    // Count the end states of the simulation results
+    let mut end_state_counts = HashMap::new();
     for result in &simulation_results {
-        final_balances.push(result.final_balance);
-        aggregate_days.push(result.simulation_length);
         *end_state_counts.entry(result.end_state.clone()).or_insert(0) += 1;
-        balances_by_end_state
-            .entry(result.end_state.clone())
-            .or_insert_with(Vec::new)
-            .push(result.final_balance);
-        days_by_end_state
-            .entry(result.end_state.clone())
-            .or_insert_with(Vec::new)
-            .push(result.simulation_length);
     }
 
     // Compute the percentage of each end state
     let mut end_state_percentages = HashMap::new();
     for (end_state, count) in &end_state_counts {
-        let percentage = (*count as f64 / config.iterations as f64) * 100.0;
+        let percentage = (*count as f64 / simulation_results.len() as f64) * 100.0;
         end_state_percentages.insert(end_state.clone(), percentage);
     }
 
-    // Determine the target end state for conditioned statistics
-    let target_end_state = match config.condition_end_state.to_lowercase().as_str() {
-        "busted" => Some(EndOfGame::Busted),
-        "timeout" => Some(EndOfGame::TimeOut),
-        "maxpayouts" => Some(EndOfGame::MaxPayouts),
-        "all" => None,
-        _ => {
-            eprintln!(
-                "Invalid end state condition '{}'. Using default aggregate data.",
-                config.condition_end_state
-            );
-            None
-        }
-    };
+    let runs: Vec<(EndOfGame, f64, u64)> = simulation_results
+        .iter()
+        .map(|r| (r.end_state.clone(), r.final_balance, r.simulation_length))
+        .collect();
+    let stats_by_end_state = end_states::end_state_stats(
+        &runs,
+        config.percentiles.as_deref().unwrap_or(&tail::DEFAULT_PERCENTILES),
+    );
 
-    // Filter data based on the target end state
-    let (filtered_balances, filtered_days) = if let Some(end_state) = target_end_state {
-        (
-            balances_by_end_state.get(&end_state).cloned().unwrap_or_default(),
-            days_by_end_state.get(&end_state).cloned().unwrap_or_default(),
-        )
-    } else {
-        (final_balances.clone(), aggregate_days.clone())
-    };
+    // Runs the conditioned statistics cover
+    let filtered_results: Vec<&IterationResult> = simulation_results
+        .iter()
+        .filter(|result| target_end_states.contains(&result.end_state))
+        .collect();
+    let filtered_balances: Vec<f64> = filtered_results.iter().map(|r| r.final_balance).collect();
+    let filtered_days: Vec<u64> = filtered_results.iter().map(|r| r.simulation_length).collect();
 
     // Check if there is data to process
     if filtered_balances.is_empty() {
-        return Err("No data available for the specified condition_end_state.".into());
+        return Err(SimulationError::Data("No data available for the specified condition_end_state.".into()).into());
     }
 
+    let ev_decomposition = Ledger::mean(filtered_results.iter().map(|r| &r.ledger));
+    let final_gross_payouts: Vec<f64> = filtered_results.iter().map(|r| r.ledger.gross_payouts).collect();
+    let gross_payouts = gross::gross_payout_stats(&final_gross_payouts);
+    let weekly_pnl: Vec<&[f64]> = filtered_results.iter().map(|r| r.weekly_pnl.as_slice()).collect();
+    let monthly_pnl: Vec<&[f64]> = filtered_results.iter().map(|r| r.monthly_pnl.as_slice()).collect();
+    let period_pnl = PeriodPnlReport {
+        weekly: periods::period_pnl_stats(&weekly_pnl),
+        monthly: periods::period_pnl_stats(&monthly_pnl),
+    };
+    let worst_trades: Vec<Option<f64>> = filtered_results.iter().map(|r| r.worst_trade).collect();
+    let worst_days: Vec<Option<f64>> = filtered_results.iter().map(|r| r.worst_day).collect();
+    let worst_outcomes = WorstOutcomeReport {
+        worst_trade: worst::worst_outcome_stats(&worst_trades),
+        worst_day: worst::worst_outcome_stats(&worst_days),
+    };
+    let funded_days: Vec<Option<u64>> = filtered_results.iter().map(|r| r.funded_day).collect();
+    let first_payout_days: Vec<Option<u64>> = filtered_results.iter().map(|r| r.first_payout_day).collect();
+    let day_bins = config.histogram.then(|| config.histogram_bins.unwrap_or(DEFAULT_HISTOGRAM_BINS));
+    let time_to_money = TimeToMoneyReport {
+        funded: time_to_money::days_to_stats(&funded_days, day_bins),
+        first_payout: time_to_money::days_to_stats(&first_payout_days, day_bins),
+    };
+    let surviving_buffers: Vec<(f64, Option<f64>)> = simulation_results
+        .iter()
+        .filter(|r| r.end_state != EndOfGame::Busted)
+        .map(|r| (r.min_drawdown_buffer, r.buffer_at_first_payout))
+        .collect();
+    let drawdown_buffer = buffer::drawdown_buffer_stats(&surviving_buffers);
+    // Snapshots cover every run: conditioning on the final end state would look ahead
+    let snapshots = snapshot_days(&config)
+        .iter()
+        .enumerate()
+        .map(|(i, &day)| {
+            let at_day: Vec<&AccountSnapshot> = simulation_results.iter().map(|r| &r.snapshots[i]).collect();
+            snapshot::day_snapshot(day, &at_day)
+        })
+        .collect();
+    // Like the snapshots, the equity curve covers every run
+    let equity_curve = config.equity_curve.then(|| {
+        let paths: Vec<&[f64]> = simulation_results.iter().map(|r| r.balance_path.as_slice()).collect();
+        equity::equity_curve(&paths, config.max_simulation_days)
+    });
+    let triggers: Vec<(RuleTriggers, u64)> = simulation_results
+        .iter()
+        .map(|r| (r.rule_triggers, r.simulation_length))
+        .collect();
+    let rule_triggers = triggers::rule_trigger_report(&triggers);
+    let soft_breaches: Vec<u64> = simulation_results.iter().map(|r| r.rule_triggers.soft_breaches).collect();
+    let soft_breaches = triggers::soft_breach_stats(&soft_breaches);
+    let eval_time_limit = config.eval_time_limit.as_ref().map(|_| {
+        let runs: Vec<(u32, u32)> = simulation_results.iter().map(|r| (r.eval_extensions, r.eval_expiries)).collect();
+        time_limit::eval_time_limit_report(&runs)
+    });
+    let resets = config.resets_allowed.filter(|&resets| resets > 0).map(|resets_allowed| {
+        let runs: Vec<(u32, bool)> = simulation_results.iter().map(|r| (r.resets_used, r.end_state == EndOfGame::Busted)).collect();
+        resets::reset_report(resets_allowed, &runs)
+    });
+    // The milestone covers every run, like the end state percentages
+    let max_payouts_days: Vec<u64> = simulation_results.iter().filter_map(|r| r.max_payouts_day).collect();
+    let max_payouts_reached_percentage = config
+        .continue_after_max_payouts
+        .then(|| max_payouts_days.len() as f64 / simulation_results.len() as f64 * 100.0);
+    let mean_days_to_max_payouts = (!max_payouts_days.is_empty())
+        .then(|| max_payouts_days.iter().sum::<u64>() as f64 / max_payouts_days.len() as f64);
+    // Every run is one account's life, whatever its end state. A busted account also
+    // used up the day it was lost; other runs end between days.
+    let cycles: Vec<(f64, u64)> = simulation_results
+        .iter()
+        .map(|r| (r.final_balance, r.simulation_length + u64::from(r.end_state == EndOfGame::Busted)))
+        .collect();
+    let stationary_income = income::stationary_income(&cycles);
+    let income = config.monthly_income_target.map(|monthly_target| {
+        let monthly_incomes: Vec<&[f64]> = filtered_results.iter().map(|r| r.monthly_income.as_slice()).collect();
+        income::income_report(&monthly_incomes, monthly_target, income::simulated_months(config.max_simulation_days))
+    });
+
     // Calculate aggregate statistics
-    let mean_balance: f64 = filtered_balances.iter().sum::<f64>() / filtered_balances.len() as f64;
+    let sorted_balances = stats::sorted(&filtered_balances);
+    let summary = stats::Summary::of_sorted(&sorted_balances)
+        .ok_or_else(|| SimulationError::Data("No final balance to summarize.".into()))?;
     let mean_days: f64 = filtered_days.iter().sum::<u64>() as f64 / filtered_days.len() as f64;
 
-    let variance: f64 = filtered_balances
-        .iter()
-        .map(|balance| (balance - mean_balance).powi(2))
-        .sum::<f64>()
-        / filtered_balances.len() as f64;
-    let std_dev = variance.sqrt();
+    let percentiles = tail::balance_percentiles(
+        &sorted_balances,
+        config.percentiles.as_deref().unwrap_or(&tail::DEFAULT_PERCENTILES),
+    );
+    let tail_risk = tail::tail_risk(&sorted_balances, config.var_confidence.unwrap_or(tail::DEFAULT_VAR_CONFIDENCE));
 
-    let mad: f64 = filtered_balances
-        .iter()
-        .map(|balance| (balance - mean_balance).abs())
-        .sum::<f64>()
-        / filtered_balances.len() as f64;
+    let balance_sample = config
+        .balance_sample_size
+        .map(|size| quantile_sample(&sorted_balances, size));
+
+    // Compute the percentage of positive balances
+    let positive_balances_count = filtered_balances.iter().filter(|&&b| b > 0.0).count();
+    let positive_balance_percentage = (positive_balances_count as f64 / filtered_balances.len() as f64) * 100.0;
 
-    let mut sorted_balances = filtered_balances.clone();
-    sorted_balances.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
-    let median_balance = if sorted_balances.len() % 2 == 0 {
-        let mid = sorted_balances.len() / 2;
-        (sorted_balances[mid - 1] + sorted_balances[mid]) / 2.0
+    let iterations = match config.output_file {
+        Some(_) => simulation_results
+            .iter()
+            .enumerate()
+            .map(|(iteration, r)| IterationRecord {
+                iteration,
+                final_balance: r.final_balance,
+                end_state: r.end_state.clone(),
+                simulation_length: r.simulation_length,
+                payouts: r.payouts,
+                gross_payouts: r.ledger.gross_payouts,
+                funded_day: r.funded_day,
+                first_payout_day: r.first_payout_day,
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    // Raw histogram bins; rendering charts is left to the frontends (see `plotting`)
+    let (histogram, gross_histogram) = if config.histogram {
+        let bins = config.histogram_bins.unwrap_or(DEFAULT_HISTOGRAM_BINS);
+        (Some(compute_histogram(&filtered_balances, bins)), Some(compute_histogram(&final_gross_payouts, bins)))
     } else {
-        sorted_balances[sorted_balances.len() / 2]
+        (None, None)
+    };
+
+    // Return the simulation result
+    Ok(SimulationResult {
+        final_balances: filtered_balances,
+        final_gross_payouts,
+        iterations,
+        mean_balance: summary.mean,
+        median_balance: summary.median,
+        std_dev: summary.std_dev,
+        mad: summary.mad,
+        iqr: summary.iqr,
+        mad_median: summary.mad_median,
+        mean_days,
+        end_state_percentages,
+        stats_by_end_state,
+        positive_balance_percentage,
+        percentiles,
+        tail_risk,
+        account_type,
+        currency: if currency_conversion.is_some() { config.currency.unwrap_or_default() } else { Currency::Usd },
+        mean_payout_fees: ev_decomposition.payout_fees,
+        mean_conversion_fees: ev_decomposition.conversion_fees,
+        ev_decomposition,
+        gross_payouts,
+        rule_triggers,
+        soft_breaches,
+        period_pnl,
+        worst_outcomes,
+        time_to_money,
+        histogram,
+        gross_histogram,
+        balance_sample,
+        income,
+        stationary_income,
+        drawdown_buffer,
+        snapshots,
+        equity_curve,
+        cohort,
+        parallel_evals,
+        hedge,
+        copy_trading,
+        holding: trades.holding_report().cloned(),
+        trade_filter: trades.filter_report().cloned(),
+        outcome_reweighting: trades.reweight_report().cloned(),
+        insurance,
+        eval_time_limit,
+        resets,
+        max_payouts_reached_percentage,
+        mean_days_to_max_payouts,
+        burn_in: None,
+        convergence,
+        outcome_chain: (config.trade_sampling == TradeSampling::Markov).then(|| trades.outcome_chain()),
+        metadata: RunMetadata::of(&config),
+    })
+}
+
+// Accounts of `custom_account` when set, otherwise of `account_type` resolved through the
+// registry (built-in and registered accounts)
+fn config_account_factory(config: &SimulationConfig) -> Result<AccountFactory, SimulationError> {
+    match &config.custom_account {
+        Some(spec) => custom_account_factory(spec.clone()),
+        None => account_factory(&config.account_type),
+    }
+}
+
+// Conversion into the configured home currency, or `None` when results stay in USD
+fn currency_conversion(config: &SimulationConfig) -> Result<Option<CurrencyConversion>, SimulationError> {
+    match config.currency {
+        None | Some(Currency::Usd) => return Ok(None),
+        Some(_) => (),
+    }
+
+    let rates = match (&config.conversion_rate_series, config.conversion_rate) {
+        (Some(series), _) => series.clone(),
+        (None, Some(rate)) => vec![rate],
+        (None, None) => {
+            return Err(SimulationError::InvalidConfig(
+                "a currency other than USD requires conversion_rate or conversion_rate_series".into(),
+            ))
+        }
     };
+    if rates.is_empty() || rates.iter().any(|rate| rate.is_nan() || *rate <= 0.0) {
+        return Err(SimulationError::InvalidConfig("conversion rates must be positive".into()));
+    }
 
-    let q1_index = sorted_balances.len() / 4;
-    let q3_index = 3 * sorted_balances.len() / 4;
-    let q1 = sorted_balances[q1_index];
-    let q3 = sorted_balances[q3_index];
-    let iqr = q3 - q1;
+    Ok(Some(CurrencyConversion {
+        rates,
+        fee_percent: config.conversion_fee_percent.unwrap_or(0.0),
+    }))
+}
 
-    let mut deviations: Vec<f64> = sorted_balances
+// Percentage loss limits are relative to the account balance, which needs the account size
+fn validate_loss_limits(config: &SimulationConfig) -> Result<(), SimulationError> {
+    let firm_percent_limit = config
+        .rule_timeline
         .iter()
-        .map(|&balance| (balance - median_balance).abs())
-        .collect();
-    deviations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        .flatten()
+        .any(|change| change.rules.daily_loss_limit.is_some_and(|limit| limit.is_percent()));
+    if (config.daily_stop_loss_percent.is_some() || firm_percent_limit) && config.account_size.is_none() {
+        return Err(SimulationError::InvalidConfig("percentage daily loss limits require account_size".into()));
+    }
+    Ok(())
+}
 
-    let mad_median = if deviations.len() % 2 == 0 {
-        let mid = deviations.len() / 2;
-        (deviations[mid - 1] + deviations[mid]) / 2.0
-    } else {
-        deviations[deviations.len() / 2]
+// Configured snapshot days, sorted and deduplicated
+fn snapshot_days(config: &SimulationConfig) -> Vec<u64> {
+    let mut days = config.snapshot_days.clone().unwrap_or_default();
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+// Pick `size` values at evenly spaced quantiles of the sorted data, so the sample
+// keeps the shape of the full distribution (including its tails)
+fn quantile_sample(sorted: &[f64], size: usize) -> Vec<f64> {
+    if size >= sorted.len() {
+        return sorted.to_vec();
+    }
+    (0..size)
+        .map(|i| {
+            let q = (i as f64 + 0.5) / size as f64;
+            sorted[((q * sorted.len() as f64) as usize).min(sorted.len() - 1)]
+        })
+        .collect()
+}
+
+// Fresh trader for one run, starting at trading day `start_day` and trading until
+// `max_simulation_days` (counted from its own start)
+fn new_trader(
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    start_day: u64,
+    max_simulation_days: u64,
+) -> Trader {
+    // Accounts of the configured type, also used to replace busts the insurance covers,
+    // resets and expired evaluations
+    let factory = Arc::clone(account_factory);
+    let overrides = RuleOverrides {
+        drawdown_type: config.drawdown_type,
+        payout_cadence: config.payout_cadence,
+        min_profitable_days: config.min_profitable_days,
+        profit_split: config.profit_split.clone(),
+        ..Default::default()
     };
+    let account_factory: AccountFactory = Arc::new(move || {
+        let mut prop_account = factory();
+        prop_account.apply_rules(&overrides);
+        prop_account
+    });
+    let prop_account = account_factory();
+    // Accounts started later in a cohort are bought on their first trading day
+    let purchase_date = config
+        .purchase_date
+        .map(|date| if start_day == 0 { date } else { calendar::trading_date(date, start_day) });
+    let mut trader = Trader::new(
+        prop_account,
+        config.max_trades_per_day,
+        config.daily_profit_target,
+        config.daily_stop_loss,
+        max_simulation_days,
+        config.max_payouts,
+    )
+    .with_payout_fees(PayoutFees {
+        fixed: config.payout_fee_fixed.unwrap_or(0.0),
+        percent: config.payout_fee_percent.unwrap_or(0.0),
+    })
+    .with_terminal_payout_fraction(config.terminal_payout_fraction)
+    .with_payout_delay_days(config.payout_delay_days)
+    .with_payout_denial_probability(config.payout_denial_probability)
+    .with_platform_fee(PlatformFee {
+        monthly: config.platform_fee_monthly.unwrap_or(0.0),
+        funded_only: config.platform_fee_funded_only,
+    })
+    .with_purchase(purchase_date, config.purchase_price.filter(|_| start_day == 0))
+    .with_insurance(config.insurance.clone(), Arc::clone(&account_factory))
+    .with_resets(config.resets_allowed, config.reset_cost, Arc::clone(&account_factory))
+    .with_eval_time_limit(config.eval_time_limit.clone(), account_factory)
+    .with_bankroll(config.bankroll.map(|starting| Bankroll { starting, floor: config.bankroll_floor.unwrap_or(0.0) }))
+    .with_news_blackout(config.news_blackout.clone())
+    .with_start_day(start_day)
+    .with_rule_timeline(config.rule_timeline.clone().unwrap_or_default())
+    .with_continue_after_max_payouts(config.continue_after_max_payouts)
+    .with_withdrawal_audit(config.audit_withdrawals)
+    .with_daily_stop_loss_percent(config.daily_stop_loss_percent)
+    .with_account_size(config.account_size.unwrap_or(0.0))
+    .with_contracts(config.traded_contracts);
+    if let Some(currency_conversion) = currency_conversion {
+        trader = trader.with_currency_conversion(currency_conversion.clone());
+    }
+    trader
+}
 
-    // Compute the percentage of positive balances
-    let positive_balances_count = filtered_balances.iter().filter(|&&b| b > 0.0).count();
-    let positive_balance_percentage = (positive_balances_count as f64 / filtered_balances.len() as f64) * 100.0;
+// Trade one day of randomly drawn trades, returning the end state if the run ended
+fn simulate_day(
+    trader: &mut Trader,
+    trades: &TradePool,
+    config: &SimulationConfig,
+    block: &mut BlockCursor,
+    rng: &mut impl Rng,
+) -> Option<EndOfGame> {
+    let mut trades_today = sample_day(trades, config, block, rng);
+    trader.trade_day(&mut trades_today, rng).end_of_game
+}
+
+// The trades of one simulated day, drawn as the config says; `block` carries block sampling
+// over from the previous day of the same stream of days
+fn sample_day(trades: &TradePool, config: &SimulationConfig, block: &mut BlockCursor, rng: &mut impl Rng) -> Vec<Trade> {
+    trades.sample_trades(config.trade_sampling, config.block_length.unwrap_or(DEFAULT_BLOCK_LENGTH), block, rng)
+}
+
+// Cohort mode: each run starts a new account every `interval_days` trading days until the
+// end of the simulation; all accounts share one bank account
+fn cohort_simulation(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    interval_days: u64,
+) -> CohortReport {
+    let start_days: Vec<u64> = (0..config.max_simulation_days).step_by(interval_days as usize).collect();
 
+    let runs: Vec<(Vec<f64>, f64)> = (0..config.iterations)
+        .into_par_iter()
+        .map(|iteration| {
+            let mut rng = SimulationRng::new(config.rng_backend, config.seed, RngStream::Cohort, iteration);
+            let mut monthly_cash_flow: Vec<f64> = Vec::new();
+            let mut total = 0.0;
+            for &start_day in &start_days {
+                let mut trader = new_trader(
+                    account_factory,
+                    currency_conversion,
+                    config,
+                    start_day,
+                    config.max_simulation_days - start_day,
+                );
+                let mut block = BlockCursor::default();
+                while simulate_day(&mut trader, trades, config, &mut block, &mut rng).is_none() {}
 
-    // Optionally generate and save a histogram
+                if monthly_cash_flow.len() < trader.monthly_income.len() {
+                    monthly_cash_flow.resize(trader.monthly_income.len(), 0.0);
+                }
+                for (month, income) in trader.monthly_income.iter().enumerate() {
+                    monthly_cash_flow[month] += income;
+                }
+                total += trader.bank_account.balance;
+            }
+            (monthly_cash_flow, total)
+        })
+        .collect();
 
-    #[cfg(feature = "web")]
-    let mut histogram_plotly_json  = None;
+    cohort::cohort_report(interval_days, start_days.len(), &runs, income::simulated_months(config.max_simulation_days))
+}
 
-    if config.histogram {
-        #[cfg(feature = "web")]
-        {
-            let plot_json = plotting::generate_plotly_histogram_json(&filtered_balances)?;
-            histogram_plotly_json = Some(plot_json);
-            info!("Histogram generated using Plotly");
+// Buy `evals` evaluations on day 0 and trade each day's trades on all of them until one
+// passes; that one is kept and the others are abandoned
+fn parallel_evals_run(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    evals: u32,
+    rng: &mut impl Rng,
+) -> EvalAttempts {
+    let mut traders: Vec<Trader> = (0..evals)
+        .map(|_| new_trader(account_factory, currency_conversion, config, 0, config.max_simulation_days))
+        .collect();
+    let eval_fees = traders.iter().map(|trader| trader.ledger.eval_fees).sum();
+    // Bank balances of the evaluations that busted or were abandoned
+    let mut closed = 0.0;
+    let mut block = BlockCursor::default();
+    let mut passed_day = None;
+    loop {
+        if passed_day.is_none() {
+            if let Some(winner) = traders.iter().position(|trader| trader.prop_account.is_funded()) {
+                passed_day = Some(traders[winner].prop_account.get_simulation_days());
+                let winner = traders.swap_remove(winner);
+                // Evaluations passing on the same day as the winner are not activated
+                closed += traders
+                    .iter()
+                    .map(|trader| trader.bank_account.balance + trader.ledger.activation_fees)
+                    .sum::<f64>();
+                traders = vec![winner];
+            }
         }
-        #[cfg(feature = "cli")]
-        {
-            if let Some(ref histogram_file) = config.histogram_file {
-                plot_histogram(&filtered_balances, histogram_file)?;
-                info!("Histogram saved to {}", histogram_file);
-            } else {
-                return Err("Histogram file path is required when histogram is enabled".into());
+        if traders.is_empty() {
+            break;
+        }
+
+        let trades_today = sample_day(trades, config, &mut block, rng);
+        let mut active = Vec::with_capacity(traders.len());
+        for mut trader in traders {
+            match trader.trade_day(&mut trades_today.clone(), rng).end_of_game {
+                Some(_) => closed += trader.bank_account.balance,
+                None => active.push(trader),
             }
         }
+        traders = active;
     }
+    EvalAttempts { passed_day, eval_fees, final_balance: closed }
+}
 
-    // Return the simulation result
-    Ok(SimulationResult {
-        final_balances: filtered_balances,
-        mean_balance,
-        median_balance,
-        std_dev,
-        mad,
-        iqr,
-        mad_median,
-        mean_days,
-        end_state_percentages,
-        positive_balance_percentage,
-        #[cfg(feature = "web")]
-        histogram_plotly_json,   // Included in JSON response
-    })
+// Buy up to `evals` evaluations one after another, each after the previous one busted
+// before passing
+fn sequential_evals_run(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    evals: u32,
+    rng: &mut impl Rng,
+) -> EvalAttempts {
+    let mut attempts = EvalAttempts { passed_day: None, eval_fees: 0.0, final_balance: 0.0 };
+    let mut start_day = 0;
+    // The evaluations trade one stream of days, one after another
+    let mut block = BlockCursor::default();
+    for _ in 0..evals {
+        if start_day >= config.max_simulation_days {
+            break;
+        }
+        let mut trader = new_trader(
+            account_factory,
+            currency_conversion,
+            config,
+            start_day,
+            config.max_simulation_days - start_day,
+        );
+        let end_state = loop {
+            if attempts.passed_day.is_none() && trader.prop_account.is_funded() {
+                attempts.passed_day = Some(start_day + trader.prop_account.get_simulation_days());
+            }
+            if let Some(end_of_game) = simulate_day(&mut trader, trades, config, &mut block, rng) {
+                break end_of_game;
+            }
+        };
+        if attempts.passed_day.is_none() && trader.prop_account.is_funded() {
+            attempts.passed_day = Some(start_day + trader.prop_account.get_simulation_days());
+        }
+        attempts.eval_fees += trader.ledger.eval_fees;
+        attempts.final_balance += trader.bank_account.balance;
+        start_day += trader.prop_account.get_simulation_days();
+        if attempts.passed_day.is_some() || end_state != EndOfGame::Busted {
+            break;
+        }
+    }
+    attempts
 }
 
-// Helper function to run the Monte Carlo simulation
-fn monte_carlo_simulation(
-    trades: &Vec<TradeRecord>,
-    trades_per_day: &Vec<usize>,
-    iterations: usize,
-    account_type: AccountType,
-    max_trades_per_day: Option<u64>,
-    daily_profit_target: Option<f64>,
-    daily_stop_loss: Option<f64>,
-    max_simulation_days: u64,
-    max_payouts: u8,
-) -> Vec<IterationResult> {
-    (0..iterations)
+fn parallel_eval_simulation(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    evals: u32,
+) -> ParallelEvalReport {
+    let (parallel, sequential): (Vec<EvalAttempts>, Vec<EvalAttempts>) = (0..config.iterations)
+        .into_par_iter()
+        .map(|iteration| {
+            // Both strategies draw the same random numbers, so they differ only by strategy
+            let rng = || SimulationRng::new(config.rng_backend, config.seed, RngStream::ParallelEvals, iteration);
+            (
+                parallel_evals_run(trades, account_factory, currency_conversion, config, evals, &mut rng()),
+                sequential_evals_run(trades, account_factory, currency_conversion, config, evals, &mut rng()),
+            )
+        })
+        .unzip();
+
+    ParallelEvalReport {
+        evals,
+        parallel: parallel_evals::eval_attempt_stats(&parallel),
+        sequential: parallel_evals::eval_attempt_stats(&sequential),
+    }
+}
+
+// Hedge mode: the primary account and a hedge account at another firm trade correlated
+// streams day by day; a run lasts until both accounts ended
+fn hedge_simulation(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    hedge_account_type: &str,
+    hedge_factory: &AccountFactory,
+) -> HedgeReport {
+    let correlation = config.hedge_correlation.unwrap_or(-1.0);
+    let round_trip_cost = config.round_trip_cost.unwrap_or(0.0);
+    // Price and rule overrides of the config belong to the primary account
+    let hedge_config = SimulationConfig {
+        purchase_price: None,
+        drawdown_type: None,
+        payout_cadence: None,
+        payout_delay_days: None,
+        payout_denial_probability: None,
+        min_profitable_days: None,
+        rule_timeline: None,
+        ..config.clone()
+    };
+
+    let runs: Vec<HedgeRun> = (0..config.iterations)
         .into_par_iter()
-        .map(|_| {
-            let mut rng = rand::thread_rng();
-            let mut trader = Trader::new(
-                account_type.clone(),
-                max_trades_per_day,
-                daily_profit_target,
-                daily_stop_loss,
-                max_simulation_days,
-                max_payouts,
-            );
-
-            let end_state = loop {
-                let num_trades_today = *trades_per_day.choose(&mut rng).unwrap_or(&0);
-                let trades_today: Vec<_> = (0..num_trades_today)
-                    .map(|_| trades.choose(&mut rng).unwrap().trade.clone())
-                    .collect();
-
-                let trading_day_result = trader.trade_day(&mut trades_today.clone());
-
-                if let Some(end_of_game) = trading_day_result.end_of_game {
-                    break end_of_game;
+        .map(|iteration| {
+            let mut rng = SimulationRng::new(config.rng_backend, config.seed, RngStream::Hedge, iteration);
+            let mut primary = new_trader(account_factory, currency_conversion, config, 0, config.max_simulation_days);
+            let mut hedge = new_trader(hedge_factory, currency_conversion, &hedge_config, 0, config.max_simulation_days);
+            let (mut primary_end, mut hedge_end) = (None, None);
+            let (mut primary_block, mut independent_block) = (BlockCursor::default(), BlockCursor::default());
+            while primary_end.is_none() || hedge_end.is_none() {
+                let mut primary_trades = sample_day(trades, config, &mut primary_block, &mut rng);
+                let independent = sample_day(trades, config, &mut independent_block, &mut rng);
+                let mut hedge_trades =
+                    hedge::hedge_trades(&primary_trades, independent, correlation, round_trip_cost, &mut rng);
+                if primary_end.is_none() {
+                    primary_end = primary.trade_day(&mut primary_trades, &mut rng).end_of_game;
+                }
+                if hedge_end.is_none() {
+                    hedge_end = hedge.trade_day(&mut hedge_trades, &mut rng).end_of_game;
                 }
-            };
+            }
+            let payouts = |trader: &Trader| trader.ledger.gross_payouts + trader.ledger.terminal_payouts;
+            HedgeRun {
+                combined_balance: primary.bank_account.balance + hedge.bank_account.balance,
+                gross_payouts: payouts(&primary) + payouts(&hedge),
+                primary_busted: primary_end == Some(EndOfGame::Busted),
+                hedge_busted: hedge_end == Some(EndOfGame::Busted),
+            }
+        })
+        .collect();
+
+    hedge::hedge_report(hedge_account_type, correlation, &runs)
+}
+
+// Copy trading: `accounts` copies of the account trade each day's shared trades, or on a
+// share of the days their own draw, until all of them ended
+fn copy_trading_simulation(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    accounts: u32,
+) -> CopyTradingReport {
+    let correlation = config.copy_correlation.unwrap_or(1.0);
 
-            IterationResult {
-                final_balance: trader.bank_account.balance,
-                end_state,
-                simulation_length: trader.prop_account.get_simulation_days(),
+    let runs: Vec<CopyTradingRun> = (0..config.iterations)
+        .into_par_iter()
+        .map(|iteration| {
+            let mut rng = SimulationRng::new(config.rng_backend, config.seed, RngStream::CopyTrading, iteration);
+            let mut traders: Vec<Trader> = (0..accounts)
+                .map(|_| new_trader(account_factory, currency_conversion, config, 0, config.max_simulation_days))
+                .collect();
+            let mut ends: Vec<Option<EndOfGame>> = vec![None; traders.len()];
+            // The shared days, and those each copy draws on its own
+            let mut shared_block = BlockCursor::default();
+            let mut own_blocks = vec![BlockCursor::default(); traders.len()];
+            let combined_balance = |traders: &[Trader]| traders.iter().map(|trader| trader.bank_account.balance).sum::<f64>();
+            let mut lowest_combined_balance = combined_balance(&traders);
+            while ends.iter().any(Option::is_none) {
+                let shared = sample_day(trades, config, &mut shared_block, &mut rng);
+                let copies = traders.iter_mut().zip(ends.iter_mut()).zip(own_blocks.iter_mut());
+                for ((trader, end), block) in copies.filter(|((_, end), _)| end.is_none()) {
+                    let mut trades_today =
+                        if rng.gen_bool(correlation) { shared.clone() } else { sample_day(trades, config, block, &mut rng) };
+                    *end = trader.trade_day(&mut trades_today, &mut rng).end_of_game;
+                }
+                lowest_combined_balance = lowest_combined_balance.min(combined_balance(&traders));
+            }
+            CopyTradingRun {
+                account_balances: traders.iter().map(|trader| trader.bank_account.balance).collect(),
+                combined_balance: combined_balance(&traders),
+                lowest_combined_balance,
+                gross_payouts: traders.iter().map(|trader| trader.ledger.gross_payouts + trader.ledger.terminal_payouts).sum(),
+                busted_accounts: ends.iter().filter(|&end| *end == Some(EndOfGame::Busted)).count() as u32,
             }
-    }).collect()
+        })
+        .collect();
+
+    copy_trading::copy_trading_report(accounts, correlation, &runs)
+}
+
+// Iterations not started when `handle` is cancelled are skipped and the run fails. With
+// `convergence` the iterations run in batches until the standard error of the runs so far
+// is within the tolerance; `target_end_states` are the runs the mean balance covers.
+fn monte_carlo_simulation(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    target_end_states: &[EndOfGame],
+    handle: &SimulationHandle,
+    on_iteration: IterationObserver,
+) -> Result<(Vec<IterationResult>, Option<ConvergenceReport>), SimulationError> {
+    let snapshot_days = snapshot_days(config);
+    let mut results = Vec::new();
+    let mut tracker = ConvergenceTracker::default();
+
+    for batch in convergence::iteration_batches(config.iterations, config.convergence.as_ref()) {
+        let batch_results = batch
+            .into_par_iter()
+            .map(|iteration| {
+                if handle.is_cancelled() {
+                    return None;
+                }
+                Some(monte_carlo_iteration(trades, account_factory, currency_conversion, config, &snapshot_days, on_iteration, iteration))
+        }).collect::<Option<Vec<_>>>()
+        .ok_or(SimulationError::Cancelled)?;
+        for result in &batch_results {
+            track_convergence(&mut tracker, result, target_end_states);
+        }
+        results.extend(batch_results);
+        if config.convergence.is_some_and(|convergence| tracker.converged(&convergence)) {
+            break;
+        }
+    }
+    Ok((results, config.convergence.and_then(|convergence| tracker.report(&convergence))))
+}
+
+// Like `monte_carlo_simulation`, but each run is folded into the statistics instead of
+// kept, for `streaming_stats`
+fn streaming_monte_carlo_simulation(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    target_end_states: &[EndOfGame],
+    handle: &SimulationHandle,
+    on_iteration: IterationObserver,
+) -> Result<(StreamingStats, Option<ConvergenceReport>), SimulationError> {
+    let mut stats = StreamingStats::default();
+    let mut tracker = ConvergenceTracker::default();
+
+    for batch in convergence::iteration_batches(config.iterations, config.convergence.as_ref()) {
+        let (batch_stats, batch_tracker) = batch
+            .into_par_iter()
+            .map(|iteration| {
+                (!handle.is_cancelled())
+                    .then(|| monte_carlo_iteration(trades, account_factory, currency_conversion, config, &[], on_iteration, iteration))
+            })
+            .try_fold(<(StreamingStats, ConvergenceTracker)>::default, |(mut stats, mut tracker), result| {
+                let result = result?;
+                track_convergence(&mut tracker, &result, target_end_states);
+                stats.add(&result);
+                Some((stats, tracker))
+            })
+            .try_reduce(<(StreamingStats, ConvergenceTracker)>::default, |(stats, mut tracker), (other, other_tracker)| {
+                tracker.merge(&other_tracker);
+                Some((stats.merge(other), tracker))
+            })
+            .ok_or(SimulationError::Cancelled)?;
+        stats = stats.merge(batch_stats);
+        tracker.merge(&batch_tracker);
+        if config.convergence.is_some_and(|convergence| tracker.converged(&convergence)) {
+            break;
+        }
+    }
+    Ok((stats, config.convergence.and_then(|convergence| tracker.report(&convergence))))
+}
+
+// Count a run towards the standard errors `convergence` checks
+fn track_convergence(tracker: &mut ConvergenceTracker, result: &IterationResult, target_end_states: &[EndOfGame]) {
+    let conditioned = target_end_states.contains(&result.end_state);
+    tracker.add(conditioned.then_some(result.final_balance), result.end_state == EndOfGame::Busted);
+}
+
+// One run of the Monte Carlo simulation
+fn monte_carlo_iteration(
+    trades: &TradePool,
+    account_factory: &AccountFactory,
+    currency_conversion: Option<&CurrencyConversion>,
+    config: &SimulationConfig,
+    snapshot_days: &[u64],
+    on_iteration: IterationObserver,
+    iteration: usize,
+) -> IterationResult {
+    let mut rng = SimulationRng::new(config.rng_backend, config.seed, RngStream::MonteCarlo, iteration);
+    let mut trader = new_trader(account_factory, currency_conversion, config, 0, config.max_simulation_days);
+
+    let mut snapshots = Vec::with_capacity(snapshot_days.len());
+    let mut balance_path = Vec::new();
+    if config.equity_curve {
+        balance_path.push(trader.bank_account.balance);
+    }
+    let mut block = BlockCursor::default();
+    let end_state = loop {
+        if let Some(end_of_game) = simulate_day(&mut trader, trades, config, &mut block, &mut rng) {
+            break end_of_game;
+        }
+        let days = trader.prop_account.get_simulation_days();
+        while snapshot_days.get(snapshots.len()).is_some_and(|&day| day <= days) {
+            snapshots.push(AccountSnapshot::capture(&trader, None));
+        }
+        while config.equity_curve && balance_path.len() as u64 <= days {
+            balance_path.push(trader.bank_account.balance);
+        }
+    };
+    // The day the run ended sees its final balance
+    if config.equity_curve {
+        balance_path.push(trader.bank_account.balance);
+    }
+    // Snapshot days after the run ended see its final state
+    while snapshots.len() < snapshot_days.len() {
+        snapshots.push(AccountSnapshot::capture(&trader, Some(end_state.clone())));
+    }
+    on_iteration(&IterationSummary {
+        iteration,
+        end_state: end_state.clone(),
+        final_balance: trader.bank_account.balance,
+        simulation_length: trader.prop_account.get_simulation_days(),
+        max_payouts_day: trader.max_payouts_day,
+        funded_day: trader.funded_day,
+        first_payout_day: trader.first_payout_day,
+        ledger: trader.ledger.clone(),
+        soft_breaches: trader.rule_triggers.soft_breaches,
+        withdrawals: std::mem::take(&mut trader.withdrawals),
+    });
+
+    IterationResult {
+        final_balance: trader.bank_account.balance,
+        end_state,
+        simulation_length: trader.prop_account.get_simulation_days(),
+        ledger: trader.ledger,
+        payouts: trader.payouts,
+        monthly_income: trader.monthly_income,
+        weekly_pnl: trader.weekly_pnl,
+        monthly_pnl: trader.monthly_pnl,
+        worst_trade: trader.worst_trade,
+        worst_day: trader.worst_day,
+        min_drawdown_buffer: trader.min_drawdown_buffer,
+        buffer_at_first_payout: trader.buffer_at_first_payout,
+        funded_day: trader.funded_day,
+        first_payout_day: trader.first_payout_day,
+        snapshots,
+        balance_path,
+        max_payouts_day: trader.max_payouts_day,
+        rule_triggers: trader.rule_triggers,
+        replacements_used: trader.replacements_used,
+        resets_used: trader.resets_used,
+        eval_extensions: trader.eval_extensions,
+        eval_expiries: trader.eval_expiries,
+    }
 }
@@ -0,0 +1,97 @@
+// src/simulator/news.rs
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+use super::trade_data::Trade;
+
+// FOMC rate decision days (the second day of each meeting)
+const FOMC_DECISIONS: [(i32, u32, u32); 24] = [
+    (2024, 1, 31), (2024, 3, 20), (2024, 5, 1), (2024, 6, 12),
+    (2024, 7, 31), (2024, 9, 18), (2024, 11, 7), (2024, 12, 18),
+    (2025, 1, 29), (2025, 3, 19), (2025, 5, 7), (2025, 6, 18),
+    (2025, 7, 30), (2025, 9, 17), (2025, 10, 29), (2025, 12, 10),
+    (2026, 1, 28), (2026, 3, 18), (2026, 4, 29), (2026, 6, 17),
+    (2026, 7, 29), (2026, 9, 16), (2026, 10, 28), (2026, 12, 9),
+];
+
+/// What happens on high-impact news days
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewsAction {
+    /// No trades are taken (the firm prohibits news trading or the trader sits out)
+    #[default]
+    SitOut,
+    /// Trades are taken with inflated variance
+    InflateVariance,
+}
+
+impl NewsAction {
+    pub const ALL: [NewsAction; 2] = [NewsAction::SitOut, NewsAction::InflateVariance];
+}
+
+impl FromStr for NewsAction {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sit_out" => Ok(NewsAction::SitOut),
+            "inflate_variance" => Ok(NewsAction::InflateVariance),
+            _ => Err("Unknown news action (options: sit_out, inflate_variance)"),
+        }
+    }
+}
+
+/// High-impact news days, placed on the calendar of the account's purchase date
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NewsBlackout {
+    /// News dates in addition to the built-in calendar
+    pub dates: Vec<NaiveDate>,
+    /// Include the built-in calendar: FOMC decisions (2024-2026) and non-farm payrolls
+    /// releases (the first Friday of every month)
+    pub builtin_calendar: bool,
+    pub action: NewsAction,
+    /// Factor the variance of news day trades is multiplied by with `inflate_variance`
+    pub variance_factor: f64,
+}
+
+impl Default for NewsBlackout {
+    fn default() -> Self {
+        NewsBlackout {
+            dates: Vec::new(),
+            builtin_calendar: true,
+            action: NewsAction::default(),
+            variance_factor: 2.0,
+        }
+    }
+}
+
+fn is_nfp_release(date: NaiveDate) -> bool {
+    date.weekday() == Weekday::Fri && date.day() <= 7
+}
+
+fn is_fomc_decision(date: NaiveDate) -> bool {
+    FOMC_DECISIONS.contains(&(date.year(), date.month(), date.day()))
+}
+
+impl NewsBlackout {
+    pub fn is_news_day(&self, date: NaiveDate) -> bool {
+        self.dates.contains(&date) || (self.builtin_calendar && (is_nfp_release(date) || is_fomc_decision(date)))
+    }
+
+    /// Apply the news day action to the trades of a news day
+    pub fn apply(&self, trades: &mut Vec<Trade>) {
+        match self.action {
+            NewsAction::SitOut => trades.clear(),
+            NewsAction::InflateVariance => {
+                // Scaling returns by the square root scales their variance by the factor
+                let scale = self.variance_factor.sqrt();
+                for trade in trades.iter_mut() {
+                    trade.return_value *= scale;
+                    trade.max_opposite_excursion *= scale;
+                }
+            }
+        }
+    }
+}
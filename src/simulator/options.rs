@@ -0,0 +1,69 @@
+// src/simulator/options.rs
+use serde::{Serialize, Deserialize};
+
+use super::formatting::Currency;
+use super::holding::HoldingAction;
+use super::news::NewsAction;
+use super::insurance::InsuranceCoverage;
+use super::convergence::ConvergenceMetric;
+use super::time_limit::ExpiryAction;
+use super::prop_account::{registered_accounts, DrawdownType, PayoutCadence};
+use super::rng::RngBackend;
+use super::trade_data::TradeSampling;
+use super::trader::EndOfGame;
+
+/// Allowed values of the config fields that take one of a fixed set of names, so
+/// frontends can fill their dropdowns from the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationOptions {
+    pub condition_end_state: Vec<String>,
+    pub account_type: Vec<String>,
+    pub trade_sampling: Vec<String>,
+    pub drawdown_type: Vec<String>,
+    pub payout_cadence: Vec<String>,
+    pub rng_backend: Vec<String>,
+    pub currency: Vec<String>,
+    /// `holding_rules.action`
+    pub holding_action: Vec<String>,
+    /// `news_blackout.action`
+    pub news_action: Vec<String>,
+    /// `insurance.covers`
+    pub insurance_covers: Vec<String>,
+    /// `eval_time_limit.on_expiry`
+    pub eval_expiry: Vec<String>,
+    /// `convergence.metric`
+    pub convergence_metric: Vec<String>,
+    /// Config fields controlling what the result includes
+    pub output_options: Vec<String>,
+}
+
+// Names of the values as they appear in a JSON config
+fn names<T: Serialize>(values: &[T]) -> Vec<String> {
+    values
+        .iter()
+        .filter_map(|value| serde_json::to_value(value).ok()?.as_str().map(String::from))
+        .collect()
+}
+
+pub fn simulation_options() -> SimulationOptions {
+    let mut condition_end_state = vec![String::from("All")];
+    condition_end_state.extend(names(&EndOfGame::ALL));
+
+    SimulationOptions {
+        condition_end_state,
+        account_type: registered_accounts(),
+        trade_sampling: names(&TradeSampling::ALL),
+        drawdown_type: names(&DrawdownType::ALL),
+        payout_cadence: names(&PayoutCadence::ALL),
+        rng_backend: names(&RngBackend::ALL),
+        currency: names(&Currency::ALL),
+        holding_action: names(&HoldingAction::ALL),
+        news_action: names(&NewsAction::ALL),
+        insurance_covers: names(&InsuranceCoverage::ALL),
+        eval_expiry: names(&ExpiryAction::ALL),
+        convergence_metric: names(&ConvergenceMetric::ALL),
+        output_options: ["histogram", "histogram_bins", "balance_sample_size", "snapshot_days", "equity_curve", "percentiles", "var_confidence"]
+            .map(String::from)
+            .to_vec(),
+    }
+}
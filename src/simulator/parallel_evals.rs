@@ -0,0 +1,43 @@
+// src/simulator/parallel_evals.rs
+use serde::{Serialize, Deserialize};
+
+/// Buying `evals` evaluations at once, all trading the same trades and keeping the first
+/// to pass, compared with buying up to `evals` evaluations one after another, each after
+/// the previous one busted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParallelEvalReport {
+    pub evals: u32,
+    pub parallel: EvalAttemptStats,
+    pub sequential: EvalAttemptStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalAttemptStats {
+    /// Runs in which an evaluation was passed
+    pub pass_percentage: f64,
+    /// Mean evaluation fees paid per run
+    pub mean_eval_fees: f64,
+    /// Mean trading day the evaluation was passed on, over the runs passing one
+    pub mean_days_to_pass: Option<f64>,
+    /// Mean final bank balance of all evaluations bought in a run
+    pub mean_final_balance: f64,
+}
+
+/// Outcome of one run of either strategy
+#[derive(Debug, Clone)]
+pub struct EvalAttempts {
+    pub passed_day: Option<u64>,
+    pub eval_fees: f64,
+    pub final_balance: f64,
+}
+
+pub fn eval_attempt_stats(runs: &[EvalAttempts]) -> EvalAttemptStats {
+    let count = runs.len().max(1) as f64;
+    let pass_days: Vec<f64> = runs.iter().filter_map(|run| run.passed_day).map(|day| day as f64).collect();
+    EvalAttemptStats {
+        pass_percentage: pass_days.len() as f64 / count * 100.0,
+        mean_eval_fees: runs.iter().map(|run| run.eval_fees).sum::<f64>() / count,
+        mean_days_to_pass: (!pass_days.is_empty()).then(|| pass_days.iter().sum::<f64>() / pass_days.len() as f64),
+        mean_final_balance: runs.iter().map(|run| run.final_balance).sum::<f64>() / count,
+    }
+}
@@ -0,0 +1,58 @@
+// src/simulator/periods.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+
+/// Prop account trading P&L per period of trading days, pooled over the periods every run
+/// traded in. The last period of a run that ended early is partial.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeriodPnlStats {
+    /// Periods over all runs
+    pub periods: usize,
+    pub mean: f64,
+    /// Standard deviation of the P&L per period
+    pub volatility: f64,
+    pub median: f64,
+    /// 5th percentile, the P&L of a bad period
+    pub p5: f64,
+    pub worst: f64,
+    pub best: f64,
+    /// Percentage of periods with a loss
+    pub losing_percentage: f64,
+    /// Mean of the worst period of each run
+    pub mean_worst: f64,
+}
+
+/// Weekly (5 trading days) and monthly (21 trading days) P&L, the figures firms' risk
+/// desks evaluate traders on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeriodPnlReport {
+    pub weekly: PeriodPnlStats,
+    pub monthly: PeriodPnlStats,
+}
+
+/// Build the stats from the P&L per period of each run
+pub fn period_pnl_stats(runs: &[&[f64]]) -> PeriodPnlStats {
+    let all: Vec<f64> = runs.iter().flat_map(|periods| periods.iter().copied()).collect();
+    let pnl = stats::sorted(&all);
+    let (Some(mean), Some(volatility), Some(median)) = (stats::mean(&all), stats::std_dev(&all), stats::median(&pnl)) else {
+        return PeriodPnlStats::default();
+    };
+    let n = pnl.len() as f64;
+    let worst_per_run: Vec<f64> = runs
+        .iter()
+        .filter_map(|periods| periods.iter().copied().reduce(f64::min))
+        .collect();
+
+    PeriodPnlStats {
+        periods: pnl.len(),
+        mean,
+        volatility,
+        median,
+        p5: stats::percentile(&pnl, 5.0).unwrap_or_default(),
+        worst: pnl[0],
+        best: pnl[pnl.len() - 1],
+        losing_percentage: pnl.iter().filter(|&&value| value < 0.0).count() as f64 / n * 100.0,
+        mean_worst: worst_per_run.iter().sum::<f64>() / worst_per_run.len() as f64,
+    }
+}
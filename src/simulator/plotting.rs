@@ -1,44 +1,85 @@
 use plotters::prelude::*;
+use serde::{Serialize, Deserialize};
+
+pub const DEFAULT_HISTOGRAM_BINS: usize = 50;
+
+/// Histogram as raw bin edges and counts, for frontends that render their own charts.
+/// `edges` has one more element than `counts`; the last bin includes its upper edge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBins {
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+}
+
+/// Bin `data` into `bin_count` equal-width bins spanning its min and max. NaN and
+/// infinite values are left out; without any other value there are no bins.
+pub fn compute_histogram(data: &[f64], bin_count: usize) -> HistogramBins {
+    let bin_count = bin_count.max(1);
+    let data: Vec<f64> = data.iter().copied().filter(|value| value.is_finite()).collect();
+    if data.is_empty() {
+        return HistogramBins { edges: Vec::new(), counts: Vec::new() };
+    }
+
+    let min_value = data.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_value = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let bin_width = (max_value - min_value) / bin_count as f64;
+    if min_value + bin_width <= min_value {
+        // All values identical, or too close to tell apart: a single bin around them,
+        // wide enough to be a range at the scale of the values
+        let half_width = (min_value.abs() * 1e-9).max(0.5);
+        return HistogramBins { edges: vec![min_value - half_width, max_value + half_width], counts: vec![data.len()] };
+    }
+
+    let edges = (0..=bin_count).map(|i| min_value + i as f64 * bin_width).collect();
+    let mut counts = vec![0; bin_count];
+    for &value in &data {
+        let bin = (((value - min_value) / bin_width).floor() as usize).min(bin_count - 1);
+        counts[bin] += 1;
+    }
+
+    HistogramBins { edges, counts }
+}
 
 /// Generate a histogram of final account balances with y-axis scaled as a percentage
 pub fn plot_histogram(data: &[f64], file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    plot_labeled_histogram(data, file_path, "Histogram of Final Account Balances", "Total Payouts - Account Cost")
+}
+
+/// Generate a histogram of the gross payouts per run (a result's `final_gross_payouts`)
+pub fn plot_gross_payout_histogram(data: &[f64], file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    plot_labeled_histogram(data, file_path, "Histogram of Gross Payouts", "Gross Payouts")
+}
+
+fn plot_labeled_histogram(data: &[f64], file_path: &str, caption: &str, x_desc: &str) -> Result<(), Box<dyn std::error::Error>> {
     let root = BitMapBackend::new(file_path, (800, 600)).into_drawing_area();
     root.fill(&WHITE)?;
 
-    let min_balance = *data.iter().min_by(|a, b| a.partial_cmp(b).unwrap()).unwrap();
-    let max_balance = *data.iter().max_by(|a, b| a.partial_cmp(b).unwrap()).unwrap();
+    // Calculate histogram bins; without data the chart is drawn empty
+    let histogram = compute_histogram(data, DEFAULT_HISTOGRAM_BINS);
+    let (min_balance, max_balance) = match (histogram.edges.first(), histogram.edges.last()) {
+        (Some(&min), Some(&max)) => (min, max),
+        _ => (0.0, 1.0),
+    };
 
-    let total_data_count = data.len() as f64;
+    let total_data_count = histogram.counts.iter().sum::<usize>().max(1) as f64;
 
     let mut chart = ChartBuilder::on(&root)
-        .caption("Histogram of Final Account Balances", ("sans-serif", 20))
+        .caption(caption, ("sans-serif", 20))
         .margin(20)
         .x_label_area_size(30)
         .y_label_area_size(40)
         .build_cartesian_2d(min_balance..max_balance, 0.0..100.0)?; // Set y-axis from 0% to 100%
 
     chart.configure_mesh()
-        .x_desc("Total Payouts - Account Cost") // Set the x-axis label
+        .x_desc(x_desc) // Set the x-axis label
         .y_desc("Percentage (%)") // Label the y-axis as percentage
         .draw()?;
 
-    // Calculate histogram bins
-    let bin_count = 50;
-    let bin_width = (max_balance - min_balance) / bin_count as f64;
-    let mut histogram = vec![0; bin_count];
-
-    for &balance in data {
-        let bin = ((balance - min_balance) / bin_width).floor() as usize;
-        if bin < bin_count {
-            histogram[bin] += 1;
-        }
-    }
-
     // Draw bars for each bin as percentages
     chart.draw_series(
-        histogram.iter().enumerate().map(|(i, &count)| {
-            let x0 = min_balance + i as f64 * bin_width;
-            let x1 = x0 + bin_width;
+        histogram.counts.iter().enumerate().map(|(i, &count)| {
+            let x0 = histogram.edges[i];
+            let x1 = histogram.edges[i + 1];
             let percent = (count as f64 / total_data_count) * 100.0; // Convert count to percentage
             Rectangle::new(
                 [(x0, 0.0), (x1, percent)],
@@ -51,18 +92,32 @@ pub fn plot_histogram(data: &[f64], file_path: &str) -> Result<(), Box<dyn std::
     Ok(())
 }
 
+/// Plotly chart JSON of a histogram of `data`, typically a result's `final_balances`
 #[cfg(feature = "web")]
 pub fn generate_plotly_histogram_json(data: &[f64]) -> Result<String, Box<dyn std::error::Error>> {
+    plotly_labeled_histogram_json(data, "Final Account Balances", "Total Payouts - Account Cost", "#1f77b4")
+}
+
+/// Plotly chart JSON of a histogram of the gross payouts per run, a result's `final_gross_payouts`
+#[cfg(feature = "web")]
+pub fn generate_plotly_gross_payout_histogram_json(data: &[f64]) -> Result<String, Box<dyn std::error::Error>> {
+    plotly_labeled_histogram_json(data, "Gross Payouts", "Gross Payouts", "#2ca02c")
+}
+
+#[cfg(feature = "web")]
+fn plotly_labeled_histogram_json(data: &[f64], name: &str, x_title: &str, color: &str) -> Result<String, Box<dyn std::error::Error>> {
     use plotly::common::{Title, Marker};
     use plotly::{Histogram, Layout, Plot};
 
-    let hist = Histogram::new(data.to_vec())
-        .name("Final Account Balances")
-        .marker(Marker::new().color("#1f77b4"));
+    // JSON has no NaN or infinity
+    let data: Vec<f64> = data.iter().copied().filter(|value| value.is_finite()).collect();
+    let hist = Histogram::new(data)
+        .name(name)
+        .marker(Marker::new().color(color.to_string()));
 
     let layout = Layout::new()
-        .title(Title::new("Histogram of Final Account Balances"))
-        .x_axis(plotly::layout::Axis::new().title(Title::new("Total Payouts - Account Cost")))
+        .title(Title::new(&format!("Histogram of {}", name)))
+        .x_axis(plotly::layout::Axis::new().title(Title::new(x_title)))
         .y_axis(plotly::layout::Axis::new().title(Title::new("Count")));
 
     let mut plot = Plot::new();
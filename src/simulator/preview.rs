@@ -0,0 +1,87 @@
+// src/simulator/preview.rs
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+
+use super::trade_data::SIMULATED_DAYS;
+use super::{load_trades, SimulationConfig, SimulationError};
+
+/// Trades loaded for a config, before any simulation: a sample and their statistics, so
+/// users can check what their synthetic-generation parameters imply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradePreview {
+    /// First trades in date order
+    pub sample: Vec<PreviewTrade>,
+    pub total_trades: usize,
+    /// Days covered, including days without trades for generated trades
+    pub days: usize,
+    /// Percentage of winning trades
+    pub win_rate: f64,
+    /// Mean return per trade
+    pub expectancy: f64,
+    pub average_win: f64,
+    pub average_loss: f64,
+    pub mean_trades_per_day: f64,
+    /// Number of days with 0, 1, 2, ... trades
+    pub trades_per_day_histogram: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewTrade {
+    pub date: NaiveDate,
+    pub return_value: f64,
+    pub max_opposite_excursion: f64,
+}
+
+/// Load (or generate) the trades of `config` without running the simulation. Generated
+/// trades follow the config's seed, so a seeded preview shows the trades the simulation uses.
+pub fn preview_trades(config: &SimulationConfig, sample_size: usize) -> Result<TradePreview, SimulationError> {
+    let pool = load_trades(config)?;
+    let trades: Vec<_> = pool.records().iter().map(|record| record.trade()).collect();
+
+    let mut trades_per_day_histogram = Vec::new();
+    for count in pool.trades_per_day() {
+        if trades_per_day_histogram.len() <= count {
+            trades_per_day_histogram.resize(count + 1, 0);
+        }
+        trades_per_day_histogram[count] += 1;
+    }
+    // Generated days drawing no trades leave no record
+    let mut days = trades_per_day_histogram.iter().sum::<usize>();
+    if config.csv_data.is_none() && config.csv_file.is_none() {
+        let empty_days = (SIMULATED_DAYS as usize).saturating_sub(days);
+        match trades_per_day_histogram.first_mut() {
+            Some(zero) => *zero += empty_days,
+            None => trades_per_day_histogram.push(empty_days),
+        }
+        days += empty_days;
+    }
+
+    let mean = |values: &[f64]| if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 };
+    let returns: Vec<f64> = trades.iter().map(|trade| trade.return_value).collect();
+    let wins: Vec<f64> = returns.iter().copied().filter(|&r| r > 0.0).collect();
+    let losses: Vec<f64> = returns.iter().copied().filter(|&r| r <= 0.0).collect();
+
+    Ok(TradePreview {
+        sample: pool
+            .records()
+            .iter()
+            .take(sample_size)
+            .map(|record| {
+                let trade = record.trade();
+                PreviewTrade {
+                    date: record.date(),
+                    return_value: trade.return_value,
+                    max_opposite_excursion: trade.max_opposite_excursion,
+                }
+            })
+            .collect(),
+        total_trades: trades.len(),
+        days,
+        win_rate: if trades.is_empty() { 0.0 } else { wins.len() as f64 / trades.len() as f64 * 100.0 },
+        expectancy: mean(&returns),
+        average_win: mean(&wins),
+        average_loss: mean(&losses),
+        mean_trades_per_day: if days == 0 { 0.0 } else { trades.len() as f64 / days as f64 },
+        trades_per_day_histogram,
+    })
+}
@@ -1,39 +1,153 @@
 // src/simulator/prop_account/account_type.rs
 use serde::{Serialize, Deserialize};
+use std::fmt;
 use std::str::FromStr;
 
-use super::{FttAccountType, TopstepAccountType};
+use super::{BulenoxAccountType, CustomAccountSpec, FttAccountType, MyFundedFuturesAccountType, TakeProfitTraderAccountType, TopstepAccountType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AccountType {
     Ftt(FttAccountType),
     TopStep(TopstepAccountType),
+    MyFundedFutures(MyFundedFuturesAccountType),
+    TakeProfitTrader(TakeProfitTraderAccountType),
+    Bulenox(BulenoxAccountType),
+    /// Account of any firm, defined by its rules instead of a built-in type
+    Custom(Box<CustomAccountSpec>),
     // Add other companies' account types here...
 }
 
 impl FromStr for AccountType {
-    type Err = &'static str;
+    type Err = String;
 
+    /// `company:account_type`, or the two separated by a space ("topstep 50k"). Names are
+    /// case-insensitive and accounts also go by their aliases and sizes, e.g. "topstep:100k".
+    /// Unknown names fail with the closest built-in account as suggestion.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(':').collect();
-        if parts.len() != 2 {
-            return Err("Invalid account type format. Use 'company:account_type'.");
-        }
-        let company = parts[0].to_lowercase();
-        let account_type = parts[1];
+        let (company, account_type) = split_account_name(s)
+            .ok_or("Invalid account type format. Use 'company:account_type'.")?;
+        let company: String = company.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
 
-        match company.as_str() {
-            "ftt" => {
-                let ftt_type = FttAccountType::from_str(account_type)?;
-                Ok(AccountType::Ftt(ftt_type))
-            }
-            "topstep" => {
-                let topstep_type = TopstepAccountType::from_str(account_type)?;
-                Ok(AccountType::TopStep(topstep_type))
-            }
+        let parsed = match company.to_lowercase().as_str() {
+            "ftt" | "fasttrack" | "fasttracktrading" => FttAccountType::from_str(account_type).map(AccountType::Ftt),
+            "topstep" => TopstepAccountType::from_str(account_type).map(AccountType::TopStep),
+            "mff" | "myfundedfutures" => MyFundedFuturesAccountType::from_str(account_type).map(AccountType::MyFundedFutures),
+            "tpt" | "takeprofittrader" => TakeProfitTraderAccountType::from_str(account_type).map(AccountType::TakeProfitTrader),
+            "bulenox" => BulenoxAccountType::from_str(account_type).map(AccountType::Bulenox),
+            "custom" => return Err("Custom accounts are defined by their rules (custom_account), not by name".into()),
             // Add other companies...
             _ => Err("Unknown company"),
+        };
+        parsed.map_err(|e| {
+            let names: Vec<String> = AccountType::all().iter().map(AccountType::to_string).collect();
+            match did_you_mean(s, &names) {
+                Some(name) => format!("{} in '{}'. Did you mean '{}'?", e, s.trim(), name),
+                None => format!("{} in '{}'", e, s.trim()),
+            }
+        })
+    }
+}
+
+/// Company and account type of a name given as `company:account_type` or
+/// `company account_type`
+pub(crate) fn split_account_name(name: &str) -> Option<(&str, &str)> {
+    let name = name.trim();
+    let (company, account_type) = name
+        .split_once(':')
+        .or_else(|| name.split_once(char::is_whitespace))?;
+    let (company, account_type) = (company.trim(), account_type.trim());
+    (!company.is_empty() && !account_type.is_empty() && !account_type.contains(':')).then_some((company, account_type))
+}
+
+/// The candidate closest to `name` in edit distance, ignoring case, spaces and punctuation,
+/// when it is close enough to be a likely typo
+pub(crate) fn did_you_mean<'a>(name: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let normalize = |s: &str| -> Vec<char> { s.chars().filter(|c| c.is_ascii_alphanumeric()).map(|c| c.to_ascii_lowercase()).collect() };
+    let name = normalize(name);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&name, &normalize(candidate)), candidate))
+        .filter(|(distance, _)| *distance <= (name.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+// Levenshtein distance
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + usize::from(ca != cb)).min(previous[j + 1] + 1).min(current[j] + 1);
         }
+        previous = current;
     }
+    previous[b.len()]
 }
 
+impl AccountType {
+    /// Every built-in account, in catalog order
+    pub fn all() -> Vec<AccountType> {
+        let mut accounts = Vec::new();
+        for ftt_type in [FttAccountType::Rally, FttAccountType::Daytona, FttAccountType::GT, FttAccountType::LeMans] {
+            accounts.push(AccountType::Ftt(ftt_type));
+        }
+        for topstep_type in [TopstepAccountType::Fifty, TopstepAccountType::OneHundred, TopstepAccountType::OneFifty] {
+            accounts.push(AccountType::TopStep(topstep_type));
+        }
+        for mff_type in [
+            MyFundedFuturesAccountType::Starter50K,
+            MyFundedFuturesAccountType::Starter100K,
+            MyFundedFuturesAccountType::Starter150K,
+            MyFundedFuturesAccountType::Expert50K,
+            MyFundedFuturesAccountType::Expert100K,
+            MyFundedFuturesAccountType::Expert150K,
+        ] {
+            accounts.push(AccountType::MyFundedFutures(mff_type));
+        }
+        for tpt_type in [
+            TakeProfitTraderAccountType::TwentyFive,
+            TakeProfitTraderAccountType::Fifty,
+            TakeProfitTraderAccountType::SeventyFive,
+            TakeProfitTraderAccountType::OneHundred,
+            TakeProfitTraderAccountType::OneFifty,
+        ] {
+            accounts.push(AccountType::TakeProfitTrader(tpt_type));
+        }
+        for bulenox_type in [
+            BulenoxAccountType::TwentyFive,
+            BulenoxAccountType::Fifty,
+            BulenoxAccountType::OneHundred,
+            BulenoxAccountType::OneFifty,
+            BulenoxAccountType::TwoFifty,
+        ] {
+            accounts.push(AccountType::Bulenox(bulenox_type));
+        }
+        accounts
+    }
+
+    pub fn company(&self) -> &'static str {
+        match self {
+            AccountType::Ftt(_) => "ftt",
+            AccountType::TopStep(_) => "topstep",
+            AccountType::MyFundedFutures(_) => "mff",
+            AccountType::TakeProfitTrader(_) => "tpt",
+            AccountType::Bulenox(_) => "bulenox",
+            AccountType::Custom(_) => "custom",
+        }
+    }
+}
+
+// Formats as the canonical `company:account_type` string accepted by `from_str`
+impl fmt::Display for AccountType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountType::Ftt(ftt_type) => write!(f, "{}:{:?}", self.company(), ftt_type),
+            AccountType::TopStep(topstep_type) => write!(f, "{}:{:?}", self.company(), topstep_type),
+            AccountType::MyFundedFutures(mff_type) => write!(f, "{}:{:?}", self.company(), mff_type),
+            AccountType::TakeProfitTrader(tpt_type) => write!(f, "{}:{:?}", self.company(), tpt_type),
+            AccountType::Bulenox(bulenox_type) => write!(f, "{}:{:?}", self.company(), bulenox_type),
+            AccountType::Custom(spec) => write!(f, "{}:{}", self.company(), spec.name),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+// src/simulator/prop_account/bulenox_account.rs
+use std::str::FromStr;
+
+use super::{AccountStatus, PropAccount};
+use super::catalog::{AccountInfo, DrawdownType, PayoutCadence};
+use super::daily_loss::DailyLoss;
+use super::drawdown::Drawdown;
+use super::rules::{BreachSeverity, ConsistencyTracker, DailyLossLimit, ProfitSplit, ProfitableDays, RuleOverrides, SplitTier, WithdrawalLimit};
+use crate::simulator::trade_data::Trade;
+use log::debug;
+use serde::{Serialize, Deserialize};
+
+// Bulenox qualification accounts with the intraday trailing drawdown and no daily loss
+// limit; passing the qualification starts a Master (funded) account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BulenoxAccountType {
+    TwentyFive,
+    Fifty,
+    OneHundred,
+    OneFifty,
+    TwoFifty,
+}
+
+const MASTER_ACTIVATION_FEE: f64 = 148.0;
+const BULENOX_CONSISTENCY_FRACTION: f64 = 0.4;
+const MIN_TRADING_DAYS_FOR_PAYOUT: u64 = 10;
+// Only the first payouts are capped
+const CAPPED_PAYOUTS: u8 = 3;
+// The Master drawdown stops trailing this far above the starting balance, and payouts
+// must leave the balance at least one drawdown above that level
+const DRAWDOWN_LOCK: f64 = 100.0;
+const PAYOUT_CADENCE: PayoutCadence = PayoutCadence::Weekly;
+// The trader keeps all of the first 10,000 paid out by a Master account and 90% beyond
+const FULL_SPLIT_PAID_OUT: f64 = 10_000.0;
+const TRADER_PERCENT_AFTER: f64 = 90.0;
+
+fn bulenox_profit_split() -> ProfitSplit {
+    ProfitSplit { trader_percent: 100.0, tiers: vec![SplitTier { after: FULL_SPLIT_PAID_OUT, trader_percent: TRADER_PERCENT_AFTER }] }
+}
+
+impl BulenoxAccountType {
+    fn initialize_account(&self) -> BulenoxAccount {
+        let (drawdown, profit_target, payout_cap) = match self {
+            BulenoxAccountType::TwentyFive => (1_500.0, 1_500.0, 1_000.0),
+            BulenoxAccountType::Fifty => (2_500.0, 3_000.0, 1_500.0),
+            BulenoxAccountType::OneHundred => (3_000.0, 6_000.0, 1_750.0),
+            BulenoxAccountType::OneFifty => (4_500.0, 9_000.0, 2_000.0),
+            BulenoxAccountType::TwoFifty => (5_500.0, 15_000.0, 2_500.0),
+        };
+        BulenoxAccount {
+            current_balance: 0.0,
+            drawdown: Drawdown::new(DrawdownType::IntradayTrailing, drawdown),
+            profit_target,
+            payout_buffer: drawdown + DRAWDOWN_LOCK,
+            payout_cap: Some(payout_cap),
+            capped_payouts: CAPPED_PAYOUTS,
+            min_trading_days: MIN_TRADING_DAYS_FOR_PAYOUT,
+            trading_days: 0,
+            max_winning_day_profit: 0.0,
+            consistency_fraction: Some(BULENOX_CONSISTENCY_FRACTION),
+            consistency: ConsistencyTracker::default(),
+            daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+            min_profitable_days: None,
+            payout_cadence: PAYOUT_CADENCE,
+            profit_split: bulenox_profit_split(),
+            passed_eval: false,
+            payout_count: 0,
+            simulation_days: 0,
+            account_type: self.clone(),
+        }
+    }
+
+    // Function to return the cost of each account type
+    pub fn get_cost(&self) -> f64 {
+        match self {
+            BulenoxAccountType::TwentyFive => 145.0,
+            BulenoxAccountType::Fifty => 175.0,
+            BulenoxAccountType::OneHundred => 215.0,
+            BulenoxAccountType::OneFifty => 325.0,
+            BulenoxAccountType::TwoFifty => 535.0,
+        }
+    }
+
+    pub fn funded_acct_cost() -> f64 {
+        MASTER_ACTIVATION_FEE
+    }
+}
+
+impl FromStr for BulenoxAccountType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // By name or account size: "Fifty", "50k", "50K" or "50000"
+        let name: String = s.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        match name.to_lowercase().as_str() {
+            "twentyfive" | "25" | "25k" | "25000" => Ok(BulenoxAccountType::TwentyFive),
+            "fifty" | "50" | "50k" | "50000" => Ok(BulenoxAccountType::Fifty),
+            "onehundred" | "hundred" | "100" | "100k" | "100000" => Ok(BulenoxAccountType::OneHundred),
+            "onefifty" | "onehundredfifty" | "150" | "150k" | "150000" => Ok(BulenoxAccountType::OneFifty),
+            "twofifty" | "twohundredfifty" | "250" | "250k" | "250000" => Ok(BulenoxAccountType::TwoFifty),
+            _ => Err("Unknown Bulenox account type"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BulenoxAccount {
+    current_balance: f64,
+    profit_target: f64,
+    drawdown: Drawdown, // trails open-trade highs, locks at DRAWDOWN_LOCK once funded
+    payout_buffer: f64, // balance that stays in the Master account after a payout
+    payout_cap: Option<f64>,
+    capped_payouts: u8, // payouts the cap applies to
+    min_trading_days: u64, // trading days required between payouts
+    trading_days: u64, // since the last payout
+    max_winning_day_profit: f64, // since the last payout, for the consistency rule
+    consistency_fraction: Option<f64>,
+    consistency: ConsistencyTracker,
+    daily_loss: DailyLoss, // firm limit, only set through a rule change
+    min_profitable_days: Option<ProfitableDays>, // only set through a rule change
+    payout_cadence: PayoutCadence,
+    profit_split: ProfitSplit,
+    passed_eval: bool,
+    payout_count: u8,
+    simulation_days: u64,
+    account_type: BulenoxAccountType,
+}
+
+impl BulenoxAccount {
+    pub fn new(account_type: BulenoxAccountType) -> Self {
+        account_type.initialize_account()
+    }
+
+    fn trade_on_account(&mut self, trade: &Trade) -> AccountStatus {
+        // A winning trade can still reach the loss level on its way
+        let worst = if trade.return_value > 0.0 { trade.max_opposite_excursion } else { trade.return_value };
+        if self.drawdown.is_breached(self.current_balance + worst) {
+            self.current_balance += worst;
+            return AccountStatus::Blown(worst);
+        }
+        if !self.passed_eval && self.current_balance + trade.return_value.max(trade.max_opposite_excursion) >= self.profit_target {
+            debug!("Profit target of {:.2} reached, starting the Master account", self.profit_target);
+            self.start_master_account();
+            return AccountStatus::PassedEval;
+        }
+        self.current_balance += trade.return_value;
+        AccountStatus::Active(trade.return_value)
+    }
+
+    // The Master account starts over from the starting balance with a fresh drawdown
+    fn start_master_account(&mut self) {
+        self.passed_eval = true;
+        self.current_balance = 0.0;
+        self.drawdown.reset(DRAWDOWN_LOCK);
+    }
+
+    pub fn passes_consistency_rule(&self) -> bool {
+        match self.consistency_fraction {
+            Some(fraction) => self.consistency.passes(fraction, self.max_winning_day_profit, self.current_balance),
+            None => true,
+        }
+    }
+
+    pub fn apply_rules(&mut self, rules: &RuleOverrides) {
+        if let Some(fraction) = rules.consistency_fraction {
+            self.consistency_fraction = Some(fraction);
+        }
+        if rules.disable_consistency {
+            self.consistency_fraction = None;
+        }
+        if let Some(window) = rules.consistency_window {
+            self.consistency.set_window(window);
+        }
+        if let Some(cap) = rules.payout_cap {
+            // One cap for all remaining payouts
+            self.payout_cap = Some(cap);
+            self.capped_payouts = u8::MAX;
+        }
+        if let Some(drawdown_type) = rules.drawdown_type {
+            self.drawdown.set_drawdown_type(drawdown_type);
+        }
+        if let Some(payout_cadence) = rules.payout_cadence {
+            self.payout_cadence = payout_cadence;
+        }
+        self.daily_loss.apply_rules(rules);
+        if let Some(profitable_days) = rules.min_profitable_days {
+            self.min_profitable_days = Some(profitable_days);
+        }
+        if let Some(profit_split) = &rules.profit_split {
+            self.profit_split = profit_split.clone();
+        }
+        if let Some(days) = rules.min_days_for_payout {
+            self.min_trading_days = days;
+        }
+    }
+
+    pub fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        if !self.passed_eval || self.trading_days < self.min_trading_days || !self.passes_consistency_rule() {
+            return None;
+        }
+        let available = self.current_balance - self.payout_buffer;
+        if available <= 0.0 {
+            return None;
+        }
+        match self.payout_cap.filter(|_| self.payout_count < self.capped_payouts) {
+            Some(cap) if cap < available => Some((cap, WithdrawalLimit::PayoutCap)),
+            _ => Some((available, WithdrawalLimit::BalanceAboveMinimum)),
+        }
+    }
+
+    pub fn make_withdrawal(&mut self, amount: f64) -> u8 {
+        self.current_balance -= amount;
+        self.start_payout_period();
+        self.payout_count += 1;
+        self.payout_count
+    }
+
+    // Counters since the last payout start over
+    fn start_payout_period(&mut self) {
+        self.max_winning_day_profit = 0.0;
+        self.consistency.record_payout();
+        self.trading_days = 0;
+    }
+
+    pub fn info(&self) -> AccountInfo {
+        AccountInfo {
+            account_type: format!("bulenox:{:?}", self.account_type),
+            display_name: format!("Bulenox {:?}", self.account_type),
+            company: String::from("bulenox"),
+            cost: self.account_type.get_cost(),
+            funded_account_cost: BulenoxAccountType::funded_acct_cost(),
+            drawdown_type: self.drawdown.drawdown_type(),
+            drawdown: self.drawdown.amount(),
+            starting_buffer: self.drawdown.buffer(self.current_balance),
+            profit_target: Some(self.profit_target),
+            payout_cap: match self.payout_cap {
+                Some(cap) if self.capped_payouts == u8::MAX => format!("balance above {:.0}, at most {:.0}", self.payout_buffer, cap),
+                Some(cap) => format!("balance above {:.0}, at most {:.0} (payouts 1-{})", self.payout_buffer, cap, self.capped_payouts),
+                None => format!("balance above {:.0}", self.payout_buffer),
+            },
+            payout_schedule: format!(
+                "Master account only: after {} trading days since the last payout, once the balance is above {:.0}",
+                self.min_trading_days, self.payout_buffer
+            ),
+            consistency_fraction: self.consistency_fraction,
+            consistency_window: self.consistency.window(),
+            payout_cadence: self.payout_cadence,
+            min_days_for_payout: self.min_trading_days,
+            qualifying_day: String::from("every trading day (Master only)"),
+            min_profitable_days: self.min_profitable_days,
+            profit_split: self.profit_split.clone(),
+            scaling_plan: None,
+            difficulty: None,
+        }
+    }
+
+    pub fn try_add_trading_day(&mut self, daily_pnl: f64) {
+        if !self.passed_eval {
+            return;
+        }
+        self.trading_days += 1;
+        if daily_pnl > self.max_winning_day_profit {
+            self.max_winning_day_profit = daily_pnl;
+        }
+        self.consistency.record_day(daily_pnl);
+    }
+}
+
+impl PropAccount for BulenoxAccount {
+    fn process_trade(&mut self, trade: &Trade) -> AccountStatus {
+        let (trade, limit_hit) = self.daily_loss.before_trade(self.current_balance, trade);
+        self.drawdown.before_trade(self.current_balance, &trade);
+        let status = self.trade_on_account(&trade);
+        self.drawdown.after_trade(self.current_balance, &status);
+        self.daily_loss.after_trade(status, limit_hit)
+    }
+
+    fn update_end_of_day(&mut self, daily_pnl: f64) {
+        self.daily_loss.end_of_day();
+        self.drawdown.end_of_day(self.current_balance);
+        self.try_add_trading_day(daily_pnl);
+    }
+
+    fn allowed_withdrawal_amount(&self) -> Option<f64> {
+        self.allowed_withdrawal().map(|(amount, _)| amount)
+    }
+
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        self.allowed_withdrawal()
+    }
+
+    fn make_withdrawal(&mut self, amount: f64) -> u8 {
+        self.make_withdrawal(amount)
+    }
+
+    fn deny_withdrawal(&mut self) {
+        self.start_payout_period();
+    }
+
+    fn get_current_balance(&self) -> f64 {
+        self.current_balance
+    }
+
+    fn get_drawdown_buffer(&self) -> f64 {
+        self.drawdown.buffer(self.current_balance)
+    }
+
+    fn is_funded(&self) -> bool {
+        self.passed_eval
+    }
+
+    // The payout buffer stays in the account
+    fn withdrawable_balance(&self) -> f64 {
+        if self.passed_eval {
+            (self.current_balance - self.payout_buffer).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn get_simulation_days(&self) -> u64 {
+        self.simulation_days
+    }
+
+    fn increment_simulation_day(&mut self) {
+        self.simulation_days += 1;
+    }
+
+    fn get_cost(&self) -> f64 {
+        self.account_type.get_cost()
+    }
+
+    fn get_funded_acct_cost(&self) -> f64 {
+        BulenoxAccountType::funded_acct_cost()
+    }
+
+    fn info(&self) -> AccountInfo {
+        self.info()
+    }
+
+    fn apply_rules(&mut self, rules: &RuleOverrides) {
+        self.apply_rules(rules)
+    }
+
+    fn get_daily_loss_limit(&self) -> Option<DailyLossLimit> {
+        self.daily_loss.limit()
+    }
+
+    fn daily_loss_breach(&self) -> BreachSeverity {
+        self.daily_loss.breach()
+    }
+
+    fn set_account_size(&mut self, account_size: f64) {
+        self.daily_loss.set_account_size(account_size);
+    }
+
+    fn payout_cadence(&self) -> PayoutCadence {
+        self.payout_cadence
+    }
+
+    fn min_profitable_days(&self) -> Option<ProfitableDays> {
+        self.min_profitable_days
+    }
+
+    fn profit_split(&self) -> ProfitSplit {
+        self.profit_split.clone()
+    }
+}
@@ -0,0 +1,132 @@
+// src/simulator/prop_account/catalog.rs
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+use crate::simulator::difficulty::Difficulty;
+use super::{create_account, registered_accounts, account_factory, AccountType};
+use super::rules::{ConsistencyWindow, ProfitSplit, ProfitableDays, ScalingPlan};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DrawdownType {
+    /// Trails the end-of-day balance high-water mark
+    EndOfDayTrailing,
+    /// Trails the intraday (open trade) high-water mark
+    IntradayTrailing,
+    /// Fixed loss level that never moves
+    Static,
+}
+
+impl DrawdownType {
+    pub const ALL: [DrawdownType; 3] = [DrawdownType::EndOfDayTrailing, DrawdownType::IntradayTrailing, DrawdownType::Static];
+}
+
+impl FromStr for DrawdownType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "eod" | "endofday" | "endofdaytrailing" => Ok(DrawdownType::EndOfDayTrailing),
+            "intraday" | "intradaytrailing" => Ok(DrawdownType::IntradayTrailing),
+            "static" => Ok(DrawdownType::Static),
+            _ => Err("Unknown drawdown type (options: eod, intraday, static)"),
+        }
+    }
+}
+
+/// How often the firm pays out: a payout can be requested once per window of trading days
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutCadence {
+    /// Any trading day
+    #[default]
+    Daily,
+    /// Once per week (5 trading days)
+    Weekly,
+    /// Once per 14 calendar days (10 trading days)
+    Biweekly,
+    /// Once per month (21 trading days)
+    Monthly,
+}
+
+impl PayoutCadence {
+    pub const ALL: [PayoutCadence; 4] = [PayoutCadence::Daily, PayoutCadence::Weekly, PayoutCadence::Biweekly, PayoutCadence::Monthly];
+
+    /// Trading days that must pass between two payouts
+    pub fn trading_days(&self) -> u64 {
+        match self {
+            PayoutCadence::Daily => 1,
+            PayoutCadence::Weekly => 5,
+            PayoutCadence::Biweekly => 10,
+            PayoutCadence::Monthly => 21,
+        }
+    }
+}
+
+impl FromStr for PayoutCadence {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "daily" => Ok(PayoutCadence::Daily),
+            "weekly" => Ok(PayoutCadence::Weekly),
+            "biweekly" | "14day" => Ok(PayoutCadence::Biweekly),
+            "monthly" => Ok(PayoutCadence::Monthly),
+            _ => Err("Unknown payout cadence (options: daily, weekly, biweekly, monthly)"),
+        }
+    }
+}
+
+/// Rule parameters and descriptions of an account, read from the same account
+/// structs the simulation uses (see `PropAccount::info`)
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountInfo {
+    pub account_type: String,
+    pub display_name: String,
+    pub company: String,
+    pub cost: f64,
+    pub funded_account_cost: f64,
+    pub drawdown_type: DrawdownType,
+    pub drawdown: f64,
+    /// Distance between the starting balance and the loss balance
+    pub starting_buffer: f64,
+    pub profit_target: Option<f64>,
+    pub payout_cap: String,
+    pub payout_schedule: String,
+    pub payout_cadence: PayoutCadence,
+    pub consistency_fraction: Option<f64>,
+    pub consistency_window: ConsistencyWindow,
+    pub min_days_for_payout: u64,
+    pub qualifying_day: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_profitable_days: Option<ProfitableDays>,
+    /// Share of the payouts the trader keeps
+    pub profit_split: ProfitSplit,
+    /// Contracts allowed by balance, for accounts with a scaling plan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scaling_plan: Option<ScalingPlan>,
+    /// Filled in by `rate_difficulty`, which simulates every account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
+}
+
+pub fn account_info(account_type: &AccountType) -> AccountInfo {
+    create_account(account_type.clone()).info()
+}
+
+/// Catalog of all registered accounts, optionally restricted to one company
+pub fn account_catalog(company: Option<&str>) -> Vec<AccountInfo> {
+    registered_accounts()
+        .iter()
+        .filter(|name| {
+            let account_company = name.split(':').next().unwrap_or_default();
+            company.is_none_or(|c| account_company.eq_ignore_ascii_case(c))
+        })
+        .filter_map(|name| {
+            let factory = account_factory(name).ok()?;
+            let mut info = factory().info();
+            // Report the name the account is selected by
+            info.account_type = name.clone();
+            Some(info)
+        })
+        .collect()
+}
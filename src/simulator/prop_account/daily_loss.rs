@@ -0,0 +1,104 @@
+// src/simulator/prop_account/daily_loss.rs
+use log::debug;
+
+use super::AccountStatus;
+use super::rules::{BreachSeverity, DailyLossLimit, RuleOverrides};
+use crate::simulator::trade_data::Trade;
+
+/// Firm daily loss limit of an account, checked trade by trade against the day's P&L and
+/// shared by the account implementations. A percentage limit is taken of the prior day's
+/// closing balance: the nominal account size plus the P&L at the start of the day.
+///
+/// An account passes each trade through `before_trade`, which closes it at the limit on a
+/// soft breach, books its outcome with `after_trade` and calls `end_of_day` when the day is
+/// over. A trade reaching the limit turns an `AccountStatus::Active` into
+/// `AccountStatus::DailyLimitHit`, or `AccountStatus::LockedForDay` on a soft breach.
+#[derive(Debug, Clone)]
+pub struct DailyLoss {
+    limit: Option<DailyLossLimit>,
+    breach: BreachSeverity,
+    account_size: f64,
+    daily_pnl: f64,
+}
+
+impl DailyLoss {
+    pub fn new(limit: Option<DailyLossLimit>, breach: BreachSeverity) -> Self {
+        DailyLoss { limit, breach, account_size: 0.0, daily_pnl: 0.0 }
+    }
+
+    pub fn limit(&self) -> Option<DailyLossLimit> {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: Option<DailyLossLimit>) {
+        self.limit = limit;
+    }
+
+    pub fn breach(&self) -> BreachSeverity {
+        self.breach
+    }
+
+    /// Nominal starting balance of the account, needed by a percentage limit
+    pub fn set_account_size(&mut self, account_size: f64) {
+        self.account_size = account_size;
+    }
+
+    /// Take over the limit and breach severity of a rule change
+    pub fn apply_rules(&mut self, rules: &RuleOverrides) {
+        if let Some(limit) = rules.daily_loss_limit {
+            self.limit = Some(limit);
+        }
+        if let Some(severity) = rules.daily_loss_breach {
+            self.breach = severity;
+        }
+    }
+
+    /// Lowest P&L allowed today for an account now at `balance`
+    pub fn floor(&self, balance: f64) -> Option<f64> {
+        let prior_day_balance = self.account_size + balance - self.daily_pnl;
+        self.limit.map(|limit| -limit.allowed_loss(prior_day_balance))
+    }
+
+    /// The trade to take on an account at `balance`, and whether it reaches the limit at its
+    /// worst point. A soft breach closes the trade at the limit.
+    pub fn before_trade(&self, balance: f64, trade: &Trade) -> (Trade, bool) {
+        let mut trade = trade.clone();
+        let Some(floor) = self.floor(balance) else {
+            return (trade, false);
+        };
+        // A winning trade can still reach the limit on its way
+        let worst = if trade.return_value > 0.0 { trade.return_value.min(trade.max_opposite_excursion) } else { trade.return_value };
+        let limit_hit = self.daily_pnl + worst <= floor;
+        if limit_hit && self.breach == BreachSeverity::Soft {
+            trade.return_value = floor - self.daily_pnl;
+        }
+        (trade, limit_hit)
+    }
+
+    /// Book the outcome of a trade taken after `before_trade`
+    pub fn after_trade(&mut self, status: AccountStatus, limit_hit: bool) -> AccountStatus {
+        match status {
+            AccountStatus::Active(ret) => {
+                self.daily_pnl += ret;
+                if !limit_hit {
+                    return AccountStatus::Active(ret);
+                }
+                debug!("Firm daily loss limit hit, daily P&L: {:.2}", self.daily_pnl);
+                match self.breach {
+                    BreachSeverity::Hard => AccountStatus::DailyLimitHit(ret),
+                    BreachSeverity::Soft => AccountStatus::LockedForDay(ret),
+                }
+            }
+            // The funded account starts over from a new day
+            AccountStatus::PassedEval => {
+                self.daily_pnl = 0.0;
+                status
+            }
+            _ => status,
+        }
+    }
+
+    pub fn end_of_day(&mut self) {
+        self.daily_pnl = 0.0;
+    }
+}
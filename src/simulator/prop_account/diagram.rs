@@ -0,0 +1,216 @@
+// src/simulator/prop_account/diagram.rs
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+use super::catalog::{AccountInfo, PayoutCadence};
+use super::rules::{BreachSeverity, ConsistencyWindow, DailyLossLimit};
+use super::account_factory;
+use crate::simulator::SimulationError;
+
+/// Language of an account state diagram
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagramFormat {
+    /// Mermaid `stateDiagram-v2`, rendered by GitHub and most Markdown viewers
+    #[default]
+    Mermaid,
+    /// Graphviz DOT, rendered with `dot -Tsvg`
+    Dot,
+}
+
+impl DiagramFormat {
+    pub const ALL: [DiagramFormat; 2] = [DiagramFormat::Mermaid, DiagramFormat::Dot];
+}
+
+impl FromStr for DiagramFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mermaid" => Ok(DiagramFormat::Mermaid),
+            "dot" | "graphviz" => Ok(DiagramFormat::Dot),
+            _ => Err("Unknown diagram format (options: mermaid, dot)"),
+        }
+    }
+}
+
+struct State {
+    id: &'static str,
+    lines: Vec<String>,
+}
+
+struct Transition {
+    from: &'static str,
+    to: &'static str,
+    label: String,
+}
+
+const START: &str = "start";
+
+/// State diagram of a registered account (evaluation, funded, payout and terminal states),
+/// drawn from the rule parameters the simulation uses
+pub fn account_diagram(account_type: &str, format: DiagramFormat) -> Result<String, SimulationError> {
+    let account = account_factory(account_type)?();
+    let mut info = account.info();
+    info.account_type = account_type.to_string();
+    Ok(state_diagram(&info, account.get_daily_loss_limit(), account.daily_loss_breach(), format))
+}
+
+/// State diagram of an account given its rule parameters and firm daily loss limit
+pub fn state_diagram(
+    info: &AccountInfo,
+    daily_loss_limit: Option<DailyLossLimit>,
+    daily_loss_breach: BreachSeverity,
+    format: DiagramFormat,
+) -> String {
+    let (states, transitions) = state_machine(info, daily_loss_limit, daily_loss_breach);
+    match format {
+        DiagramFormat::Mermaid => mermaid(info, &states, &transitions),
+        DiagramFormat::Dot => dot(info, &states, &transitions),
+    }
+}
+
+fn state_machine(info: &AccountInfo, daily_loss_limit: Option<DailyLossLimit>, daily_loss_breach: BreachSeverity) -> (Vec<State>, Vec<Transition>) {
+    let mut states = Vec::new();
+    let mut transitions = Vec::new();
+    let mut bust_rules = vec![format!("balance at the loss level ({:?} drawdown of {:.0})", info.drawdown_type, info.drawdown)];
+    let daily_loss_rule = match daily_loss_limit {
+        Some(DailyLossLimit::Amount(amount)) => Some(format!("day's loss reaches {:.0}", amount.abs())),
+        Some(DailyLossLimit::Percent(percent)) => Some(format!("day's loss reaches {}% of the prior close", percent)),
+        None => None,
+    };
+    // A soft breach only ends the day
+    let soft_breach_label = match (daily_loss_rule, daily_loss_breach) {
+        (Some(rule), BreachSeverity::Soft) => Some(format!("{} (soft breach), trading stops for the day", rule)),
+        (Some(rule), BreachSeverity::Hard) => {
+            bust_rules.push(rule);
+            None
+        }
+        (None, _) => None,
+    };
+    let bust_label = bust_rules.join(" or ");
+
+    let mut trading_states = Vec::new();
+    if let Some(profit_target) = info.profit_target {
+        states.push(State {
+            id: "evaluation",
+            lines: vec![
+                String::from("Evaluation"),
+                format!("cost {:.0}", info.cost),
+                format!("buffer {:.0}", info.starting_buffer),
+            ],
+        });
+        transitions.push(Transition { from: START, to: "evaluation", label: String::from("account bought") });
+        let mut label = format!("profit target of {:.0} reached", profit_target);
+        if info.funded_account_cost > 0.0 {
+            label.push_str(&format!(", activation fee {:.0}", info.funded_account_cost));
+        }
+        transitions.push(Transition { from: "evaluation", to: "funded", label });
+        trading_states.push("evaluation");
+    } else {
+        transitions.push(Transition { from: START, to: "funded", label: format!("account bought for {:.0}", info.cost) });
+    }
+
+    let mut funded_lines = vec![String::from("Funded"), format!("qualifying day: {}", info.qualifying_day)];
+    if let Some(plan) = &info.scaling_plan {
+        funded_lines.push(format!("contracts: {}", plan));
+    }
+    states.push(State { id: "funded", lines: funded_lines });
+    let mut eligible_label = format!("{} qualifying days since the last payout", info.min_days_for_payout);
+    if info.payout_cadence != PayoutCadence::Daily {
+        eligible_label.push_str(&format!(", {} trading days since the last payout ({:?} cadence)", info.payout_cadence.trading_days(), info.payout_cadence));
+    }
+    match info.min_profitable_days {
+        Some(rule) if rule.min_profit > 0.0 => {
+            eligible_label.push_str(&format!(", {} days with P&L of at least {:.0} since the last payout", rule.days, rule.min_profit));
+        }
+        Some(rule) => eligible_label.push_str(&format!(", {} profitable days since the last payout", rule.days)),
+        None => (),
+    }
+    transitions.push(Transition { from: "funded", to: "eligible", label: eligible_label });
+    trading_states.push("funded");
+
+    states.push(State {
+        id: "eligible",
+        lines: vec![String::from("Payout eligible"), info.payout_schedule.clone()],
+    });
+    let mut payout_label = String::from("withdrawal requested");
+    if let Some(fraction) = info.consistency_fraction {
+        let base = match info.consistency_window {
+            ConsistencyWindow::Account => String::from("the balance"),
+            ConsistencyWindow::SinceLastPayout => String::from("the profit since the last payout"),
+            ConsistencyWindow::TrailingDays(days) => format!("the profit of the last {} trading days", days),
+        };
+        payout_label.push_str(&format!(", largest winning day at most {:.0}% of {}", fraction * 100.0, base));
+    }
+    transitions.push(Transition { from: "eligible", to: "payout", label: payout_label });
+    trading_states.push("eligible");
+
+    states.push(State {
+        id: "payout",
+        lines: vec![String::from("Payout"), format!("cap: {}", info.payout_cap), format!("trader keeps: {}", info.profit_split)],
+    });
+    transitions.push(Transition { from: "payout", to: "funded", label: String::from("trading resumes") });
+
+    states.push(State { id: "busted", lines: vec![String::from("Busted")] });
+    states.push(State { id: "timeout", lines: vec![String::from("TimeOut")] });
+    states.push(State { id: "max_payouts", lines: vec![String::from("MaxPayouts")] });
+    for &state in &trading_states {
+        transitions.push(Transition { from: state, to: "busted", label: bust_label.clone() });
+    }
+    if let Some(label) = soft_breach_label {
+        for &state in &trading_states {
+            transitions.push(Transition { from: state, to: state, label: label.clone() });
+        }
+    }
+    for &state in &trading_states {
+        transitions.push(Transition { from: state, to: "timeout", label: String::from("max simulation days reached") });
+    }
+    transitions.push(Transition { from: "payout", to: "max_payouts", label: String::from("max payouts reached") });
+
+    (states, transitions)
+}
+
+const TERMINAL_STATES: [&str; 3] = ["busted", "timeout", "max_payouts"];
+
+// Mermaid treats these characters as syntax inside labels
+fn mermaid_text(text: &str) -> String {
+    text.replace(';', "#59;").replace('<', "#60;").replace('>', "#62;").replace(':', "#58;")
+}
+
+fn mermaid(info: &AccountInfo, states: &[State], transitions: &[Transition]) -> String {
+    let mut out = format!("---\ntitle: {}\n---\nstateDiagram-v2\n", mermaid_text(&info.display_name));
+    for state in states {
+        for line in &state.lines {
+            out.push_str(&format!("    {} : {}\n", state.id, mermaid_text(line)));
+        }
+    }
+    for transition in transitions {
+        let from = if transition.from == START { "[*]" } else { transition.from };
+        out.push_str(&format!("    {} --> {} : {}\n", from, transition.to, mermaid_text(&transition.label)));
+    }
+    for terminal in TERMINAL_STATES {
+        out.push_str(&format!("    {} --> [*]\n", terminal));
+    }
+    out
+}
+
+fn dot_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn dot(info: &AccountInfo, states: &[State], transitions: &[Transition]) -> String {
+    let mut out = format!("digraph \"{}\" {{\n", dot_text(&info.account_type));
+    out.push_str(&format!("    label=\"{}\";\n    labelloc=t;\n    rankdir=LR;\n", dot_text(&info.display_name)));
+    out.push_str(&format!("    {} [shape=point];\n", START));
+    for state in states {
+        let shape = if TERMINAL_STATES.contains(&state.id) { "doublecircle" } else { "box" };
+        let label = state.lines.iter().map(|line| dot_text(line)).collect::<Vec<_>>().join("\\n");
+        out.push_str(&format!("    {} [shape={}, label=\"{}\"];\n", state.id, shape, label));
+    }
+    for transition in transitions {
+        out.push_str(&format!("    {} -> {} [label=\"{}\"];\n", transition.from, transition.to, dot_text(&transition.label)));
+    }
+    out.push_str("}\n");
+    out
+}
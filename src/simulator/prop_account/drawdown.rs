@@ -0,0 +1,101 @@
+// src/simulator/prop_account/drawdown.rs
+use log::debug;
+
+use super::AccountStatus;
+use super::catalog::DrawdownType;
+use crate::simulator::trade_data::Trade;
+
+/// Loss level of an account under its drawdown mode, shared by the account implementations.
+/// Balances are P&L relative to the starting balance: the loss level starts `amount` below
+/// it and, unless the drawdown is static, trails new highs (end-of-day balances, or
+/// open-trade highs for intraday trailing) until it reaches `lock`.
+///
+/// An account calls `before_trade` and `after_trade` around each trade and `end_of_day`
+/// with the closing balance; the drawdown mode decides which of them move the loss level.
+#[derive(Debug, Clone)]
+pub struct Drawdown {
+    drawdown_type: DrawdownType,
+    amount: f64,
+    lock: f64,
+    hwm_balance: f64,
+    loss_balance: f64,
+}
+
+impl Drawdown {
+    /// Drawdown of `amount` that stops trailing at the starting balance
+    pub fn new(drawdown_type: DrawdownType, amount: f64) -> Self {
+        Drawdown { drawdown_type, amount, lock: 0.0, hwm_balance: 0.0, loss_balance: -amount }
+    }
+
+    /// Stop trailing `lock` above the starting balance instead
+    pub fn with_lock(mut self, lock: f64) -> Self {
+        self.lock = lock;
+        self
+    }
+
+    /// Start over from the starting balance, e.g. for a funded account after the evaluation
+    pub fn reset(&mut self, lock: f64) {
+        self.lock = lock;
+        self.hwm_balance = 0.0;
+        self.loss_balance = -self.amount;
+    }
+
+    pub fn drawdown_type(&self) -> DrawdownType {
+        self.drawdown_type
+    }
+
+    /// Change the mode mid-simulation; the loss level stays where it is
+    pub fn set_drawdown_type(&mut self, drawdown_type: DrawdownType) {
+        self.drawdown_type = drawdown_type;
+    }
+
+    /// Distance between the starting balance and the initial loss level
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    pub fn loss_balance(&self) -> f64 {
+        self.loss_balance
+    }
+
+    /// Whether `balance` is at or below the loss level
+    pub fn is_breached(&self, balance: f64) -> bool {
+        balance <= self.loss_balance
+    }
+
+    /// Distance between `balance` and the loss level
+    pub fn buffer(&self, balance: f64) -> f64 {
+        balance - self.loss_balance
+    }
+
+    /// A losing trade first moves in our favor: with an intraday trailing drawdown, trail that
+    /// high before the loss is taken
+    pub fn before_trade(&mut self, balance: f64, trade: &Trade) {
+        if self.drawdown_type == DrawdownType::IntradayTrailing && trade.return_value <= 0.0 && trade.max_opposite_excursion > 0.0 {
+            self.trail(balance + trade.max_opposite_excursion);
+        }
+    }
+
+    /// Trail the balance after a trade the account survived, with an intraday trailing drawdown
+    pub fn after_trade(&mut self, balance: f64, status: &AccountStatus) {
+        if self.drawdown_type == DrawdownType::IntradayTrailing && matches!(status, AccountStatus::Active(_)) {
+            self.trail(balance);
+        }
+    }
+
+    /// Trail the closing balance, unless the drawdown is static
+    pub fn end_of_day(&mut self, balance: f64) {
+        if self.drawdown_type != DrawdownType::Static {
+            self.trail(balance);
+        }
+    }
+
+    // Trail the loss level behind a new high until it reaches the lock
+    fn trail(&mut self, high: f64) {
+        if high > self.hwm_balance && self.loss_balance < self.lock {
+            self.loss_balance = (high - self.amount).min(self.lock);
+            debug!("trail updated. new loss balance: {}", self.loss_balance);
+            self.hwm_balance = high;
+        }
+    }
+}
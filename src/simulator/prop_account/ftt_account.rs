@@ -1,11 +1,19 @@
 use std::str::FromStr;
 
 use super::{AccountStatus, PropAccount};
+use super::catalog::{AccountInfo, DrawdownType, PayoutCadence};
+use super::daily_loss::DailyLoss;
+use super::drawdown::Drawdown;
+use super::rules::{BreachSeverity, ConsistencyTracker, DailyLossLimit, ProfitSplit, ProfitableDays, RuleOverrides, WithdrawalLimit};
 use crate::simulator::trade_data::Trade;
-use log::debug;
 use serde::{Serialize, Deserialize};
 
 const FTT_CONSISTENCY_FRACTION: f64 = 0.2;
+const FTT_MIN_TRADING_DAYS: u64 = 10;
+// Payouts are processed every 14 days
+const PAYOUT_CADENCE: PayoutCadence = PayoutCadence::Biweekly;
+// Share of each payout the trader keeps
+const FTT_TRADER_PERCENT: f64 = 90.0;
 
 #[derive(Debug)]
 pub struct RealTradingDay{
@@ -46,9 +54,7 @@ impl FttAccountType {
             FttAccountType::Rally => {
                 FttAccount{
                     current_balance: 0.0,
-                    hwm_balance: 0.0,
-                    drawdown: 1_250.0,
-                    loss_balance: -1_250.0,
+                    drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, 1_250.0),
                     payout_cap: PayoutCap { first_8_payouts: 1_500.0, payouts_9_to_12: 3_000.0 },
                     real_trading_day: RealTradingDay::new(-62.5, 62.5),
                     payout_count: 0,
@@ -56,6 +62,13 @@ impl FttAccountType {
                     min_balance_to_withdraw_subsequent_payouts: 1_500.0,
                     min_balance_after_withdrawal: 1_250.0,
                     max_winning_day_profit: 0.0,
+                    daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+                    min_profitable_days: None,
+                    payout_cadence: PAYOUT_CADENCE,
+                    profit_split: ProfitSplit::flat(FTT_TRADER_PERCENT),
+                    consistency: ConsistencyTracker::default(),
+                    consistency_fraction: Some(FTT_CONSISTENCY_FRACTION),
+                    min_trading_days: FTT_MIN_TRADING_DAYS,
                     trading_days: 0,
                     simulation_days: 0,
                     account_type: FttAccountType::Rally,
@@ -64,9 +77,7 @@ impl FttAccountType {
             FttAccountType::Daytona => {
                 FttAccount{
                     current_balance: 0.0,
-                    hwm_balance: 0.0,
-                    drawdown: 2_500.0,
-                    loss_balance: -2_500.0,
+                    drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, 2_500.0),
                     payout_cap: PayoutCap { first_8_payouts: 2_000.0, payouts_9_to_12: 4_000.0 },
                     real_trading_day: RealTradingDay::new(-125.0, 125.0),
                     payout_count: 0,
@@ -74,6 +85,13 @@ impl FttAccountType {
                     min_balance_to_withdraw_subsequent_payouts: 2_750.0,
                     min_balance_after_withdrawal: 2_500.0,
                     max_winning_day_profit: 0.0,
+                    daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+                    min_profitable_days: None,
+                    payout_cadence: PAYOUT_CADENCE,
+                    profit_split: ProfitSplit::flat(FTT_TRADER_PERCENT),
+                    consistency: ConsistencyTracker::default(),
+                    consistency_fraction: Some(FTT_CONSISTENCY_FRACTION),
+                    min_trading_days: FTT_MIN_TRADING_DAYS,
                     trading_days: 0,
                     simulation_days: 0,
                     account_type: FttAccountType::Daytona,
@@ -82,9 +100,7 @@ impl FttAccountType {
             FttAccountType::GT => {
                 FttAccount{
                     current_balance: 0.0,
-                    hwm_balance: 0.0,
-                    drawdown: 7_500.0,
-                    loss_balance: -7_500.0,
+                    drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, 7_500.0),
                     payout_cap: PayoutCap { first_8_payouts: 3_000.0, payouts_9_to_12: 6_000.0 },
                     real_trading_day: RealTradingDay::new(-187.5, 375.0),
                     payout_count: 0,
@@ -92,6 +108,13 @@ impl FttAccountType {
                     min_balance_to_withdraw_subsequent_payouts: 4_750.0,
                     min_balance_after_withdrawal: 4_500.0,
                     max_winning_day_profit: 0.0,
+                    daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+                    min_profitable_days: None,
+                    payout_cadence: PAYOUT_CADENCE,
+                    profit_split: ProfitSplit::flat(FTT_TRADER_PERCENT),
+                    consistency: ConsistencyTracker::default(),
+                    consistency_fraction: Some(FTT_CONSISTENCY_FRACTION),
+                    min_trading_days: FTT_MIN_TRADING_DAYS,
                     trading_days: 0,
                     simulation_days: 0,
                     account_type: FttAccountType::GT,
@@ -100,9 +123,7 @@ impl FttAccountType {
             FttAccountType::LeMans => {
                 FttAccount{
                     current_balance: 0.0,
-                    hwm_balance: 0.0,
-                    drawdown: 15_000.0,
-                    loss_balance: -15_000.0,
+                    drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, 15_000.0),
                     payout_cap: PayoutCap { first_8_payouts: 4_000.0, payouts_9_to_12: 8_000.0 },
                     real_trading_day: RealTradingDay::new(-300.0, 600.0),
                     payout_count: 0,
@@ -110,6 +131,13 @@ impl FttAccountType {
                     min_balance_to_withdraw_subsequent_payouts: 11_250.0,
                     min_balance_after_withdrawal: 11_000.0,
                     max_winning_day_profit: 0.0,
+                    daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+                    min_profitable_days: None,
+                    payout_cadence: PAYOUT_CADENCE,
+                    profit_split: ProfitSplit::flat(FTT_TRADER_PERCENT),
+                    consistency: ConsistencyTracker::default(),
+                    consistency_fraction: Some(FTT_CONSISTENCY_FRACTION),
+                    min_trading_days: FTT_MIN_TRADING_DAYS,
                     trading_days: 0,
                     simulation_days: 0,
                     account_type: FttAccountType::LeMans,
@@ -134,7 +162,9 @@ impl FromStr for FttAccountType {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
+        // Spaces and punctuation are ignored: "Le Mans", "le-mans" and "GT+" are accepted
+        let name: String = s.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        match name.to_lowercase().as_str() {
             "rally" => Ok(FttAccountType::Rally),
             "daytona" => Ok(FttAccountType::Daytona),
             "gt" => Ok(FttAccountType::GT),
@@ -147,9 +177,7 @@ impl FromStr for FttAccountType {
 #[derive(Debug)]
 pub struct FttAccount {
     current_balance: f64,        // current balance
-    hwm_balance: f64,           //high water mark
-    drawdown: f64,          //drawdown  == profit target
-    loss_balance: f64,   // accounts for max loss limit / drawdown allowance (Drawdown updates EOD, stops at initial balance. max loss is intraday)
+    drawdown: Drawdown, // loss level, trails up to the initial balance (EOD by default)
     payout_cap: PayoutCap,
     real_trading_day: RealTradingDay, //rtd params for account
     payout_count: u8,   // Number of successful payouts
@@ -157,6 +185,13 @@ pub struct FttAccount {
     min_balance_to_withdraw_subsequent_payouts: f64,
     min_balance_after_withdrawal: f64,
     max_winning_day_profit: f64, //for consistency rule
+    consistency_fraction: Option<f64>, // None once the consistency rule no longer applies
+    min_trading_days: u64, // real trading days required between payouts
+    daily_loss: DailyLoss, // firm limit, only set through a rule change
+    min_profitable_days: Option<ProfitableDays>, // only set through a rule change
+    payout_cadence: PayoutCadence,
+    profit_split: ProfitSplit,
+    consistency: ConsistencyTracker, // days the consistency rule is computed over
     trading_days: u64, //since last withdrawal
     simulation_days: u64,
     account_type: FttAccountType,
@@ -169,7 +204,7 @@ impl FttAccount {
 
     pub fn trade_on_account(&mut self, trade: &Trade) -> AccountStatus{
         if trade.return_value > 0.0 {
-            if self.current_balance + trade.max_opposite_excursion <= self.loss_balance{
+            if self.current_balance + trade.max_opposite_excursion <= self.drawdown.loss_balance(){
                 //trade would have won but mae blew us out
                 self.current_balance += trade.max_opposite_excursion;
                 return AccountStatus::Blown(trade.max_opposite_excursion);
@@ -180,7 +215,7 @@ impl FttAccount {
             }
         }
         else{
-            if self.current_balance + trade.return_value <= self.loss_balance{
+            if self.current_balance + trade.return_value <= self.drawdown.loss_balance(){
                 self.current_balance += trade.return_value;
                 return AccountStatus::Blown(trade.return_value);
             }
@@ -193,63 +228,128 @@ impl FttAccount {
 
     // Update drawdown based on the current balance (EOD)
     pub fn update_loss_balance(&mut self) {
-        if self.hwm_balance < self.drawdown{
-            //if havent hit profit target yet, still trailing dd
-            if self.current_balance > self.hwm_balance{
-                //made new hwm
-                self.loss_balance = self.current_balance - self.drawdown;
-                if self.loss_balance > 0.0{
-                    self.loss_balance = 0.0;
-                }
-                debug!("eod trail updated. new loss balance: {}", self.loss_balance);
-                self.hwm_balance = self.current_balance;
-            }
-        }
+        self.drawdown.end_of_day(self.current_balance);
     }
 
     pub fn passes_consistency_rule(&self) -> bool{
-        if self.max_winning_day_profit  > FTT_CONSISTENCY_FRACTION * self.current_balance {
-            return false;
+        match self.consistency_fraction {
+            Some(fraction) => self.consistency.passes(fraction, self.max_winning_day_profit, self.current_balance),
+            None => true,
+        }
+    }
+
+    pub fn apply_rules(&mut self, rules: &RuleOverrides) {
+        if let Some(fraction) = rules.consistency_fraction {
+            self.consistency_fraction = Some(fraction);
+        }
+        if rules.disable_consistency {
+            self.consistency_fraction = None;
+        }
+        if let Some(window) = rules.consistency_window {
+            self.consistency.set_window(window);
+        }
+        if let Some(cap) = rules.payout_cap {
+            // One cap for all remaining payouts
+            self.payout_cap = PayoutCap { first_8_payouts: cap, payouts_9_to_12: cap };
+        }
+        if let Some(drawdown_type) = rules.drawdown_type {
+            self.drawdown.set_drawdown_type(drawdown_type);
+        }
+        if let Some(payout_cadence) = rules.payout_cadence {
+            self.payout_cadence = payout_cadence;
+        }
+        self.daily_loss.apply_rules(rules);
+        if let Some(profitable_days) = rules.min_profitable_days {
+            self.min_profitable_days = Some(profitable_days);
+        }
+        if let Some(profit_split) = &rules.profit_split {
+            self.profit_split = profit_split.clone();
+        }
+        if let Some(days) = rules.min_days_for_payout {
+            self.min_trading_days = days;
         }
-        true
     }
 
     pub fn allowed_withdrawal_amount(&self) -> Option<f64>{
-        if self.trading_days >= 10{
-            if self.payout_count == 0{
-                if self.current_balance >= self.min_balance_to_withdraw_first_payout && self.passes_consistency_rule(){
-                    if self.current_balance - self.min_balance_after_withdrawal > self.payout_cap.first_8_payouts{
-                        return Some(self.payout_cap.first_8_payouts)
-                    }
-                    return Some(self.current_balance - self.min_balance_after_withdrawal);
-                }
-            } else{
-                if self.current_balance >= self.min_balance_to_withdraw_subsequent_payouts && self.passes_consistency_rule(){
-                    if self.payout_count + 1 > 8{
-                        if self.current_balance - self.min_balance_after_withdrawal > self.payout_cap.payouts_9_to_12{
-                            return Some(self.payout_cap.payouts_9_to_12)
-                        }
-                    }
-                    else{
-                        if self.current_balance - self.min_balance_after_withdrawal > self.payout_cap.first_8_payouts{
-                            return Some(self.payout_cap.first_8_payouts)
-                        }
-                    }
-                    return Some(self.current_balance - self.min_balance_after_withdrawal);
-                }
-            }
+        self.allowed_withdrawal().map(|(amount, _)| amount)
+    }
+
+    pub fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)>{
+        if self.trading_days < self.min_trading_days || !self.passes_consistency_rule(){
+            return None;
+        }
+        let min_balance_to_withdraw = if self.payout_count == 0{
+            self.min_balance_to_withdraw_first_payout
+        } else{
+            self.min_balance_to_withdraw_subsequent_payouts
+        };
+        if self.current_balance < min_balance_to_withdraw{
+            return None;
+        }
+        let cap = if self.payout_count + 1 > 8{
+            self.payout_cap.payouts_9_to_12
+        } else{
+            self.payout_cap.first_8_payouts
+        };
+        let available = self.current_balance - self.min_balance_after_withdrawal;
+        if available > cap{
+            Some((cap, WithdrawalLimit::PayoutCap))
+        } else{
+            Some((available, WithdrawalLimit::BalanceAboveMinimum))
         }
-        None
     }
 
     pub fn make_withdrawal(&mut self, amount: f64) -> u8 {
         self.current_balance -= amount;
-        self.max_winning_day_profit = 0.0; //TODO: is this reset every withdrawal?
-        self.trading_days = 0;
+        self.start_payout_period();
         self.payout_count += 1;
         return self.payout_count;
     }
 
+    // Counters since the last payout start over
+    fn start_payout_period(&mut self) {
+        self.max_winning_day_profit = 0.0; //TODO: is this reset every withdrawal?
+        self.consistency.record_payout();
+        self.trading_days = 0;
+    }
+
+    pub fn info(&self) -> AccountInfo {
+        AccountInfo {
+            account_type: format!("ftt:{:?}", self.account_type),
+            display_name: format!("Fast Track Trading {:?}", self.account_type),
+            company: String::from("ftt"),
+            cost: self.account_type.get_cost(),
+            funded_account_cost: 0.0,
+            drawdown_type: self.drawdown.drawdown_type(),
+            drawdown: self.drawdown.amount(),
+            starting_buffer: self.drawdown.buffer(self.current_balance),
+            profit_target: None,
+            payout_cap: format!(
+                "{:.0} (payouts 1-8), {:.0} (payouts 9-12)",
+                self.payout_cap.first_8_payouts, self.payout_cap.payouts_9_to_12
+            ),
+            payout_schedule: format!(
+                "After {} real trading days since the last payout, once the balance is at least {:.0} ({:.0} for the first payout); {:.0} must remain after withdrawing",
+                self.min_trading_days,
+                self.min_balance_to_withdraw_subsequent_payouts,
+                self.min_balance_to_withdraw_first_payout,
+                self.min_balance_after_withdrawal
+            ),
+            consistency_fraction: self.consistency_fraction,
+            consistency_window: self.consistency.window(),
+            payout_cadence: self.payout_cadence,
+            min_days_for_payout: self.min_trading_days,
+            qualifying_day: format!(
+                "P&L > {:.2} or < {:.2}",
+                self.real_trading_day.min_win, self.real_trading_day.min_loss
+            ),
+            min_profitable_days: self.min_profitable_days,
+            profit_split: self.profit_split.clone(),
+            scaling_plan: None,
+            difficulty: None,
+        }
+    }
+
     pub fn try_add_trading_day(&mut self, daily_pnl: f64){
         
         if self.real_trading_day.was_rtd(daily_pnl){
@@ -259,16 +359,22 @@ impl FttAccount {
         if daily_pnl > self.max_winning_day_profit{
             self.max_winning_day_profit = daily_pnl;
         }
+        self.consistency.record_day(daily_pnl);
 
     }
 }
 
 impl PropAccount for FttAccount{
     fn process_trade(&mut self, trade: &Trade) -> AccountStatus {
-        self.trade_on_account(trade)
+        let (trade, limit_hit) = self.daily_loss.before_trade(self.current_balance, trade);
+        self.drawdown.before_trade(self.current_balance, &trade);
+        let status = self.trade_on_account(&trade);
+        self.drawdown.after_trade(self.current_balance, &status);
+        self.daily_loss.after_trade(status, limit_hit)
     }
 
     fn update_end_of_day(&mut self, daily_pnl: f64) {
+        self.daily_loss.end_of_day();
         self.update_loss_balance();
         self.try_add_trading_day(daily_pnl);
     }
@@ -277,14 +383,36 @@ impl PropAccount for FttAccount{
         self.allowed_withdrawal_amount()
     }
 
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        self.allowed_withdrawal()
+    }
+
     fn make_withdrawal(&mut self, amount: f64) -> u8 {
         self.make_withdrawal(amount)
     }
 
+    fn deny_withdrawal(&mut self) {
+        self.start_payout_period();
+    }
+
     fn get_current_balance(&self) -> f64 {
         self.current_balance
     }
 
+    fn get_drawdown_buffer(&self) -> f64 {
+        self.drawdown.buffer(self.current_balance)
+    }
+
+    // FTT accounts are funded from the start, there is no evaluation phase
+    fn is_funded(&self) -> bool {
+        true
+    }
+
+    // The account must keep its minimum balance after a withdrawal
+    fn withdrawable_balance(&self) -> f64 {
+        (self.current_balance - self.min_balance_after_withdrawal).max(0.0)
+    }
+
     fn get_simulation_days(&self) -> u64 {
         self.simulation_days
     }
@@ -300,4 +428,36 @@ impl PropAccount for FttAccount{
     fn get_funded_acct_cost(&self)-> f64 {
         0.0
     }
+
+    fn info(&self) -> AccountInfo {
+        self.info()
+    }
+
+    fn apply_rules(&mut self, rules: &RuleOverrides) {
+        self.apply_rules(rules)
+    }
+
+    fn get_daily_loss_limit(&self) -> Option<DailyLossLimit> {
+        self.daily_loss.limit()
+    }
+
+    fn daily_loss_breach(&self) -> BreachSeverity {
+        self.daily_loss.breach()
+    }
+
+    fn set_account_size(&mut self, account_size: f64) {
+        self.daily_loss.set_account_size(account_size);
+    }
+
+    fn payout_cadence(&self) -> PayoutCadence {
+        self.payout_cadence
+    }
+
+    fn min_profitable_days(&self) -> Option<ProfitableDays> {
+        self.min_profitable_days
+    }
+
+    fn profit_split(&self) -> ProfitSplit {
+        self.profit_split.clone()
+    }
 }
\ No newline at end of file
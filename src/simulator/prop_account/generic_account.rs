@@ -0,0 +1,453 @@
+// src/simulator/prop_account/generic_account.rs
+use std::fs;
+use std::path::Path;
+
+use super::{AccountStatus, PropAccount};
+use super::catalog::{AccountInfo, DrawdownType, PayoutCadence};
+use super::daily_loss::DailyLoss;
+use super::drawdown::Drawdown;
+use super::rules::{BreachSeverity, ConsistencyTracker, ConsistencyWindow, DailyLossLimit, ProfitSplit, ProfitableDays, RuleOverrides, ScalingPlan, WithdrawalLimit};
+use crate::simulator::trade_data::Trade;
+use crate::simulator::SimulationError;
+use log::debug;
+use serde::{Serialize, Deserialize};
+
+/// Rules of a firm's account given as data instead of a built-in account type, e.g. in TOML:
+///
+/// ```toml
+/// name = "Acme 50K"
+/// cost = 99.0
+/// drawdown = 2000.0
+/// profit_target = 3000.0
+/// min_days_for_payout = 5
+/// qualifying_day_profit = 150.0
+/// payout_fraction = 0.5
+/// consistency_fraction = 0.3
+/// profit_split = { trader_percent = 90.0 }
+/// ```
+///
+/// Balances are P&L relative to the starting balance. The drawdown trails until the loss
+/// level reaches the starting balance. Passing the evaluation (when `profit_target` is set)
+/// starts a funded account with a fresh balance and drawdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomAccountSpec {
+    /// Name shown in results and diagrams; the account is reported as `custom:<name>`
+    pub name: String,
+    /// Price in USD of the account (the evaluation when there is one)
+    pub cost: f64,
+    /// Activation fee in USD paid on passing the evaluation
+    pub funded_account_cost: f64,
+    pub drawdown_type: DrawdownType,
+    /// Distance between the starting balance and the loss level
+    pub drawdown: f64,
+    /// Profit that passes the evaluation; without one the account is funded from the start
+    pub profit_target: Option<f64>,
+    pub daily_loss_limit: Option<DailyLossLimit>,
+    pub daily_loss_breach: BreachSeverity,
+    /// Qualifying days required since the last payout
+    pub min_days_for_payout: u64,
+    /// Smallest P&L of a qualifying day (every trading day qualifies if not set)
+    pub qualifying_day_profit: Option<f64>,
+    /// Profitable days required since the last payout, e.g. `{ days = 5, min_profit = 100.0 }`
+    pub min_profitable_days: Option<ProfitableDays>,
+    /// Balance required to request a payout
+    pub min_balance_for_payout: f64,
+    /// Balance that must remain after a payout
+    pub min_balance_after_payout: f64,
+    /// Share of the balance above `min_balance_after_payout` paid out
+    pub payout_fraction: f64,
+    /// Maximum amount of each payout
+    pub payout_cap: Option<f64>,
+    pub payout_cadence: PayoutCadence,
+    /// Trading days between requesting a payout and receiving it
+    pub payout_delay_days: u64,
+    /// Chance that the firm denies a requested payout, between 0 and 1
+    pub payout_denial_probability: f64,
+    /// Share of the payouts the trader keeps (all of them if not set)
+    pub profit_split: ProfitSplit,
+    /// Largest winning day allowed for a payout, as a fraction of the profit over `consistency_window`
+    pub consistency_fraction: Option<f64>,
+    pub consistency_window: ConsistencyWindow,
+    /// Contracts allowed by balance, e.g. `{ contracts = 2, tiers = [{ balance = 1500.0, contracts = 5 }] }`;
+    /// the funded account starts over at the first tier
+    pub scaling_plan: Option<ScalingPlan>,
+}
+
+impl Default for CustomAccountSpec {
+    fn default() -> Self {
+        CustomAccountSpec {
+            name: String::from("Custom"),
+            cost: 0.0,
+            funded_account_cost: 0.0,
+            drawdown_type: DrawdownType::EndOfDayTrailing,
+            drawdown: 2_000.0,
+            profit_target: None,
+            daily_loss_limit: None,
+            daily_loss_breach: BreachSeverity::Hard,
+            min_days_for_payout: 5,
+            qualifying_day_profit: None,
+            min_profitable_days: None,
+            min_balance_for_payout: 0.0,
+            min_balance_after_payout: 0.0,
+            payout_fraction: 1.0,
+            payout_cap: None,
+            payout_cadence: PayoutCadence::Daily,
+            payout_delay_days: 0,
+            payout_denial_probability: 0.0,
+            profit_split: ProfitSplit::default(),
+            consistency_fraction: None,
+            consistency_window: ConsistencyWindow::Account,
+            scaling_plan: None,
+        }
+    }
+}
+
+impl CustomAccountSpec {
+    /// Read a spec from a JSON file (`.json`) or a TOML file (any other extension)
+    pub fn load(path: &str) -> Result<Self, SimulationError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| SimulationError::InvalidConfig(format!("{}: {}", path, e)))?;
+        let spec = if Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&contents).map_err(|e| e.to_string())
+        };
+        spec.map_err(|e| SimulationError::InvalidConfig(format!("{}: {}", path, e)))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("custom_account.name must not be empty".into());
+        }
+        if self.drawdown <= 0.0 {
+            return Err("custom_account.drawdown must be greater than zero".into());
+        }
+        if self.profit_target.is_some_and(|target| target <= 0.0) {
+            return Err("custom_account.profit_target must be greater than zero".into());
+        }
+        if self.cost < 0.0 || self.funded_account_cost < 0.0 {
+            return Err("custom_account costs must not be negative".into());
+        }
+        if self.payout_fraction <= 0.0 || self.payout_fraction > 1.0 {
+            return Err("custom_account.payout_fraction must be greater than 0 and at most 1".into());
+        }
+        if self.payout_cap.is_some_and(|cap| cap <= 0.0) {
+            return Err("custom_account.payout_cap must be greater than zero".into());
+        }
+        if !(0.0..=1.0).contains(&self.payout_denial_probability) {
+            return Err("custom_account.payout_denial_probability must be between 0 and 1".into());
+        }
+        if self.consistency_fraction.is_some_and(|fraction| fraction <= 0.0 || fraction > 1.0) {
+            return Err("custom_account.consistency_fraction must be greater than 0 and at most 1".into());
+        }
+        self.profit_split.validate().map_err(|e| format!("custom_account.{}", e))?;
+        if let Some(plan) = &self.scaling_plan {
+            plan.validate().map_err(|e| format!("custom_account.{}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Name the account is reported as
+    pub fn account_type(&self) -> String {
+        format!("custom:{}", self.name)
+    }
+}
+
+/// Account interpreting a `CustomAccountSpec`
+#[derive(Debug)]
+pub struct GenericPropAccount {
+    spec: CustomAccountSpec,
+    current_balance: f64,
+    drawdown: Drawdown,
+    daily_loss: DailyLoss,
+    passed_eval: bool,
+    qualifying_days: u64, // since the last payout
+    max_winning_day_profit: f64, // since the last payout, for the consistency rule
+    consistency: ConsistencyTracker,
+    payout_count: u8,
+    simulation_days: u64,
+}
+
+impl GenericPropAccount {
+    pub fn new(spec: CustomAccountSpec) -> Self {
+        GenericPropAccount {
+            current_balance: 0.0,
+            drawdown: Drawdown::new(spec.drawdown_type, spec.drawdown),
+            daily_loss: DailyLoss::new(spec.daily_loss_limit, spec.daily_loss_breach),
+            passed_eval: spec.profit_target.is_none(),
+            qualifying_days: 0,
+            max_winning_day_profit: 0.0,
+            consistency: ConsistencyTracker::new(spec.consistency_window),
+            payout_count: 0,
+            simulation_days: 0,
+            spec,
+        }
+    }
+
+    fn trade_on_account(&mut self, trade: &Trade) -> AccountStatus {
+        // A winning trade can still reach the loss level on its way
+        let worst = if trade.return_value > 0.0 { trade.max_opposite_excursion } else { trade.return_value };
+        if self.drawdown.is_breached(self.current_balance + worst) {
+            self.current_balance += worst;
+            return AccountStatus::Blown(worst);
+        }
+        if !self.passed_eval {
+            let best = trade.return_value.max(trade.max_opposite_excursion);
+            if let Some(target) = self.spec.profit_target.filter(|&target| self.current_balance + best >= target) {
+                debug!("Profit target of {:.2} reached, starting the funded account", target);
+                self.start_funded_account();
+                return AccountStatus::PassedEval;
+            }
+        }
+        self.current_balance += trade.return_value;
+        AccountStatus::Active(trade.return_value)
+    }
+
+    // The funded account starts over from the starting balance
+    fn start_funded_account(&mut self) {
+        self.passed_eval = true;
+        self.current_balance = 0.0;
+        self.drawdown.reset(0.0);
+    }
+
+    fn passes_consistency_rule(&self) -> bool {
+        match self.spec.consistency_fraction {
+            Some(fraction) => self.consistency.passes(fraction, self.max_winning_day_profit, self.current_balance),
+            None => true,
+        }
+    }
+
+    fn try_add_trading_day(&mut self, daily_pnl: f64) {
+        if !self.passed_eval {
+            return;
+        }
+        if self.spec.qualifying_day_profit.is_none_or(|profit| daily_pnl >= profit) {
+            self.qualifying_days += 1;
+        }
+        if daily_pnl > self.max_winning_day_profit {
+            self.max_winning_day_profit = daily_pnl;
+        }
+        self.consistency.record_day(daily_pnl);
+    }
+
+    fn apply_rules(&mut self, rules: &RuleOverrides) {
+        if let Some(fraction) = rules.consistency_fraction {
+            self.spec.consistency_fraction = Some(fraction);
+        }
+        if rules.disable_consistency {
+            self.spec.consistency_fraction = None;
+        }
+        if let Some(window) = rules.consistency_window {
+            self.spec.consistency_window = window;
+            self.consistency.set_window(window);
+        }
+        if let Some(cap) = rules.payout_cap {
+            self.spec.payout_cap = Some(cap);
+        }
+        if let Some(drawdown_type) = rules.drawdown_type {
+            self.spec.drawdown_type = drawdown_type;
+            self.drawdown.set_drawdown_type(drawdown_type);
+        }
+        if let Some(payout_cadence) = rules.payout_cadence {
+            self.spec.payout_cadence = payout_cadence;
+        }
+        if let Some(limit) = rules.daily_loss_limit {
+            self.spec.daily_loss_limit = Some(limit);
+        }
+        if let Some(severity) = rules.daily_loss_breach {
+            self.spec.daily_loss_breach = severity;
+        }
+        self.daily_loss.apply_rules(rules);
+        if let Some(profitable_days) = rules.min_profitable_days {
+            self.spec.min_profitable_days = Some(profitable_days);
+        }
+        if let Some(profit_split) = &rules.profit_split {
+            self.spec.profit_split = profit_split.clone();
+        }
+        if let Some(days) = rules.min_days_for_payout {
+            self.spec.min_days_for_payout = days;
+        }
+    }
+
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        if !self.passed_eval
+            || self.qualifying_days < self.spec.min_days_for_payout
+            || self.current_balance < self.spec.min_balance_for_payout
+            || !self.passes_consistency_rule()
+        {
+            return None;
+        }
+        let (amount, limit) = if self.spec.payout_fraction < 1.0 {
+            ((self.current_balance - self.spec.min_balance_after_payout) * self.spec.payout_fraction, WithdrawalLimit::BalanceFraction)
+        } else if self.spec.min_balance_after_payout > 0.0 {
+            (self.current_balance - self.spec.min_balance_after_payout, WithdrawalLimit::BalanceAboveMinimum)
+        } else {
+            (self.current_balance, WithdrawalLimit::FullBalance)
+        };
+        if amount <= 0.0 {
+            return None;
+        }
+        match self.spec.payout_cap {
+            Some(cap) if cap < amount => Some((cap, WithdrawalLimit::PayoutCap)),
+            _ => Some((amount, limit)),
+        }
+    }
+
+    fn info(&self) -> AccountInfo {
+        let spec = &self.spec;
+        let mut payout_cap = if spec.payout_fraction < 1.0 {
+            format!("{:.0}% of the balance above {:.0}", spec.payout_fraction * 100.0, spec.min_balance_after_payout)
+        } else {
+            format!("balance above {:.0}", spec.min_balance_after_payout)
+        };
+        if let Some(cap) = spec.payout_cap {
+            payout_cap.push_str(&format!(", at most {:.0}", cap));
+        }
+        AccountInfo {
+            account_type: spec.account_type(),
+            display_name: spec.name.clone(),
+            company: String::from("custom"),
+            cost: spec.cost,
+            funded_account_cost: spec.funded_account_cost,
+            drawdown_type: spec.drawdown_type,
+            drawdown: spec.drawdown,
+            starting_buffer: self.drawdown.buffer(self.current_balance),
+            profit_target: spec.profit_target,
+            payout_cap,
+            payout_schedule: format!(
+                "After {} qualifying days since the last payout, once the balance is at least {:.0}",
+                spec.min_days_for_payout, spec.min_balance_for_payout
+            ),
+            payout_cadence: spec.payout_cadence,
+            consistency_fraction: spec.consistency_fraction,
+            consistency_window: self.consistency.window(),
+            min_days_for_payout: spec.min_days_for_payout,
+            qualifying_day: match spec.qualifying_day_profit {
+                Some(profit) => format!("P&L >= {:.2}", profit),
+                None => String::from("every trading day"),
+            },
+            min_profitable_days: spec.min_profitable_days,
+            profit_split: spec.profit_split.clone(),
+            scaling_plan: spec.scaling_plan.clone(),
+            difficulty: None,
+        }
+    }
+}
+
+impl PropAccount for GenericPropAccount {
+    fn process_trade(&mut self, trade: &Trade) -> AccountStatus {
+        let (trade, limit_hit) = self.daily_loss.before_trade(self.current_balance, trade);
+        self.drawdown.before_trade(self.current_balance, &trade);
+        let status = self.trade_on_account(&trade);
+        self.drawdown.after_trade(self.current_balance, &status);
+        self.daily_loss.after_trade(status, limit_hit)
+    }
+
+    fn update_end_of_day(&mut self, daily_pnl: f64) {
+        self.daily_loss.end_of_day();
+        self.drawdown.end_of_day(self.current_balance);
+        self.try_add_trading_day(daily_pnl);
+    }
+
+    fn allowed_withdrawal_amount(&self) -> Option<f64> {
+        self.allowed_withdrawal().map(|(amount, _)| amount)
+    }
+
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        self.allowed_withdrawal()
+    }
+
+    fn make_withdrawal(&mut self, amount: f64) -> u8 {
+        self.current_balance -= amount;
+        self.deny_withdrawal();
+        self.payout_count += 1;
+        self.payout_count
+    }
+
+    fn deny_withdrawal(&mut self) {
+        self.max_winning_day_profit = 0.0;
+        self.consistency.record_payout();
+        self.qualifying_days = 0;
+    }
+
+    fn get_current_balance(&self) -> f64 {
+        self.current_balance
+    }
+
+    fn get_drawdown_buffer(&self) -> f64 {
+        self.drawdown.buffer(self.current_balance)
+    }
+
+    fn is_funded(&self) -> bool {
+        self.passed_eval
+    }
+
+    fn withdrawable_balance(&self) -> f64 {
+        if self.passed_eval {
+            (self.current_balance - self.spec.min_balance_after_payout).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn get_simulation_days(&self) -> u64 {
+        self.simulation_days
+    }
+
+    fn increment_simulation_day(&mut self) {
+        self.simulation_days += 1;
+    }
+
+    fn get_cost(&self) -> f64 {
+        self.spec.cost
+    }
+
+    fn get_funded_acct_cost(&self) -> f64 {
+        self.spec.funded_account_cost
+    }
+
+    fn info(&self) -> AccountInfo {
+        self.info()
+    }
+
+    fn apply_rules(&mut self, rules: &RuleOverrides) {
+        self.apply_rules(rules)
+    }
+
+    fn get_daily_loss_limit(&self) -> Option<DailyLossLimit> {
+        self.spec.daily_loss_limit
+    }
+
+    fn daily_loss_breach(&self) -> BreachSeverity {
+        self.spec.daily_loss_breach
+    }
+
+    fn set_account_size(&mut self, account_size: f64) {
+        self.daily_loss.set_account_size(account_size);
+    }
+
+    fn payout_cadence(&self) -> PayoutCadence {
+        self.spec.payout_cadence
+    }
+
+    fn payout_delay_days(&self) -> u64 {
+        self.spec.payout_delay_days
+    }
+
+    fn payout_denial_probability(&self) -> f64 {
+        self.spec.payout_denial_probability
+    }
+
+    fn min_profitable_days(&self) -> Option<ProfitableDays> {
+        self.spec.min_profitable_days
+    }
+
+    fn profit_split(&self) -> ProfitSplit {
+        self.spec.profit_split.clone()
+    }
+
+    fn max_contracts(&self) -> Option<u32> {
+        self.spec.scaling_plan.as_ref().map(|plan| plan.max_contracts(self.current_balance))
+    }
+}
@@ -0,0 +1,436 @@
+// src/simulator/prop_account/mff_account.rs
+use std::str::FromStr;
+
+use super::{AccountStatus, PropAccount};
+use super::catalog::{AccountInfo, DrawdownType, PayoutCadence};
+use super::daily_loss::DailyLoss;
+use super::drawdown::Drawdown;
+use super::rules::{BreachSeverity, ConsistencyTracker, DailyLossLimit, ProfitSplit, ProfitableDays, RuleOverrides, SplitTier, WithdrawalLimit};
+use crate::simulator::trade_data::Trade;
+use log::debug;
+use serde::{Serialize, Deserialize};
+
+// MyFundedFutures evaluations: Starter plans have no daily loss limit and a wider drawdown,
+// Expert plans trade a tighter drawdown and a daily loss limit for larger payouts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MyFundedFuturesAccountType {
+    Starter50K,
+    Starter100K,
+    Starter150K,
+    Expert50K,
+    Expert100K,
+    Expert150K,
+}
+
+// Largest winning day allowed, as a fraction of the profit since the last payout
+const MFF_CONSISTENCY_FRACTION: f64 = 0.4;
+const WINNING_DAYS_FOR_PAYOUT: u32 = 5;
+// Smallest payout the firm processes
+const MIN_PAYOUT: f64 = 500.0;
+// The funded drawdown stops trailing this far above the starting balance
+const DRAWDOWN_LOCK: f64 = 100.0;
+const PAYOUT_CADENCE: PayoutCadence = PayoutCadence::Daily;
+// The trader keeps all of the first 10,000 paid out by a funded account and 90% beyond
+const FULL_SPLIT_PAID_OUT: f64 = 10_000.0;
+const TRADER_PERCENT_AFTER: f64 = 90.0;
+
+fn mff_profit_split() -> ProfitSplit {
+    ProfitSplit { trader_percent: 100.0, tiers: vec![SplitTier { after: FULL_SPLIT_PAID_OUT, trader_percent: TRADER_PERCENT_AFTER }] }
+}
+
+impl MyFundedFuturesAccountType {
+    fn initialize_account(&self) -> MyFundedFuturesAccount {
+        let drawdown = self.drawdown();
+        MyFundedFuturesAccount {
+            current_balance: 0.0,
+            drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, drawdown),
+            profit_target: self.profit_target(),
+            // The payout buffer: only the balance above one drawdown can be withdrawn
+            payout_buffer: drawdown,
+            simulation_days: 0,
+            winning_days_since_last_payout: 0,
+            winning_day_profit: self.winning_day_profit(),
+            passed_eval: false,
+            payout_count: 0,
+            max_winning_day_profit: 0.0,
+            daily_loss: DailyLoss::new(self.daily_loss_limit().map(DailyLossLimit::Amount), BreachSeverity::Soft),
+            min_profitable_days: None,
+            payout_cadence: PAYOUT_CADENCE,
+            profit_split: mff_profit_split(),
+            consistency: ConsistencyTracker::default(),
+            consistency_fraction: Some(MFF_CONSISTENCY_FRACTION),
+            payout_cap: self.payout_cap(),
+            winning_days_for_payout: WINNING_DAYS_FOR_PAYOUT,
+            account_type: self.clone(),
+        }
+    }
+
+    fn is_expert(&self) -> bool {
+        matches!(self, MyFundedFuturesAccountType::Expert50K | MyFundedFuturesAccountType::Expert100K | MyFundedFuturesAccountType::Expert150K)
+    }
+
+    // Account size in thousands
+    fn size(&self) -> u32 {
+        match self {
+            MyFundedFuturesAccountType::Starter50K | MyFundedFuturesAccountType::Expert50K => 50,
+            MyFundedFuturesAccountType::Starter100K | MyFundedFuturesAccountType::Expert100K => 100,
+            MyFundedFuturesAccountType::Starter150K | MyFundedFuturesAccountType::Expert150K => 150,
+        }
+    }
+
+    fn profit_target(&self) -> f64 {
+        match self.size() {
+            50 => 3_000.0,
+            100 => 6_000.0,
+            _ => 9_000.0,
+        }
+    }
+
+    fn drawdown(&self) -> f64 {
+        match (self.is_expert(), self.size()) {
+            (false, 50) => 2_500.0,
+            (false, 100) => 3_500.0,
+            (false, _) => 5_000.0,
+            (true, 50) => 2_000.0,
+            (true, 100) => 3_000.0,
+            (true, _) => 4_500.0,
+        }
+    }
+
+    // Starter plans have no daily loss limit
+    fn daily_loss_limit(&self) -> Option<f64> {
+        if !self.is_expert() {
+            return None;
+        }
+        match self.size() {
+            50 => Some(1_200.0),
+            100 => Some(2_200.0),
+            _ => Some(3_300.0),
+        }
+    }
+
+    // Expert payouts are not capped
+    fn payout_cap(&self) -> Option<f64> {
+        if self.is_expert() {
+            return None;
+        }
+        match self.size() {
+            50 => Some(1_500.0),
+            100 => Some(2_000.0),
+            _ => Some(2_500.0),
+        }
+    }
+
+    fn winning_day_profit(&self) -> f64 {
+        if self.is_expert() { 200.0 } else { 150.0 }
+    }
+
+    // Function to return the cost of each account type
+    pub fn get_cost(&self) -> f64 {
+        match self {
+            MyFundedFuturesAccountType::Starter50K => 77.0,
+            MyFundedFuturesAccountType::Starter100K => 137.0,
+            MyFundedFuturesAccountType::Starter150K => 197.0,
+            MyFundedFuturesAccountType::Expert50K => 165.0,
+            MyFundedFuturesAccountType::Expert100K => 265.0,
+            MyFundedFuturesAccountType::Expert150K => 365.0,
+        }
+    }
+
+    // Funded accounts have no activation fee
+    pub fn funded_acct_cost() -> f64 {
+        0.0
+    }
+}
+
+impl FromStr for MyFundedFuturesAccountType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Plan and account size: "Starter100K", "starter 100k", "expert-50" or "expert150000"
+        let name: String = s.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+        let (expert, size) = if let Some(size) = name.strip_prefix("starter") {
+            (false, size)
+        } else if let Some(size) = name.strip_prefix("expert") {
+            (true, size)
+        } else {
+            return Err("Unknown MyFundedFutures account type");
+        };
+        match (expert, size) {
+            (false, "50" | "50k" | "50000") => Ok(MyFundedFuturesAccountType::Starter50K),
+            (false, "100" | "100k" | "100000") => Ok(MyFundedFuturesAccountType::Starter100K),
+            (false, "150" | "150k" | "150000") => Ok(MyFundedFuturesAccountType::Starter150K),
+            (true, "50" | "50k" | "50000") => Ok(MyFundedFuturesAccountType::Expert50K),
+            (true, "100" | "100k" | "100000") => Ok(MyFundedFuturesAccountType::Expert100K),
+            (true, "150" | "150k" | "150000") => Ok(MyFundedFuturesAccountType::Expert150K),
+            _ => Err("Unknown MyFundedFutures account type"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MyFundedFuturesAccount {
+    current_balance: f64,
+    profit_target: f64,
+    drawdown: Drawdown, // trails the EOD high, locks at DRAWDOWN_LOCK once funded
+    payout_buffer: f64, // balance that stays in the funded account after a payout
+    winning_days_since_last_payout: u32,
+    winning_day_profit: f64,
+    max_winning_day_profit: f64, // since the last payout, for the consistency rule
+    consistency_fraction: Option<f64>,
+    payout_cap: Option<f64>,
+    winning_days_for_payout: u32,
+    daily_loss: DailyLoss, // firm limit
+    min_profitable_days: Option<ProfitableDays>, // only set through a rule change
+    payout_cadence: PayoutCadence,
+    profit_split: ProfitSplit,
+    consistency: ConsistencyTracker,
+    passed_eval: bool,
+    payout_count: u8,
+    simulation_days: u64,
+    account_type: MyFundedFuturesAccountType,
+}
+
+impl MyFundedFuturesAccount {
+    pub fn new(account_type: MyFundedFuturesAccountType) -> Self {
+        account_type.initialize_account()
+    }
+
+    fn trade_on_account(&mut self, trade: &Trade) -> AccountStatus {
+        // A winning trade can still reach the loss level on its way
+        let worst = if trade.return_value > 0.0 { trade.max_opposite_excursion } else { trade.return_value };
+        if self.drawdown.is_breached(self.current_balance + worst) {
+            self.current_balance += worst;
+            return AccountStatus::Blown(worst);
+        }
+        if !self.passed_eval && self.current_balance + trade.return_value.max(trade.max_opposite_excursion) >= self.profit_target {
+            debug!("Profit target of {:.2} reached, starting the funded account", self.profit_target);
+            self.start_funded_account();
+            return AccountStatus::PassedEval;
+        }
+        self.current_balance += trade.return_value;
+        AccountStatus::Active(trade.return_value)
+    }
+
+    // The funded account starts over from the starting balance with a fresh drawdown
+    fn start_funded_account(&mut self) {
+        self.passed_eval = true;
+        self.current_balance = 0.0;
+        self.drawdown.reset(DRAWDOWN_LOCK);
+    }
+
+    pub fn passes_consistency_rule(&self) -> bool {
+        match self.consistency_fraction {
+            Some(fraction) => self.consistency.passes(fraction, self.max_winning_day_profit, self.current_balance),
+            None => true,
+        }
+    }
+
+    pub fn apply_rules(&mut self, rules: &RuleOverrides) {
+        if let Some(fraction) = rules.consistency_fraction {
+            self.consistency_fraction = Some(fraction);
+        }
+        if rules.disable_consistency {
+            self.consistency_fraction = None;
+        }
+        if let Some(window) = rules.consistency_window {
+            self.consistency.set_window(window);
+        }
+        if let Some(cap) = rules.payout_cap {
+            self.payout_cap = Some(cap);
+        }
+        if let Some(drawdown_type) = rules.drawdown_type {
+            self.drawdown.set_drawdown_type(drawdown_type);
+        }
+        if let Some(payout_cadence) = rules.payout_cadence {
+            self.payout_cadence = payout_cadence;
+        }
+        self.daily_loss.apply_rules(rules);
+        if let Some(profitable_days) = rules.min_profitable_days {
+            self.min_profitable_days = Some(profitable_days);
+        }
+        if let Some(profit_split) = &rules.profit_split {
+            self.profit_split = profit_split.clone();
+        }
+        if let Some(days) = rules.min_days_for_payout {
+            self.winning_days_for_payout = days as u32;
+        }
+    }
+
+    pub fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        if !self.passed_eval
+            || self.winning_days_since_last_payout < self.winning_days_for_payout
+            || !self.passes_consistency_rule()
+        {
+            return None;
+        }
+        let available = self.current_balance - self.payout_buffer;
+        if available < MIN_PAYOUT {
+            return None;
+        }
+        match self.payout_cap {
+            Some(cap) if cap < available => Some((cap, WithdrawalLimit::PayoutCap)),
+            _ => Some((available, WithdrawalLimit::BalanceAboveMinimum)),
+        }
+    }
+
+    pub fn make_withdrawal(&mut self, amount: f64) -> u8 {
+        self.current_balance -= amount;
+        self.start_payout_period();
+        self.payout_count += 1;
+        self.payout_count
+    }
+
+    // Counters since the last payout start over
+    fn start_payout_period(&mut self) {
+        self.max_winning_day_profit = 0.0;
+        self.consistency.record_payout();
+        self.winning_days_since_last_payout = 0;
+    }
+
+    pub fn info(&self) -> AccountInfo {
+        let plan = if self.account_type.is_expert() { "Expert" } else { "Starter" };
+        AccountInfo {
+            account_type: format!("mff:{:?}", self.account_type),
+            display_name: format!("MyFundedFutures {} {}K", plan, self.account_type.size()),
+            company: String::from("mff"),
+            cost: self.account_type.get_cost(),
+            funded_account_cost: MyFundedFuturesAccountType::funded_acct_cost(),
+            drawdown_type: self.drawdown.drawdown_type(),
+            drawdown: self.drawdown.amount(),
+            starting_buffer: self.drawdown.buffer(self.current_balance),
+            profit_target: Some(self.profit_target),
+            payout_cap: match self.payout_cap {
+                Some(cap) => format!("balance above {:.0}, at most {:.0}", self.payout_buffer, cap),
+                None => format!("balance above {:.0}", self.payout_buffer),
+            },
+            payout_schedule: format!(
+                "Funded account only: after {} winning days since the last payout, once at least {:.0} is above the {:.0} buffer",
+                self.winning_days_for_payout, MIN_PAYOUT, self.payout_buffer
+            ),
+            consistency_fraction: self.consistency_fraction,
+            consistency_window: self.consistency.window(),
+            payout_cadence: self.payout_cadence,
+            min_days_for_payout: self.winning_days_for_payout as u64,
+            qualifying_day: format!("P&L >= {:.2} (funded only)", self.winning_day_profit),
+            min_profitable_days: self.min_profitable_days,
+            profit_split: self.profit_split.clone(),
+            scaling_plan: None,
+            difficulty: None,
+        }
+    }
+
+    pub fn try_add_trading_day(&mut self, daily_pnl: f64) {
+        if !self.passed_eval {
+            return;
+        }
+        if daily_pnl >= self.winning_day_profit {
+            self.winning_days_since_last_payout += 1;
+        }
+        if daily_pnl > self.max_winning_day_profit {
+            self.max_winning_day_profit = daily_pnl;
+        }
+        self.consistency.record_day(daily_pnl);
+    }
+}
+
+impl PropAccount for MyFundedFuturesAccount {
+    fn process_trade(&mut self, trade: &Trade) -> AccountStatus {
+        let (trade, limit_hit) = self.daily_loss.before_trade(self.current_balance, trade);
+        self.drawdown.before_trade(self.current_balance, &trade);
+        let status = self.trade_on_account(&trade);
+        self.drawdown.after_trade(self.current_balance, &status);
+        self.daily_loss.after_trade(status, limit_hit)
+    }
+
+    fn update_end_of_day(&mut self, daily_pnl: f64) {
+        self.daily_loss.end_of_day();
+        self.drawdown.end_of_day(self.current_balance);
+        self.try_add_trading_day(daily_pnl);
+    }
+
+    fn allowed_withdrawal_amount(&self) -> Option<f64> {
+        self.allowed_withdrawal().map(|(amount, _)| amount)
+    }
+
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        self.allowed_withdrawal()
+    }
+
+    fn make_withdrawal(&mut self, amount: f64) -> u8 {
+        self.make_withdrawal(amount)
+    }
+
+    fn deny_withdrawal(&mut self) {
+        self.start_payout_period();
+    }
+
+    fn get_current_balance(&self) -> f64 {
+        self.current_balance
+    }
+
+    fn get_drawdown_buffer(&self) -> f64 {
+        self.drawdown.buffer(self.current_balance)
+    }
+
+    fn is_funded(&self) -> bool {
+        self.passed_eval
+    }
+
+    // The payout buffer stays in the account
+    fn withdrawable_balance(&self) -> f64 {
+        if self.passed_eval {
+            (self.current_balance - self.payout_buffer).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn get_simulation_days(&self) -> u64 {
+        self.simulation_days
+    }
+
+    fn increment_simulation_day(&mut self) {
+        self.simulation_days += 1;
+    }
+
+    fn get_cost(&self) -> f64 {
+        self.account_type.get_cost()
+    }
+
+    fn get_funded_acct_cost(&self) -> f64 {
+        MyFundedFuturesAccountType::funded_acct_cost()
+    }
+
+    fn info(&self) -> AccountInfo {
+        self.info()
+    }
+
+    fn apply_rules(&mut self, rules: &RuleOverrides) {
+        self.apply_rules(rules)
+    }
+
+    fn get_daily_loss_limit(&self) -> Option<DailyLossLimit> {
+        self.daily_loss.limit()
+    }
+
+    fn daily_loss_breach(&self) -> BreachSeverity {
+        self.daily_loss.breach()
+    }
+
+    fn set_account_size(&mut self, account_size: f64) {
+        self.daily_loss.set_account_size(account_size);
+    }
+
+    fn payout_cadence(&self) -> PayoutCadence {
+        self.payout_cadence
+    }
+
+    fn min_profitable_days(&self) -> Option<ProfitableDays> {
+        self.min_profitable_days
+    }
+
+    fn profit_split(&self) -> ProfitSplit {
+        self.profit_split.clone()
+    }
+}
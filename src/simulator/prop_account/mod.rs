@@ -1,7 +1,17 @@
 pub mod ftt_account;
 pub mod topstep_account;
+pub mod mff_account;
+pub mod tpt_account;
+pub mod bulenox_account;
+pub mod generic_account;
 // Add other account modules here...
 pub mod account_type;
+pub mod catalog;
+pub mod daily_loss;
+pub mod diagram;
+pub mod drawdown;
+pub mod registry;
+pub mod rules;
 
 use crate::simulator::trade_data::Trade;
 
@@ -10,31 +20,108 @@ pub enum AccountStatus {
     Blown(f64),
     Active(f64),
     PassedEval,
+    /// The trade reached the firm daily loss limit and the account is lost
+    DailyLimitHit(f64),
+    /// The trade reached a soft firm daily loss limit: it was closed at the limit and the
+    /// account is locked for the rest of the day
+    LockedForDay(f64),
 }
 
 pub trait PropAccount {
     fn process_trade(&mut self, trade: &Trade) -> AccountStatus;
     fn update_end_of_day(&mut self, daily_pnl: f64);
     fn allowed_withdrawal_amount(&self) -> Option<f64>;
+    /// `allowed_withdrawal_amount` with the rule that set the amount
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        self.allowed_withdrawal_amount().map(|amount| (amount, WithdrawalLimit::Unspecified))
+    }
     fn make_withdrawal(&mut self, amount: f64) -> u8;
+    /// The firm denied a requested payout: the balance stays on the account, but the
+    /// consistency and trading-day counters start over as after a payout
+    fn deny_withdrawal(&mut self);
     fn get_current_balance(&self) -> f64;
+    /// Distance between the current balance and the balance at which the account is blown
+    fn get_drawdown_buffer(&self) -> f64;
+    /// Whether the account is past its evaluation and eligible for payouts
+    fn is_funded(&self) -> bool;
+    /// Balance that could eventually be withdrawn if trading stopped now
+    fn withdrawable_balance(&self) -> f64 {
+        if self.is_funded() {
+            self.get_current_balance().max(0.0)
+        } else {
+            0.0
+        }
+    }
     fn get_simulation_days(&self) -> u64;
     fn increment_simulation_day(&mut self);
     fn get_cost(&self) -> f64;
     fn get_funded_acct_cost(&self)-> f64;
+    /// Structured description of the account's rules, used by the account catalog
+    fn info(&self) -> AccountInfo;
+    /// Change rule parameters mid-simulation (see `RuleChange`); accounts without a
+    /// parameter ignore it
+    fn apply_rules(&mut self, _rules: &RuleOverrides) {}
+    /// Firm daily loss limit, enforced by the account against the day's running P&L (see `DailyLoss`)
+    fn get_daily_loss_limit(&self) -> Option<DailyLossLimit> {
+        None
+    }
+    /// Whether reaching the firm daily loss limit blows the account or only ends the day;
+    /// the drawdown is always a hard breach
+    fn daily_loss_breach(&self) -> BreachSeverity {
+        BreachSeverity::Hard
+    }
+    /// Nominal starting balance of the account, used by a percentage daily loss limit
+    fn set_account_size(&mut self, _account_size: f64) {}
+    /// How often the firm pays out, enforced by the trader between payouts
+    fn payout_cadence(&self) -> PayoutCadence {
+        PayoutCadence::Daily
+    }
+    /// Trading days between requesting a payout and receiving it, enforced by the trader
+    fn payout_delay_days(&self) -> u64 {
+        0
+    }
+    /// Chance that the firm denies a requested payout, drawn by the trader
+    fn payout_denial_probability(&self) -> f64 {
+        0.0
+    }
+    /// Profitable days required since the last payout, enforced by the trader
+    fn min_profitable_days(&self) -> Option<ProfitableDays> {
+        None
+    }
+    /// Share of each payout the trader keeps; the trader credits only that share to the
+    /// bank account
+    fn profit_split(&self) -> ProfitSplit {
+        ProfitSplit::default()
+    }
+    /// Contracts the firm allows at the current balance under its scaling plan; the trader
+    /// scales trades taken with more contracts down to it (see `SimulationConfig::traded_contracts`)
+    fn max_contracts(&self) -> Option<u32> {
+        None
+    }
 }
 
 // Re-export account structs
 pub use ftt_account::{FttAccount, FttAccountType};
 pub use topstep_account::{TopstepAccount, TopstepAccountType};
+pub use mff_account::{MyFundedFuturesAccount, MyFundedFuturesAccountType};
+pub use tpt_account::{TakeProfitTraderAccount, TakeProfitTraderAccountType};
+pub use bulenox_account::{BulenoxAccount, BulenoxAccountType};
+pub use generic_account::{CustomAccountSpec, GenericPropAccount};
 pub use account_type::AccountType;
+pub use catalog::{AccountInfo, DrawdownType, PayoutCadence, account_catalog, account_info};
+pub use diagram::{DiagramFormat, account_diagram, state_diagram};
+pub use daily_loss::DailyLoss;
+pub use drawdown::Drawdown;
 // Add other account re-exports here...
-
+pub use registry::{AccountFactory, account_factory, custom_account_factory, register_account, registered_accounts, resolve_account_type};
+pub use rules::{BreachSeverity, ConsistencyTracker, ConsistencyWindow, DailyLossLimit, Milestone, ProfitSplit, ProfitableDays, RuleChange, RuleOverrides, ScalingPlan, ScalingTier, SplitTier, WithdrawalLimit};
 
 pub fn create_account(account_type: AccountType) -> Box<dyn PropAccount + Send + Sync> {
-    match account_type {
-        AccountType::Ftt(ftt_type) => Box::new(FttAccount::new(ftt_type)),
-        AccountType::TopStep(topstep_type) => Box::new(TopstepAccount::new(topstep_type)),
-        // Handle other companies...
+    // Custom accounts are defined by their spec, not registered by name
+    if let AccountType::Custom(spec) = account_type {
+        return Box::new(GenericPropAccount::new(*spec));
     }
+    let factory = account_factory(&account_type.to_string())
+        .expect("built-in account types are always registered");
+    factory()
 }
@@ -0,0 +1,116 @@
+// src/simulator/prop_account/registry.rs
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use super::account_type::{did_you_mean, split_account_name};
+use super::{AccountType, BulenoxAccount, CustomAccountSpec, FttAccount, GenericPropAccount, MyFundedFuturesAccount, PropAccount, TakeProfitTraderAccount, TopstepAccount};
+use crate::simulator::SimulationError;
+
+/// Creates a fresh account for each simulation iteration
+pub type AccountFactory = Arc<dyn Fn() -> Box<dyn PropAccount + Send + Sync> + Send + Sync>;
+
+struct RegisteredAccount {
+    name: String,
+    factory: AccountFactory,
+}
+
+// Kept in registration order; names are matched case-insensitively
+static REGISTRY: OnceLock<RwLock<Vec<RegisteredAccount>>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<Vec<RegisteredAccount>> {
+    REGISTRY.get_or_init(|| {
+        let accounts = AccountType::all()
+            .into_iter()
+            .map(|account_type| {
+                let name = account_type.to_string();
+                let factory: AccountFactory = Arc::new(move || new_builtin_account(&account_type));
+                RegisteredAccount { name, factory }
+            })
+            .collect();
+        RwLock::new(accounts)
+    })
+}
+
+fn new_builtin_account(account_type: &AccountType) -> Box<dyn PropAccount + Send + Sync> {
+    match account_type {
+        AccountType::Ftt(ftt_type) => Box::new(FttAccount::new(ftt_type.clone())),
+        AccountType::TopStep(topstep_type) => Box::new(TopstepAccount::new(topstep_type.clone())),
+        AccountType::MyFundedFutures(mff_type) => Box::new(MyFundedFuturesAccount::new(mff_type.clone())),
+        AccountType::TakeProfitTrader(tpt_type) => Box::new(TakeProfitTraderAccount::new(tpt_type.clone())),
+        AccountType::Bulenox(bulenox_type) => Box::new(BulenoxAccount::new(bulenox_type.clone())),
+        AccountType::Custom(spec) => Box::new(GenericPropAccount::new((**spec).clone())),
+    }
+}
+
+/// Factory of accounts following `spec`, after checking its rules
+pub fn custom_account_factory(spec: CustomAccountSpec) -> Result<AccountFactory, SimulationError> {
+    spec.validate().map_err(SimulationError::InvalidConfig)?;
+    Ok(Arc::new(move || Box::new(GenericPropAccount::new(spec.clone()))))
+}
+
+/// Register an account factory under `company:account_type` so it can be used
+/// as `account_type` in a `SimulationConfig`. Names must not already be registered.
+pub fn register_account<F>(name: &str, factory: F) -> Result<(), SimulationError>
+where
+    F: Fn() -> Box<dyn PropAccount + Send + Sync> + Send + Sync + 'static,
+{
+    let parts: Vec<&str> = name.split(':').collect();
+    if parts.len() != 2 || parts.iter().any(|p| p.trim().is_empty()) {
+        return Err(SimulationError::InvalidConfig(format!(
+            "Invalid account name '{}'. Use 'company:account_type'.", name
+        )));
+    }
+
+    let mut accounts = registry().write().unwrap();
+    if accounts.iter().any(|account| account.name.eq_ignore_ascii_case(name)) {
+        return Err(SimulationError::InvalidConfig(format!("Account '{}' is already registered", name)));
+    }
+    accounts.push(RegisteredAccount { name: name.to_string(), factory: Arc::new(factory) });
+    Ok(())
+}
+
+/// Look up the factory registered for `company:account_type`, or for an alias of it
+/// (see `resolve_account_type`)
+pub fn account_factory(name: &str) -> Result<AccountFactory, SimulationError> {
+    let name = resolve_account_type(name)?;
+    let accounts = registry().read().unwrap();
+    accounts
+        .iter()
+        .find(|account| account.name == name)
+        .map(|account| account.factory.clone())
+        .ok_or_else(|| SimulationError::InvalidConfig(format!("Unknown account type '{}'", name)))
+}
+
+/// Registered name of the account `name` refers to. Besides the registered name in any
+/// case, this accepts a space instead of the colon ("topstep 50k") and the aliases and
+/// sizes of the built-in accounts ("topstep:100k", "ftt:le mans"). Unknown names fail with
+/// the closest registered name as suggestion.
+pub fn resolve_account_type(name: &str) -> Result<String, SimulationError> {
+    let accounts = registry().read().unwrap();
+    if let Some(account) = accounts.iter().find(|account| account.name.eq_ignore_ascii_case(name.trim())) {
+        return Ok(account.name.clone());
+    }
+    if let Ok(account_type) = AccountType::from_str(name) {
+        return Ok(account_type.to_string());
+    }
+    // Registered accounts by company and account type, whatever the separator
+    if let Some((company, account_type)) = split_account_name(name) {
+        let found = accounts.iter().find(|account| {
+            split_account_name(&account.name).is_some_and(|(c, a)| c.eq_ignore_ascii_case(company) && a.eq_ignore_ascii_case(account_type))
+        });
+        if let Some(account) = found {
+            return Ok(account.name.clone());
+        }
+    }
+
+    let names: Vec<String> = accounts.iter().map(|account| account.name.clone()).collect();
+    Err(SimulationError::InvalidConfig(match did_you_mean(name, &names) {
+        Some(suggestion) => format!("Unknown account type '{}'. Did you mean '{}'?", name.trim(), suggestion),
+        None => format!("Unknown account type '{}' (known accounts: {})", name.trim(), names.join(", ")),
+    }))
+}
+
+/// Names of all registered accounts, built-in ones included
+pub fn registered_accounts() -> Vec<String> {
+    registry().read().unwrap().iter().map(|account| account.name.clone()).collect()
+}
@@ -0,0 +1,385 @@
+// src/simulator/prop_account/rules.rs
+use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use super::catalog::{DrawdownType, PayoutCadence};
+
+/// Point in an account's life at which a rule change takes effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Milestone {
+    /// After this many simulation days on the account
+    Day(u64),
+    /// After this many payouts
+    Payouts(u32),
+}
+
+impl Milestone {
+    pub fn reached(&self, simulation_days: u64, payouts: u32) -> bool {
+        match *self {
+            Milestone::Day(day) => simulation_days >= day,
+            Milestone::Payouts(count) => payouts >= count,
+        }
+    }
+}
+
+/// Maximum loss allowed within one trading day
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DailyLossLimit {
+    /// Fixed amount, e.g. `{ amount = 1000.0 }`
+    Amount(f64),
+    /// Percentage of the prior day's closing balance (account size plus P&L), e.g. `{ percent = 5.0 }`
+    Percent(f64),
+}
+
+impl DailyLossLimit {
+    /// Loss allowed today (a positive amount) given the prior day's closing balance
+    pub fn allowed_loss(&self, prior_day_balance: f64) -> f64 {
+        match *self {
+            DailyLossLimit::Amount(amount) => amount.abs(),
+            DailyLossLimit::Percent(percent) => prior_day_balance.max(0.0) * percent / 100.0,
+        }
+    }
+
+    pub fn is_percent(&self) -> bool {
+        matches!(self, DailyLossLimit::Percent(_))
+    }
+}
+
+/// What breaching a firm limit costs the trader
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreachSeverity {
+    /// The account is lost
+    #[default]
+    Hard,
+    /// Open trades are closed at the limit and trading stops for the day; the account survives
+    Soft,
+}
+
+impl BreachSeverity {
+    pub const ALL: [BreachSeverity; 2] = [BreachSeverity::Hard, BreachSeverity::Soft];
+}
+
+impl FromStr for BreachSeverity {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hard" => Ok(BreachSeverity::Hard),
+            "soft" => Ok(BreachSeverity::Soft),
+            _ => Err("Unknown breach severity (options: hard, soft)"),
+        }
+    }
+}
+
+/// Payout precondition: at least `days` profitable trading days, with a P&L of at least
+/// `min_profit`, since the last payout (or since the account was funded), e.g.
+/// `{ days = 5, min_profit = 100.0 }`. Other days in between do not reset the count.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProfitableDays {
+    pub days: u32,
+    pub min_profit: f64,
+}
+
+impl ProfitableDays {
+    /// Whether the daily P&L of the payout period meets the rule
+    pub fn met(&self, period_pnl: &[f64]) -> bool {
+        period_pnl.iter().filter(|&&pnl| pnl > 0.0 && pnl >= self.min_profit).count() >= self.days as usize
+    }
+}
+
+/// Share of each payout the trader keeps, the firm retaining the rest: `trader_percent`
+/// of the payouts, changing to a tier's `trader_percent` once the account has paid out
+/// `after` in total, e.g. `{ trader_percent = 100.0, tiers = [{ after = 10000.0, trader_percent = 90.0 }] }`
+/// for 100% of the first 10,000 and 90% beyond
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfitSplit {
+    pub trader_percent: f64,
+    #[serde(default)]
+    pub tiers: Vec<SplitTier>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SplitTier {
+    /// Total paid out by the account, before the split, from which the tier applies
+    pub after: f64,
+    pub trader_percent: f64,
+}
+
+impl Default for ProfitSplit {
+    /// The trader keeps every payout
+    fn default() -> Self {
+        ProfitSplit::flat(100.0)
+    }
+}
+
+impl ProfitSplit {
+    pub fn flat(trader_percent: f64) -> Self {
+        ProfitSplit { trader_percent, tiers: Vec::new() }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let mut percents = std::iter::once(self.trader_percent).chain(self.tiers.iter().map(|tier| tier.trader_percent));
+        if percents.any(|percent| !(0.0..=100.0).contains(&percent)) {
+            return Err("profit_split percentages must be between 0 and 100".into());
+        }
+        if self.tiers.iter().any(|tier| tier.after <= 0.0 || tier.after.is_nan()) || self.tiers.windows(2).any(|pair| pair[0].after >= pair[1].after) {
+            return Err("profit_split tiers must start after increasing positive amounts".into());
+        }
+        Ok(())
+    }
+
+    /// Trader's share of a payout of `amount` from an account that paid out `paid_out`
+    /// before it; a payout crossing a tier is split at the tier
+    pub fn trader_share(&self, paid_out: f64, amount: f64) -> f64 {
+        let mut share = 0.0;
+        let mut from = paid_out;
+        let end = paid_out + amount;
+        let mut percent = self.trader_percent;
+        for tier in &self.tiers {
+            if tier.after > from {
+                let to = tier.after.min(end);
+                share += (to - from) * percent / 100.0;
+                from = to;
+            }
+            if from >= end {
+                return share;
+            }
+            percent = tier.trader_percent;
+        }
+        share + (end - from) * percent / 100.0
+    }
+}
+
+impl fmt::Display for ProfitSplit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.trader_percent)?;
+        for tier in &self.tiers {
+            write!(f, ", {}% after {:.0}", tier.trader_percent, tier.after)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ProfitSplit {
+    type Err = String;
+
+    /// `90` for 90% of every payout, or `100,90@10000` for 100% of the first 10,000 paid
+    /// out and 90% after
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid profit split {} (e.g. 90, or 100,90@10000)", s.trim());
+        let mut parts = s.split(',').map(str::trim);
+        let trader_percent = parts.next().unwrap_or_default().parse().map_err(|_| invalid())?;
+        let tiers = parts
+            .map(|part| {
+                let (percent, after) = part.split_once('@').ok_or_else(invalid)?;
+                Ok(SplitTier {
+                    after: after.trim().parse().map_err(|_| invalid())?,
+                    trader_percent: percent.trim().parse().map_err(|_| invalid())?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let split = ProfitSplit { trader_percent, tiers };
+        split.validate()?;
+        Ok(split)
+    }
+}
+
+/// Contracts a firm allows by account balance: `contracts` from the starting balance,
+/// changing to a tier's `contracts` once the balance (P&L) reaches its `balance`, e.g.
+/// `{ contracts = 2, tiers = [{ balance = 1500.0, contracts = 3 }, { balance = 2000.0, contracts = 5 }] }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScalingPlan {
+    pub contracts: u32,
+    #[serde(default)]
+    pub tiers: Vec<ScalingTier>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScalingTier {
+    /// Balance from which the tier applies
+    pub balance: f64,
+    pub contracts: u32,
+}
+
+impl ScalingPlan {
+    pub fn flat(contracts: u32) -> Self {
+        ScalingPlan { contracts, tiers: Vec::new() }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.contracts == 0 || self.tiers.iter().any(|tier| tier.contracts == 0) {
+            return Err("scaling_plan contracts must be greater than zero".into());
+        }
+        if self.tiers.iter().any(|tier| !tier.balance.is_finite()) || self.tiers.windows(2).any(|pair| pair[0].balance >= pair[1].balance) {
+            return Err("scaling_plan tiers must start at increasing balances".into());
+        }
+        Ok(())
+    }
+
+    /// Contracts allowed at `balance`
+    pub fn max_contracts(&self, balance: f64) -> u32 {
+        self.tiers
+            .iter()
+            .take_while(|tier| balance >= tier.balance)
+            .last()
+            .map_or(self.contracts, |tier| tier.contracts)
+    }
+}
+
+impl fmt::Display for ScalingPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.contracts)?;
+        for tier in &self.tiers {
+            write!(f, ", {} from {:.0}", tier.contracts, tier.balance)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ScalingPlan {
+    type Err = String;
+
+    /// `5` for at most 5 contracts, or `2,3@1500,5@2000` for 2 contracts, 3 once the
+    /// balance reaches 1,500 and 5 from 2,000
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid scaling plan {} (e.g. 5, or 2,3@1500,5@2000)", s.trim());
+        let mut parts = s.split(',').map(str::trim);
+        let contracts = parts.next().unwrap_or_default().parse().map_err(|_| invalid())?;
+        let tiers = parts
+            .map(|part| {
+                let (contracts, balance) = part.split_once('@').ok_or_else(invalid)?;
+                Ok(ScalingTier {
+                    balance: balance.trim().parse().map_err(|_| invalid())?,
+                    contracts: contracts.trim().parse().map_err(|_| invalid())?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let plan = ScalingPlan { contracts, tiers };
+        plan.validate()?;
+        Ok(plan)
+    }
+}
+
+/// Rule that set the amount of a withdrawal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalLimit {
+    /// The firm's maximum payout
+    PayoutCap,
+    /// The balance above the minimum the account must keep after a payout
+    BalanceAboveMinimum,
+    /// The share of the balance the firm pays out
+    BalanceFraction,
+    /// The whole balance
+    FullBalance,
+    /// An account that does not report its rule
+    Unspecified,
+}
+
+/// Days the consistency rule looks at: the largest winning day must stay within
+/// `consistency_fraction` of the profit made over them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsistencyWindow {
+    /// Largest winning day since the last payout against the account balance
+    #[default]
+    Account,
+    /// Largest winning day against the profit made since the last payout
+    SinceLastPayout,
+    /// Largest winning day against the profit of the trailing N trading days, e.g. `{ trailing_days = 10 }`
+    TrailingDays(u64),
+}
+
+/// Daily P&L kept for the consistency rule under its window
+#[derive(Debug, Clone, Default)]
+pub struct ConsistencyTracker {
+    window: ConsistencyWindow,
+    days: VecDeque<f64>,
+}
+
+impl ConsistencyTracker {
+    pub fn new(window: ConsistencyWindow) -> Self {
+        ConsistencyTracker { window, days: VecDeque::new() }
+    }
+
+    pub fn window(&self) -> ConsistencyWindow {
+        self.window
+    }
+
+    pub fn set_window(&mut self, window: ConsistencyWindow) {
+        self.window = window;
+    }
+
+    pub fn record_day(&mut self, daily_pnl: f64) {
+        match self.window {
+            ConsistencyWindow::Account => {}
+            ConsistencyWindow::SinceLastPayout => self.days.push_back(daily_pnl),
+            ConsistencyWindow::TrailingDays(days) => {
+                self.days.push_back(daily_pnl);
+                while self.days.len() as u64 > days {
+                    self.days.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn record_payout(&mut self) {
+        if self.window == ConsistencyWindow::SinceLastPayout {
+            self.days.clear();
+        }
+    }
+
+    /// Whether a payout passes the rule, given the largest winning day since the last payout
+    /// and the account balance (used by the `Account` window)
+    pub fn passes(&self, fraction: f64, max_winning_day_profit: f64, balance: f64) -> bool {
+        match self.window {
+            ConsistencyWindow::Account => max_winning_day_profit <= fraction * balance,
+            _ => {
+                let best_day = self.days.iter().copied().fold(0.0, f64::max);
+                best_day <= fraction * self.days.iter().sum::<f64>()
+            }
+        }
+    }
+}
+
+/// Rule parameters to change; parameters left out keep their current value.
+/// Accounts ignore parameters their rule set does not have.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleOverrides {
+    /// Largest winning day as a fraction of the balance allowed for a payout
+    pub consistency_fraction: Option<f64>,
+    /// Drop the consistency rule altogether
+    pub disable_consistency: bool,
+    /// Days the consistency rule is computed over
+    pub consistency_window: Option<ConsistencyWindow>,
+    /// Maximum amount of each payout
+    pub payout_cap: Option<f64>,
+    /// Qualifying days required before each payout
+    pub min_days_for_payout: Option<u64>,
+    /// Profitable days required within each payout period
+    pub min_profitable_days: Option<ProfitableDays>,
+    /// Firm daily loss limit; reaching it blows the account unless `daily_loss_breach` is soft
+    pub daily_loss_limit: Option<DailyLossLimit>,
+    /// Whether reaching the firm daily loss limit blows the account or only ends the day
+    pub daily_loss_breach: Option<BreachSeverity>,
+    /// Whether the drawdown trails end-of-day balances, intraday highs or not at all
+    pub drawdown_type: Option<DrawdownType>,
+    /// How often payouts can be requested
+    pub payout_cadence: Option<PayoutCadence>,
+    /// Share of the payouts the trader keeps
+    pub profit_split: Option<ProfitSplit>,
+}
+
+/// One entry of a rule-change timeline, e.g. `{ after = { payouts = 1 }, disable_consistency = true }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleChange {
+    pub after: Milestone,
+    #[serde(flatten)]
+    pub rules: RuleOverrides,
+}
@@ -1,8 +1,11 @@
 use std::str::FromStr;
 
 use super::{AccountStatus, PropAccount};
+use super::catalog::{AccountInfo, DrawdownType, PayoutCadence};
+use super::daily_loss::DailyLoss;
+use super::drawdown::Drawdown;
+use super::rules::{BreachSeverity, ConsistencyTracker, DailyLossLimit, ProfitSplit, ProfitableDays, RuleOverrides, ScalingPlan, ScalingTier, SplitTier, WithdrawalLimit};
 use crate::simulator::trade_data::Trade;
-use log::debug;
 use serde::{Serialize, Deserialize};
 
 // Enum for FTT account types and their rule sets
@@ -15,8 +18,32 @@ pub enum TopstepAccountType {
 }
 
 const WINNING_DAY_TOPSTEP: f64 = 200.0;
+#[allow(dead_code)] // consistency rule is not enforced on withdrawals yet
 const TOPSTED_CONSISTENCY_FRACTION: f64 = 0.5;
 const XFA_COST: f64 = 149.0;
+const WINNING_DAYS_FOR_PAYOUT: u32 = 5;
+const WINNING_DAYS_FOR_FULL_PAYOUT: u32 = 30;
+const PAYOUT_FRACTION: f64 = 0.5;
+// Funded accounts can request a payout on any trading day once eligible
+const PAYOUT_CADENCE: PayoutCadence = PayoutCadence::Daily;
+// The trader keeps all of the first 10,000 paid out by a funded account and 90% beyond
+const FULL_SPLIT_PAID_OUT: f64 = 10_000.0;
+const TRADER_PERCENT_AFTER: f64 = 90.0;
+
+fn topstep_profit_split() -> ProfitSplit {
+    ProfitSplit { trader_percent: 100.0, tiers: vec![SplitTier { after: FULL_SPLIT_PAID_OUT, trader_percent: TRADER_PERCENT_AFTER }] }
+}
+
+// Contracts allowed in the funded account (XFA) by its profit; the Combine allows the
+// largest size from the start
+fn topstep_scaling_plan(account_type: &TopstepAccountType) -> ScalingPlan {
+    let tier = |balance: f64, contracts: u32| ScalingTier { balance, contracts };
+    match account_type {
+        TopstepAccountType::Fifty => ScalingPlan { contracts: 2, tiers: vec![tier(1_500.0, 3), tier(2_000.0, 5)] },
+        TopstepAccountType::OneHundred => ScalingPlan { contracts: 3, tiers: vec![tier(1_500.0, 4), tier(2_000.0, 5), tier(3_000.0, 10)] },
+        TopstepAccountType::OneFifty => ScalingPlan { contracts: 3, tiers: vec![tier(1_500.0, 4), tier(2_000.0, 5), tier(3_000.0, 10), tier(4_500.0, 15)] },
+    }
+}
 
 impl TopstepAccountType {
 
@@ -25,45 +52,66 @@ impl TopstepAccountType {
             TopstepAccountType::Fifty => {
                 TopstepAccount{
                     current_balance: 0.0,
-                    hwm_balance: 0.0,
-                    drawdown: 2_000.0,
+                    drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, 2_000.0),
                     profit_target: 3_000.0,
-                    loss_balance: -2_000.0,
                     simulation_days: 0,
                     winning_days_since_last_payout: 0,
                     total_winning_days: 0,
                     passed_eval: false,
                     max_winning_day_profit: 0.0,
+                    daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+                    min_profitable_days: None,
+                    payout_cadence: PAYOUT_CADENCE,
+                    profit_split: topstep_profit_split(),
+                    consistency: ConsistencyTracker::default(),
+                    consistency_fraction: None,
+                    payout_cap: None,
+                    winning_days_for_payout: WINNING_DAYS_FOR_PAYOUT,
+                    scaling_plan: topstep_scaling_plan(&TopstepAccountType::Fifty),
                     account_type: TopstepAccountType::Fifty,
                 }
             },
             TopstepAccountType::OneHundred => {
                 TopstepAccount{
                     current_balance: 0.0,
-                    hwm_balance: 0.0,
-                    drawdown: 3_000.0,
+                    drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, 3_000.0),
                     profit_target: 6_000.0,
-                    loss_balance: -3_000.0,
                     simulation_days: 0,
                     winning_days_since_last_payout: 0,
                     total_winning_days: 0,
                     passed_eval: false,
                     max_winning_day_profit: 0.0,
+                    daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+                    min_profitable_days: None,
+                    payout_cadence: PAYOUT_CADENCE,
+                    profit_split: topstep_profit_split(),
+                    consistency: ConsistencyTracker::default(),
+                    consistency_fraction: None,
+                    payout_cap: None,
+                    winning_days_for_payout: WINNING_DAYS_FOR_PAYOUT,
+                    scaling_plan: topstep_scaling_plan(&TopstepAccountType::OneHundred),
                     account_type: TopstepAccountType::OneHundred,
                 }
             },
             TopstepAccountType::OneFifty => {
                 TopstepAccount{
                     current_balance: 0.0,
-                    hwm_balance: 0.0,
-                    drawdown: 4_500.0,
+                    drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, 4_500.0),
                     profit_target: 9_000.0,
-                    loss_balance: -4_500.0,
                     simulation_days: 0,
                     winning_days_since_last_payout: 0,
                     total_winning_days: 0,
                     passed_eval: false,
                     max_winning_day_profit: 0.0,
+                    daily_loss: DailyLoss::new(None, BreachSeverity::Hard),
+                    min_profitable_days: None,
+                    payout_cadence: PAYOUT_CADENCE,
+                    profit_split: topstep_profit_split(),
+                    consistency: ConsistencyTracker::default(),
+                    consistency_fraction: None,
+                    payout_cap: None,
+                    winning_days_for_payout: WINNING_DAYS_FOR_PAYOUT,
+                    scaling_plan: topstep_scaling_plan(&TopstepAccountType::OneFifty),
                     account_type: TopstepAccountType::OneFifty,
                 }
             },
@@ -88,10 +136,12 @@ impl FromStr for TopstepAccountType {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "fifty" => Ok(TopstepAccountType::Fifty),
-            "onehundred" => Ok(TopstepAccountType::OneHundred),
-            "onefifty" => Ok(TopstepAccountType::OneFifty),
+        // By name or account size: "Fifty", "50k", "50K" or "50000"
+        let name: String = s.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        match name.to_lowercase().as_str() {
+            "fifty" | "50" | "50k" | "50000" => Ok(TopstepAccountType::Fifty),
+            "onehundred" | "hundred" | "100" | "100k" | "100000" => Ok(TopstepAccountType::OneHundred),
+            "onefifty" | "onehundredfifty" | "150" | "150k" | "150000" => Ok(TopstepAccountType::OneFifty),
             _ => Err("Unknown Topstep account type"),
         }
     }
@@ -100,13 +150,20 @@ impl FromStr for TopstepAccountType {
 #[derive(Debug)]
 pub struct TopstepAccount {
     current_balance: f64,        // current balance
-    hwm_balance: f64,           //high water mark
     profit_target: f64,
-    drawdown: f64,          //drawdown  == profit target
-    loss_balance: f64,   // accounts for max loss limit / drawdown allowance (Drawdown updates EOD, stops at initial balance. max loss is intraday)
+    drawdown: Drawdown, // loss level, trails up to the initial balance (EOD by default)
     winning_days_since_last_payout: u32,
     total_winning_days: u32, //total winning days
     max_winning_day_profit: f64,
+    consistency_fraction: Option<f64>, // only enforced when set through a rule change
+    payout_cap: Option<f64>,
+    winning_days_for_payout: u32,
+    scaling_plan: ScalingPlan, // of the funded account, by profit since it started
+    daily_loss: DailyLoss, // firm limit, only set through a rule change
+    min_profitable_days: Option<ProfitableDays>, // only set through a rule change
+    payout_cadence: PayoutCadence,
+    profit_split: ProfitSplit,
+    consistency: ConsistencyTracker, // days the consistency rule is computed over
     passed_eval: bool,
     simulation_days: u64, //every 30 simulation days not in xfa incurs cost
     account_type: TopstepAccountType,
@@ -119,7 +176,7 @@ impl TopstepAccount {
 
     pub fn trade_on_combine(&mut self, trade: &Trade) -> AccountStatus{
         if trade.return_value > 0.0 {
-            if self.current_balance + trade.max_opposite_excursion <= self.loss_balance{
+            if self.current_balance + trade.max_opposite_excursion <= self.drawdown.loss_balance(){
                 //trade would have won but mae blew us out
                 self.current_balance += trade.max_opposite_excursion;
                 return AccountStatus::Blown(trade.max_opposite_excursion);
@@ -135,7 +192,7 @@ impl TopstepAccount {
             }
         }
         else{
-            if self.current_balance + trade.return_value <= self.loss_balance{
+            if self.current_balance + trade.return_value <= self.drawdown.loss_balance(){
                 self.current_balance += trade.return_value;
                 return AccountStatus::Blown(trade.return_value);
             }
@@ -152,7 +209,7 @@ impl TopstepAccount {
 
     pub fn trade_on_account(&mut self, trade: &Trade) -> AccountStatus{
         if trade.return_value > 0.0 {
-            if self.current_balance + trade.max_opposite_excursion <= self.loss_balance{
+            if self.current_balance + trade.max_opposite_excursion <= self.drawdown.loss_balance(){
                 //trade would have won but mae blew us out
                 self.current_balance += trade.max_opposite_excursion;
                 return AccountStatus::Blown(trade.max_opposite_excursion);
@@ -163,7 +220,7 @@ impl TopstepAccount {
             }
         }
         else{
-            if self.current_balance + trade.return_value <= self.loss_balance{
+            if self.current_balance + trade.return_value <= self.drawdown.loss_balance(){
                 self.current_balance += trade.return_value;
                 return AccountStatus::Blown(trade.return_value);
             }
@@ -174,45 +231,84 @@ impl TopstepAccount {
         }
     }
 
+    fn trade_on_phase(&mut self, trade: &Trade) -> AccountStatus {
+        if !self.passed_eval {
+            // During the combine phase
+            self.trade_on_combine(trade)
+        } else {
+            // Live trading
+            self.trade_on_account(trade)
+        }
+    }
+
     // Update drawdown based on the current balance (EOD)
     pub fn update_loss_balance(&mut self) {
-        if self.hwm_balance < self.profit_target{
-            //if havent hit profit target yet, still trailing dd
-            if self.current_balance > self.hwm_balance{
-                //made new hwm
-                self.loss_balance = self.current_balance - self.drawdown;
-                
-                if self.loss_balance > 0.0{
-                    self.loss_balance = 0.0;
-                }
-                debug!("eod trail updated. new loss balance: {}", self.loss_balance);
-                self.hwm_balance = self.current_balance;
-            }
-        }
+        self.drawdown.end_of_day(self.current_balance);
     }
 
     pub fn passes_consistency_rule(&self) -> bool{
-        if self.max_winning_day_profit  > TOPSTED_CONSISTENCY_FRACTION * self.current_balance {
-            return false;
+        match self.consistency_fraction {
+            Some(fraction) => self.consistency.passes(fraction, self.max_winning_day_profit, self.current_balance),
+            None => true,
+        }
+    }
+
+    pub fn apply_rules(&mut self, rules: &RuleOverrides) {
+        if let Some(fraction) = rules.consistency_fraction {
+            self.consistency_fraction = Some(fraction);
+        }
+        if rules.disable_consistency {
+            self.consistency_fraction = None;
+        }
+        if let Some(window) = rules.consistency_window {
+            self.consistency.set_window(window);
+        }
+        if let Some(cap) = rules.payout_cap {
+            self.payout_cap = Some(cap);
+        }
+        if let Some(drawdown_type) = rules.drawdown_type {
+            self.drawdown.set_drawdown_type(drawdown_type);
+        }
+        if let Some(payout_cadence) = rules.payout_cadence {
+            self.payout_cadence = payout_cadence;
+        }
+        self.daily_loss.apply_rules(rules);
+        if let Some(profitable_days) = rules.min_profitable_days {
+            self.min_profitable_days = Some(profitable_days);
+        }
+        if let Some(profit_split) = &rules.profit_split {
+            self.profit_split = profit_split.clone();
+        }
+        if let Some(days) = rules.min_days_for_payout {
+            self.winning_days_for_payout = days as u32;
         }
-        true
     }
 
     pub fn allowed_withdrawal_amount(&self) -> Option<f64>{
-        if self.total_winning_days >= 30{
-            return Some(self.current_balance);
-        } else if self.winning_days_since_last_payout >= 5{
-            return Some(self.current_balance * 0.5);
+        self.allowed_withdrawal().map(|(amount, _)| amount)
+    }
+
+    pub fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)>{
+        if !self.passes_consistency_rule() {
+            return None;
+        }
+        let (amount, limit) = if self.total_winning_days >= WINNING_DAYS_FOR_FULL_PAYOUT{
+            (self.current_balance, WithdrawalLimit::FullBalance)
+        } else if self.winning_days_since_last_payout >= self.winning_days_for_payout{
+            (self.current_balance * PAYOUT_FRACTION, WithdrawalLimit::BalanceFraction)
         }
         else{
             return None;
+        };
+        match self.payout_cap {
+            Some(cap) if cap < amount => Some((cap, WithdrawalLimit::PayoutCap)),
+            _ => Some((amount, limit)),
         }
     }
 
     pub fn make_withdrawal(&mut self, amount: f64) -> u8 {
         self.current_balance -= amount;
-        self.max_winning_day_profit = 0.0; //TODO: is this reset every withdrawal?
-        self.winning_days_since_last_payout = 0;
+        self.start_payout_period();
         if self.current_balance <= 0.01{
             return 1; //end of game for topstep account
         }
@@ -221,6 +317,51 @@ impl TopstepAccount {
         }
     }
 
+    // Counters since the last payout start over
+    fn start_payout_period(&mut self) {
+        self.max_winning_day_profit = 0.0; //TODO: is this reset every withdrawal?
+        self.consistency.record_payout();
+        self.winning_days_since_last_payout = 0;
+    }
+
+    pub fn info(&self) -> AccountInfo {
+        AccountInfo {
+            account_type: format!("topstep:{:?}", self.account_type),
+            display_name: format!("Topstep {:?} Combine", self.account_type),
+            company: String::from("topstep"),
+            cost: self.account_type.get_cost(),
+            funded_account_cost: TopstepAccountType::funded_acct_cost(),
+            drawdown_type: self.drawdown.drawdown_type(),
+            drawdown: self.drawdown.amount(),
+            starting_buffer: self.drawdown.buffer(self.current_balance),
+            profit_target: Some(self.profit_target),
+            payout_cap: match self.payout_cap {
+                Some(cap) => format!(
+                    "{:.0}% of balance, 100% after {} winning days, at most {:.0}",
+                    PAYOUT_FRACTION * 100.0, WINNING_DAYS_FOR_FULL_PAYOUT, cap
+                ),
+                None => format!(
+                    "{:.0}% of balance, 100% after {} winning days",
+                    PAYOUT_FRACTION * 100.0, WINNING_DAYS_FOR_FULL_PAYOUT
+                ),
+            },
+            payout_schedule: format!(
+                "Funded account only: after {} winning days since the last payout; the whole balance once {} winning days are reached in total",
+                self.winning_days_for_payout, WINNING_DAYS_FOR_FULL_PAYOUT
+            ),
+            // TOPSTED_CONSISTENCY_FRACTION is not enforced on withdrawals unless a rule change sets a fraction
+            consistency_fraction: self.consistency_fraction,
+            consistency_window: self.consistency.window(),
+            payout_cadence: self.payout_cadence,
+            min_days_for_payout: self.winning_days_for_payout as u64,
+            qualifying_day: format!("P&L >= {:.2} (funded only)", WINNING_DAY_TOPSTEP),
+            min_profitable_days: self.min_profitable_days,
+            profit_split: self.profit_split.clone(),
+            scaling_plan: Some(self.scaling_plan.clone()),
+            difficulty: None,
+        }
+    }
+
     pub fn try_add_trading_day(&mut self, daily_pnl: f64){
         
         if self.passed_eval{
@@ -232,22 +373,22 @@ impl TopstepAccount {
             if daily_pnl > self.max_winning_day_profit{
                 self.max_winning_day_profit = daily_pnl;
             }
+            self.consistency.record_day(daily_pnl);
         }
     }
 }
 
 impl PropAccount for TopstepAccount {
     fn process_trade(&mut self, trade: &Trade) -> AccountStatus {
-        if !self.passed_eval {
-            // During the combine phase
-            self.trade_on_combine(trade)
-        } else {
-            // Live trading
-            self.trade_on_account(trade)
-        }
+        let (trade, limit_hit) = self.daily_loss.before_trade(self.current_balance, trade);
+        self.drawdown.before_trade(self.current_balance, &trade);
+        let status = self.trade_on_phase(&trade);
+        self.drawdown.after_trade(self.current_balance, &status);
+        self.daily_loss.after_trade(status, limit_hit)
     }
 
     fn update_end_of_day(&mut self, daily_pnl: f64) {
+        self.daily_loss.end_of_day();
         self.update_loss_balance();
         self.try_add_trading_day(daily_pnl);
     }
@@ -260,14 +401,34 @@ impl PropAccount for TopstepAccount {
         }
     }
 
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        if self.passed_eval {
+            self.allowed_withdrawal()
+        } else {
+            None
+        }
+    }
+
     fn make_withdrawal(&mut self, amount: f64) -> u8 {
         self.make_withdrawal(amount)
     }
 
+    fn deny_withdrawal(&mut self) {
+        self.start_payout_period();
+    }
+
     fn get_current_balance(&self) -> f64 {
         self.current_balance
     }
 
+    fn get_drawdown_buffer(&self) -> f64 {
+        self.drawdown.buffer(self.current_balance)
+    }
+
+    fn is_funded(&self) -> bool {
+        self.passed_eval
+    }
+
     fn get_simulation_days(&self) -> u64 {
         self.simulation_days
     }
@@ -282,4 +443,46 @@ impl PropAccount for TopstepAccount {
     fn get_funded_acct_cost(&self)-> f64 {
         TopstepAccountType::funded_acct_cost()
     }
+
+    fn info(&self) -> AccountInfo {
+        self.info()
+    }
+
+    fn apply_rules(&mut self, rules: &RuleOverrides) {
+        self.apply_rules(rules)
+    }
+
+    fn get_daily_loss_limit(&self) -> Option<DailyLossLimit> {
+        self.daily_loss.limit()
+    }
+
+    fn daily_loss_breach(&self) -> BreachSeverity {
+        self.daily_loss.breach()
+    }
+
+    fn set_account_size(&mut self, account_size: f64) {
+        self.daily_loss.set_account_size(account_size);
+    }
+
+    fn payout_cadence(&self) -> PayoutCadence {
+        self.payout_cadence
+    }
+
+    fn min_profitable_days(&self) -> Option<ProfitableDays> {
+        self.min_profitable_days
+    }
+
+    fn profit_split(&self) -> ProfitSplit {
+        self.profit_split.clone()
+    }
+
+    // The funded account keeps trading from the Combine's profit target
+    fn max_contracts(&self) -> Option<u32> {
+        let largest = self.scaling_plan.tiers.last().map_or(self.scaling_plan.contracts, |tier| tier.contracts);
+        if self.passed_eval {
+            Some(self.scaling_plan.max_contracts(self.current_balance - self.profit_target))
+        } else {
+            Some(largest)
+        }
+    }
 }
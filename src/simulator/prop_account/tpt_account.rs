@@ -0,0 +1,370 @@
+// src/simulator/prop_account/tpt_account.rs
+use std::str::FromStr;
+
+use super::{AccountStatus, PropAccount};
+use super::catalog::{AccountInfo, DrawdownType, PayoutCadence};
+use super::daily_loss::DailyLoss;
+use super::drawdown::Drawdown;
+use super::rules::{BreachSeverity, ConsistencyTracker, DailyLossLimit, ProfitSplit, ProfitableDays, RuleOverrides, WithdrawalLimit};
+use crate::simulator::trade_data::Trade;
+use log::debug;
+use serde::{Serialize, Deserialize};
+
+// Take Profit Trader Test accounts; passing the Test starts a PRO (funded) account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TakeProfitTraderAccountType {
+    TwentyFive,
+    Fifty,
+    SeventyFive,
+    OneHundred,
+    OneFifty,
+}
+
+const PRO_ACTIVATION_FEE: f64 = 130.0;
+// Share of each PRO payout the trader keeps
+const PRO_TRADER_PERCENT: f64 = 80.0;
+// PRO accounts can withdraw from their first day, on any trading day
+const PAYOUT_CADENCE: PayoutCadence = PayoutCadence::Daily;
+const PRO_MIN_DAYS_FOR_PAYOUT: u64 = 0;
+
+impl TakeProfitTraderAccountType {
+    fn initialize_account(&self) -> TakeProfitTraderAccount {
+        let (drawdown, profit_target, daily_loss_limit) = match self {
+            TakeProfitTraderAccountType::TwentyFive => (1_500.0, 1_500.0, 500.0),
+            TakeProfitTraderAccountType::Fifty => (2_000.0, 3_000.0, 1_100.0),
+            TakeProfitTraderAccountType::SeventyFive => (2_500.0, 4_500.0, 1_700.0),
+            TakeProfitTraderAccountType::OneHundred => (3_000.0, 6_000.0, 2_200.0),
+            TakeProfitTraderAccountType::OneFifty => (4_500.0, 9_000.0, 3_300.0),
+        };
+        TakeProfitTraderAccount {
+            current_balance: 0.0,
+            drawdown: Drawdown::new(DrawdownType::EndOfDayTrailing, drawdown),
+            profit_target,
+            // PRO withdrawals are limited to the balance above one drawdown
+            payout_buffer: drawdown,
+            test_daily_loss_limit: Some(DailyLossLimit::Amount(daily_loss_limit)),
+            // PRO accounts have no daily loss limit
+            pro_daily_loss_limit: None,
+            daily_loss: DailyLoss::new(Some(DailyLossLimit::Amount(daily_loss_limit)), BreachSeverity::Soft),
+            min_profitable_days: None,
+            min_days_for_payout: PRO_MIN_DAYS_FOR_PAYOUT,
+            trading_days: 0,
+            max_winning_day_profit: 0.0,
+            consistency_fraction: None,
+            consistency: ConsistencyTracker::default(),
+            payout_cap: None,
+            payout_cadence: PAYOUT_CADENCE,
+            profit_split: ProfitSplit::flat(PRO_TRADER_PERCENT),
+            passed_eval: false,
+            payout_count: 0,
+            simulation_days: 0,
+            account_type: self.clone(),
+        }
+    }
+
+    // Function to return the cost of each account type
+    pub fn get_cost(&self) -> f64 {
+        match self {
+            TakeProfitTraderAccountType::TwentyFive => 150.0,
+            TakeProfitTraderAccountType::Fifty => 170.0,
+            TakeProfitTraderAccountType::SeventyFive => 245.0,
+            TakeProfitTraderAccountType::OneHundred => 330.0,
+            TakeProfitTraderAccountType::OneFifty => 360.0,
+        }
+    }
+
+    pub fn funded_acct_cost() -> f64 {
+        PRO_ACTIVATION_FEE
+    }
+}
+
+impl FromStr for TakeProfitTraderAccountType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // By name or account size: "Fifty", "50k", "50K" or "50000"
+        let name: String = s.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        match name.to_lowercase().as_str() {
+            "twentyfive" | "25" | "25k" | "25000" => Ok(TakeProfitTraderAccountType::TwentyFive),
+            "fifty" | "50" | "50k" | "50000" => Ok(TakeProfitTraderAccountType::Fifty),
+            "seventyfive" | "75" | "75k" | "75000" => Ok(TakeProfitTraderAccountType::SeventyFive),
+            "onehundred" | "hundred" | "100" | "100k" | "100000" => Ok(TakeProfitTraderAccountType::OneHundred),
+            "onefifty" | "onehundredfifty" | "150" | "150k" | "150000" => Ok(TakeProfitTraderAccountType::OneFifty),
+            _ => Err("Unknown Take Profit Trader account type"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TakeProfitTraderAccount {
+    current_balance: f64,
+    profit_target: f64,
+    drawdown: Drawdown, // trails the EOD high until it reaches the starting balance
+    payout_buffer: f64, // balance that stays in the PRO account after a payout
+    test_daily_loss_limit: Option<DailyLossLimit>,
+    pro_daily_loss_limit: Option<DailyLossLimit>, // only set through a rule change
+    daily_loss: DailyLoss, // with the limit of the current phase
+    min_profitable_days: Option<ProfitableDays>, // only set through a rule change
+    min_days_for_payout: u64,
+    trading_days: u64, // since the last payout
+    max_winning_day_profit: f64, // since the last payout, for the consistency rule
+    consistency_fraction: Option<f64>, // only enforced when set through a rule change
+    consistency: ConsistencyTracker,
+    payout_cap: Option<f64>, // only set through a rule change
+    payout_cadence: PayoutCadence,
+    profit_split: ProfitSplit,
+    passed_eval: bool,
+    payout_count: u8,
+    simulation_days: u64,
+    account_type: TakeProfitTraderAccountType,
+}
+
+impl TakeProfitTraderAccount {
+    pub fn new(account_type: TakeProfitTraderAccountType) -> Self {
+        account_type.initialize_account()
+    }
+
+    fn trade_on_account(&mut self, trade: &Trade) -> AccountStatus {
+        // A winning trade can still reach the loss level on its way
+        let worst = if trade.return_value > 0.0 { trade.max_opposite_excursion } else { trade.return_value };
+        if self.drawdown.is_breached(self.current_balance + worst) {
+            self.current_balance += worst;
+            return AccountStatus::Blown(worst);
+        }
+        if !self.passed_eval && self.current_balance + trade.return_value.max(trade.max_opposite_excursion) >= self.profit_target {
+            debug!("Profit target of {:.2} reached, starting the PRO account", self.profit_target);
+            self.start_pro_account();
+            return AccountStatus::PassedEval;
+        }
+        self.current_balance += trade.return_value;
+        AccountStatus::Active(trade.return_value)
+    }
+
+    // The PRO account starts over from the starting balance with a fresh drawdown
+    fn start_pro_account(&mut self) {
+        self.passed_eval = true;
+        self.current_balance = 0.0;
+        self.drawdown.reset(0.0);
+        self.daily_loss.set_limit(self.pro_daily_loss_limit);
+    }
+
+    pub fn passes_consistency_rule(&self) -> bool {
+        match self.consistency_fraction {
+            Some(fraction) => self.consistency.passes(fraction, self.max_winning_day_profit, self.current_balance),
+            None => true,
+        }
+    }
+
+    pub fn apply_rules(&mut self, rules: &RuleOverrides) {
+        if let Some(fraction) = rules.consistency_fraction {
+            self.consistency_fraction = Some(fraction);
+        }
+        if rules.disable_consistency {
+            self.consistency_fraction = None;
+        }
+        if let Some(window) = rules.consistency_window {
+            self.consistency.set_window(window);
+        }
+        if let Some(cap) = rules.payout_cap {
+            self.payout_cap = Some(cap);
+        }
+        if let Some(drawdown_type) = rules.drawdown_type {
+            self.drawdown.set_drawdown_type(drawdown_type);
+        }
+        if let Some(payout_cadence) = rules.payout_cadence {
+            self.payout_cadence = payout_cadence;
+        }
+        // A rule change sets the limit of both the Test and the PRO account
+        if let Some(limit) = rules.daily_loss_limit {
+            self.test_daily_loss_limit = Some(limit);
+            self.pro_daily_loss_limit = Some(limit);
+        }
+        self.daily_loss.apply_rules(rules);
+        if let Some(profitable_days) = rules.min_profitable_days {
+            self.min_profitable_days = Some(profitable_days);
+        }
+        if let Some(profit_split) = &rules.profit_split {
+            self.profit_split = profit_split.clone();
+        }
+        if let Some(days) = rules.min_days_for_payout {
+            self.min_days_for_payout = days;
+        }
+    }
+
+    pub fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        if !self.passed_eval || self.trading_days < self.min_days_for_payout || !self.passes_consistency_rule() {
+            return None;
+        }
+        let available = self.current_balance - self.payout_buffer;
+        if available <= 0.0 {
+            return None;
+        }
+        match self.payout_cap {
+            Some(cap) if cap < available => Some((cap, WithdrawalLimit::PayoutCap)),
+            _ => Some((available, WithdrawalLimit::BalanceAboveMinimum)),
+        }
+    }
+
+    pub fn make_withdrawal(&mut self, amount: f64) -> u8 {
+        self.current_balance -= amount;
+        self.start_payout_period();
+        self.payout_count += 1;
+        self.payout_count
+    }
+
+    // Counters since the last payout start over
+    fn start_payout_period(&mut self) {
+        self.max_winning_day_profit = 0.0;
+        self.consistency.record_payout();
+        self.trading_days = 0;
+    }
+
+    pub fn info(&self) -> AccountInfo {
+        let daily_loss_limit = match self.test_daily_loss_limit {
+            Some(DailyLossLimit::Amount(amount)) => format!(", Test daily loss limit {:.0}", amount),
+            Some(DailyLossLimit::Percent(percent)) => format!(", Test daily loss limit {}%", percent),
+            None => String::new(),
+        };
+        AccountInfo {
+            account_type: format!("tpt:{:?}", self.account_type),
+            display_name: format!("Take Profit Trader {:?} Test", self.account_type),
+            company: String::from("tpt"),
+            cost: self.account_type.get_cost(),
+            funded_account_cost: TakeProfitTraderAccountType::funded_acct_cost(),
+            drawdown_type: self.drawdown.drawdown_type(),
+            drawdown: self.drawdown.amount(),
+            starting_buffer: self.drawdown.buffer(self.current_balance),
+            profit_target: Some(self.profit_target),
+            payout_cap: match self.payout_cap {
+                Some(cap) => format!("balance above {:.0}, at most {:.0}", self.payout_buffer, cap),
+                None => format!("balance above {:.0}", self.payout_buffer),
+            },
+            payout_schedule: format!(
+                "PRO account only: from its first day, whenever the balance is above the {:.0} buffer{}",
+                self.payout_buffer, daily_loss_limit
+            ),
+            consistency_fraction: self.consistency_fraction,
+            consistency_window: self.consistency.window(),
+            payout_cadence: self.payout_cadence,
+            min_days_for_payout: self.min_days_for_payout,
+            qualifying_day: String::from("every trading day"),
+            min_profitable_days: self.min_profitable_days,
+            profit_split: self.profit_split.clone(),
+            scaling_plan: None,
+            difficulty: None,
+        }
+    }
+
+    pub fn try_add_trading_day(&mut self, daily_pnl: f64) {
+        if !self.passed_eval {
+            return;
+        }
+        self.trading_days += 1;
+        if daily_pnl > self.max_winning_day_profit {
+            self.max_winning_day_profit = daily_pnl;
+        }
+        self.consistency.record_day(daily_pnl);
+    }
+}
+
+impl PropAccount for TakeProfitTraderAccount {
+    fn process_trade(&mut self, trade: &Trade) -> AccountStatus {
+        let (trade, limit_hit) = self.daily_loss.before_trade(self.current_balance, trade);
+        self.drawdown.before_trade(self.current_balance, &trade);
+        let status = self.trade_on_account(&trade);
+        self.drawdown.after_trade(self.current_balance, &status);
+        self.daily_loss.after_trade(status, limit_hit)
+    }
+
+    fn update_end_of_day(&mut self, daily_pnl: f64) {
+        self.daily_loss.end_of_day();
+        self.drawdown.end_of_day(self.current_balance);
+        self.try_add_trading_day(daily_pnl);
+    }
+
+    fn allowed_withdrawal_amount(&self) -> Option<f64> {
+        self.allowed_withdrawal().map(|(amount, _)| amount)
+    }
+
+    fn allowed_withdrawal(&self) -> Option<(f64, WithdrawalLimit)> {
+        self.allowed_withdrawal()
+    }
+
+    fn make_withdrawal(&mut self, amount: f64) -> u8 {
+        self.make_withdrawal(amount)
+    }
+
+    fn deny_withdrawal(&mut self) {
+        self.start_payout_period();
+    }
+
+    fn get_current_balance(&self) -> f64 {
+        self.current_balance
+    }
+
+    fn get_drawdown_buffer(&self) -> f64 {
+        self.drawdown.buffer(self.current_balance)
+    }
+
+    fn is_funded(&self) -> bool {
+        self.passed_eval
+    }
+
+    // The payout buffer stays in the account
+    fn withdrawable_balance(&self) -> f64 {
+        if self.passed_eval {
+            (self.current_balance - self.payout_buffer).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn get_simulation_days(&self) -> u64 {
+        self.simulation_days
+    }
+
+    fn increment_simulation_day(&mut self) {
+        self.simulation_days += 1;
+    }
+
+    fn get_cost(&self) -> f64 {
+        self.account_type.get_cost()
+    }
+
+    fn get_funded_acct_cost(&self) -> f64 {
+        TakeProfitTraderAccountType::funded_acct_cost()
+    }
+
+    fn info(&self) -> AccountInfo {
+        self.info()
+    }
+
+    fn apply_rules(&mut self, rules: &RuleOverrides) {
+        self.apply_rules(rules)
+    }
+
+    // The Test account has a daily loss limit, the PRO account none
+    fn get_daily_loss_limit(&self) -> Option<DailyLossLimit> {
+        self.daily_loss.limit()
+    }
+
+    fn daily_loss_breach(&self) -> BreachSeverity {
+        self.daily_loss.breach()
+    }
+
+    fn set_account_size(&mut self, account_size: f64) {
+        self.daily_loss.set_account_size(account_size);
+    }
+
+    fn payout_cadence(&self) -> PayoutCadence {
+        self.payout_cadence
+    }
+
+    fn min_profitable_days(&self) -> Option<ProfitableDays> {
+        self.min_profitable_days
+    }
+
+    fn profit_split(&self) -> ProfitSplit {
+        self.profit_split.clone()
+    }
+}
@@ -0,0 +1,122 @@
+// src/simulator/recommend.rs
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+
+use super::formatting::Currency;
+use super::prop_account::account_catalog;
+use super::trader::EndOfGame;
+use super::{run_simulations, SimulationConfig, SimulationError, SimulationResult};
+
+/// What a plan recommendation searches and how much the trader can spend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendOptions {
+    /// Most the trader can spend per month on the account purchase and platform fee (USD)
+    pub monthly_budget: f64,
+    /// Contract counts to try; each scales the multiplier of the base config
+    pub contracts: Vec<u32>,
+    /// Only consider accounts of this company
+    pub company: Option<String>,
+    /// Number of best plans returned with their full results
+    pub top: usize,
+}
+
+impl RecommendOptions {
+    pub fn new(monthly_budget: f64) -> Self {
+        RecommendOptions {
+            monthly_budget,
+            contracts: vec![1, 2, 3],
+            company: None,
+            top: 3,
+        }
+    }
+}
+
+/// One account and contract size, with the key statistics of its simulation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Plan {
+    pub account_type: String,
+    pub contracts: u32,
+    /// Account purchase plus one month of platform fee, checked against the budget
+    pub monthly_cost: f64,
+    /// Currency of the balances and the standard deviation; the monthly cost stays in USD
+    #[serde(default)]
+    pub currency: Currency,
+    pub mean_balance: f64,
+    pub median_balance: f64,
+    pub std_dev: f64,
+    pub positive_balance_percentage: f64,
+    pub busted_percentage: f64,
+    /// Mean final balance per unit of standard deviation (floored at 1), the ranking key
+    pub risk_adjusted_ev: f64,
+    /// Full statistics, for the top plans only
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub result: Option<SimulationResult>,
+}
+
+/// Simulate every affordable combination of account type and contract count on the
+/// trades of `base` and rank them by risk-adjusted EV, best first. All plans share the
+/// base config's seed, so they are compared on the same random draws.
+pub fn recommend_plans(base: &SimulationConfig, options: &RecommendOptions) -> Result<Vec<Plan>, Box<dyn Error>> {
+    if options.contracts.is_empty() || options.contracts.contains(&0) {
+        return Err(SimulationError::InvalidConfig("contracts must be a non-empty list of positive counts".into()).into());
+    }
+    let platform_fee = base.platform_fee_monthly.unwrap_or(0.0);
+    let accounts: Vec<(String, f64)> = account_catalog(options.company.as_deref())
+        .into_iter()
+        .map(|info| (info.account_type, info.cost + platform_fee))
+        .filter(|(_, monthly_cost)| *monthly_cost <= options.monthly_budget)
+        .collect();
+    if accounts.is_empty() {
+        return Err(SimulationError::InvalidConfig(format!(
+            "no account fits a monthly budget of {:.2}",
+            options.monthly_budget
+        ))
+        .into());
+    }
+
+    let candidates: Vec<(String, f64, u32)> = accounts
+        .iter()
+        .flat_map(|(account_type, monthly_cost)| {
+            options.contracts.iter().map(move |&contracts| (account_type.clone(), *monthly_cost, contracts))
+        })
+        .collect();
+    let configs = candidates
+        .iter()
+        .map(|(account_type, _, contracts)| {
+            let mut config = base.clone();
+            config.account_type = account_type.clone();
+            config.custom_account = None;
+            config.multiplier = base.multiplier * *contracts as f64;
+            // Plans beyond the account's scaling plan trade fewer contracts than planned
+            config.traded_contracts = Some(base.traded_contracts.unwrap_or(1) * contracts);
+            // A purchase price belongs to one particular account
+            config.purchase_price = None;
+            config.histogram = false;
+            config
+        })
+        .collect();
+    let results = run_simulations(configs)?;
+
+    let mut plans: Vec<Plan> = candidates
+        .into_iter()
+        .zip(results)
+        .map(|((account_type, monthly_cost, contracts), result)| Plan {
+            account_type,
+            contracts,
+            monthly_cost,
+            currency: result.currency,
+            mean_balance: result.mean_balance,
+            median_balance: result.median_balance,
+            std_dev: result.std_dev,
+            positive_balance_percentage: result.positive_balance_percentage,
+            busted_percentage: result.end_state_percentages.get(&EndOfGame::Busted).copied().unwrap_or(0.0),
+            risk_adjusted_ev: result.mean_balance / result.std_dev.max(1.0),
+            result: Some(result),
+        })
+        .collect();
+    plans.sort_by(|a, b| b.risk_adjusted_ev.total_cmp(&a.risk_adjusted_ev));
+    for plan in plans.iter_mut().skip(options.top) {
+        plan.result = None;
+    }
+    Ok(plans)
+}
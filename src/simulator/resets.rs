@@ -0,0 +1,32 @@
+// src/simulator/resets.rs
+use serde::{Serialize, Deserialize};
+
+/// How often runs bought a new evaluation after a bust, over all runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResetReport {
+    /// Resets allowed per run
+    pub resets_allowed: u8,
+    /// Runs that reset at least once
+    pub reset_percentage: f64,
+    pub mean_resets: f64,
+    /// Runs that busted with every reset used
+    pub exhausted_percentage: f64,
+    /// Mean evaluations bought per run, the first one included
+    pub mean_evaluations: f64,
+}
+
+/// Build the report from `(resets used, busted)` of each run
+pub fn reset_report(resets_allowed: u8, runs: &[(u32, bool)]) -> ResetReport {
+    let n = runs.len().max(1) as f64;
+    let percentage = |count: usize| count as f64 / n * 100.0;
+    let mean_resets = runs.iter().map(|(resets, _)| *resets as f64).sum::<f64>() / n;
+    ResetReport {
+        resets_allowed,
+        reset_percentage: percentage(runs.iter().filter(|(resets, _)| *resets > 0).count()),
+        mean_resets,
+        exhausted_percentage: percentage(
+            runs.iter().filter(|(resets, busted)| *busted && *resets >= resets_allowed as u32).count(),
+        ),
+        mean_evaluations: 1.0 + mean_resets,
+    }
+}
@@ -0,0 +1,140 @@
+// src/simulator/reweight.rs
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Trades grouped by their outcome
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeBucket {
+    /// The best this percent of trades by return, e.g. 1 for the top 1% of winners
+    TopPercent(f64),
+    /// The worst this percent of trades by return
+    BottomPercent(f64),
+    /// Trades returning between `min` (inclusive) and `max` (exclusive) R, one R being the
+    /// mean loss of the losing trades
+    RMultiple {
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+}
+
+impl fmt::Display for OutcomeBucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutcomeBucket::TopPercent(percent) => write!(f, "top {}%", percent),
+            OutcomeBucket::BottomPercent(percent) => write!(f, "bottom {}%", percent),
+            OutcomeBucket::RMultiple { min: Some(min), max: Some(max) } => write!(f, "{}R to {}R", min, max),
+            OutcomeBucket::RMultiple { min: Some(min), max: None } => write!(f, "{}R and up", min),
+            OutcomeBucket::RMultiple { min: None, max: Some(max) } => write!(f, "below {}R", max),
+            OutcomeBucket::RMultiple { min: None, max: None } => write!(f, "all trades"),
+        }
+    }
+}
+
+/// Multiply the chance of resampling the trades of `bucket` by `weight`, e.g. 0.5 to
+/// halve it or 0 to drop the trades. Trades in several buckets get the product.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeReweight {
+    pub bucket: OutcomeBucket,
+    pub weight: f64,
+}
+
+impl FromStr for OutcomeReweight {
+    type Err = String;
+
+    /// `top1%=0.5`, `bottom5%=2`, `r3..=0` or `r..-2=1.5`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid reweighting {} (e.g. top1%=0.5, bottom5%=2, r3..=0, r..-2=1.5)", s.trim());
+        let (bucket, weight) = s.trim().split_once('=').ok_or_else(invalid)?;
+        let weight = weight.trim().parse().map_err(|_| invalid())?;
+        let bucket = bucket.trim().to_lowercase();
+        let percent = |rest: &str| rest.trim().trim_end_matches('%').trim().parse::<f64>().map_err(|_| invalid());
+        let bucket = if let Some(rest) = bucket.strip_prefix("top") {
+            OutcomeBucket::TopPercent(percent(rest)?)
+        } else if let Some(rest) = bucket.strip_prefix("bottom") {
+            OutcomeBucket::BottomPercent(percent(rest)?)
+        } else if let Some(rest) = bucket.strip_prefix('r') {
+            let (min, max) = rest.split_once("..").ok_or_else(invalid)?;
+            let bound = |r: &str| match r.trim() {
+                "" => Ok(None),
+                r => r.parse().map(Some).map_err(|_| invalid()),
+            };
+            OutcomeBucket::RMultiple { min: bound(min)?, max: bound(max)? }
+        } else {
+            return Err(invalid());
+        };
+        Ok(OutcomeReweight { bucket, weight })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReweightedBucket {
+    pub bucket: OutcomeBucket,
+    pub weight: f64,
+    /// Trades of the pool in the bucket
+    pub trades: usize,
+}
+
+/// Effect of `outcome_reweighting` on the resampled trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReweightReport {
+    pub buckets: Vec<ReweightedBucket>,
+    /// Mean loss of the losing trades, the R of the R-multiple buckets
+    pub r_unit: Option<f64>,
+    /// Mean return per resampled trade, before and after reweighting
+    pub mean_return: f64,
+    pub weighted_mean_return: f64,
+}
+
+/// Sampling weight of every trade, given its return, and the report of what they do
+pub fn trade_weights(returns: &[f64], reweights: &[OutcomeReweight]) -> Result<(Vec<f64>, ReweightReport), String> {
+    let losses: Vec<f64> = returns.iter().copied().filter(|&r| r < 0.0).collect();
+    let r_unit = (!losses.is_empty()).then(|| -losses.iter().sum::<f64>() / losses.len() as f64);
+    // Trades from best to worst return, ties in pool order
+    let mut ranked: Vec<usize> = (0..returns.len()).collect();
+    ranked.sort_by(|&a, &b| returns[b].total_cmp(&returns[a]));
+    let ranked_count = |percent: f64| (returns.len() as f64 * percent / 100.0).ceil() as usize;
+
+    let mut weights = vec![1.0; returns.len()];
+    let mut buckets = Vec::with_capacity(reweights.len());
+    for reweight in reweights {
+        if !(reweight.weight >= 0.0 && reweight.weight.is_finite()) {
+            return Err(format!("outcome_reweighting: the weight of {} must not be negative", reweight.bucket));
+        }
+        let members: Vec<usize> = match reweight.bucket {
+            OutcomeBucket::TopPercent(percent) | OutcomeBucket::BottomPercent(percent) if !(percent > 0.0 && percent <= 100.0) => {
+                return Err(format!("outcome_reweighting: {} must be a percentage between 0 and 100", reweight.bucket));
+            }
+            OutcomeBucket::TopPercent(percent) => ranked[..ranked_count(percent)].to_vec(),
+            OutcomeBucket::BottomPercent(percent) => ranked[ranked.len() - ranked_count(percent)..].to_vec(),
+            OutcomeBucket::RMultiple { min, max } => {
+                let r_unit = r_unit.ok_or("outcome_reweighting: R-multiple buckets need losing trades to measure R")?;
+                (0..returns.len())
+                    .filter(|&i| {
+                        let r = returns[i] / r_unit;
+                        min.is_none_or(|min| r >= min) && max.is_none_or(|max| r < max)
+                    })
+                    .collect()
+            }
+        };
+        for &i in &members {
+            weights[i] *= reweight.weight;
+        }
+        buckets.push(ReweightedBucket { bucket: reweight.bucket, weight: reweight.weight, trades: members.len() });
+    }
+
+    let total_weight: f64 = weights.iter().sum();
+    if total_weight <= 0.0 {
+        return Err("outcome_reweighting leaves no trades to sample".into());
+    }
+    let report = ReweightReport {
+        buckets,
+        r_unit,
+        mean_return: returns.iter().sum::<f64>() / returns.len().max(1) as f64,
+        weighted_mean_return: returns.iter().zip(&weights).map(|(r, w)| r * w).sum::<f64>() / total_weight,
+    };
+    Ok((weights, report))
+}
@@ -0,0 +1,117 @@
+// src/simulator/rng.rs
+use rand::rngs::{SmallRng, StdRng, ThreadRng};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+/// Random number generator the simulation draws from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RngBackend {
+    /// rand's thread-local generator; fast to set up but cannot be seeded
+    Thread,
+    /// rand's standard generator (ChaCha12)
+    #[default]
+    Std,
+    /// Small, fast non-cryptographic generator (Xoshiro256++)
+    Small,
+    /// ChaCha20, for cryptographic-quality streams
+    Chacha,
+}
+
+impl RngBackend {
+    pub const ALL: [RngBackend; 4] = [RngBackend::Thread, RngBackend::Std, RngBackend::Small, RngBackend::Chacha];
+
+    pub fn seedable(&self) -> bool {
+        *self != RngBackend::Thread
+    }
+}
+
+impl FromStr for RngBackend {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "thread" => Ok(RngBackend::Thread),
+            "std" => Ok(RngBackend::Std),
+            "small" => Ok(RngBackend::Small),
+            "chacha" | "chacha20" => Ok(RngBackend::Chacha),
+            _ => Err("Unknown RNG backend (options: thread, std, small, chacha)"),
+        }
+    }
+}
+
+/// Independent random streams drawn from one seed
+#[derive(Clone, Copy)]
+pub(crate) enum RngStream {
+    TradeGeneration = 0,
+    MonteCarlo = 1,
+    Cohort = 2,
+    ParallelEvals = 3,
+    Hedge = 4,
+    CopyTrading = 5,
+}
+
+/// Generator of the configured backend
+pub enum SimulationRng {
+    Thread(ThreadRng),
+    Std(StdRng),
+    Small(SmallRng),
+    Chacha(ChaCha20Rng),
+}
+
+impl SimulationRng {
+    /// Generator for one iteration. With a seed every iteration gets its own deterministic
+    /// stream, so results do not depend on how rayon schedules the iterations.
+    pub(crate) fn new(backend: RngBackend, seed: Option<u64>, stream: RngStream, iteration: usize) -> Self {
+        // Mix before offsetting so that neighbouring seeds do not share iterations
+        let seed = seed.map(|seed| mix64(mix64(seed) ^ stream as u64).wrapping_add(iteration as u64));
+        match (backend, seed) {
+            (RngBackend::Thread, _) => SimulationRng::Thread(rand::thread_rng()),
+            (RngBackend::Std, Some(seed)) => SimulationRng::Std(StdRng::seed_from_u64(seed)),
+            (RngBackend::Std, None) => SimulationRng::Std(StdRng::from_entropy()),
+            (RngBackend::Small, Some(seed)) => SimulationRng::Small(SmallRng::seed_from_u64(seed)),
+            (RngBackend::Small, None) => SimulationRng::Small(SmallRng::from_entropy()),
+            (RngBackend::Chacha, Some(seed)) => SimulationRng::Chacha(ChaCha20Rng::seed_from_u64(seed)),
+            (RngBackend::Chacha, None) => SimulationRng::Chacha(ChaCha20Rng::from_entropy()),
+        }
+    }
+}
+
+macro_rules! dispatch {
+    ($self:ident, $rng:ident => $call:expr) => {
+        match $self {
+            SimulationRng::Thread($rng) => $call,
+            SimulationRng::Std($rng) => $call,
+            SimulationRng::Small($rng) => $call,
+            SimulationRng::Chacha($rng) => $call,
+        }
+    };
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        dispatch!(self, rng => rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        dispatch!(self, rng => rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dispatch!(self, rng => rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        dispatch!(self, rng => rng.try_fill_bytes(dest))
+    }
+}
+
+// SplitMix64 finalizer
+fn mix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
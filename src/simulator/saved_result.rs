@@ -0,0 +1,154 @@
+// src/simulator/saved_result.rs
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+use super::{EndOfGame, SimulationConfig, SimulationError, SimulationResult};
+
+// z-score for a two-sided 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// A simulation result persisted to disk together with the config that produced it
+/// and the per-iteration final balances, so later runs can be compared statistically.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedResult {
+    pub crate_version: String,
+    pub config: Option<SimulationConfig>,
+    pub result: SimulationResult,
+    #[serde(default)]
+    pub final_balances: Vec<f64>,
+}
+
+impl SavedResult {
+    pub fn new(config: SimulationConfig, result: SimulationResult) -> Self {
+        let final_balances = result.final_balances.clone();
+        SavedResult {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            config: Some(config),
+            result,
+            final_balances,
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), SimulationError> {
+        let json = serde_json::to_string(self).map_err(|e| SimulationError::Data(e.to_string()))?;
+        fs::write(path, json).map_err(|e| SimulationError::Data(format!("{}: {}", path, e)))
+    }
+
+    /// Load a saved result. Plain `SimulationResult` JSON (e.g. from `--output json`) is also
+    /// accepted, but without per-iteration data no confidence intervals can be computed.
+    pub fn load(path: &str) -> Result<Self, SimulationError> {
+        let contents = fs::read_to_string(path).map_err(|e| SimulationError::Data(format!("{}: {}", path, e)))?;
+        if let Ok(saved) = serde_json::from_str::<SavedResult>(&contents) {
+            return Ok(saved);
+        }
+        let result: SimulationResult = serde_json::from_str(&contents)
+            .map_err(|e| SimulationError::Data(format!("{}: not a saved simulation result ({})", path, e)))?;
+        Ok(SavedResult {
+            crate_version: String::from("unknown"),
+            config: None,
+            result,
+            final_balances: Vec::new(),
+        })
+    }
+
+    // Number of iterations the end state percentages are based on
+    fn iterations(&self) -> Option<usize> {
+        self.config.as_ref().map(|c| c.iterations)
+    }
+
+    /// Compute the differences `other - self` with 95% confidence intervals where the data allows
+    pub fn diff(&self, other: &SavedResult) -> ResultDiff {
+        let (a, b) = (&self.result, &other.result);
+
+        let mean_se = match (sample_variance(&self.final_balances), sample_variance(&other.final_balances)) {
+            (Some(var_a), Some(var_b)) => {
+                Some((var_a / self.final_balances.len() as f64 + var_b / other.final_balances.len() as f64).sqrt())
+            }
+            _ => None,
+        };
+
+        let n_positive = (non_zero(self.final_balances.len()), non_zero(other.final_balances.len()));
+        let positive_se = match n_positive {
+            (Some(n_a), Some(n_b)) => Some(proportion_se(a.positive_balance_percentage, n_a, b.positive_balance_percentage, n_b)),
+            _ => None,
+        };
+
+        let mut end_states = BTreeMap::new();
+        for end_state in EndOfGame::ALL {
+            let pct_a = a.end_state_percentages.get(&end_state).copied().unwrap_or(0.0);
+            let pct_b = b.end_state_percentages.get(&end_state).copied().unwrap_or(0.0);
+            let se = match (self.iterations(), other.iterations()) {
+                (Some(n_a), Some(n_b)) => Some(proportion_se(pct_a, n_a, pct_b, n_b)),
+                _ => None,
+            };
+            end_states.insert(format!("{:?}", end_state), StatDiff::new(pct_a, pct_b, se));
+        }
+
+        ResultDiff {
+            mean_balance: StatDiff::new(a.mean_balance, b.mean_balance, mean_se),
+            median_balance: StatDiff::new(a.median_balance, b.median_balance, None),
+            std_dev: StatDiff::new(a.std_dev, b.std_dev, None),
+            positive_balance_percentage: StatDiff::new(a.positive_balance_percentage, b.positive_balance_percentage, positive_se),
+            mean_days: StatDiff::new(a.mean_days, b.mean_days, None),
+            end_state_percentages: end_states,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatDiff {
+    pub baseline: f64,
+    pub candidate: f64,
+    pub delta: f64,
+    /// 95% confidence interval of the delta, when per-iteration data is available
+    pub ci95: Option<(f64, f64)>,
+}
+
+impl StatDiff {
+    fn new(baseline: f64, candidate: f64, standard_error: Option<f64>) -> Self {
+        let delta = candidate - baseline;
+        StatDiff {
+            baseline,
+            candidate,
+            delta,
+            ci95: standard_error.map(|se| (delta - Z_95 * se, delta + Z_95 * se)),
+        }
+    }
+
+    /// True when the confidence interval excludes zero
+    pub fn is_significant(&self) -> bool {
+        match self.ci95 {
+            Some((low, high)) => low > 0.0 || high < 0.0,
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResultDiff {
+    pub mean_balance: StatDiff,
+    pub median_balance: StatDiff,
+    pub std_dev: StatDiff,
+    pub positive_balance_percentage: StatDiff,
+    pub mean_days: StatDiff,
+    pub end_state_percentages: BTreeMap<String, StatDiff>,
+}
+
+fn non_zero(n: usize) -> Option<usize> {
+    if n > 0 { Some(n) } else { None }
+}
+
+fn sample_variance(data: &[f64]) -> Option<f64> {
+    if data.len() < 2 {
+        return None;
+    }
+    let mean = data.iter().sum::<f64>() / data.len() as f64;
+    Some(data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (data.len() - 1) as f64)
+}
+
+// Standard error (in percentage points) of the difference of two percentages
+fn proportion_se(pct_a: f64, n_a: usize, pct_b: f64, n_b: usize) -> f64 {
+    let (p_a, p_b) = (pct_a / 100.0, pct_b / 100.0);
+    100.0 * (p_a * (1.0 - p_a) / n_a as f64 + p_b * (1.0 - p_b) / n_b as f64).sqrt()
+}
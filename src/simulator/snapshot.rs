@@ -0,0 +1,68 @@
+// src/simulator/snapshot.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats;
+use super::trader::{EndOfGame, Trader};
+
+/// State of one run after a given number of trading days
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    pub end_state: Option<EndOfGame>, // `None` while the run is still going
+    pub funded: bool,
+    pub account_balance: f64,
+    pub bank_balance: f64,
+}
+
+impl AccountSnapshot {
+    pub fn capture(trader: &Trader, end_state: Option<EndOfGame>) -> Self {
+        AccountSnapshot {
+            end_state,
+            funded: trader.prop_account.is_funded(),
+            account_balance: trader.prop_account.get_current_balance(),
+            bank_balance: trader.bank_account.balance,
+        }
+    }
+}
+
+/// Where all runs stand after `day` trading days ("where will I likely be after one month")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaySnapshot {
+    pub day: u64,
+    /// Runs still in the evaluation phase
+    pub evaluation_percentage: f64,
+    /// Runs still trading a funded account
+    pub funded_percentage: f64,
+    /// Runs that had already ended, by end state
+    pub busted_percentage: f64,
+    pub max_payouts_percentage: f64,
+    pub timeout_percentage: f64,
+    /// Median prop account balance of the runs still going
+    pub median_account_balance: Option<f64>,
+    pub median_bank_balance: f64,
+    pub mean_bank_balance: f64,
+    pub positive_bank_percentage: f64,
+}
+
+/// Aggregate the snapshots all runs took at `day`
+pub fn day_snapshot(day: u64, snapshots: &[&AccountSnapshot]) -> DaySnapshot {
+    let runs = snapshots.len().max(1) as f64;
+    let percentage = |count: usize| count as f64 / runs * 100.0;
+    let count = |state: Option<EndOfGame>| snapshots.iter().filter(|s| s.end_state == state).count();
+
+    let active: Vec<&&AccountSnapshot> = snapshots.iter().filter(|s| s.end_state.is_none()).collect();
+    let account_balances: Vec<f64> = active.iter().map(|s| s.account_balance).collect();
+    let bank_balances: Vec<f64> = snapshots.iter().map(|s| s.bank_balance).collect();
+
+    DaySnapshot {
+        day,
+        evaluation_percentage: percentage(active.iter().filter(|s| !s.funded).count()),
+        funded_percentage: percentage(active.iter().filter(|s| s.funded).count()),
+        busted_percentage: percentage(count(Some(EndOfGame::Busted))),
+        max_payouts_percentage: percentage(count(Some(EndOfGame::MaxPayouts))),
+        timeout_percentage: percentage(count(Some(EndOfGame::TimeOut))),
+        median_account_balance: stats::median(&stats::sorted(&account_balances)),
+        mean_bank_balance: bank_balances.iter().sum::<f64>() / runs,
+        positive_bank_percentage: percentage(bank_balances.iter().filter(|&&b| b > 0.0).count()),
+        median_bank_balance: stats::median(&stats::sorted(&bank_balances)).unwrap_or(0.0),
+    }
+}
@@ -0,0 +1,335 @@
+// src/simulator/statement.rs
+use chrono::NaiveDate;
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::calendar;
+use super::formatting::{Currency, MoneyFormatter};
+use super::ledger::Ledger;
+use super::rng::{RngStream, SimulationRng};
+use super::trade_data::BlockCursor;
+use super::trader::{EndOfGame, Trader};
+use super::{config_account_factory, currency_conversion, load_trades, new_trader, simulate_day, simulation_limits, SimulationConfig, SimulationError};
+
+/// Which run of a simulation a statement is written for
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementPick {
+    /// The iteration with this index
+    Iteration(usize),
+    /// The run with the median final bank balance
+    Typical,
+    /// The run at the 10th percentile of the final bank balances
+    Unlucky,
+    /// The run at the 90th percentile of the final bank balances
+    Lucky,
+}
+
+impl StatementPick {
+    /// Iteration this picks among the final bank balances of a simulation, indexed by iteration
+    pub fn select(&self, final_balances: &[f64]) -> Option<usize> {
+        let quantile = match *self {
+            StatementPick::Iteration(iteration) => return (iteration < final_balances.len()).then_some(iteration),
+            StatementPick::Typical => 0.5,
+            StatementPick::Unlucky => 0.1,
+            StatementPick::Lucky => 0.9,
+        };
+        if final_balances.is_empty() {
+            return None;
+        }
+        let mut ranked: Vec<usize> = (0..final_balances.len()).collect();
+        ranked.sort_by(|&a, &b| final_balances[a].partial_cmp(&final_balances[b]).unwrap());
+        Some(ranked[((ranked.len() - 1) as f64 * quantile).round() as usize])
+    }
+}
+
+impl FromStr for StatementPick {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "typical" | "median" => Ok(StatementPick::Typical),
+            "unlucky" => Ok(StatementPick::Unlucky),
+            "lucky" => Ok(StatementPick::Lucky),
+            other => other.parse().map(StatementPick::Iteration).map_err(|_| {
+                format!("Unknown statement run {} (options: an iteration number, typical, unlucky, lucky)", s.trim())
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountPhase {
+    Evaluation,
+    Funded,
+}
+
+/// One line of an account statement. Account values are in USD; fees, payouts and the
+/// bank balance in the result currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementDay {
+    /// Trading day of the run, day 0 being the account purchase
+    pub day: u64,
+    /// Calendar date of the day, with `purchase_date`
+    pub date: Option<NaiveDate>,
+    /// Phase of the account at the end of the day
+    pub phase: AccountPhase,
+    /// Trading P&L of the day
+    pub pnl: f64,
+    /// Prop account balance at the end of the day, counted from `account_size`
+    pub balance: f64,
+    /// Balance at which the account is lost
+    pub drawdown_level: f64,
+    /// Fees paid and profit split retained by the firm
+    pub fees: f64,
+    /// Gross payouts received
+    pub payout: f64,
+    pub bank_balance: f64,
+    /// What happened on the day, e.g. "Payout" or "Passed evaluation"
+    pub events: String,
+}
+
+/// Day by day statement of one run, like the account dashboard of a firm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountStatement {
+    pub iteration: usize,
+    pub account_type: String,
+    pub account_name: String,
+    pub currency: Currency,
+    pub end_state: EndOfGame,
+    pub days: Vec<StatementDay>,
+}
+
+// Statements show amounts to the cent
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+// Everything the firm and third parties took so far
+fn fees(ledger: &Ledger) -> f64 {
+    ledger.eval_fees
+        + ledger.reset_fees
+        + ledger.activation_fees
+        + ledger.platform_fees
+        + ledger.insurance_fees
+        + ledger.payout_fees
+        + ledger.conversion_fees
+        + ledger.profit_split
+}
+
+fn payouts(ledger: &Ledger) -> f64 {
+    ledger.gross_payouts + ledger.terminal_payouts
+}
+
+// Running totals of a run, compared before and after each day
+struct Totals {
+    traded: f64,
+    fees: f64,
+    payouts: f64,
+    funded: bool,
+    reset_fees: f64,
+    extensions: u32,
+    expiries: u32,
+    replacements: u32,
+}
+
+impl Totals {
+    fn of(trader: &Trader) -> Self {
+        Totals {
+            traded: trader.weekly_pnl.iter().sum(),
+            fees: fees(&trader.ledger),
+            payouts: payouts(&trader.ledger),
+            funded: trader.prop_account.is_funded(),
+            reset_fees: trader.ledger.reset_fees,
+            extensions: trader.eval_extensions,
+            expiries: trader.eval_expiries,
+            replacements: trader.replacements_used,
+        }
+    }
+
+    // Events between `self` and the later `after`
+    fn events(&self, after: &Totals) -> Vec<&'static str> {
+        let mut events = Vec::new();
+        if !self.funded && after.funded {
+            events.push("Passed evaluation");
+        }
+        if after.payouts > self.payouts {
+            events.push("Payout");
+        }
+        if after.extensions > self.extensions {
+            events.push("Extension");
+        }
+        if after.expiries > self.expiries {
+            events.push("Evaluation expired");
+        }
+        if after.reset_fees > self.reset_fees {
+            events.push("Reset");
+        }
+        if after.replacements > self.replacements {
+            events.push("Replacement account");
+        }
+        events
+    }
+}
+
+/// Replay iteration `iteration` of `config` day by day. With a seed this is the same run as
+/// that iteration of `run_simulation`; without one it is a fresh random run.
+pub fn account_statement(config: &SimulationConfig, iteration: usize) -> Result<AccountStatement, Box<dyn Error>> {
+    simulation_limits().check_config(config)?;
+    if iteration >= config.iterations {
+        return Err(SimulationError::InvalidConfig(format!(
+            "statement iteration {} is out of range ({} iterations)",
+            iteration, config.iterations
+        ))
+        .into());
+    }
+    let trades = load_trades(config)?;
+    // The same pool the run samples from
+    let trades = trades.without_burn_in(config.burn_in_trades.unwrap_or(0), config.burn_in_days.unwrap_or(0))?;
+    if trades.is_empty() {
+        return Err(SimulationError::Data("The burn-in period excludes all trades".into()).into());
+    }
+    let account_factory = config_account_factory(config)?;
+    let currency_conversion = currency_conversion(config)?;
+
+    let mut rng = SimulationRng::new(config.rng_backend, config.seed, RngStream::MonteCarlo, iteration);
+    let mut trader = new_trader(&account_factory, currency_conversion.as_ref(), config, 0, config.max_simulation_days);
+    let account_size = config.account_size.unwrap_or(0.0);
+    let info = trader.prop_account.info();
+    let line = |trader: &Trader, day: u64, before: Option<&Totals>, end_state: Option<&EndOfGame>| {
+        let after = Totals::of(trader);
+        let mut events = match before {
+            Some(before) => before.events(&after),
+            None => vec!["Account purchased"],
+        };
+        let end_state = end_state.map(|state| format!("{:?}", state));
+        events.extend(end_state.as_deref());
+        let balance = account_size + trader.prop_account.get_current_balance();
+        StatementDay {
+            day,
+            date: config.purchase_date.map(|date| if day == 0 { date } else { calendar::trading_date(date, day - 1) }),
+            phase: if after.funded { AccountPhase::Funded } else { AccountPhase::Evaluation },
+            pnl: cents(before.map_or(0.0, |before| after.traded - before.traded)),
+            balance: cents(balance),
+            drawdown_level: cents(balance - trader.prop_account.get_drawdown_buffer()),
+            fees: cents(after.fees - before.map_or(0.0, |before| before.fees)),
+            payout: cents(after.payouts - before.map_or(0.0, |before| before.payouts)),
+            bank_balance: cents(trader.bank_account.balance),
+            events: events.join("; "),
+        }
+    };
+
+    let mut days = vec![line(&trader, 0, None, None)];
+    let mut block = BlockCursor::default();
+    let end_state = loop {
+        let before = Totals::of(&trader);
+        let end_state = simulate_day(&mut trader, &trades, config, &mut block, &mut rng);
+        days.push(line(&trader, days.len() as u64, Some(&before), end_state.as_ref()));
+        if let Some(end_state) = end_state {
+            break end_state;
+        }
+    };
+
+    Ok(AccountStatement {
+        iteration,
+        account_type: info.account_type,
+        account_name: info.display_name,
+        currency: config.currency.filter(|_| currency_conversion.is_some()).unwrap_or_default(),
+        end_state,
+        days,
+    })
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl AccountStatement {
+    pub fn to_csv(&self) -> Result<String, Box<dyn Error>> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for day in &self.days {
+            writer.serialize(day)?;
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// Self-contained HTML page of the statement, with a summary above the daily lines
+    pub fn to_html(&self) -> String {
+        let usd = MoneyFormatter::default();
+        let money = usd.for_currency(self.currency);
+        let last = self.days.last();
+        let total = |value: fn(&StatementDay) -> f64| self.days.iter().map(value).sum::<f64>();
+        let signed = |formatted: String, value: f64| {
+            let class = if value < 0.0 { "loss" } else if value > 0.0 { "gain" } else { "" };
+            format!("<td class=\"{}\">{}</td>", class, formatted)
+        };
+
+        let mut html = String::new();
+        let title = format!("{} - iteration {}", escape_html(&self.account_name), self.iteration);
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Account Statement: {}</title>\n<style>\n\
+             body {{ font-family: system-ui, sans-serif; margin: 2em; color: #1f2937; }}\n\
+             .summary {{ display: flex; gap: 2em; margin-bottom: 1.5em; }}\n\
+             .summary div {{ background: #f3f4f6; border-radius: 6px; padding: 0.75em 1.25em; }}\n\
+             .summary span {{ display: block; font-size: 0.8em; color: #6b7280; }}\n\
+             table {{ border-collapse: collapse; width: 100%; font-size: 0.9em; }}\n\
+             th, td {{ padding: 0.35em 0.75em; border-bottom: 1px solid #e5e7eb; text-align: right; }}\n\
+             th {{ background: #111827; color: #f9fafb; position: sticky; top: 0; }}\n\
+             td.text {{ text-align: left; }}\n\
+             .gain {{ color: #047857; }}\n.loss {{ color: #b91c1c; }}\n\
+             </style>\n</head>\n<body>\n<h1>Account Statement</h1>\n<p>{} ({}), ended {:?} after {} trading days</p>\n",
+            title,
+            title,
+            escape_html(&self.account_type),
+            self.end_state,
+            self.days.len().saturating_sub(1)
+        );
+        let _ = write!(
+            html,
+            "<div class=\"summary\">\n<div><span>Trading P&amp;L</span>{}</div>\n<div><span>Payouts</span>{}</div>\n\
+             <div><span>Fees</span>{}</div>\n<div><span>Bank Balance</span>{}</div>\n</div>\n",
+            usd.format(total(|day| day.pnl)),
+            money.format(total(|day| day.payout)),
+            money.format(total(|day| day.fees)),
+            money.format(last.map_or(0.0, |day| day.bank_balance))
+        );
+        html.push_str(
+            "<table>\n<tr><th>Day</th><th>Date</th><th>Phase</th><th>P&amp;L</th><th>Balance</th><th>Drawdown Level</th>\
+             <th>Fees</th><th>Payout</th><th>Bank Balance</th><th>Events</th></tr>\n",
+        );
+        for day in &self.days {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"text\">{:?}</td>{}<td>{}</td><td>{}</td><td>{}</td><td>{}</td>{}<td class=\"text\">{}</td></tr>",
+                day.day,
+                day.date.map(|date| date.to_string()).unwrap_or_default(),
+                day.phase,
+                signed(usd.format(day.pnl), day.pnl),
+                usd.format(day.balance),
+                usd.format(day.drawdown_level),
+                money.format(day.fees),
+                money.format(day.payout),
+                signed(money.format(day.bank_balance), day.bank_balance),
+                day.events
+            );
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    /// Write the statement as HTML when `path` ends in .html or .htm, as CSV otherwise
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let html = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+        let contents = if html { self.to_html() } else { self.to_csv()? };
+        fs::write(path, contents).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+// src/simulator/stats.rs
+//! Estimators behind the statistics of a `SimulationResult`, public so that tools working
+//! on exported per-iteration data compute the same figures. NaN values are left out, and
+//! every estimator returns `None` when no value remains. Functions taking `sorted` data
+//! expect it as returned by `sorted`: ascending, without NaN.
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Serialize, Deserialize};
+use std::borrow::Cow;
+
+/// The values of `data` other than NaN, in ascending order
+pub fn sorted(data: &[f64]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = data.iter().copied().filter(|value| !value.is_nan()).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+pub fn mean(data: &[f64]) -> Option<f64> {
+    let (sum, count) = data
+        .iter()
+        .filter(|value| !value.is_nan())
+        .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Population standard deviation
+pub fn std_dev(data: &[f64]) -> Option<f64> {
+    let mean = mean(data)?;
+    mean_of(data, |value| (value - mean).powi(2)).map(f64::sqrt)
+}
+
+/// Mean absolute deviation from the mean
+pub fn mean_absolute_deviation(data: &[f64]) -> Option<f64> {
+    let mean = mean(data)?;
+    mean_of(data, |value| (value - mean).abs())
+}
+
+fn mean_of(data: &[f64], f: impl Fn(f64) -> f64) -> Option<f64> {
+    let values: Vec<f64> = data.iter().copied().filter(|value| !value.is_nan()).map(f).collect();
+    mean(&values)
+}
+
+/// The middle value, or the mean of the two middle values
+pub fn median(sorted: &[f64]) -> Option<f64> {
+    let n = sorted.len();
+    (n > 0).then(|| (sorted[(n - 1) / 2] + sorted[n / 2]) / 2.0)
+}
+
+/// Value at percentile `p` (0-100): the value below which `p` percent of the data lies,
+/// without interpolation
+pub fn percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    let n = sorted.len();
+    (n > 0).then(|| sorted[((p / 100.0 * n as f64).max(0.0) as usize).min(n - 1)])
+}
+
+/// Interquartile range, between the 25th and 75th percentile
+pub fn iqr(sorted: &[f64]) -> Option<f64> {
+    Some(percentile(sorted, 75.0)? - percentile(sorted, 25.0)?)
+}
+
+/// Median absolute deviation from the median
+pub fn median_absolute_deviation(sorted: &[f64]) -> Option<f64> {
+    let median = median(sorted)?;
+    let deviations: Vec<f64> = sorted.iter().map(|value| (value - median).abs()).collect();
+    self::median(&self::sorted(&deviations))
+}
+
+/// The location and spread statistics a simulation reports of its final balances
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    /// Mean absolute deviation from the mean
+    pub mad: f64,
+    pub iqr: f64,
+    /// Median absolute deviation from the median
+    pub mad_median: f64,
+}
+
+impl Summary {
+    pub fn of(data: &[f64]) -> Option<Summary> {
+        Summary::of_sorted(&sorted(data))
+    }
+
+    /// Summary of data already `sorted`, which also fixes the order of summation: the
+    /// result does not depend on the order the values came in
+    pub fn of_sorted(sorted: &[f64]) -> Option<Summary> {
+        Some(Summary {
+            count: sorted.len(),
+            mean: mean(sorted)?,
+            median: median(sorted)?,
+            std_dev: std_dev(sorted)?,
+            mad: mean_absolute_deviation(sorted)?,
+            iqr: iqr(sorted)?,
+            mad_median: median_absolute_deviation(sorted)?,
+        })
+    }
+}
+
+/// Running count, mean and variance of a stream of values (Welford's algorithm), in
+/// constant memory. Moments of parts of the data merge into those of all of it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunningMoments {
+    count: usize,
+    mean: f64,
+    // Sum of squared deviations from the mean
+    m2: f64,
+}
+
+impl RunningMoments {
+    /// Values added, NaN excluded
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Add all values of `other`
+    pub fn merge(&mut self, other: &RunningMoments) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Population standard deviation, like `std_dev`
+    pub fn std_dev(&self) -> Option<f64> {
+        (self.count > 0).then(|| (self.m2 / self.count as f64).max(0.0).sqrt())
+    }
+}
+
+/// Default `TDigest` compression: about 1% relative error in the middle of the
+/// distribution and much less in the tails
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// Quantile sketch of a stream of values (a merging t-digest) in memory bounded by its
+/// compression, for data too large to sort. Digests of parts of the data merge into one
+/// of all of it, e.g. one per thread or file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    #[serde(skip)]
+    buffer: Vec<f64>,
+    count: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        TDigest::new(DEFAULT_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        TDigest {
+            compression: compression.max(1.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn from_values(data: &[f64]) -> Self {
+        let mut digest = TDigest::default();
+        digest.extend(data.iter().copied());
+        digest
+    }
+
+    /// Values added, NaN excluded
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(value);
+        if self.buffer.len() as f64 >= 5.0 * self.compression {
+            self.compress();
+        }
+    }
+
+    pub fn extend(&mut self, values: impl IntoIterator<Item = f64>) {
+        for value in values {
+            self.add(value);
+        }
+    }
+
+    /// Add all values of `other`
+    pub fn merge(&mut self, other: &TDigest) {
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.centroids.extend(other.centroids.iter().copied());
+        self.buffer.extend(other.buffer.iter().copied());
+        self.compress();
+    }
+
+    /// Estimated value at quantile `q` (0-1)
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let centroids = self.compressed_centroids();
+        let centroids = centroids.as_ref();
+
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = q.clamp(0.0, 1.0) * total;
+        // Each centroid sits at the middle of the weight it holds; the extremes are exact
+        let mut before = 0.0;
+        let mut previous = (0.0, self.min);
+        for centroid in centroids {
+            let position = before + centroid.weight / 2.0;
+            if target < position {
+                let (from, value) = previous;
+                let share = if position > from { (target - from) / (position - from) } else { 0.0 };
+                return Some(value + share * (centroid.mean - value));
+            }
+            previous = (position, centroid.mean);
+            before += centroid.weight;
+        }
+        let (from, value) = previous;
+        let share = if total > from { (target - from) / (total - from) } else { 1.0 };
+        Some(value + share * (self.max - value))
+    }
+
+    /// Estimated mean of the values below quantile `q` (0-1), over at least one value
+    pub fn mean_below(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let centroids = self.compressed_centroids();
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = (q.clamp(0.0, 1.0) * total).max(1.0);
+        let (mut weight, mut sum) = (0.0, 0.0);
+        for centroid in centroids.iter() {
+            let taken = centroid.weight.min(target - weight);
+            weight += taken;
+            sum += taken * centroid.mean;
+            if weight >= target {
+                break;
+            }
+        }
+        Some(sum / weight)
+    }
+
+    /// Estimated mean absolute deviation from `center`, e.g. the mean
+    pub fn mean_absolute_deviation(&self, center: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let centroids = self.compressed_centroids();
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        Some(centroids.iter().map(|c| c.weight * (c.mean - center).abs()).sum::<f64>() / total)
+    }
+
+    /// Estimated median absolute deviation from the median
+    pub fn median_absolute_deviation(&self) -> Option<f64> {
+        let median = self.quantile(0.5)?;
+        let centroids = self.compressed_centroids();
+        let mut deviations: Vec<(f64, f64)> = centroids.iter().map(|c| ((c.mean - median).abs(), c.weight)).collect();
+        deviations.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let half = deviations.iter().map(|(_, weight)| weight).sum::<f64>() / 2.0;
+        let mut before = 0.0;
+        deviations.into_iter().find_map(|(deviation, weight)| {
+            before += weight;
+            (before >= half).then_some(deviation)
+        })
+    }
+
+    fn compressed_centroids(&self) -> Cow<'_, [Centroid]> {
+        if self.buffer.is_empty() {
+            return Cow::Borrowed(&self.centroids);
+        }
+        let mut digest = self.clone();
+        digest.compress();
+        Cow::Owned(digest.centroids)
+    }
+
+    // Merge the buffered values into the centroids, keeping each centroid within one unit
+    // of the k1 scale function so centroids near the tails stay small
+    fn compress(&mut self) {
+        let mut items = std::mem::take(&mut self.centroids);
+        items.extend(self.buffer.drain(..).map(|value| Centroid { mean: value, weight: 1.0 }));
+        if items.is_empty() {
+            return;
+        }
+        items.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = items.iter().map(|c| c.weight).sum();
+        let scale = |q: f64| self.compression / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).clamp(-1.0, 1.0).asin();
+
+        let mut merged = Vec::new();
+        let mut current = items[0];
+        let mut before = 0.0;
+        let mut k_lower = scale(0.0);
+        for item in items.into_iter().skip(1) {
+            if scale((before + current.weight + item.weight) / total) - k_lower <= 1.0 {
+                let weight = current.weight + item.weight;
+                current.mean += (item.mean - current.mean) * item.weight / weight;
+                current.weight = weight;
+            } else {
+                before += current.weight;
+                k_lower = scale(before / total);
+                merged.push(current);
+                current = item;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+}
+
+/// Estimate with a bootstrap confidence interval
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    /// The statistic on the data itself
+    pub estimate: f64,
+    pub lower: f64,
+    pub upper: f64,
+    /// Confidence level in percent, e.g. 95
+    pub confidence: f64,
+}
+
+/// Percentile bootstrap confidence interval of `statistic` at `confidence` percent, from
+/// `resamples` resamples of `data` drawn with replacement. The same seed gives the same
+/// interval.
+pub fn bootstrap_ci(
+    data: &[f64],
+    statistic: impl Fn(&[f64]) -> f64,
+    resamples: usize,
+    confidence: f64,
+    seed: u64,
+) -> Option<ConfidenceInterval> {
+    let data: Vec<f64> = data.iter().copied().filter(|value| !value.is_nan()).collect();
+    if data.is_empty() || resamples == 0 {
+        return None;
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut resample = vec![0.0; data.len()];
+    let estimates: Vec<f64> = (0..resamples)
+        .map(|_| {
+            for value in resample.iter_mut() {
+                *value = data[rng.gen_range(0..data.len())];
+            }
+            statistic(&resample)
+        })
+        .collect();
+    let estimates = sorted(&estimates);
+    let tail = (100.0 - confidence.clamp(0.0, 100.0)) / 2.0;
+    Some(ConfidenceInterval {
+        estimate: statistic(&data),
+        lower: percentile(&estimates, tail)?,
+        upper: percentile(&estimates, 100.0 - tail)?,
+        confidence,
+    })
+}
@@ -0,0 +1,351 @@
+// src/simulator/streaming.rs
+use std::collections::HashMap;
+
+use super::buffer::DrawdownBufferStats;
+use super::end_states::EndStateStats;
+use super::gross::GrossPayoutStats;
+use super::income::{CycleSums, StationaryIncome};
+use super::ledger::Ledger;
+use super::periods::{PeriodPnlReport, PeriodPnlStats};
+use super::stats::{RunningMoments, TDigest};
+use super::tail::{BalancePercentile, TailRisk};
+use super::time_to_money::{DaysToStats, TimeToMoneyReport};
+use super::trader::EndOfGame;
+use super::triggers::{self, RuleTriggerReport, RuleTriggers, SoftBreachStats};
+use super::worst::{WorstOutcomeReport, WorstOutcomeStats};
+use super::IterationResult;
+
+/// Statistics of the Monte Carlo runs gathered run by run with `streaming_stats`, in memory
+/// that does not grow with the number of iterations. Means and standard deviations are
+/// exact up to rounding; medians, percentiles and the other order statistics are
+/// `TDigest` estimates. Statistics of parts of the runs merge into those of all of them.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingStats {
+    by_end_state: HashMap<EndOfGame, RunStream>,
+    // Pooled over every run, like the rule trigger report
+    rule_triggers: RuleTriggers,
+    days: u64,
+    max_soft_breaches: u64,
+    soft_breached_runs: usize,
+    // Every run, as one account's life
+    cycles: CycleSums,
+    // Runs that were not busted, whatever `condition_end_state`
+    min_drawdown_buffers: ValueStream,
+    buffers_at_first_payout: RunningMoments,
+}
+
+/// Running statistics of one value over the runs
+#[derive(Debug, Clone, Default)]
+struct ValueStream {
+    moments: RunningMoments,
+    digest: TDigest,
+    positive: usize,
+    negative: usize,
+}
+
+impl ValueStream {
+    fn add(&mut self, value: f64) {
+        self.moments.add(value);
+        self.digest.add(value);
+        self.positive += usize::from(value > 0.0);
+        self.negative += usize::from(value < 0.0);
+    }
+
+    fn merge(&mut self, other: &ValueStream) {
+        self.moments.merge(&other.moments);
+        self.digest.merge(&other.digest);
+        self.positive += other.positive;
+        self.negative += other.negative;
+    }
+
+    fn count(&self) -> usize {
+        self.moments.count()
+    }
+
+    /// Stats of the worst trade or day of each run, `None` when no run traded
+    fn worst_outcome_stats(&self) -> Option<WorstOutcomeStats> {
+        let quantile = |q: f64| self.digest.quantile(q);
+        Some(WorstOutcomeStats {
+            runs: self.count(),
+            mean: self.moments.mean()?,
+            median: quantile(0.5)?,
+            p10: quantile(0.1)?,
+            p5: quantile(0.05)?,
+            p1: quantile(0.01)?,
+            worst: quantile(0.0)?,
+        })
+    }
+
+    /// Stats of the day a milestone was reached, by those of `runs` that did
+    fn days_to_stats(&self, runs: usize) -> Option<DaysToStats> {
+        let quantile = |q: f64| self.digest.quantile(q);
+        Some(DaysToStats {
+            runs: self.count(),
+            reached_percentage: self.count() as f64 / runs as f64 * 100.0,
+            mean: self.moments.mean()?,
+            median: quantile(0.5)?,
+            p10: quantile(0.1)?,
+            p25: quantile(0.25)?,
+            p75: quantile(0.75)?,
+            p90: quantile(0.9)?,
+            histogram: None,
+        })
+    }
+
+    fn percentiles(&self, percentiles: &[f64]) -> Vec<BalancePercentile> {
+        percentiles
+            .iter()
+            .filter_map(|&p| Some(BalancePercentile { percentile: p, balance: self.digest.quantile(p / 100.0)? }))
+            .collect()
+    }
+}
+
+/// P&L of the periods of the runs, pooled
+#[derive(Debug, Clone, Default)]
+struct PeriodStream {
+    pnl: ValueStream,
+    worst_per_run: RunningMoments,
+}
+
+impl PeriodStream {
+    fn add(&mut self, periods: &[f64]) {
+        periods.iter().for_each(|&pnl| self.pnl.add(pnl));
+        if let Some(worst) = periods.iter().copied().reduce(f64::min) {
+            self.worst_per_run.add(worst);
+        }
+    }
+
+    fn merge(&mut self, other: &PeriodStream) {
+        self.pnl.merge(&other.pnl);
+        self.worst_per_run.merge(&other.worst_per_run);
+    }
+
+    /// `None` when no run traded a period
+    fn stats(&self) -> Option<PeriodPnlStats> {
+        let pnl = &self.pnl;
+        let quantile = |q: f64| pnl.digest.quantile(q);
+        Some(PeriodPnlStats {
+            periods: pnl.count(),
+            mean: pnl.moments.mean()?,
+            volatility: pnl.moments.std_dev()?,
+            median: quantile(0.5)?,
+            p5: quantile(0.05)?,
+            worst: quantile(0.0)?,
+            best: quantile(1.0)?,
+            losing_percentage: pnl.negative as f64 / pnl.count() as f64 * 100.0,
+            mean_worst: self.worst_per_run.mean()?,
+        })
+    }
+}
+
+/// Runs ending in one end state
+#[derive(Debug, Clone, Default)]
+struct RunStream {
+    balances: ValueStream,
+    gross_payouts: ValueStream,
+    days: u64,
+    ledger: Ledger, // total over the runs
+    weekly_pnl: PeriodStream,
+    monthly_pnl: PeriodStream,
+    worst_trades: ValueStream,
+    worst_days: ValueStream,
+    funded_days: ValueStream,
+    first_payout_days: ValueStream,
+}
+
+impl RunStream {
+    fn merge(&mut self, other: &RunStream) {
+        self.balances.merge(&other.balances);
+        self.gross_payouts.merge(&other.gross_payouts);
+        self.days += other.days;
+        self.ledger.add(&other.ledger);
+        self.weekly_pnl.merge(&other.weekly_pnl);
+        self.monthly_pnl.merge(&other.monthly_pnl);
+        self.worst_trades.merge(&other.worst_trades);
+        self.worst_days.merge(&other.worst_days);
+        self.funded_days.merge(&other.funded_days);
+        self.first_payout_days.merge(&other.first_payout_days);
+    }
+
+    fn runs(&self) -> usize {
+        self.balances.count()
+    }
+}
+
+/// Statistics of the runs `condition_end_state` selects, as the `SimulationResult` reports them
+#[derive(Debug, Clone)]
+pub struct StreamingSummary {
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    pub mad: f64,
+    pub iqr: f64,
+    pub mad_median: f64,
+    pub mean_days: f64,
+    pub positive_percentage: f64,
+    pub percentiles: Vec<BalancePercentile>,
+    pub tail_risk: TailRisk,
+    pub ev_decomposition: Ledger,
+    pub gross_payouts: GrossPayoutStats,
+    pub period_pnl: PeriodPnlReport,
+    pub worst_outcomes: WorstOutcomeReport,
+    pub time_to_money: TimeToMoneyReport,
+}
+
+impl StreamingStats {
+    pub(crate) fn add(&mut self, result: &IterationResult) {
+        let stream = self.by_end_state.entry(result.end_state.clone()).or_default();
+        stream.balances.add(result.final_balance);
+        stream.gross_payouts.add(result.ledger.gross_payouts);
+        stream.days += result.simulation_length;
+        stream.ledger.add(&result.ledger);
+        stream.weekly_pnl.add(&result.weekly_pnl);
+        stream.monthly_pnl.add(&result.monthly_pnl);
+        result.worst_trade.into_iter().for_each(|worst| stream.worst_trades.add(worst));
+        result.worst_day.into_iter().for_each(|worst| stream.worst_days.add(worst));
+        result.funded_day.into_iter().for_each(|day| stream.funded_days.add(day as f64));
+        result.first_payout_day.into_iter().for_each(|day| stream.first_payout_days.add(day as f64));
+        self.rule_triggers.add(&result.rule_triggers);
+        self.days += result.simulation_length;
+        self.max_soft_breaches = self.max_soft_breaches.max(result.rule_triggers.soft_breaches);
+        self.soft_breached_runs += usize::from(result.rule_triggers.soft_breaches > 0);
+        // A busted account also used up the day it was lost
+        let busted = result.end_state == EndOfGame::Busted;
+        self.cycles.add(result.final_balance, result.simulation_length + u64::from(busted));
+        if !busted {
+            self.min_drawdown_buffers.add(result.min_drawdown_buffer);
+            result.buffer_at_first_payout.into_iter().for_each(|buffer| self.buffers_at_first_payout.add(buffer));
+        }
+    }
+
+    pub fn merge(mut self, other: StreamingStats) -> StreamingStats {
+        for (end_state, stream) in other.by_end_state {
+            self.by_end_state.entry(end_state).or_default().merge(&stream);
+        }
+        self.rule_triggers.add(&other.rule_triggers);
+        self.days += other.days;
+        self.max_soft_breaches = self.max_soft_breaches.max(other.max_soft_breaches);
+        self.soft_breached_runs += other.soft_breached_runs;
+        self.cycles.merge(&other.cycles);
+        self.min_drawdown_buffers.merge(&other.min_drawdown_buffers);
+        self.buffers_at_first_payout.merge(&other.buffers_at_first_payout);
+        self
+    }
+
+    pub fn runs(&self) -> usize {
+        self.by_end_state.values().map(RunStream::runs).sum()
+    }
+
+    pub fn end_state_percentages(&self) -> HashMap<EndOfGame, f64> {
+        let runs = self.runs().max(1) as f64;
+        self.by_end_state
+            .iter()
+            .map(|(end_state, stream)| (end_state.clone(), stream.runs() as f64 / runs * 100.0))
+            .collect()
+    }
+
+    pub fn stats_by_end_state(&self, percentiles: &[f64]) -> HashMap<EndOfGame, EndStateStats> {
+        let runs = self.runs().max(1) as f64;
+        self.by_end_state
+            .iter()
+            .filter_map(|(end_state, stream)| {
+                let balances = &stream.balances;
+                let n = stream.runs();
+                let stats = EndStateStats {
+                    runs: n,
+                    percentage: n as f64 / runs * 100.0,
+                    mean_balance: balances.moments.mean()?,
+                    median_balance: balances.digest.quantile(0.5)?,
+                    std_dev: balances.moments.std_dev()?,
+                    mean_days: stream.days as f64 / n as f64,
+                    percentiles: balances.percentiles(percentiles),
+                };
+                Some((end_state.clone(), stats))
+            })
+            .collect()
+    }
+
+    pub fn rule_trigger_report(&self) -> RuleTriggerReport {
+        triggers::rule_trigger_report(&[(self.rule_triggers, self.days)])
+    }
+
+    /// Soft breaches per run, `None` when no run had one
+    pub fn soft_breaches(&self) -> Option<SoftBreachStats> {
+        let runs = self.runs() as f64;
+        (self.max_soft_breaches > 0).then(|| SoftBreachStats {
+            mean_per_run: self.rule_triggers.soft_breaches as f64 / runs,
+            max_per_run: self.max_soft_breaches,
+            breached_percentage: self.soft_breached_runs as f64 / runs * 100.0,
+        })
+    }
+
+    pub fn stationary_income(&self) -> Option<StationaryIncome> {
+        self.cycles.stationary_income()
+    }
+
+    /// Drawdown buffer of the runs that were not busted, `None` when every run was
+    pub fn drawdown_buffer(&self) -> Option<DrawdownBufferStats> {
+        let buffers = &self.min_drawdown_buffers;
+        let quantile = |q: f64| buffers.digest.quantile(q);
+        Some(DrawdownBufferStats {
+            surviving_runs: buffers.count(),
+            mean_min_buffer: buffers.moments.mean()?,
+            median_min_buffer: quantile(0.5)?,
+            p10_min_buffer: quantile(0.1)?,
+            p90_min_buffer: quantile(0.9)?,
+            mean_buffer_at_first_payout: self.buffers_at_first_payout.mean(),
+        })
+    }
+
+    /// Summary of the runs ending in `end_states`, `None` when there is none
+    pub fn summary(&self, end_states: &[EndOfGame], percentiles: &[f64], var_confidence: f64) -> Option<StreamingSummary> {
+        let mut stream = RunStream::default();
+        for end_state in end_states {
+            if let Some(other) = self.by_end_state.get(end_state) {
+                stream.merge(other);
+            }
+        }
+        let n = stream.runs() as f64;
+        let (balances, gross_payouts) = (&stream.balances, &stream.gross_payouts);
+        let quantile = |q: f64| balances.digest.quantile(q);
+        let mean = balances.moments.mean()?;
+        let tail = 1.0 - var_confidence / 100.0;
+        Some(StreamingSummary {
+            mean,
+            median: quantile(0.5)?,
+            std_dev: balances.moments.std_dev()?,
+            mad: balances.digest.mean_absolute_deviation(mean)?,
+            iqr: quantile(0.75)? - quantile(0.25)?,
+            mad_median: balances.digest.median_absolute_deviation()?,
+            mean_days: stream.days as f64 / n,
+            positive_percentage: balances.positive as f64 / n * 100.0,
+            percentiles: balances.percentiles(percentiles),
+            tail_risk: TailRisk {
+                confidence: var_confidence,
+                value_at_risk: -quantile(tail)?,
+                conditional_value_at_risk: -balances.digest.mean_below(tail)?,
+            },
+            ev_decomposition: stream.ledger.divided(n),
+            gross_payouts: GrossPayoutStats {
+                mean: gross_payouts.moments.mean()?,
+                median: gross_payouts.digest.quantile(0.5)?,
+                std_dev: gross_payouts.moments.std_dev()?,
+                p10: gross_payouts.digest.quantile(0.1)?,
+                p90: gross_payouts.digest.quantile(0.9)?,
+                paid_percentage: gross_payouts.positive as f64 / n * 100.0,
+            },
+            period_pnl: PeriodPnlReport {
+                weekly: stream.weekly_pnl.stats().unwrap_or_default(),
+                monthly: stream.monthly_pnl.stats().unwrap_or_default(),
+            },
+            worst_outcomes: WorstOutcomeReport {
+                worst_trade: stream.worst_trades.worst_outcome_stats().unwrap_or_default(),
+                worst_day: stream.worst_days.worst_outcome_stats().unwrap_or_default(),
+            },
+            time_to_money: TimeToMoneyReport {
+                funded: stream.funded_days.days_to_stats(stream.runs()),
+                first_payout: stream.first_payout_days.days_to_stats(stream.runs()),
+            },
+        })
+    }
+}
@@ -0,0 +1,47 @@
+// src/simulator/tail.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats::percentile;
+
+/// Percentiles of the final balance reported when `percentiles` is not set
+pub const DEFAULT_PERCENTILES: [f64; 6] = [1.0, 5.0, 10.0, 90.0, 95.0, 99.0];
+/// Confidence level of the Value-at-Risk when `var_confidence` is not set
+pub const DEFAULT_VAR_CONFIDENCE: f64 = 95.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BalancePercentile {
+    pub percentile: f64,
+    pub balance: f64,
+}
+
+/// Tail risk of the final balance, as losses: positive values are amounts lost
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TailRisk {
+    /// Confidence level in percent, e.g. 95
+    pub confidence: f64,
+    /// Loss not exceeded with the given confidence (the negated `100 - confidence` percentile)
+    pub value_at_risk: f64,
+    /// Mean loss of the runs beyond the Value-at-Risk (expected shortfall)
+    pub conditional_value_at_risk: f64,
+}
+
+/// `percentiles` of the sorted final balances
+pub fn balance_percentiles(sorted: &[f64], percentiles: &[f64]) -> Vec<BalancePercentile> {
+    percentiles
+        .iter()
+        .filter_map(|&p| Some(BalancePercentile { percentile: p, balance: percentile(sorted, p)? }))
+        .collect()
+}
+
+/// Value-at-Risk and Conditional VaR of the sorted final balances at `confidence` percent
+pub fn tail_risk(sorted: &[f64], confidence: f64) -> TailRisk {
+    let Some(value_at_risk) = percentile(sorted, 100.0 - confidence) else {
+        return TailRisk { confidence, ..TailRisk::default() };
+    };
+    let tail = ((sorted.len() as f64 * (1.0 - confidence / 100.0)).ceil() as usize).clamp(1, sorted.len());
+    TailRisk {
+        confidence,
+        value_at_risk: -value_at_risk,
+        conditional_value_at_risk: -sorted[..tail].iter().sum::<f64>() / tail as f64,
+    }
+}
@@ -0,0 +1,60 @@
+// src/simulator/throughput.rs
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use super::{load_trades, simulate_trades, simulation_limits, SimulationConfig, SimulationHandle};
+
+/// Iterations of the calibration run, and the fewest a time budget selects
+pub const CALIBRATION_ITERATIONS: usize = 200;
+
+/// Speed of a config measured by a short calibration run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Throughput {
+    pub calibration_iterations: usize,
+    /// Seconds spent reading or generating the trades, paid once per run
+    pub setup_seconds: f64,
+    pub iterations_per_second: f64,
+}
+
+impl Throughput {
+    /// Expected seconds of a run of `iterations`
+    pub fn expected_seconds(&self, iterations: usize) -> f64 {
+        self.setup_seconds + iterations as f64 / self.iterations_per_second
+    }
+
+    /// Most iterations a run can do within `budget`, at least `CALIBRATION_ITERATIONS`
+    /// and at most the configured `SimulationLimits::max_iterations`
+    pub fn iterations_for(&self, budget: Duration) -> usize {
+        let seconds = (budget.as_secs_f64() - self.setup_seconds).max(0.0);
+        let iterations = ((seconds * self.iterations_per_second) as usize).max(CALIBRATION_ITERATIONS);
+        simulation_limits().max_iterations.map_or(iterations, |limit| iterations.min(limit))
+    }
+}
+
+/// Time `CALIBRATION_ITERATIONS` iterations of `config`, with everything else it runs
+/// (comparison runs, side analyses) included. `config.iterations` is ignored.
+pub fn measure_throughput(config: &SimulationConfig) -> Result<Throughput, Box<dyn Error>> {
+    let calibration = SimulationConfig {
+        iterations: CALIBRATION_ITERATIONS,
+        // Any end state will do, a short run may not reach the conditioned one
+        condition_end_state: String::from("All"),
+        histogram: false,
+        ..config.clone()
+    };
+    simulation_limits().check_config(&calibration)?;
+
+    let start = Instant::now();
+    let trades = load_trades(&calibration)?;
+    let setup_seconds = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    simulate_trades(calibration, &trades, &SimulationHandle::new(), &|_| {}).map_err(|e| -> Box<dyn Error> { e })?;
+    let seconds = start.elapsed().as_secs_f64().max(f64::EPSILON);
+
+    Ok(Throughput {
+        calibration_iterations: CALIBRATION_ITERATIONS,
+        setup_seconds,
+        iterations_per_second: CALIBRATION_ITERATIONS as f64 / seconds,
+    })
+}
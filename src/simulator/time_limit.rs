@@ -0,0 +1,105 @@
+// src/simulator/time_limit.rs
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+
+/// What happens to an evaluation that runs out of time and extensions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryAction {
+    /// The fee is forfeited and the trader buys a new evaluation
+    #[default]
+    Reset,
+    /// The fee is forfeited and the run ends as `EvalExpired`
+    End,
+}
+
+impl ExpiryAction {
+    pub const ALL: [ExpiryAction; 2] = [ExpiryAction::Reset, ExpiryAction::End];
+}
+
+impl FromStr for ExpiryAction {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "reset" => Ok(ExpiryAction::Reset),
+            "end" => Ok(ExpiryAction::End),
+            _ => Err("Unknown expiry action (options: reset, end)"),
+        }
+    }
+}
+
+/// Calendar days allowed to pass the evaluation phase, counted from the day it starts,
+/// e.g. `{ calendar_days = 30, extensions = 1, extension_cost = 50.0 }`. Without a
+/// purchase date a week of five trading days counts as seven calendar days.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalTimeLimit {
+    pub calendar_days: u64,
+    /// Paid extensions bought one after the other when the time runs out
+    pub extensions: u32,
+    /// Calendar days each extension adds
+    pub extension_days: u64,
+    /// Price in USD of each extension
+    pub extension_cost: f64,
+    pub on_expiry: ExpiryAction,
+    /// Price in USD of the new evaluation bought on expiry with `reset` (the account price if not set)
+    pub reset_cost: Option<f64>,
+}
+
+impl Default for EvalTimeLimit {
+    fn default() -> Self {
+        EvalTimeLimit {
+            calendar_days: 30,
+            extensions: 0,
+            extension_days: 30,
+            extension_cost: 0.0,
+            on_expiry: ExpiryAction::default(),
+            reset_cost: None,
+        }
+    }
+}
+
+impl EvalTimeLimit {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.calendar_days == 0 {
+            return Err("eval_time_limit.calendar_days must be greater than zero".into());
+        }
+        if self.extensions > 0 && self.extension_days == 0 {
+            return Err("eval_time_limit.extension_days must be greater than zero".into());
+        }
+        if self.extension_cost < 0.0 || self.reset_cost.is_some_and(|cost| cost < 0.0) {
+            return Err("eval_time_limit costs must not be negative".into());
+        }
+        Ok(())
+    }
+
+    /// Calendar days allowed after `extensions_used` extensions
+    pub fn allowed_days(&self, extensions_used: u32) -> u64 {
+        self.calendar_days + extensions_used as u64 * self.extension_days
+    }
+}
+
+/// How often evaluations ran out of time, over all runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvalTimeLimitReport {
+    /// Runs that bought at least one extension
+    pub extended_percentage: f64,
+    pub mean_extensions: f64,
+    /// Runs with at least one evaluation that expired
+    pub expired_percentage: f64,
+    /// Mean expired evaluations per run, each followed by a reset or the end of the run
+    pub mean_expiries: f64,
+}
+
+/// Build the report from `(extensions bought, evaluations expired)` of each run
+pub fn eval_time_limit_report(runs: &[(u32, u32)]) -> EvalTimeLimitReport {
+    let n = runs.len().max(1) as f64;
+    let percentage = |count: usize| count as f64 / n * 100.0;
+    EvalTimeLimitReport {
+        extended_percentage: percentage(runs.iter().filter(|(extensions, _)| *extensions > 0).count()),
+        mean_extensions: runs.iter().map(|(extensions, _)| *extensions as f64).sum::<f64>() / n,
+        expired_percentage: percentage(runs.iter().filter(|(_, expiries)| *expiries > 0).count()),
+        mean_expiries: runs.iter().map(|(_, expiries)| *expiries as f64).sum::<f64>() / n,
+    }
+}
@@ -0,0 +1,55 @@
+// src/simulator/time_to_money.rs
+use serde::{Serialize, Deserialize};
+
+use super::plotting::{compute_histogram, HistogramBins};
+use super::stats::{self, percentile};
+
+/// Distribution over runs of the simulation day a milestone was reached, counted in
+/// trading days from the start of the run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaysToStats {
+    /// Runs that reached the milestone
+    pub runs: usize,
+    /// Their percentage of all runs covered
+    pub reached_percentage: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p10: f64,
+    pub p25: f64,
+    pub p75: f64,
+    pub p90: f64,
+    /// Bins of the days, when `histogram` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub histogram: Option<HistogramBins>,
+}
+
+/// How long until the account is funded and until the first payout: "how long until I
+/// see money"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimeToMoneyReport {
+    /// Day the first evaluation of the run was passed, or 0 for an account funded from the start
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub funded: Option<DaysToStats>,
+    /// Day of the first payout of the run
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub first_payout: Option<DaysToStats>,
+}
+
+/// Build the stats from the day each run reached the milestone, `None` for runs that never
+/// did; `None` when no run did. `histogram_bins` adds a histogram of the days.
+pub fn days_to_stats(runs: &[Option<u64>], histogram_bins: Option<usize>) -> Option<DaysToStats> {
+    let days: Vec<f64> = runs.iter().flatten().map(|&day| day as f64).collect();
+    let days = stats::sorted(&days);
+    let (mean, median) = (stats::mean(&days)?, stats::median(&days)?);
+    Some(DaysToStats {
+        runs: days.len(),
+        reached_percentage: days.len() as f64 / runs.len() as f64 * 100.0,
+        mean,
+        median,
+        p10: percentile(&days, 10.0).unwrap_or_default(),
+        p25: percentile(&days, 25.0).unwrap_or_default(),
+        p75: percentile(&days, 75.0).unwrap_or_default(),
+        p90: percentile(&days, 90.0).unwrap_or_default(),
+        histogram: histogram_bins.map(|bins| compute_histogram(&days, bins)),
+    })
+}
@@ -0,0 +1,142 @@
+// src/simulator/trade_cache.rs
+use log::debug;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::bundle::data_hash;
+use super::trade_data::TradePool;
+use super::{load_trades, SimulationConfig, SimulationError};
+
+// What the trade pool of a config depends on: the data (by its SHA-256 or by file, with
+// the file's size and modification time so an edited file is read again), the multiplier,
+// the round trip cost and the holding rules, trade filter and outcome reweighting
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TradeKey {
+    source: String,
+    multiplier: u64,
+    round_trip_cost: Option<u64>,
+    options: String,
+}
+
+impl TradeKey {
+    // `None` for generated trades, which are random and never shared
+    fn of(config: &SimulationConfig) -> Option<TradeKey> {
+        let source = match (&config.csv_data, &config.csv_file) {
+            (Some(csv_data), _) => format!("data:{}", data_hash(csv_data.as_bytes())),
+            (None, Some(csv_file)) => {
+                let metadata = std::fs::metadata(csv_file).ok()?;
+                let modified = metadata.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+                format!("file:{}:{}:{}", csv_file, metadata.len(), modified.as_nanos())
+            }
+            (None, None) => return None,
+        };
+        Some(TradeKey {
+            source,
+            multiplier: config.multiplier.to_bits(),
+            round_trip_cost: config.round_trip_cost.map(f64::to_bits),
+            options: format!("{:?} {:?} {:?}", config.holding_rules, config.trade_filter, config.outcome_reweighting),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CachedPool {
+    pool: Arc<TradePool>,
+    bytes: usize,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    pools: HashMap<TradeKey, CachedPool>,
+    bytes: usize,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Usage of a `TradeCache`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeCacheStats {
+    pub datasets: usize,
+    pub bytes: usize,
+    pub max_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Parsed trade data kept between simulations, so that runs on data seen before skip
+/// reading the CSV. Pools are keyed by the SHA-256 of inline CSV data (or by file) and
+/// the config fields applied while loading, and the least recently used ones are dropped
+/// once the pools take more than `max_bytes`. Shared between threads; two runs loading
+/// the same data at once both read it.
+#[derive(Debug, Default)]
+pub struct TradeCache {
+    max_bytes: usize,
+    state: Mutex<CacheState>,
+}
+
+impl TradeCache {
+    pub fn new(max_bytes: usize) -> Self {
+        TradeCache { max_bytes, state: Mutex::default() }
+    }
+
+    /// The trade pool of `config`, from the cache or loaded and added to it
+    pub fn get_or_load(&self, config: &SimulationConfig) -> Result<Arc<TradePool>, SimulationError> {
+        let key = match TradeKey::of(config) {
+            Some(key) => key,
+            None => return load_trades(config).map(Arc::new),
+        };
+        {
+            let mut state = self.state.lock().unwrap();
+            state.clock += 1;
+            let clock = state.clock;
+            if let Some(cached) = state.pools.get_mut(&key) {
+                cached.last_used = clock;
+                let pool = Arc::clone(&cached.pool);
+                state.hits += 1;
+                debug!("Trade cache hit for {}", key.source);
+                return Ok(pool);
+            }
+            state.misses += 1;
+        }
+
+        let pool = Arc::new(load_trades(config)?);
+        let bytes = pool.memory_bytes();
+        if bytes <= self.max_bytes {
+            let mut state = self.state.lock().unwrap();
+            while state.bytes + bytes > self.max_bytes {
+                let oldest = state.pools.iter().min_by_key(|(_, cached)| cached.last_used).map(|(key, _)| key.clone());
+                match oldest.and_then(|oldest| state.pools.remove(&oldest)) {
+                    Some(evicted) => state.bytes -= evicted.bytes,
+                    None => break,
+                }
+            }
+            state.clock += 1;
+            let last_used = state.clock;
+            if let Some(replaced) = state.pools.insert(key, CachedPool { pool: Arc::clone(&pool), bytes, last_used }) {
+                state.bytes -= replaced.bytes;
+            }
+            state.bytes += bytes;
+        }
+        Ok(pool)
+    }
+
+    pub fn stats(&self) -> TradeCacheStats {
+        let state = self.state.lock().unwrap();
+        TradeCacheStats {
+            datasets: state.pools.len(),
+            bytes: state.bytes,
+            max_bytes: self.max_bytes,
+            hits: state.hits,
+            misses: state.misses,
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.pools.clear();
+        state.bytes = 0;
+    }
+}
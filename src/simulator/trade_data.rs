@@ -1,8 +1,18 @@
-use std::{collections::HashMap, error::Error};
-use csv::Reader;
-use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::{error::Error, fs::File, ops::Range, str::FromStr};
+use csv::{ByteRecord, Reader};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use log::{info, warn};
+use memmap2::Mmap;
 use rand::Rng;
+use rand::seq::SliceRandom;
+use rand::distributions::WeightedIndex;
 use rand_distr::{Poisson, Normal, Distribution};
+use serde::{Serialize, Deserialize};
+
+use super::holding::{HoldingAction, HoldingReport, HoldingRules};
+use super::reweight::{self, OutcomeReweight, ReweightReport};
+use super::trade_filter::{TradeFilter, TradeFilterReport};
+use super::SimulationError;
 
 #[derive(Debug, Clone)]
 pub struct Trade{
@@ -10,69 +20,210 @@ pub struct Trade{
     pub max_opposite_excursion: f64,
 }
 
-// Struct to store the data from the CSV
-#[derive(Debug)]
+// Precision of the stored trade values. The `f32` feature halves the memory read per
+// sampled trade for very large runs; account balances and aggregation stay f64.
+#[cfg(feature = "f32")]
+mod precision {
+    pub type StoredValue = f32;
+    pub fn store(value: f64) -> StoredValue { value as f32 }
+    pub fn load(value: StoredValue) -> f64 { value as f64 }
+}
+#[cfg(not(feature = "f32"))]
+mod precision {
+    pub type StoredValue = f64;
+    pub fn store(value: f64) -> StoredValue { value }
+    pub fn load(value: StoredValue) -> f64 { value }
+}
+use precision::StoredValue;
+
+// Struct to store the data from the CSV. Only the trading date is kept (as days since
+// 0001-01-01), so a trade takes 24 bytes (12 with `f32`) and a file loads into one
+// flat C-layout array.
+#[derive(Debug, Clone)]
+#[repr(C)]
 pub struct TradeRecord {
-    datetime: DateTime<Utc>,
-    pub trade: Trade,
+    day: i32,
+    return_value: StoredValue,
+    max_opposite_excursion: StoredValue,
 }
 
-// Function to read and parse the CSV file
-pub fn read_csv(file_path: &str, multiplier: f64, round_trip_cost: Option<f64>) -> Result<Vec<TradeRecord>, Box<dyn Error>> {
-    let mut rdr = Reader::from_path(file_path)?;
-    let mut trades = Vec::new();
-    let mut cost = 0.0;
-    if let Some(rt_cost) = round_trip_cost{
-        cost = rt_cost;
+impl TradeRecord {
+    pub fn new(date: NaiveDate, trade: Trade) -> Self {
+        TradeRecord {
+            day: date.num_days_from_ce(),
+            return_value: precision::store(trade.return_value),
+            max_opposite_excursion: precision::store(trade.max_opposite_excursion),
+        }
     }
 
-    for result in rdr.records() {
-        let record = result?;
-        let datetime_str = &record[0];
-        let naive = NaiveDateTime::parse_from_str(datetime_str, "%Y%m%d %H:%M:%S")?;
-        // Using TimeZone::from_utc_datetime
-        let datetime = Utc.from_utc_datetime(&naive);
-        let return_value: f64 = record[1].parse()?;
-        let max_opposite_excursion: f64 = record[2].parse()?;
-
-        trades.push(TradeRecord {
-            datetime,
-            trade: Trade{
-                return_value: return_value*multiplier - cost,
-                max_opposite_excursion: max_opposite_excursion*multiplier - cost
-            },
-        });
+    pub fn date(&self) -> NaiveDate {
+        NaiveDate::from_num_days_from_ce_opt(self.day).unwrap_or_default()
+    }
+
+    pub fn trade(&self) -> Trade {
+        Trade {
+            return_value: precision::load(self.return_value),
+            max_opposite_excursion: precision::load(self.max_opposite_excursion),
+        }
     }
+}
+
+// Format of the entry and exit timestamps in CSV data
+pub(crate) const DATETIME_FORMAT: &str = "%Y%m%d %H:%M:%S";
+
+/// Calendar days covered by generated bracket trades
+pub const SIMULATED_DAYS: i64 = 365;
+
+// Files at least this large log their loading progress
+const PROGRESS_MIN_BYTES: usize = 64 * 1024 * 1024;
 
-    Ok(trades)
+// Trades read from a CSV, the holding rule violations found in it when rules were given and
+// the trades kept by the trade filter when one was given
+pub type CsvTrades = (Vec<TradeRecord>, Option<HoldingReport>, Option<TradeFilterReport>);
+
+// Holding rule violations listed when refusing to load a CSV
+const LISTED_VIOLATIONS: usize = 5;
+
+// Function to read and parse the CSV file. The file is memory-mapped and parsed in place,
+// so even files with millions of rows are never copied into memory as a whole.
+pub fn read_csv(
+    file_path: &str,
+    multiplier: f64,
+    round_trip_cost: Option<f64>,
+    holding_rules: Option<&HoldingRules>,
+    trade_filter: Option<&str>,
+) -> Result<CsvTrades, Box<dyn Error>> {
+    let file = File::open(file_path)?;
+    // Safety: the map is read-only and dropped before returning; the file must not be truncated while it loads
+    let mmap = unsafe { Mmap::map(&file)? };
+    parse_trades(&mmap, multiplier, round_trip_cost, holding_rules, trade_filter, file_path)
 }
 
 // Function to read and parse CSV data from a string
-pub fn read_csv_from_string(data: &str, multiplier: f64, round_trip_cost: Option<f64>) -> Result<Vec<TradeRecord>, Box<dyn Error>> {
-    let mut rdr = csv::Reader::from_reader(data.as_bytes());
+pub fn read_csv_from_string(
+    data: &str,
+    multiplier: f64,
+    round_trip_cost: Option<f64>,
+    holding_rules: Option<&HoldingRules>,
+    trade_filter: Option<&str>,
+) -> Result<CsvTrades, Box<dyn Error>> {
+    parse_trades(data.as_bytes(), multiplier, round_trip_cost, holding_rules, trade_filter, "csv_data")
+}
+
+// Parse "datetime,return,max opposite excursion[,exit datetime][,metadata...]" rows, reusing
+// one byte record so no row allocates. Columns after the third are metadata the trade filter
+// can select trades by.
+fn parse_trades(
+    data: &[u8],
+    multiplier: f64,
+    round_trip_cost: Option<f64>,
+    holding_rules: Option<&HoldingRules>,
+    trade_filter: Option<&str>,
+    label: &str,
+) -> Result<CsvTrades, Box<dyn Error>> {
+    let mut rdr = Reader::from_reader(data);
+    let mut record = ByteRecord::new();
     let mut trades = Vec::new();
     let mut cost = 0.0;
     if let Some(rt_cost) = round_trip_cost{
         cost = rt_cost;
     }
-    for result in rdr.records() {
-        let record = result?;
-        let datetime_str = &record[0];
-        let naive = NaiveDateTime::parse_from_str(datetime_str, "%Y%m%d %H:%M:%S")?;
-        let datetime = Utc.from_utc_datetime(&naive);
-        let return_value: f64 = record[1].parse()?;
-        let max_opposite_excursion: f64 = record[2].parse()?;
-
-        trades.push(TradeRecord {
-            datetime,
-            trade: Trade {
-                return_value: return_value * multiplier - cost,
-                max_opposite_excursion: max_opposite_excursion * multiplier - cost,
-            },
-        });
+
+    let limits = super::limits::simulation_limits();
+    let report_progress = data.len() >= PROGRESS_MIN_BYTES;
+    let mut next_report = 10;
+    let mut holding_report = holding_rules.map(|rules| HoldingReport::new(rules.action));
+    let mut listed_violations = Vec::new();
+    let filter = match trade_filter {
+        Some(expression) => Some(
+            TradeFilter::compile(expression, rdr.byte_headers()?).map_err(|e| SimulationError::InvalidConfig(format!("{}: {}", label, e)))?,
+        ),
+        None => None,
+    };
+    let mut filter_report = trade_filter.map(|expression| TradeFilterReport { expression: expression.to_string(), ..Default::default() });
+    while rdr.read_byte_record(&mut record)? {
+        limits.check_trades(trades.len() + 1)?;
+        let datetime_str = std::str::from_utf8(&record[0])?;
+        let naive = NaiveDateTime::parse_from_str(datetime_str, DATETIME_FORMAT)?;
+        if let (Some(filter), Some(report)) = (&filter, filter_report.as_mut()) {
+            if !filter.matches(&record, naive) {
+                report.excluded_trades += 1;
+                continue;
+            }
+            report.kept_trades += 1;
+        }
+        let return_value: f64 = std::str::from_utf8(&record[1])?.parse()?;
+        let max_opposite_excursion: f64 = std::str::from_utf8(&record[2])?.parse()?;
+        // "NaN" and "inf" parse as numbers but would poison every statistic
+        if !return_value.is_finite() || !max_opposite_excursion.is_finite() {
+            let line = record.position().map_or(0, |position| position.line());
+            return Err(SimulationError::Data(format!("{}: line {}: trade values must be finite numbers", label, line)).into());
+        }
+        let mut trade = Trade {
+            return_value: return_value * multiplier - cost,
+            max_opposite_excursion: max_opposite_excursion * multiplier - cost,
+        };
+
+        if let (Some(rules), Some(report)) = (holding_rules, holding_report.as_mut()) {
+            let exit_str = match record.get(3) {
+                Some(exit) if !exit.is_empty() => std::str::from_utf8(exit)?,
+                _ => {
+                    report.unchecked_trades += 1;
+                    ""
+                }
+            };
+            if !exit_str.is_empty() {
+                let exit = NaiveDateTime::parse_from_str(exit_str, DATETIME_FORMAT)?;
+                if let Some(violation) = rules.violation(naive, exit) {
+                    report.record(violation);
+                    match rules.action {
+                        HoldingAction::Fail => {
+                            if listed_violations.len() < LISTED_VIOLATIONS {
+                                let line = record.position().map_or(0, |position| position.line());
+                                listed_violations.push(format!("line {} ({} to {}): {}", line, datetime_str, exit_str, violation));
+                            }
+                        }
+                        HoldingAction::Exclude => continue,
+                        HoldingAction::Truncate => trade = rules.truncate(trade),
+                    }
+                }
+            }
+        }
+
+        trades.push(TradeRecord::new(naive.date(), trade));
+
+        if report_progress {
+            let percent = rdr.position().byte() as usize * 100 / data.len();
+            if percent >= next_report {
+                info!("Loading {}: {}% ({} trades)", label, percent, trades.len());
+                next_report = percent / 10 * 10 + 10;
+            }
+        }
+    }
+
+    if let Some(report) = &holding_report {
+        if report.action == HoldingAction::Fail && report.violations() > 0 {
+            return Err(SimulationError::Data(format!(
+                "{}: {} trades break the holding rules ({} overnight, {} over a weekend), e.g. {}",
+                label,
+                report.violations(),
+                report.overnight_trades,
+                report.weekend_trades,
+                listed_violations.join("; ")
+            ))
+            .into());
+        }
+        if report.unchecked_trades > 0 {
+            warn!("{}: {} trades have no exit time and were not checked against the holding rules", label, report.unchecked_trades);
+        }
     }
 
-    Ok(trades)
+    if filter_report.as_ref().is_some_and(|report| report.kept_trades == 0) {
+        return Err(SimulationError::Data(format!("{}: the trade filter matches no trades", label)).into());
+    }
+
+    trades.shrink_to_fit();
+    Ok((trades, holding_report, filter_report))
 }
 
 #[allow(dead_code)]
@@ -85,7 +236,27 @@ pub fn generate_simulated_trades(
     multiplier: f64,
     round_trip_cost: Option<f64>,
 ) -> Vec<TradeRecord> {
-    let mut rng = rand::thread_rng();
+    generate_simulated_trades_with_rng(
+        avg_trades_per_day,
+        stop_loss,
+        take_profit,
+        win_percentage,
+        multiplier,
+        round_trip_cost,
+        &mut rand::thread_rng(),
+    )
+}
+
+/// Same as `generate_simulated_trades`, drawing from the given random number generator
+pub fn generate_simulated_trades_with_rng<R: Rng>(
+    avg_trades_per_day: f64,
+    stop_loss: f64,
+    take_profit: f64,
+    win_percentage: f64,
+    multiplier: f64,
+    round_trip_cost: Option<f64>,
+    rng: &mut R,
+) -> Vec<TradeRecord> {
     let poisson = Poisson::new(avg_trades_per_day).unwrap();
     let mut cost = 0.0;
     if let Some(rt_cost) = round_trip_cost{
@@ -105,8 +276,8 @@ pub fn generate_simulated_trades(
 
     let mut trades = Vec::new();
 
-    for day in 0..365 { // Simulating 365 days
-        let num_trades_today = poisson.sample(&mut rng) as usize;
+    for day in 0..SIMULATED_DAYS {
+        let num_trades_today = poisson.sample(rng) as usize;
         for _ in 0..num_trades_today {
             let datetime = start_date + chrono::Duration::days(day);
 
@@ -114,35 +285,326 @@ pub fn generate_simulated_trades(
             let win = rng.gen_bool(win_percentage / 100.0);
             let (return_value, max_opposite_excursion) = if win {
                 // Winning trade: use adverse move for max_opposite_excursion
-                let mae = normal_mae.sample(&mut rng).abs().min(stop_loss); // Cap MAE at stop-loss
+                let mae = normal_mae.sample(rng).abs().min(stop_loss); // Cap MAE at stop-loss
                 (take_profit * multiplier, mae * multiplier) // Take profit is the return value
             } else {
                 // Losing trade: use favorable move for max_opposite_excursion
-                let mfe = normal_mfe.sample(&mut rng).abs().min(take_profit); // Cap MFE at take-profit
+                let mfe = normal_mfe.sample(rng).abs().min(take_profit); // Cap MFE at take-profit
                 (-1.0 * stop_loss * multiplier, mfe * multiplier) // Stop loss is the return value (loss)
             };
 
-            trades.push(TradeRecord {
-                datetime,
-                trade: Trade {
+            trades.push(TradeRecord::new(
+                datetime.date_naive(),
+                Trade {
                     return_value: return_value - cost,
                     max_opposite_excursion: max_opposite_excursion - cost,
                 },
-            });
+            ));
         }
     }
     //println!("{:#?}", trades);
     trades
 }
 
-// Group trades by day and calculate the number of trades per day
-pub fn calculate_trades_per_day(trades: &Vec<TradeRecord>) -> HashMap<NaiveDate, usize> {
-    let mut trades_per_day = HashMap::new();
+/// How the trades of a simulated day are drawn from the pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSampling {
+    /// Every trade is drawn independently from the whole pool
+    #[default]
+    Independent,
+    /// Wins and losses follow a Markov chain fitted to consecutive trades within each
+    /// historical day; each trade is drawn from the wins or the losses of the pool
+    Markov,
+    /// Runs of `block_length` consecutive trades of the data, each starting at a random
+    /// trade, keep the serial correlation of returns within a block, across the day
+    /// boundaries of the data and of the simulation
+    Block,
+    /// Every simulated day replays all trades of a random historical day in their order
+    #[serde(alias = "historical")]
+    Day,
+}
 
-    for trade in trades {
-        let date = trade.datetime.date_naive(); // Get the date without time component
-        *trades_per_day.entry(date).or_insert(0) += 1; // Increment count of trades for this date
+impl TradeSampling {
+    pub const ALL: [TradeSampling; 4] = [TradeSampling::Independent, TradeSampling::Markov, TradeSampling::Block, TradeSampling::Day];
+}
+
+/// Trades per block of `TradeSampling::Block` when the config sets no `block_length`
+pub const DEFAULT_BLOCK_LENGTH: usize = 5;
+
+/// Where the block of `TradeSampling::Block` stands, so that a block left unfinished at the
+/// end of a simulated day continues on the next. One per stream of simulated days.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockCursor {
+    next: usize, // index of the next trade of the block
+    left: usize, // trades left in the block
+}
+
+impl FromStr for TradeSampling {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "independent" | "iid" => Ok(TradeSampling::Independent),
+            "markov" => Ok(TradeSampling::Markov),
+            "block" | "blocks" => Ok(TradeSampling::Block),
+            "day" | "days" | "historical" => Ok(TradeSampling::Day),
+            _ => Err("Unknown trade sampling (options: independent, markov, block, day)"),
+        }
+    }
+}
+
+/// Lag-1 dependence of trade outcomes within a day, estimated from the trade data
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct OutcomeChain {
+    /// Probability that the first trade of a day wins
+    pub first_win: f64,
+    /// Probability of a win right after a win
+    pub win_after_win: f64,
+    /// Probability of a win right after a loss
+    pub win_after_loss: f64,
+}
+
+/// Trades sorted by date with a precomputed index of each trading day's trades, built once
+/// at load time and shared by all iterations
+#[derive(Debug, Clone, Default)]
+pub struct TradePool {
+    trades: Vec<TradeRecord>,
+    // Only days with at least one trade: every simulated day trades, so the simulation
+    // clock has no idle stretches to skip
+    days: Vec<Range<usize>>,
+    // Indices of winning and losing trades, and their transitions, for Markov sampling
+    wins: Vec<u32>,
+    losses: Vec<u32>,
+    outcome_chain: OutcomeChain,
+    holding: Option<HoldingReport>,
+    filter: Option<TradeFilterReport>,
+    reweighting: Option<Reweighting>,
+}
+
+// Sampling weights of `outcome_reweighting` over all trades, over the winning and losing
+// trades for Markov sampling (`None` for a side with no weight left) and over the days,
+// each with the mean weight of its trades, for day sampling
+#[derive(Debug, Clone)]
+struct Reweighting {
+    reweights: Vec<OutcomeReweight>,
+    all: WeightedIndex<f64>,
+    wins: Option<WeightedIndex<f64>>,
+    losses: Option<WeightedIndex<f64>>,
+    days: WeightedIndex<f64>,
+    report: ReweightReport,
+}
+
+impl TradePool {
+    pub fn new(mut trades: Vec<TradeRecord>) -> Self {
+        // Stable, so trades within a day keep their order
+        trades.sort_by_key(|record| record.day);
+
+        let mut days = Vec::new();
+        let mut start = 0;
+        for i in 1..=trades.len() {
+            if i == trades.len() || trades[i].day != trades[start].day {
+                days.push(start..i);
+                start = i;
+            }
+        }
+
+        let is_win = |i: usize| trades[i].trade().return_value > 0.0;
+        let (wins, losses): (Vec<u32>, Vec<u32>) = (0..trades.len() as u32).partition(|&i| is_win(i as usize));
+
+        // Count outcomes of the first trade of each day and of consecutive trades within a day
+        let (mut first, mut first_wins) = (0, 0);
+        let (mut after_win, mut win_after_win, mut after_loss, mut win_after_loss) = (0, 0, 0, 0);
+        for day in &days {
+            first += 1;
+            first_wins += is_win(day.start) as usize;
+            for i in day.start + 1..day.end {
+                if is_win(i - 1) {
+                    after_win += 1;
+                    win_after_win += is_win(i) as usize;
+                } else {
+                    after_loss += 1;
+                    win_after_loss += is_win(i) as usize;
+                }
+            }
+        }
+        // Fall back to the overall win rate where there is nothing to estimate from
+        let win_rate = if trades.is_empty() { 0.0 } else { wins.len() as f64 / trades.len() as f64 };
+        let rate = |hits: usize, total: usize| if total == 0 { win_rate } else { hits as f64 / total as f64 };
+        let outcome_chain = OutcomeChain {
+            first_win: rate(first_wins, first),
+            win_after_win: rate(win_after_win, after_win),
+            win_after_loss: rate(win_after_loss, after_loss),
+        };
+
+        TradePool { trades, days, wins, losses, outcome_chain, holding: None, filter: None, reweighting: None }
+    }
+
+    /// Attach the holding rule check of the CSV the trades came from
+    pub fn with_holding_report(mut self, holding: Option<HoldingReport>) -> Self {
+        self.holding = holding;
+        self
+    }
+
+    pub fn holding_report(&self) -> Option<&HoldingReport> {
+        self.holding.as_ref()
+    }
+
+    /// Attach the trade filter applied to the CSV the trades came from
+    pub fn with_filter_report(mut self, filter: Option<TradeFilterReport>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn filter_report(&self) -> Option<&TradeFilterReport> {
+        self.filter.as_ref()
+    }
+
+    /// Resample the trades with the chances `reweights` give their outcomes instead of uniformly
+    pub fn with_reweighting(mut self, reweights: &[OutcomeReweight]) -> Result<Self, SimulationError> {
+        if reweights.is_empty() || self.trades.is_empty() {
+            return Ok(self);
+        }
+        let returns: Vec<f64> = self.trades.iter().map(|record| record.trade().return_value).collect();
+        let (weights, report) = reweight::trade_weights(&returns, reweights).map_err(SimulationError::InvalidConfig)?;
+        let side = |indices: &[u32]| WeightedIndex::new(indices.iter().map(|&i| weights[i as usize])).ok();
+        let day_weights = self.days.iter().map(|day| weights[day.clone()].iter().sum::<f64>() / day.len() as f64);
+        let invalid = |e| SimulationError::InvalidConfig(format!("outcome_reweighting: {}", e));
+        self.reweighting = Some(Reweighting {
+            reweights: reweights.to_vec(),
+            all: WeightedIndex::new(&weights).map_err(invalid)?,
+            wins: side(&self.wins),
+            losses: side(&self.losses),
+            days: WeightedIndex::new(day_weights).map_err(invalid)?,
+            report,
+        });
+        Ok(self)
     }
 
-    trades_per_day
+    pub fn reweight_report(&self) -> Option<&ReweightReport> {
+        self.reweighting.as_ref().map(|reweighting| &reweighting.report)
+    }
+
+    pub fn len(&self) -> usize {
+        self.trades.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trades.is_empty()
+    }
+
+    /// Pool without the first `trades` trades and the first `days` trading days of the data
+    /// (e.g. a strategy development period)
+    pub fn without_burn_in(&self, trades: usize, days: usize) -> Result<TradePool, SimulationError> {
+        let day_start = self.days.get(days).map_or(self.trades.len(), |day| day.start);
+        let start = day_start.max(trades).min(self.trades.len());
+        let pool = TradePool::new(self.trades[start..].to_vec())
+            .with_holding_report(self.holding.clone())
+            .with_filter_report(self.filter.clone());
+        // Percentile buckets are taken again over the remaining trades
+        match &self.reweighting {
+            Some(reweighting) => pool.with_reweighting(&reweighting.reweights),
+            None => Ok(pool),
+        }
+    }
+
+    /// Approximate heap memory of the pool in bytes, for caches bounded by memory
+    pub fn memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+        let reweighting = self.reweighting.as_ref().map_or(0, |_| {
+            // Cumulative weights over all trades, the two sides and the days
+            2 * self.trades.len() * size_of::<f64>() + self.days.len() * size_of::<f64>()
+        });
+        self.trades.capacity() * size_of::<TradeRecord>()
+            + self.days.capacity() * size_of::<Range<usize>>()
+            + (self.wins.capacity() + self.losses.capacity()) * size_of::<u32>()
+            + reweighting
+    }
+
+    pub fn records(&self) -> &[TradeRecord] {
+        &self.trades
+    }
+
+    /// Number of trades of each trading day in the data, in date order
+    pub fn trades_per_day(&self) -> impl Iterator<Item = usize> + '_ {
+        self.days.iter().map(|day| day.len())
+    }
+
+    pub fn outcome_chain(&self) -> OutcomeChain {
+        self.outcome_chain
+    }
+
+    /// The trade count of a random historical day, filled with trades drawn as `sampling` says.
+    /// `block_length` is the number of trades per block of block sampling, and `block` the
+    /// block the previous day of the same stream left off at.
+    pub fn sample_trades<R: Rng>(
+        &self,
+        sampling: TradeSampling,
+        block_length: usize,
+        block: &mut BlockCursor,
+        rng: &mut R,
+    ) -> Vec<Trade> {
+        let day = match (sampling, &self.reweighting) {
+            (TradeSampling::Day, Some(reweighting)) => self.days.get(reweighting.days.sample(rng)),
+            _ => self.days.choose(rng),
+        };
+        let Some(day) = day.cloned() else {
+            return Vec::new();
+        };
+        let num_trades = day.len();
+        match sampling {
+            TradeSampling::Independent => (0..num_trades)
+                .map(|_| match &self.reweighting {
+                    Some(reweighting) => self.trades[reweighting.all.sample(rng)].trade(),
+                    None => self.trades.choose(rng).unwrap().trade(),
+                })
+                .collect(),
+            TradeSampling::Markov => {
+                let mut trades = Vec::with_capacity(num_trades);
+                let mut p_win = self.outcome_chain.first_win;
+                for _ in 0..num_trades {
+                    // A side of the pool can be empty (e.g. no losing trades at all)
+                    let (no_wins, no_losses) = match &self.reweighting {
+                        Some(reweighting) => (reweighting.wins.is_none(), reweighting.losses.is_none()),
+                        None => (self.wins.is_empty(), self.losses.is_empty()),
+                    };
+                    let win = match (no_wins, no_losses) {
+                        (true, _) => false,
+                        (_, true) => true,
+                        _ => rng.gen_bool(p_win),
+                    };
+                    let side = if win { &self.wins } else { &self.losses };
+                    let index = match &self.reweighting {
+                        Some(reweighting) => {
+                            let weighted = if win { &reweighting.wins } else { &reweighting.losses };
+                            side[weighted.as_ref().unwrap().sample(rng)]
+                        }
+                        None => *side.choose(rng).unwrap(),
+                    };
+                    trades.push(self.trades[index as usize].trade());
+                    p_win = if win { self.outcome_chain.win_after_win } else { self.outcome_chain.win_after_loss };
+                }
+                trades
+            }
+            TradeSampling::Block => {
+                // Blocks wrap around from the last trade of the data to the first
+                let mut trades = Vec::with_capacity(num_trades);
+                while trades.len() < num_trades {
+                    if block.left == 0 {
+                        block.next = match &self.reweighting {
+                            Some(reweighting) => reweighting.all.sample(rng),
+                            None => rng.gen_range(0..self.trades.len()),
+                        };
+                        block.left = block_length.max(1);
+                    }
+                    let length = block.left.min(num_trades - trades.len());
+                    trades.extend((block.next..block.next + length).map(|i| self.trades[i % self.trades.len()].trade()));
+                    block.next = (block.next + length) % self.trades.len();
+                    block.left -= length;
+                }
+                trades
+            }
+            TradeSampling::Day => self.trades[day].iter().map(TradeRecord::trade).collect(),
+        }
+    }
 }
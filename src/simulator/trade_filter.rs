@@ -0,0 +1,279 @@
+// src/simulator/trade_filter.rs
+use chrono::{Datelike, NaiveDateTime, Timelike, Weekday};
+use csv::ByteRecord;
+use serde::{Serialize, Deserialize};
+
+// Fields derived from the entry time, available unless a CSV column has the same name
+const DERIVED_FIELDS: [&str; 3] = ["dow", "hour", "date"];
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Column(usize),
+    DayOfWeek,
+    Hour,
+    Date,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Literal {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Compare(Field, Op, Literal),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Text(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+/// A filter expression over the columns of a CSV, e.g. `setup == "ORB" && dow != "Fri"`,
+/// selecting the trades that enter the resampling pool
+///
+/// Columns are named by their header, lowercased with every run of other characters than
+/// letters and digits replaced by `_` (`Max Opposite Excursion` is `max_opposite_excursion`).
+/// `dow` (`Mon` to `Sun`), `hour` (0 to 23) and `date` (`2024-09-12`) are derived from the
+/// entry time. Comparisons with a number compare numerically, anything else compares text.
+#[derive(Debug, Clone)]
+pub struct TradeFilter {
+    expr: Expr,
+}
+
+impl TradeFilter {
+    /// Parse `expression` against the header row of a CSV
+    pub fn compile(expression: &str, headers: &ByteRecord) -> Result<Self, String> {
+        let columns: Vec<String> = headers.iter().map(|header| column_name(&String::from_utf8_lossy(header))).collect();
+        let mut parser = Parser { tokens: tokenize(expression)?, position: 0, columns: &columns };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(format!("unexpected {:?} in trade filter", token));
+        }
+        Ok(TradeFilter { expr })
+    }
+
+    /// Whether the trade in `record`, entered at `entry`, passes the filter
+    pub fn matches(&self, record: &ByteRecord, entry: NaiveDateTime) -> bool {
+        evaluate(&self.expr, record, entry)
+    }
+}
+
+fn column_name(header: &str) -> String {
+    let mut name = String::new();
+    for c in header.trim().chars() {
+        if c.is_alphanumeric() {
+            name.extend(c.to_lowercase());
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    name.trim_end_matches('_').to_string()
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('"' | '\'', _) => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&d| d == c)
+                    .ok_or("unterminated string in trade filter")?;
+                (Token::Text(chars[i + 1..i + 1 + end].iter().collect()), end + 2)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let length = 1 + chars[i + 1..].iter().take_while(|d| d.is_ascii_digit() || **d == '.').count();
+                let text: String = chars[i..i + length].iter().collect();
+                let number = text.parse().map_err(|_| format!("invalid number {} in trade filter", text))?;
+                (Token::Number(number), length)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let length = chars[i..].iter().take_while(|d| d.is_alphanumeric() || **d == '_').count();
+                (Token::Ident(chars[i..i + length].iter().collect()), length)
+            }
+            _ => return Err(format!("unexpected '{}' in trade filter", c)),
+        };
+        tokens.push(token);
+        i += length;
+    }
+    Ok(tokens)
+}
+
+// Recursive descent parser: `||` binds looser than `&&`, which binds looser than `!`
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    columns: &'a [String],
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.position) == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing ')' in trade filter".into());
+                }
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => {
+                let field = self.field(&name)?;
+                let op = match self.next() {
+                    Some(Token::Op(op)) => op,
+                    _ => return Err(format!("expected a comparison after {} in trade filter", name)),
+                };
+                let literal = match self.next() {
+                    Some(Token::Number(number)) => Literal::Number(number),
+                    Some(Token::Text(text)) => Literal::Text(text),
+                    _ => return Err(format!("expected a number or a quoted string after {} in trade filter", name)),
+                };
+                Ok(Expr::Compare(field, op, literal))
+            }
+            Some(token) => Err(format!("unexpected {:?} in trade filter", token)),
+            None => Err("trade filter ends unexpectedly".into()),
+        }
+    }
+
+    fn field(&self, name: &str) -> Result<Field, String> {
+        let name = name.to_lowercase();
+        if let Some(column) = self.columns.iter().position(|column| *column == name) {
+            return Ok(Field::Column(column));
+        }
+        match name.as_str() {
+            "dow" => Ok(Field::DayOfWeek),
+            "hour" => Ok(Field::Hour),
+            "date" => Ok(Field::Date),
+            _ => Err(format!(
+                "unknown trade filter field {} (options: {})",
+                name,
+                self.columns.iter().map(String::as_str).chain(DERIVED_FIELDS).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+fn day_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Mon",
+        Weekday::Tue => "Tue",
+        Weekday::Wed => "Wed",
+        Weekday::Thu => "Thu",
+        Weekday::Fri => "Fri",
+        Weekday::Sat => "Sat",
+        Weekday::Sun => "Sun",
+    }
+}
+
+fn evaluate(expr: &Expr, record: &ByteRecord, entry: NaiveDateTime) -> bool {
+    match expr {
+        Expr::Not(expr) => !evaluate(expr, record, entry),
+        Expr::And(left, right) => evaluate(left, record, entry) && evaluate(right, record, entry),
+        Expr::Or(left, right) => evaluate(left, record, entry) || evaluate(right, record, entry),
+        Expr::Compare(field, op, literal) => {
+            let value = match field {
+                Field::Column(column) => String::from_utf8_lossy(record.get(*column).unwrap_or_default()).trim().to_string(),
+                Field::DayOfWeek => day_name(entry.weekday()).to_string(),
+                Field::Hour => entry.hour().to_string(),
+                Field::Date => entry.date().to_string(),
+            };
+            let ordering = match literal {
+                // Values that are not numbers never match a numeric comparison
+                Literal::Number(number) => match value.parse::<f64>().ok().and_then(|value| value.partial_cmp(number)) {
+                    Some(ordering) => ordering,
+                    None => return false,
+                },
+                Literal::Text(text) => value.as_str().cmp(text.as_str()),
+            };
+            match op {
+                Op::Eq => ordering.is_eq(),
+                Op::Ne => ordering.is_ne(),
+                Op::Lt => ordering.is_lt(),
+                Op::Le => ordering.is_le(),
+                Op::Gt => ordering.is_gt(),
+                Op::Ge => ordering.is_ge(),
+            }
+        }
+    }
+}
+
+/// Trades the trade filter kept in and left out of the resampling pool
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeFilterReport {
+    pub expression: String,
+    pub kept_trades: usize,
+    pub excluded_trades: usize,
+}
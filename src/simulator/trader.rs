@@ -1,7 +1,22 @@
+use chrono::NaiveDate;
 use log::debug;
-use serde::{Serialize, Deserialize};
-use super::prop_account::{create_account, AccountStatus, AccountType, PropAccount};
+use rand::Rng;
+use serde::{Deserializer, Serialize, Deserialize};
+use std::str::FromStr;
+use super::prop_account::{AccountFactory, AccountStatus, DailyLossLimit, PropAccount, RuleChange, RuleOverrides, WithdrawalLimit};
 use super::trade_data::Trade;
+use super::ledger::Ledger;
+use super::triggers::RuleTriggers;
+use super::calendar;
+use super::news::NewsBlackout;
+use super::insurance::Insurance;
+use super::time_limit::{EvalTimeLimit, ExpiryAction};
+
+/// Trading days counted as one month for income reporting
+pub const TRADING_DAYS_PER_MONTH: u64 = 21;
+
+/// Trading days counted as one week for P&L reporting
+pub const TRADING_DAYS_PER_WEEK: u64 = 5;
 
 
 #[derive(Debug)]
@@ -9,6 +24,102 @@ pub struct BankAccount {
     pub balance: f64,  
 }
 
+/// Fees charged on each payout when it is credited to the bank account
+/// (wire, crypto, payment processor fees)
+#[derive(Debug, Clone, Default)]
+pub struct PayoutFees {
+    pub fixed: f64,
+    pub percent: f64, // percentage of the payout amount, e.g. 2.5 for 2.5%
+}
+
+impl PayoutFees {
+    pub fn fee_for(&self, amount: f64) -> f64 {
+        (self.fixed + amount * self.percent / 100.0).min(amount)
+    }
+}
+
+/// Monthly platform and data feed costs, paid from the bank account at the start of
+/// each month of the account (calendar months from its purchase date when known),
+/// independently of the firm's fees
+#[derive(Debug, Clone, Default)]
+pub struct PlatformFee {
+    pub monthly: f64,
+    pub funded_only: bool, // only paid in months the account is funded
+}
+
+/// The trader's own money available for account purchases and fees, in the home currency.
+/// A payment that would take the bankroll plus the cash flow so far below `floor` cannot
+/// be made and ends the run as `OutOfFunds`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bankroll {
+    pub starting: f64,
+    pub floor: f64,
+}
+
+/// One withdrawal of a run with `audit_withdrawals`, with the rule that set its amount, to
+/// tell why simulated payouts differ from those received at the firm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalAudit {
+    pub day: u64, // simulation day of the withdrawal
+    pub payout: u32, // number of the payout in the run
+    pub balance: f64, // prop account balance before the withdrawal
+    pub amount: f64, // USD withdrawn from the prop account, before the split and fees
+    pub limit: WithdrawalLimit,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub consistency_fraction: Option<f64>, // consistency rule the withdrawal passed, if any
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub denied: bool, // the firm denied the payout and nothing was withdrawn
+}
+
+/// Converts USD amounts (account costs and payouts) into the trader's home currency,
+/// charging an FX fee on every conversion
+#[derive(Debug, Clone)]
+pub struct CurrencyConversion {
+    pub rates: Vec<f64>, // home currency per USD by simulation day; the last rate holds afterwards
+    pub fee_percent: f64,
+}
+
+impl Default for CurrencyConversion {
+    fn default() -> Self {
+        CurrencyConversion {
+            rates: vec![1.0],
+            fee_percent: 0.0,
+        }
+    }
+}
+
+impl CurrencyConversion {
+    pub fn rate_on(&self, day: u64) -> f64 {
+        self.rates
+            .get(day as usize)
+            .or(self.rates.last())
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    // Home currency needed to pay a USD amount, and the FX fee included in it
+    pub fn pay(&self, usd_amount: f64, day: u64) -> (f64, f64) {
+        let converted = usd_amount * self.rate_on(day);
+        let fee = converted * self.fee_percent / 100.0;
+        (converted + fee, fee)
+    }
+
+    // Home currency received for a USD amount, and the FX fee deducted from it
+    pub fn receive(&self, usd_amount: f64, day: u64) -> (f64, f64) {
+        let converted = usd_amount * self.rate_on(day);
+        let fee = converted * self.fee_percent / 100.0;
+        (converted - fee, fee)
+    }
+}
+
+// A payout requested from the firm and credited to the bank account once processed
+#[derive(Debug, Clone)]
+struct PendingPayout {
+    due_day: u64, // simulation day it is credited on
+    amount: f64, // USD withdrawn from the account
+    share: f64, // the trader's share of it after the profit split
+}
+
 // Struct representing the user, with a bank account and FTT account
 pub struct Trader {
     pub bank_account: BankAccount,
@@ -16,8 +127,57 @@ pub struct Trader {
     max_trades_per_day: Option<u64>,    //should be positive if Some
     daily_profit_target: Option<f64>, //should be positive if Some
     daily_stop_loss: Option<f64>, //should be negative if Some
+    daily_stop_loss_percent: Option<f64>, // of the prior day's closing balance
+    account_size: f64, // nominal starting balance, for percentage-based limits
+    contracts: Option<u32>, // size of the trades, scaled down to the account's scaling plan
     max_simulation_days: u64,
     max_payouts: u8,
+    payout_fees: PayoutFees,
+    platform_fee: PlatformFee,
+    platform_months_paid: u64, // months of the account's life already charged the platform fee
+    purchase_date: Option<NaiveDate>, // puts the account's trading days on the calendar
+    purchase_price: Option<f64>, // USD actually paid for the account instead of its list price
+    terminal_payout_fraction: Option<f64>, // of the withdrawable balance, credited when the run times out
+    payout_delay_days: Option<u64>, // overrides the account's processing delay of payouts
+    payout_denial_probability: Option<f64>, // overrides the account's chance of denying a payout
+    pending_payouts: Vec<PendingPayout>, // requested, not yet credited
+    currency_conversion: CurrencyConversion,
+    pub ledger: Ledger,
+    pub monthly_income: Vec<f64>, // net bank account cash flow per month of trading days
+    pub weekly_pnl: Vec<f64>, // prop account trading P&L per week of trading days
+    pub monthly_pnl: Vec<f64>, // prop account trading P&L per month of trading days
+    pub worst_trade: Option<f64>, // lowest return of a single trade over the run
+    pub worst_day: Option<f64>, // lowest trading P&L of a day over the run
+    start_day: u64, // trading day the account was bought on, for accounts started later in a cohort
+    pub min_drawdown_buffer: f64, // closest the balance came to the loss balance
+    pub buffer_at_first_payout: Option<f64>,
+    pub payouts: u32, // payouts received over the run
+    pub funded_day: Option<u64>, // simulation day the first evaluation of the run was passed
+    pub first_payout_day: Option<u64>, // simulation day of the first payout of the run
+    last_payout_day: Option<u64>, // simulation day of the latest payout of the current account, for the payout cadence
+    account_paid_out: f64, // USD paid out by the current account before the split, for its profit split tiers
+    payout_period_pnl: Vec<f64>, // daily P&L of the funded account since its latest payout, for the profitable days rule
+    continue_after_max_payouts: bool,
+    pub max_payouts_day: Option<u64>,
+    pub rule_triggers: RuleTriggers, // simulation day `max_payouts` was reached when trading continues past it
+    rule_timeline: Vec<RuleChange>, // rule changes still to come, in order
+    news_blackout: Option<NewsBlackout>, // news days, placed on the calendar of the purchase date
+    bankroll: Option<Bankroll>,
+    out_of_funds: bool, // a payment could not be made
+    insurance: Option<Insurance>,
+    replacement_account: Option<AccountFactory>, // new evaluations, for covered busts, resets and expired evaluations
+    resets_allowed: u8, // new evaluations bought after busts the insurance does not cover
+    reset_cost: Option<f64>, // USD per reset, the account price if not set
+    pub resets_used: u32,
+    eval_time_limit: Option<EvalTimeLimit>,
+    eval_start_day: u64, // simulation day the current evaluation started
+    extensions_used: u32, // extensions bought for the current evaluation
+    pub eval_extensions: u32, // extensions bought over the run
+    pub eval_expiries: u32, // evaluations that ran out of time over the run
+    pub replacements_used: u32,
+    applied_rules: Vec<RuleOverrides>, // rule changes already made, for replacement accounts
+    audit_withdrawals: bool,
+    pub withdrawals: Vec<WithdrawalAudit>, // recorded with `audit_withdrawals` only
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -25,6 +185,71 @@ pub enum EndOfGame {
     Busted,
     TimeOut,
     MaxPayouts,
+    /// The trader could not pay for a fee from the bankroll
+    OutOfFunds,
+    /// The evaluation ran out of time and `eval_time_limit` ends the run
+    EvalExpired,
+}
+
+impl EndOfGame {
+    pub const ALL: [EndOfGame; 5] = [EndOfGame::Busted, EndOfGame::TimeOut, EndOfGame::MaxPayouts, EndOfGame::OutOfFunds, EndOfGame::EvalExpired];
+}
+
+impl FromStr for EndOfGame {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "busted" => Ok(EndOfGame::Busted),
+            "timeout" => Ok(EndOfGame::TimeOut),
+            "maxpayouts" => Ok(EndOfGame::MaxPayouts),
+            "outoffunds" => Ok(EndOfGame::OutOfFunds),
+            "evalexpired" => Ok(EndOfGame::EvalExpired),
+            _ => Err(format!("Unknown end state {} (options: All, Busted, TimeOut, MaxPayouts, OutOfFunds, EvalExpired)", s.trim())),
+        }
+    }
+}
+
+/// End states a `condition_end_state` covers: `All`, states separated by commas for their
+/// union (`Busted,TimeOut`), or states to leave out prefixed with `!` (`!Busted` for every
+/// run that did not bust)
+pub fn conditioned_end_states(condition: &str) -> Result<Vec<EndOfGame>, String> {
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    for term in condition.split(',').map(str::trim).filter(|term| !term.is_empty()) {
+        let (states, term) = match term.strip_prefix('!') {
+            Some(term) => (&mut excluded, term),
+            None => (&mut included, term),
+        };
+        if term.trim().eq_ignore_ascii_case("all") {
+            states.extend(EndOfGame::ALL);
+        } else {
+            states.push(term.parse()?);
+        }
+    }
+    // Only exclusions: every other state
+    if included.is_empty() {
+        included.extend(EndOfGame::ALL);
+    }
+    Ok(EndOfGame::ALL
+        .into_iter()
+        .filter(|state| included.contains(state) && !excluded.contains(state))
+        .collect())
+}
+
+// `condition_end_state` as one string or a list of states, e.g. `["Busted", "TimeOut"]`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EndStateCondition {
+    One(String),
+    List(Vec<String>),
+}
+
+pub fn deserialize_condition_end_state<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match EndStateCondition::deserialize(deserializer)? {
+        EndStateCondition::One(condition) => condition,
+        EndStateCondition::List(states) => states.join(","),
+    })
 }
 
 #[derive(Debug)]
@@ -41,23 +266,23 @@ pub struct TradingDayResult{
 
 impl Trader {
 
-    // Create a new Trader by specifying only the FTT account type
-    pub fn new(account_type: AccountType, 
+    // Create a new Trader trading the given (freshly created) prop account
+    pub fn new(prop_account: Box<dyn PropAccount + Send + Sync>,
         max_trades_per_day: Option<u64>, 
         daily_profit_target: Option<f64>, 
         daily_stop_loss: Option<f64>,
         max_simulation_days: u64,
         max_payouts: u8,
     ) -> Self {
-        // Create the PropAccount based on the account type
-        let prop_account: Box<dyn PropAccount + Send +Sync> = create_account(account_type);
-        // Set the bank account balance to the negative cost of the FTT account
         let bank_account = BankAccount {
-            balance: -prop_account.get_cost(),
+            balance: 0.0,
         };
 
         //TODO: ensure stop/pt / trades per day are properly signed if Some
 
+        // An account without an evaluation is funded from the start
+        let funded_day = prop_account.is_funded().then_some(0);
+
         // Return the new user with both accounts initialized
         Self {
             bank_account,
@@ -65,13 +290,500 @@ impl Trader {
             max_trades_per_day,
             daily_profit_target,
             daily_stop_loss,
+            daily_stop_loss_percent: None,
+            account_size: 0.0,
+            contracts: None,
             max_simulation_days,
             max_payouts,
+            payout_fees: PayoutFees::default(),
+            platform_fee: PlatformFee::default(),
+            platform_months_paid: 0,
+            purchase_date: None,
+            purchase_price: None,
+            terminal_payout_fraction: None,
+            payout_delay_days: None,
+            payout_denial_probability: None,
+            pending_payouts: Vec::new(),
+            currency_conversion: CurrencyConversion::default(),
+            ledger: Ledger::default(),
+            monthly_income: Vec::new(),
+            weekly_pnl: Vec::new(),
+            monthly_pnl: Vec::new(),
+            worst_trade: None,
+            worst_day: None,
+            start_day: 0,
+            min_drawdown_buffer: f64::INFINITY,
+            buffer_at_first_payout: None,
+            payouts: 0,
+            funded_day,
+            first_payout_day: None,
+            last_payout_day: None,
+            account_paid_out: 0.0,
+            payout_period_pnl: Vec::new(),
+            continue_after_max_payouts: false,
+            max_payouts_day: None,
+            rule_triggers: RuleTriggers::default(),
+            rule_timeline: Vec::new(),
+            news_blackout: None,
+            bankroll: None,
+            out_of_funds: false,
+            insurance: None,
+            replacement_account: None,
+            resets_allowed: 0,
+            reset_cost: None,
+            resets_used: 0,
+            eval_time_limit: None,
+            eval_start_day: 0,
+            extensions_used: 0,
+            eval_extensions: 0,
+            eval_expiries: 0,
+            replacements_used: 0,
+            applied_rules: Vec::new(),
+            audit_withdrawals: false,
+            withdrawals: Vec::new(),
+        }
+        .with_initial_purchase()
+    }
+
+    // Buy the prop account: the bank account starts at its (negative) cost
+    fn with_initial_purchase(mut self) -> Self {
+        self.bank_account.balance = 0.0;
+        self.ledger = Ledger::default();
+        self.monthly_income.clear();
+        self.weekly_pnl.clear();
+        self.monthly_pnl.clear();
+        self.worst_trade = None;
+        self.worst_day = None;
+        self.out_of_funds = false;
+        let price = self.purchase_price.unwrap_or_else(|| self.prop_account.get_cost());
+        self.ledger.eval_fees += self.pay_usd(price).unwrap_or(0.0);
+        if let Some(insurance) = &self.insurance {
+            let cost = insurance.cost;
+            self.ledger.insurance_fees += self.pay_usd(cost).unwrap_or(0.0);
         }
+        self.track_drawdown_buffer();
+        self
+    }
+
+    pub fn with_payout_fees(mut self, payout_fees: PayoutFees) -> Self {
+        self.payout_fees = payout_fees;
+        self
     }
 
-    fn adj_trade_for_daily_stop_or_target(&self, trade: &mut Trade, daily_pnl_pretrade: f64) -> DailyStopTPStatus{
-        if let Some(daily_sl) = self.daily_stop_loss{
+    pub fn with_platform_fee(mut self, platform_fee: PlatformFee) -> Self {
+        self.platform_fee = platform_fee;
+        self
+    }
+
+    // Account bought on `purchase_date` (for calendar months) at `purchase_price` (e.g. a
+    // promo price); the account purchase made in `new` is re-booked
+    pub fn with_purchase(mut self, purchase_date: Option<NaiveDate>, purchase_price: Option<f64>) -> Self {
+        self.purchase_date = purchase_date;
+        self.purchase_price = purchase_price;
+        self.with_initial_purchase()
+    }
+
+    // Pay fees only while the bankroll allows; the account purchase made in `new` is re-booked
+    pub fn with_bankroll(mut self, bankroll: Option<Bankroll>) -> Self {
+        self.bankroll = bankroll;
+        self.with_initial_purchase()
+    }
+
+    // Insurance bought with the account, replacing covered busts with accounts from
+    // `replacement_account`; the account purchase made in `new` is re-booked
+    pub fn with_insurance(mut self, insurance: Option<Insurance>, replacement_account: AccountFactory) -> Self {
+        if insurance.is_some() {
+            self.replacement_account = Some(replacement_account);
+        }
+        self.insurance = insurance;
+        self.with_initial_purchase()
+    }
+
+    // Buy up to `resets_allowed` new evaluations from `new_evaluation` after busts, each for
+    // `reset_cost` or the account price
+    pub fn with_resets(mut self, resets_allowed: Option<u8>, reset_cost: Option<f64>, new_evaluation: AccountFactory) -> Self {
+        if let Some(resets_allowed) = resets_allowed.filter(|&resets| resets > 0) {
+            self.replacement_account = Some(new_evaluation);
+            self.resets_allowed = resets_allowed;
+        }
+        self.reset_cost = reset_cost;
+        self
+    }
+
+    // Deadline for passing the evaluation; expired evaluations are replaced with accounts
+    // from `new_evaluation` when the limit resets them
+    pub fn with_eval_time_limit(mut self, eval_time_limit: Option<EvalTimeLimit>, new_evaluation: AccountFactory) -> Self {
+        if eval_time_limit.is_some() {
+            self.replacement_account = Some(new_evaluation);
+        }
+        self.eval_time_limit = eval_time_limit;
+        self
+    }
+
+    // Sit out (or trade with inflated variance) on news days; needs a purchase date
+    pub fn with_news_blackout(mut self, news_blackout: Option<NewsBlackout>) -> Self {
+        self.news_blackout = news_blackout;
+        self
+    }
+
+    // Assume this fraction of the withdrawable balance is eventually withdrawn when a run times out
+    pub fn with_terminal_payout_fraction(mut self, terminal_payout_fraction: Option<f64>) -> Self {
+        self.terminal_payout_fraction = terminal_payout_fraction;
+        self
+    }
+
+    // Trading days between requesting a payout and receiving it, instead of the account's own
+    pub fn with_payout_delay_days(mut self, payout_delay_days: Option<u64>) -> Self {
+        self.payout_delay_days = payout_delay_days;
+        self
+    }
+
+    // Chance that the firm denies a requested payout, instead of the account's own
+    pub fn with_payout_denial_probability(mut self, payout_denial_probability: Option<f64>) -> Self {
+        self.payout_denial_probability = payout_denial_probability;
+        self
+    }
+
+    // Book cash flows from `start_day` on; the account purchase made in `new` is re-booked
+    pub fn with_start_day(mut self, start_day: u64) -> Self {
+        self.start_day = start_day;
+        self.with_initial_purchase()
+    }
+
+    // Keep the bank account in the home currency; the account purchase made in `new` is re-priced
+    pub fn with_currency_conversion(mut self, currency_conversion: CurrencyConversion) -> Self {
+        self.currency_conversion = currency_conversion;
+        self.with_initial_purchase()
+    }
+
+    // Keep trading and withdrawing after `max_payouts`, which then only marks a milestone
+    pub fn with_continue_after_max_payouts(mut self, continue_after_max_payouts: bool) -> Self {
+        self.continue_after_max_payouts = continue_after_max_payouts;
+        self
+    }
+
+    // Record every withdrawal with the rule that set its amount
+    pub fn with_withdrawal_audit(mut self, audit_withdrawals: bool) -> Self {
+        self.audit_withdrawals = audit_withdrawals;
+        self
+    }
+
+    // Personal daily stop as a percentage of the prior day's closing balance
+    pub fn with_daily_stop_loss_percent(mut self, daily_stop_loss_percent: Option<f64>) -> Self {
+        self.daily_stop_loss_percent = daily_stop_loss_percent;
+        self
+    }
+
+    // Starting balance the account's P&L is added to for percentage-based limits
+    pub fn with_account_size(mut self, account_size: f64) -> Self {
+        self.account_size = account_size;
+        self.prop_account.set_account_size(account_size);
+        self
+    }
+
+    // Contracts the trades were taken with, for accounts with a scaling plan
+    pub fn with_contracts(mut self, contracts: Option<u32>) -> Self {
+        self.contracts = contracts;
+        self
+    }
+
+    // Share of a trade's size the account's scaling plan allows at the current balance
+    fn allowed_size(&self) -> Option<f64> {
+        let contracts = self.contracts?;
+        let allowed = self.prop_account.max_contracts()?;
+        (allowed < contracts).then(|| allowed as f64 / contracts as f64)
+    }
+
+    // Tightest of the fixed and the percentage daily stop for a day starting at `prior_day_balance`
+    fn daily_stop_loss_today(&self, prior_day_balance: f64) -> Option<f64> {
+        let percent_stop = self
+            .daily_stop_loss_percent
+            .map(|percent| -DailyLossLimit::Percent(percent).allowed_loss(prior_day_balance));
+        match (self.daily_stop_loss, percent_stop) {
+            (Some(fixed), Some(percent)) => Some(fixed.max(percent)),
+            (fixed, percent) => fixed.or(percent),
+        }
+    }
+
+    // Rule changes of the account over time (e.g. no consistency rule after the first payout)
+    pub fn with_rule_timeline(mut self, rule_timeline: Vec<RuleChange>) -> Self {
+        self.rule_timeline = rule_timeline;
+        self.apply_due_rule_changes();
+        self
+    }
+
+    fn apply_due_rule_changes(&mut self) {
+        let (days, payouts) = (self.prop_account.get_simulation_days(), self.payouts);
+        let prop_account = &mut self.prop_account;
+        let applied_rules = &mut self.applied_rules;
+        self.rule_timeline.retain(|change| {
+            let due = change.after.reached(days, payouts);
+            if due {
+                debug!("Rule change after {:?}: {:?}", change.after, change.rules);
+                prop_account.apply_rules(&change.rules);
+                applied_rules.push(change.rules.clone());
+            }
+            !due
+        });
+    }
+
+    // Pay a USD amount (account purchase) from the bank account, returning the
+    // price in the home currency (the FX fee is booked separately), or `None` if the
+    // bankroll cannot cover it
+    fn pay_usd(&mut self, usd_amount: f64) -> Option<f64> {
+        let day = self.current_day();
+        let (amount, fee) = self.currency_conversion.pay(usd_amount, day);
+        if let Some(bankroll) = self.bankroll {
+            if bankroll.starting + self.bank_account.balance - amount < bankroll.floor {
+                debug!("Out of funds: cannot pay {:.2}, bank balance: {:.2}", amount, self.bank_account.balance);
+                self.out_of_funds = true;
+                return None;
+            }
+        }
+        self.credit_bank(-amount, day);
+        self.ledger.conversion_fees += fee;
+        Some(amount - fee)
+    }
+
+    // Charge the platform fee once for each month of the account's life, from the first day
+    // of the month (or the first funded day of it). With a purchase date the months are
+    // calendar months from the purchase, otherwise blocks of trading days.
+    fn pay_platform_fee(&mut self) {
+        if self.platform_fee.monthly <= 0.0 || (self.platform_fee.funded_only && !self.prop_account.is_funded()) {
+            return;
+        }
+        let days = self.prop_account.get_simulation_days();
+        let month = match self.purchase_date {
+            Some(purchase_date) => calendar::billing_month(purchase_date, calendar::trading_date(purchase_date, days)),
+            None => days / TRADING_DAYS_PER_MONTH,
+        };
+        if month >= self.platform_months_paid {
+            self.platform_months_paid = month + 1;
+            self.ledger.platform_fees += self.pay_usd(self.platform_fee.monthly).unwrap_or(0.0);
+        }
+    }
+
+    // Apply the news day action if today is a news day
+    fn apply_news_blackout(&mut self, trades_today: &mut Vec<Trade>) {
+        let (Some(news_blackout), Some(purchase_date)) = (&self.news_blackout, self.purchase_date) else {
+            return;
+        };
+        let today = calendar::trading_date(purchase_date, self.prop_account.get_simulation_days());
+        if news_blackout.is_news_day(today) {
+            debug!("News day {}", today);
+            news_blackout.apply(trades_today);
+            self.rule_triggers.news_days += 1;
+        }
+    }
+
+    // The account busted during the day: unless the insurance covers it or a reset is
+    // bought, the run ends
+    fn busted(&mut self) -> TradingDayResult {
+        if self.replace_busted_account() || self.reset_busted_account() {
+            let timed_out = self.prop_account.get_simulation_days() >= self.max_simulation_days;
+            return TradingDayResult{
+                end_of_game: timed_out.then_some(EndOfGame::TimeOut),
+            }
+        }
+        TradingDayResult{
+            end_of_game: Some(if self.out_of_funds { EndOfGame::OutOfFunds } else { EndOfGame::Busted }),
+        }
+    }
+
+    // Continue on a new evaluation from the next day if the insurance covers the bust and
+    // its replacement fee can be paid
+    fn replace_busted_account(&mut self) -> bool {
+        let (Some(insurance), Some(replacement_account)) = (&self.insurance, &self.replacement_account) else {
+            return false;
+        };
+        if self.replacements_used >= insurance.replacements || !insurance.covers.covers(self.prop_account.is_funded()) {
+            return false;
+        }
+        let (replacement_fee, account) = (insurance.replacement_fee, replacement_account());
+        if replacement_fee > 0.0 {
+            match self.pay_usd(replacement_fee) {
+                Some(paid) => self.ledger.reset_fees += paid,
+                None => return false,
+            }
+        }
+        debug!("Bust covered by the insurance, continuing on a new evaluation");
+        self.replacements_used += 1;
+        // The bust day is over; the replacement keeps the simulation's day count
+        self.switch_to_new_evaluation(account, self.prop_account.get_simulation_days() + 1);
+        true
+    }
+
+    // Buy a new evaluation from the next day while resets are left and the bankroll can
+    // pay for it
+    fn reset_busted_account(&mut self) -> bool {
+        let Some(new_evaluation) = &self.replacement_account else {
+            return false;
+        };
+        if self.resets_used >= self.resets_allowed as u32 {
+            return false;
+        }
+        let account = new_evaluation();
+        let price = self.reset_cost.unwrap_or_else(|| account.get_cost());
+        match self.pay_usd(price) {
+            Some(paid) => self.ledger.reset_fees += paid,
+            None => return false,
+        }
+        self.resets_used += 1;
+        debug!("Account busted, reset {} of {}: continuing on a new evaluation", self.resets_used, self.resets_allowed);
+        self.switch_to_new_evaluation(account, self.prop_account.get_simulation_days() + 1);
+        true
+    }
+
+    // Continue on `account`, a new evaluation with the rule changes made so far, from
+    // simulation day `day`
+    fn switch_to_new_evaluation(&mut self, mut account: Box<dyn PropAccount + Send + Sync>, day: u64) {
+        for rules in &self.applied_rules {
+            account.apply_rules(rules);
+        }
+        account.set_account_size(self.account_size);
+        for _ in 0..day {
+            account.increment_simulation_day();
+        }
+        self.prop_account = account;
+        self.last_payout_day = None;
+        self.track_drawdown_buffer();
+        self.start_evaluation();
+    }
+
+    // The current account starts a new evaluation today, with the full time limit
+    fn start_evaluation(&mut self) {
+        self.eval_start_day = self.prop_account.get_simulation_days();
+        self.extensions_used = 0;
+        self.payout_period_pnl.clear();
+        self.account_paid_out = 0.0;
+    }
+
+    // Calendar days between two simulation days of the account
+    fn calendar_days_between(&self, from: u64, to: u64) -> u64 {
+        match self.purchase_date {
+            Some(purchase_date) => {
+                (calendar::trading_date(purchase_date, to) - calendar::trading_date(purchase_date, from)).num_days() as u64
+            }
+            None => (to - from) * 7 / 5,
+        }
+    }
+
+    // Before the day's trading, extend an evaluation that has run out of time, or reset it
+    // or end the run once the extensions are used up. Payments the bankroll cannot cover
+    // leave the run out of funds.
+    fn enforce_eval_time_limit(&mut self) -> Option<EndOfGame> {
+        let limit = self.eval_time_limit.as_ref().filter(|_| !self.prop_account.is_funded())?;
+        let today = self.prop_account.get_simulation_days();
+        if self.calendar_days_between(self.eval_start_day, today) < limit.allowed_days(self.extensions_used) {
+            return None;
+        }
+        let limit = limit.clone();
+
+        if self.extensions_used < limit.extensions {
+            debug!("Evaluation out of time, buying extension {}", self.extensions_used + 1);
+            self.ledger.reset_fees += self.pay_usd(limit.extension_cost)?;
+            self.extensions_used += 1;
+            self.eval_extensions += 1;
+            return None;
+        }
+
+        self.eval_expiries += 1;
+        let new_evaluation = match (limit.on_expiry, &self.replacement_account) {
+            (ExpiryAction::Reset, Some(new_evaluation)) => new_evaluation,
+            _ => {
+                debug!("Evaluation expired");
+                return Some(EndOfGame::EvalExpired);
+            }
+        };
+        let account = new_evaluation();
+        let price = limit.reset_cost.unwrap_or_else(|| account.get_cost());
+        self.ledger.reset_fees += self.pay_usd(price)?;
+        debug!("Evaluation expired, continuing on a new evaluation");
+        self.switch_to_new_evaluation(account, today);
+        None
+    }
+
+    // Credit the trader's share of a payout to the bank account after payout and FX fees,
+    // returning its gross amount in the home currency
+    fn receive_payout(&mut self, amount: f64) -> f64 {
+        let share = self.trader_share(amount);
+        self.credit_payout(amount, share)
+    }
+
+    // The trader's share of a payout from the current account under its profit split
+    fn trader_share(&mut self, amount: f64) -> f64 {
+        let share = self.prop_account.profit_split().trader_share(self.account_paid_out, amount);
+        self.account_paid_out += amount;
+        share
+    }
+
+    // Credit the trader's `share` of a payout of `amount` today, returning the payout in the
+    // home currency
+    fn credit_payout(&mut self, amount: f64, share: f64) -> f64 {
+        let fee = self.payout_fees.fee_for(share);
+        let day = self.current_day();
+        let (received, conversion_fee) = self.currency_conversion.receive(share - fee, day);
+        // The payout is made at the end of the day that was just completed
+        self.credit_bank(received, day.saturating_sub(1));
+        let rate = self.currency_conversion.rate_on(day);
+        self.ledger.profit_split += (amount - share) * rate;
+        self.ledger.payout_fees += fee * rate;
+        self.ledger.conversion_fees += conversion_fee;
+        debug!(
+            "Withdrawal made: {:.2} (trader's share: {:.2}, fees: {:.2}, FX fees: {:.2}), bank balance after withdrawal: {:.2}",
+            amount, share, fee, conversion_fee, self.bank_account.balance
+        );
+        amount * rate
+    }
+
+    // Whether the firm's payout cadence allows a payout today
+    fn payout_window_open(&self) -> bool {
+        let cadence_days = self.prop_account.payout_cadence().trading_days();
+        self.last_payout_day
+            .is_none_or(|day| self.prop_account.get_simulation_days() - day >= cadence_days)
+    }
+
+    // Whether the payout period has the profitable days the firm requires
+    fn profitable_days_met(&self) -> bool {
+        self.prop_account
+            .min_profitable_days()
+            .is_none_or(|rule| rule.met(&self.payout_period_pnl))
+    }
+
+    fn track_drawdown_buffer(&mut self) {
+        self.min_drawdown_buffer = self.min_drawdown_buffer.min(self.prop_account.get_drawdown_buffer());
+    }
+
+    // Trading day in progress, counted from the start of the simulation (not of this account)
+    fn current_day(&self) -> u64 {
+        self.start_day + self.prop_account.get_simulation_days()
+    }
+
+    // Book a day's trading P&L in the week and month of the current trading day
+    fn record_pnl(&mut self, pnl: f64) {
+        self.worst_day = Some(self.worst_day.map_or(pnl, |worst| worst.min(pnl)));
+        let day = self.current_day();
+        for (periods, days) in [(&mut self.weekly_pnl, TRADING_DAYS_PER_WEEK), (&mut self.monthly_pnl, TRADING_DAYS_PER_MONTH)] {
+            let period = (day / days) as usize;
+            if periods.len() <= period {
+                periods.resize(period + 1, 0.0);
+            }
+            periods[period] += pnl;
+        }
+    }
+
+    // Apply a cash flow to the bank account, booking it in the month of trading day `day`
+    fn credit_bank(&mut self, amount: f64, day: u64) {
+        self.bank_account.balance += amount;
+        let month = (day / TRADING_DAYS_PER_MONTH) as usize;
+        if self.monthly_income.len() <= month {
+            self.monthly_income.resize(month + 1, 0.0);
+        }
+        self.monthly_income[month] += amount;
+    }
+
+    fn adj_trade_for_daily_stop_or_target(&self, trade: &mut Trade, daily_pnl_pretrade: f64, daily_stop_loss: Option<f64>) -> DailyStopTPStatus{
+        if let Some(daily_sl) = daily_stop_loss{
             if trade.return_value + daily_pnl_pretrade <= daily_sl { 
                 trade.return_value = daily_sl - daily_pnl_pretrade;
                 return DailyStopTPStatus::StopHit;
@@ -99,55 +811,135 @@ impl Trader {
 
     }
 
-    // given simulated trades for today, apply updates to account balance
-    pub fn trade_day(&mut self, trades_today: &mut Vec<Trade>) -> TradingDayResult {
+    // given simulated trades for today, apply updates to account balance; `rng` draws
+    // whether the firm denies a payout
+    pub fn trade_day(&mut self, trades_today: &mut Vec<Trade>, rng: &mut impl Rng) -> TradingDayResult {
+        let result = self.trade_account_day(trades_today, rng);
+        // The firm still pays the payouts it approved when the run ends
+        self.credit_pending_payouts(result.end_of_game.is_some());
+        result
+    }
+
+    // Credit the pending payouts due by today, or all of them when `settle`
+    fn credit_pending_payouts(&mut self, settle: bool) {
+        if self.pending_payouts.is_empty() {
+            return;
+        }
+        let today = self.prop_account.get_simulation_days();
+        let (due, pending): (Vec<PendingPayout>, Vec<PendingPayout>) =
+            std::mem::take(&mut self.pending_payouts).into_iter().partition(|payout| settle || payout.due_day <= today);
+        self.pending_payouts = pending;
+        for payout in due {
+            debug!("Payout of {:.2} due on day {} credited", payout.amount, payout.due_day);
+            let received = self.credit_payout(payout.amount, payout.share);
+            self.ledger.gross_payouts += received;
+            self.first_payout_day.get_or_insert(payout.due_day);
+        }
+    }
+
+    fn payout_delay(&self) -> u64 {
+        self.payout_delay_days.unwrap_or_else(|| self.prop_account.payout_delay_days())
+    }
+
+    // Draw whether the firm denies the payout requested today; nothing is drawn when it never does
+    fn payout_denied(&self, rng: &mut impl Rng) -> bool {
+        let probability = self.payout_denial_probability.unwrap_or_else(|| self.prop_account.payout_denial_probability());
+        probability > 0.0 && rng.gen_bool(probability)
+    }
+
+    fn trade_account_day(&mut self, trades_today: &mut Vec<Trade>, rng: &mut impl Rng) -> TradingDayResult {
 
         let mut daily_pnl = 0.0;
         let mut num_trades_today = 0;
+        let prior_day_balance = self.account_size + self.prop_account.get_current_balance();
+        let daily_stop_loss = self.daily_stop_loss_today(prior_day_balance);
 
         debug!("Starting a new trading day");
+        self.pay_platform_fee();
+        let expired = self.enforce_eval_time_limit();
+        if self.out_of_funds {
+            return TradingDayResult{
+                end_of_game: Some(EndOfGame::OutOfFunds),
+            }
+        }
+        if expired.is_some() {
+            return TradingDayResult{
+                end_of_game: expired,
+            }
+        }
+        self.apply_news_blackout(trades_today);
 
         for trade in trades_today.iter_mut(){
             //for a given trade:
             if let Some(max_trades) = self.max_trades_per_day{
                 if num_trades_today >= max_trades{
                     debug!("Reached max trades per day limit: {}", max_trades);
+                    self.rule_triggers.max_trades += 1;
                     break;
                 }
             }
+            // The scaling plan caps the contracts of the trade
+            if let Some(fraction) = self.allowed_size() {
+                trade.return_value *= fraction;
+                trade.max_opposite_excursion *= fraction;
+                self.rule_triggers.scaled_trades += 1;
+            }
             //do we adjust trade to account for daily stop/target?
             let daily_stop_tp_status = 
-                self.adj_trade_for_daily_stop_or_target(trade, daily_pnl);
+                self.adj_trade_for_daily_stop_or_target(trade, daily_pnl, daily_stop_loss);
             //did we blow account?
             let account_status = self.prop_account.process_trade(trade);
+            if let AccountStatus::Blown(ret) | AccountStatus::Active(ret) | AccountStatus::DailyLimitHit(ret) | AccountStatus::LockedForDay(ret) = account_status {
+                self.worst_trade = Some(self.worst_trade.map_or(ret, |worst| worst.min(ret)));
+            }
 
             match account_status {
                 AccountStatus::Blown(ret) =>{
+                    self.rule_triggers.max_drawdown += 1;
                     debug!("Trade executed, return: {:.2}, cumulative daily P&L: {:.2}", ret, daily_pnl+ret);
                     debug!("Account blown during trade, daily P&L: {:.2}, trades taken: {}", daily_pnl+ret, num_trades_today+1);
-                    return TradingDayResult{
-                        end_of_game: Some(EndOfGame::Busted),
-                    }
+                    self.record_pnl(daily_pnl + ret);
+                    return self.busted();
+                },
+                AccountStatus::DailyLimitHit(ret) =>{
+                    self.rule_triggers.firm_daily_loss_limit += 1;
+                    self.record_pnl(daily_pnl + ret);
+                    return self.busted();
+                },
+                // A soft breach closed the trade at the firm limit: skip the rest of the day
+                AccountStatus::LockedForDay(ret) =>{
+                    self.rule_triggers.firm_daily_loss_limit += 1;
+                    self.rule_triggers.soft_breaches += 1;
+                    daily_pnl += ret;
+                    self.track_drawdown_buffer();
+                    debug!("Account locked for the day, daily P&L: {:.2}", daily_pnl);
+                    break;
                 },
                 AccountStatus::Active(ret) =>{
                     daily_pnl += ret;
+                    self.track_drawdown_buffer();
                     debug!("Trade executed, return: {:.2}, cumulative daily P&L: {:.2}", ret, daily_pnl);
                 },
                 AccountStatus::PassedEval =>{
-                    self.bank_account.balance -= self.prop_account.get_funded_acct_cost();
+                    self.funded_day.get_or_insert(self.prop_account.get_simulation_days());
+                    self.record_pnl(daily_pnl);
+                    self.track_drawdown_buffer();
+                    self.ledger.activation_fees += self.pay_usd(self.prop_account.get_funded_acct_cost()).unwrap_or(0.0);
                     debug!("Passed eval, prop acct balance: {}", self.prop_account.get_current_balance());
                     return TradingDayResult{
-                        end_of_game: None,
+                        end_of_game: self.out_of_funds.then_some(EndOfGame::OutOfFunds),
                     }
                 }
             }
             //didnt blow acct if we got here. did we hit daily stop/target?
             match daily_stop_tp_status {
                 DailyStopTPStatus::TPHit => {
+                    self.rule_triggers.daily_target += 1;
                     debug!("Daily profit target hit with P&L: {:.2}", daily_pnl); 
                     break;
                 },
                 DailyStopTPStatus::StopHit => {
+                    self.rule_triggers.daily_stop += 1;
                     debug!("Daily stop loss hit with P&L: {:.2}", daily_pnl);
                     break;
                 },
@@ -156,8 +948,14 @@ impl Trader {
             num_trades_today += 1;
         }
         // Update account at the end of the day
+        self.record_pnl(daily_pnl);
+        if self.prop_account.is_funded() {
+            self.payout_period_pnl.push(daily_pnl);
+        }
         self.prop_account.update_end_of_day(daily_pnl);
         self.prop_account.increment_simulation_day();
+        self.track_drawdown_buffer();
+        self.apply_due_rule_changes();
 
         // Log the bank and ccount balances at the end of the trading day
         debug!(
@@ -165,20 +963,64 @@ impl Trader {
             daily_pnl, num_trades_today, self.bank_account.balance, self.prop_account.get_current_balance()
         );
         //can we make a withdrawal?
-        if let Some(amount) = self.prop_account.allowed_withdrawal_amount(){
-            let num_payouts = self.prop_account.make_withdrawal(amount);
-            self.bank_account.balance += amount;
-            debug!("Withdrawal made: {:.2}, bank balance after withdrawal: {:.2}", amount, self.bank_account.balance);
-            if num_payouts >= self.max_payouts{
-                debug!("Reached max payouts: {}, ending simulation for this trader.", self.max_payouts);
-                return TradingDayResult{
-                    end_of_game: Some(EndOfGame::MaxPayouts),
+        if let Some((amount, limit)) = self.prop_account.allowed_withdrawal().filter(|_| self.payout_window_open() && self.profitable_days_met()){
+            let denied = self.payout_denied(rng);
+            if self.audit_withdrawals {
+                self.withdrawals.push(WithdrawalAudit {
+                    day: self.prop_account.get_simulation_days(),
+                    payout: self.payouts + 1,
+                    balance: self.prop_account.get_current_balance(),
+                    amount,
+                    limit,
+                    consistency_fraction: self.prop_account.info().consistency_fraction,
+                    denied,
+                });
+            }
+            if denied {
+                // The money stays on the account and the trader requalifies for the next request
+                debug!("Payout of {:.2} denied by the firm", amount);
+                self.rule_triggers.payout_denials += 1;
+                self.prop_account.deny_withdrawal();
+                self.last_payout_day = Some(self.prop_account.get_simulation_days());
+                self.payout_period_pnl.clear();
+            } else {
+                let num_payouts = self.prop_account.make_withdrawal(amount);
+                self.payouts += 1;
+                self.last_payout_day = Some(self.prop_account.get_simulation_days());
+                self.payout_period_pnl.clear();
+                self.apply_due_rule_changes();
+                self.track_drawdown_buffer();
+                if self.buffer_at_first_payout.is_none() {
+                    self.buffer_at_first_payout = Some(self.prop_account.get_drawdown_buffer());
+                }
+                // The firm pays out once the payout is processed
+                let share = self.trader_share(amount);
+                let due_day = self.prop_account.get_simulation_days() + self.payout_delay();
+                self.pending_payouts.push(PendingPayout { due_day, amount, share });
+                self.credit_pending_payouts(false);
+                if num_payouts >= self.max_payouts && self.continue_after_max_payouts {
+                    if self.max_payouts_day.is_none() {
+                        debug!("Reached max payouts: {}, continuing to trade.", self.max_payouts);
+                        self.max_payouts_day = Some(self.prop_account.get_simulation_days());
+                    }
+                } else if num_payouts >= self.max_payouts{
+                    debug!("Reached max payouts: {}, ending simulation for this trader.", self.max_payouts);
+                    return TradingDayResult{
+                        end_of_game: Some(EndOfGame::MaxPayouts),
+                    }
                 }
             }
         }
 
         if self.prop_account.get_simulation_days() >= self.max_simulation_days{
             debug!("Max simulation days reached: {}", self.max_simulation_days);
+            if let Some(fraction) = self.terminal_payout_fraction {
+                let amount = self.prop_account.withdrawable_balance() * fraction;
+                if amount > 0.0 {
+                    let payout = self.receive_payout(amount);
+                    self.ledger.terminal_payouts += payout;
+                }
+            }
             return TradingDayResult{
                 end_of_game: Some(EndOfGame::TimeOut),
             }
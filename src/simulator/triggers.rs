@@ -0,0 +1,105 @@
+// src/simulator/triggers.rs
+use serde::{Serialize, Deserialize};
+
+/// How often each personal and firm rule fired during one run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleTriggers {
+    pub daily_stop: u64,
+    pub daily_target: u64,
+    pub max_trades: u64,
+    pub firm_daily_loss_limit: u64,
+    /// Firm daily loss limits reached that only ended the day
+    pub soft_breaches: u64,
+    pub max_drawdown: u64,
+    pub news_days: u64,
+    /// Trades cut down to the contracts the account's scaling plan allows
+    pub scaled_trades: u64,
+    /// Payouts requested but denied by the firm
+    pub payout_denials: u64,
+}
+
+impl RuleTriggers {
+    /// Add the counts of another run
+    pub fn add(&mut self, other: &RuleTriggers) {
+        self.daily_stop += other.daily_stop;
+        self.daily_target += other.daily_target;
+        self.max_trades += other.max_trades;
+        self.firm_daily_loss_limit += other.firm_daily_loss_limit;
+        self.soft_breaches += other.soft_breaches;
+        self.max_drawdown += other.max_drawdown;
+        self.news_days += other.news_days;
+        self.scaled_trades += other.scaled_trades;
+        self.payout_denials += other.payout_denials;
+    }
+}
+
+/// Average number of times each rule fired per 100 simulated days, over all runs.
+/// Rules that rarely fire do not constrain the strategy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleTriggerReport {
+    /// Personal daily stop loss reached
+    pub daily_stop: f64,
+    /// Personal daily profit target reached
+    pub daily_target: f64,
+    /// Trades left untaken because of the max trades per day
+    pub max_trades: f64,
+    /// Firm daily loss limit reached (blows the account unless it is a soft breach)
+    pub firm_daily_loss_limit: f64,
+    /// Firm daily loss limit reached as a soft breach, ending the day
+    #[serde(default)]
+    pub soft_breaches: f64,
+    /// Firm drawdown reached (blows the account)
+    pub max_drawdown: f64,
+    /// News blackout days, sat out or traded with inflated variance
+    #[serde(default)]
+    pub news_days: f64,
+    /// Trades cut down to the contracts the account's scaling plan allows
+    #[serde(default)]
+    pub scaled_trades: f64,
+    /// Payouts requested but denied by the firm (see `payout_denial_probability`)
+    #[serde(default)]
+    pub payout_denials: f64,
+}
+
+/// Pool the triggers and simulated days of every run
+pub fn rule_trigger_report(runs: &[(RuleTriggers, u64)]) -> RuleTriggerReport {
+    let days: u64 = runs.iter().map(|(_, days)| days).sum();
+    let per_100_days = |count: fn(&RuleTriggers) -> u64| -> f64 {
+        if days == 0 {
+            return 0.0;
+        }
+        runs.iter().map(|(triggers, _)| count(triggers)).sum::<u64>() as f64 / days as f64 * 100.0
+    };
+
+    RuleTriggerReport {
+        daily_stop: per_100_days(|t| t.daily_stop),
+        daily_target: per_100_days(|t| t.daily_target),
+        max_trades: per_100_days(|t| t.max_trades),
+        firm_daily_loss_limit: per_100_days(|t| t.firm_daily_loss_limit),
+        soft_breaches: per_100_days(|t| t.soft_breaches),
+        max_drawdown: per_100_days(|t| t.max_drawdown),
+        news_days: per_100_days(|t| t.news_days),
+        scaled_trades: per_100_days(|t| t.scaled_trades),
+        payout_denials: per_100_days(|t| t.payout_denials),
+    }
+}
+
+/// Soft breaches of the firm daily loss limit per run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SoftBreachStats {
+    pub mean_per_run: f64,
+    pub max_per_run: u64,
+    /// Percentage of runs with at least one soft breach
+    pub breached_percentage: f64,
+}
+
+/// Build the stats from the soft breaches of each run, or `None` when no run had one
+pub fn soft_breach_stats(soft_breaches: &[u64]) -> Option<SoftBreachStats> {
+    let max_per_run = soft_breaches.iter().copied().max().filter(|&max| max > 0)?;
+    let runs = soft_breaches.len() as f64;
+    Some(SoftBreachStats {
+        mean_per_run: soft_breaches.iter().sum::<u64>() as f64 / runs,
+        max_per_run,
+        breached_percentage: soft_breaches.iter().filter(|&&breaches| breaches > 0).count() as f64 / runs * 100.0,
+    })
+}
@@ -0,0 +1,44 @@
+// src/simulator/worst.rs
+use serde::{Serialize, Deserialize};
+
+use super::stats::{self, percentile};
+
+/// Distribution over runs of the worst trade or day of each run: the median is the worst
+/// a typical run sits through, the low percentiles what one run in 10, 20 or 100 does
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorstOutcomeStats {
+    /// Runs with at least one trade or day
+    pub runs: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p10: f64,
+    pub p5: f64,
+    pub p1: f64,
+    /// Worst of all runs
+    pub worst: f64,
+}
+
+/// Worst single trade and worst trading day P&L of each run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorstOutcomeReport {
+    pub worst_trade: WorstOutcomeStats,
+    pub worst_day: WorstOutcomeStats,
+}
+
+/// Build the stats from the worst value of each run, `None` for runs that never traded
+pub fn worst_outcome_stats(runs: &[Option<f64>]) -> WorstOutcomeStats {
+    let worst: Vec<f64> = runs.iter().flatten().copied().collect();
+    let worst = stats::sorted(&worst);
+    let (Some(mean), Some(median)) = (stats::mean(&worst), stats::median(&worst)) else {
+        return WorstOutcomeStats::default();
+    };
+    WorstOutcomeStats {
+        runs: worst.len(),
+        mean,
+        median,
+        p10: percentile(&worst, 10.0).unwrap_or_default(),
+        p5: percentile(&worst, 5.0).unwrap_or_default(),
+        p1: percentile(&worst, 1.0).unwrap_or_default(),
+        worst: worst[0],
+    }
+}
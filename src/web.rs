@@ -1,18 +1,155 @@
 // src/web.rs
 #[allow(unused_imports)]
-use actix_web::{post, web, App, HttpServer, Responder, HttpResponse, middleware::Logger};
-use prop_simulator::simulator::{SimulationConfig, run_simulation};
+use actix_web::{delete, get, post, web, App, HttpRequest, HttpServer, Responder, HttpResponse, middleware::Logger};
+use prop_simulator::prelude::{
+    SimulationConfig, SimulationHandle, SimulationLimits, SimulationResult, generate_plotly_gross_payout_histogram_json, generate_plotly_histogram_json,
+    preview_trades, run_simulation_cached, run_simulations_cached, set_simulation_limits, simulation_options, TradeCache,
+};
 use env_logger::Env;
-use log::info;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 
 use actix_multipart::Multipart;
 use futures_util::stream::StreamExt as _;
 
+mod jobs;
+mod webhook;
+mod storage;
+#[cfg(feature = "cloud-storage")]
+mod object_store;
+use jobs::{Admission, JobError, Jobs, KeysFile};
+use storage::Storage;
+use webhook::{valid_callback_url, Callback, Webhooks};
+
+// Header carrying the API key when the server runs with a keys file
+const API_KEY_HEADER: &str = "X-Api-Key";
+
+fn api_key(req: &HttpRequest) -> Option<&str> {
+    req.headers().get(API_KEY_HEADER).and_then(|value| value.to_str().ok())
+}
+
+fn job_error(e: JobError) -> HttpResponse {
+    match e {
+        JobError::UnknownKey => HttpResponse::Unauthorized().body(format!("Error: {}", e)),
+        _ => HttpResponse::TooManyRequests().body(format!("Error: {}", e)),
+    }
+}
+
+fn read_callback_url(data: Vec<u8>) -> Result<String, HttpResponse> {
+    match String::from_utf8(data) {
+        Ok(url) if valid_callback_url(url.trim()) => Ok(url.trim().to_string()),
+        _ => Err(HttpResponse::BadRequest().body("Error: callback_url must be an http(s) URL")),
+    }
+}
+
+// A simulation result as returned by the API, with the Plotly histograms of the net
+// balances and gross payouts rendered when the config asked for them
+#[derive(Serialize)]
+struct SimulationResponse {
+    #[serde(flatten)]
+    result: SimulationResult,
+    histogram_plotly_json: Option<String>,
+    gross_histogram_plotly_json: Option<String>,
+    /// Id of the result in the server's storage, for `GET /results/{id}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result_id: Option<String>,
+}
+
+fn render_result(result: SimulationResult, histogram: bool, result_id: Option<String>) -> Result<SimulationResponse, String> {
+    let (histogram_plotly_json, gross_histogram_plotly_json) = if histogram {
+        (
+            Some(generate_plotly_histogram_json(&result.final_balances).map_err(|e| e.to_string())?),
+            Some(generate_plotly_gross_payout_histogram_json(&result.final_gross_payouts).map_err(|e| e.to_string())?),
+        )
+    } else {
+        (None, None)
+    };
+    Ok(SimulationResponse { result, histogram_plotly_json, gross_histogram_plotly_json, result_id })
+}
+
+// Memory for parsed trade data kept between requests unless `PROP_SIM_TRADE_CACHE_MB` says otherwise
+const DEFAULT_TRADE_CACHE_MB: usize = 256;
+
+// Run a config, storing the result and its uploaded data when the server has storage
+fn run_and_store(config: SimulationConfig, storage: &Storage, trade_cache: &TradeCache, handle: &SimulationHandle) -> Result<SimulationResponse, String> {
+    let histogram = config.histogram;
+    let stored_config = storage.bucket().is_some().then(|| config.clone());
+    let dataset = config.csv_data.as_deref().and_then(|csv_data| storage.save_dataset(csv_data));
+    let result = run_simulation_cached(config, trade_cache, handle).map_err(|e| e.to_string())?;
+    let result_id = stored_config.and_then(|config| storage.save_result(config, &result, dataset.as_deref()));
+    render_result(result, histogram, result_id)
+}
+
+// Cancels the simulation when dropped, e.g. when the client of a request goes away
+struct CancelOnDrop(SimulationHandle);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+// Run an admitted job on the blocking thread pool once the queue gives it a slot and
+// answer with its result; the job stops if the client disconnects. With a callback URL
+// the job is accepted right away, can be followed and cancelled under `/jobs/{id}`, and
+// its outcome is POSTed to the callback when it finishes.
+async fn submit_job<T: Serialize + Send + 'static>(
+    admission: Admission,
+    callback_url: Option<String>,
+    jobs: web::Data<Jobs>,
+    webhooks: web::Data<Webhooks>,
+    job: impl FnOnce(&SimulationHandle) -> Result<T, String> + Send + 'static,
+) -> HttpResponse {
+    let handle = SimulationHandle::new();
+    let callback_url = match callback_url {
+        Some(callback_url) => callback_url,
+        None => {
+            let _cancel = CancelOnDrop(handle.clone());
+            return match web::block(move || admission.run(|| job(&handle))).await {
+                Ok(Ok(result)) => HttpResponse::Ok().json(result),
+                Ok(Err(e)) => HttpResponse::BadRequest().body(format!("Error: {}", e)),
+                Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+            };
+        }
+    };
+
+    let job_id = webhooks.next_job_id();
+    jobs.track(job_id, &admission, handle.clone());
+    actix_web::rt::spawn(async move {
+        let outcome = match web::block(move || admission.run(|| job(&handle))).await {
+            Ok(outcome) => outcome,
+            Err(e) => Err(e.to_string()),
+        };
+        jobs.untrack(job_id);
+        let body = match serde_json::to_string(&Callback::new(job_id, outcome)) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Job {}: result could not be serialized: {}", job_id, e);
+                return;
+            }
+        };
+        match web::block(move || webhooks.deliver(&callback_url, &body)).await {
+            Ok(Ok(())) => info!("Job {}: result delivered", job_id),
+            Ok(Err(e)) => warn!("Job {}: callback failed: {}", job_id, e),
+            Err(e) => warn!("Job {}: callback failed: {}", job_id, e),
+        }
+    });
+    HttpResponse::Accepted().json(serde_json::json!({ "job_id": job_id, "status": "queued" }))
+}
+
 #[post("/simulate")]
-async fn simulate(mut payload: Multipart) -> impl Responder {
+async fn simulate(
+    req: HttpRequest,
+    jobs: web::Data<Jobs>,
+    webhooks: web::Data<Webhooks>,
+    storage: web::Data<Storage>,
+    trade_cache: web::Data<TradeCache>,
+    mut payload: Multipart,
+) -> impl Responder {
     // Initialize variables to hold the configuration and CSV data
     let mut config: Option<SimulationConfig> = None;
     let mut csv_data: Option<String> = None;
+    let mut callback_url: Option<String> = None;
 
     // Iterate over multipart form data
     while let Some(item) = payload.next().await {
@@ -41,7 +178,16 @@ async fn simulate(mut payload: Multipart) -> impl Responder {
                 data.extend_from_slice(&chunk.unwrap());
             }
             csv_data = Some(String::from_utf8(data).unwrap());
-        } 
+        } else if name == "callback_url" {
+            let mut data = Vec::new();
+            while let Some(chunk) = field.next().await {
+                data.extend_from_slice(&chunk.unwrap());
+            }
+            match read_callback_url(data) {
+                Ok(url) => callback_url = Some(url),
+                Err(response) => return response,
+            }
+        }
     }
 
     // Ensure config is present
@@ -59,28 +205,282 @@ async fn simulate(mut payload: Multipart) -> impl Responder {
         config.csv_data = Some(data);
     }
 
-    // Run the simulation with the provided parameters
-    match run_simulation(config) {
-        Ok(result) => {
-            // Return the result as JSON
-            HttpResponse::Ok().json(result)
+    let admission = match jobs.admit(api_key(&req), config.iterations as u64) {
+        Ok(admission) => admission,
+        Err(e) => return job_error(e),
+    };
+
+    // Run the simulation with the provided parameters and return the result as JSON
+    submit_job(admission, callback_url, jobs, webhooks, move |handle| run_and_store(config, &storage, &trade_cache, handle)).await
+}
+
+// Run several configs against the same (optional) CSV in one request. The CSV is parsed
+// once and the runs execute in parallel; results come back in the order of `configs`.
+#[post("/simulate/batch")]
+async fn simulate_batch(
+    req: HttpRequest,
+    jobs: web::Data<Jobs>,
+    webhooks: web::Data<Webhooks>,
+    storage: web::Data<Storage>,
+    trade_cache: web::Data<TradeCache>,
+    mut payload: Multipart,
+) -> impl Responder {
+    let mut configs: Option<Vec<SimulationConfig>> = None;
+    let mut csv_data: Option<String> = None;
+    let mut callback_url: Option<String> = None;
+
+    while let Some(item) = payload.next().await {
+        let mut field = item.unwrap();
+
+        let content_disposition = field.content_disposition();
+        let name = match content_disposition.get_name() {
+            Some(name) => name.to_string(),
+            None => {
+                return HttpResponse::BadRequest().body("Missing field name in content disposition");
+            }
+        };
+
+        let mut data = Vec::new();
+        while let Some(chunk) = field.next().await {
+            data.extend_from_slice(&chunk.unwrap());
+        }
+
+        if name == "configs" {
+            match serde_json::from_slice(&data) {
+                Ok(parsed) => configs = Some(parsed),
+                Err(e) => return HttpResponse::BadRequest().body(format!("Error: invalid configs: {}", e)),
+            }
+        } else if name == "csv_file" {
+            csv_data = Some(String::from_utf8(data).unwrap());
+        } else if name == "callback_url" {
+            match read_callback_url(data) {
+                Ok(url) => callback_url = Some(url),
+                Err(response) => return response,
+            }
+        }
+    }
+
+    let mut configs = match configs {
+        Some(c) => c,
+        None => {
+            return HttpResponse::BadRequest().body("Missing simulation configurations");
         }
-        Err(e) => {
-            // Return an error response
-            HttpResponse::BadRequest().body(format!("Error: {}", e))
+    };
+
+    // Every config without its own data uses the uploaded CSV
+    if let Some(data) = csv_data {
+        for config in configs.iter_mut().filter(|c| c.csv_data.is_none()) {
+            config.csv_data = Some(data.clone());
+        }
+    }
+
+    let iterations = configs.iter().map(|config| config.iterations as u64).sum();
+    let admission = match jobs.admit(api_key(&req), iterations) {
+        Ok(admission) => admission,
+        Err(e) => return job_error(e),
+    };
+
+    submit_job(admission, callback_url, jobs, webhooks, move |handle| {
+        let histograms: Vec<bool> = configs.iter().map(|config| config.histogram).collect();
+        let stored_configs = storage.bucket().is_some().then(|| configs.clone());
+        let results = run_simulations_cached(configs, &trade_cache, handle, &|_, _| {}).map_err(|e| e.to_string())?;
+        let result_ids: Vec<Option<String>> = match stored_configs {
+            Some(stored_configs) => stored_configs
+                .into_iter()
+                .zip(&results)
+                .map(|(config, result)| {
+                    let dataset = config.csv_data.as_deref().and_then(|csv_data| storage.save_dataset(csv_data));
+                    storage.save_result(config, result, dataset.as_deref())
+                })
+                .collect(),
+            None => vec![None; results.len()],
+        };
+        results
+            .into_iter()
+            .zip(histograms)
+            .zip(result_ids)
+            .map(|((result, histogram), result_id)| render_result(result, histogram, result_id))
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await
+}
+
+// Trades shown by `/preview-trades` unless the request asks for another number
+const DEFAULT_PREVIEW_SIZE: usize = 20;
+
+#[derive(Deserialize)]
+struct PreviewRequest {
+    #[serde(flatten)]
+    config: SimulationConfig,
+    sample_size: Option<usize>,
+}
+
+// Generate trades from the synthetic-generation parameters of a config and return a
+// sample with summary statistics, without running the simulation
+#[post("/preview-trades")]
+async fn preview(request: web::Json<PreviewRequest>) -> impl Responder {
+    let PreviewRequest { config, sample_size } = request.into_inner();
+    if config.csv_file.is_some() || config.csv_data.is_some() {
+        return HttpResponse::BadRequest().body("Error: preview-trades takes synthetic-generation parameters, not CSV data");
+    }
+    match preview_trades(&config, sample_size.unwrap_or(DEFAULT_PREVIEW_SIZE)) {
+        Ok(preview) => HttpResponse::Ok().json(preview),
+        Err(e) => HttpResponse::BadRequest().body(format!("Error: {}", e)),
+    }
+}
+
+// Results listed by `GET /results` unless the request asks for fewer
+const MAX_LISTED_RESULTS: usize = 100;
+
+#[derive(Deserialize)]
+struct ResultsQuery {
+    /// Comma separated tags a result must all carry
+    tags: Option<String>,
+    limit: Option<usize>,
+}
+
+// Stored results, newest first, optionally only those with the given tags
+#[get("/results")]
+async fn stored_results(req: HttpRequest, jobs: web::Data<Jobs>, storage: web::Data<Storage>, query: web::Query<ResultsQuery>) -> impl Responder {
+    if let Err(e) = jobs.authorize(api_key(&req)) {
+        return job_error(e);
+    }
+    if storage.bucket().is_none() {
+        return HttpResponse::NotFound().body("Error: the server does not store results");
+    }
+    let ResultsQuery { tags, limit } = query.into_inner();
+    let tags: Vec<String> = tags
+        .iter()
+        .flat_map(|tags| tags.split(','))
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    let limit = limit.unwrap_or(MAX_LISTED_RESULTS).min(MAX_LISTED_RESULTS);
+    match web::block(move || storage.list_results(&tags, limit)).await {
+        Ok(Ok(entries)) => HttpResponse::Ok().json(entries),
+        Ok(Err(e)) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// A result stored by the server, in the layout of `--save-results` files
+#[get("/results/{id}")]
+async fn stored_result(req: HttpRequest, jobs: web::Data<Jobs>, storage: web::Data<Storage>, id: web::Path<String>) -> impl Responder {
+    if let Err(e) = jobs.authorize(api_key(&req)) {
+        return job_error(e);
+    }
+    if storage.bucket().is_none() {
+        return HttpResponse::NotFound().body("Error: the server does not store results");
+    }
+    match web::block(move || storage.load_result(&id)).await {
+        Ok(Ok(Some(json))) => HttpResponse::Ok().content_type("application/json").body(json),
+        Ok(Ok(None)) => HttpResponse::NotFound().body("Error: no such result"),
+        Ok(Err(e)) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error: {}", e)),
+    }
+}
+
+// Progress of a job submitted with a callback URL, until its outcome is delivered
+#[get("/jobs/{id}")]
+async fn job_status(req: HttpRequest, jobs: web::Data<Jobs>, id: web::Path<u64>) -> impl Responder {
+    let job_id = id.into_inner();
+    let handle = match jobs.job(job_id, api_key(&req)) {
+        Ok(Some(handle)) => handle,
+        Ok(None) => return HttpResponse::NotFound().body("Error: no such job in progress"),
+        Err(e) => return job_error(e),
+    };
+    let progress = handle.progress();
+    // The simulation counts its iterations once it leaves the queue and has loaded its data
+    let status = match (handle.is_cancelled(), progress.total_iterations) {
+        (true, _) => "cancelling",
+        (false, 0) => "queued",
+        (false, _) => "running",
+    };
+    HttpResponse::Ok().json(serde_json::json!({
+        "job_id": job_id,
+        "status": status,
+        "completed_iterations": progress.completed_iterations,
+        "total_iterations": progress.total_iterations,
+    }))
+}
+
+// Cancel a job submitted with a callback URL; its callback reports the cancellation
+#[delete("/jobs/{id}")]
+async fn cancel_job(req: HttpRequest, jobs: web::Data<Jobs>, id: web::Path<u64>) -> impl Responder {
+    let job_id = id.into_inner();
+    match jobs.job(job_id, api_key(&req)) {
+        Ok(Some(handle)) => {
+            handle.cancel();
+            HttpResponse::Accepted().json(serde_json::json!({ "job_id": job_id, "status": "cancelling" }))
         }
+        Ok(None) => HttpResponse::NotFound().body("Error: no such job in progress"),
+        Err(e) => job_error(e),
     }
 }
 
+// Allowed values of the enumerated config fields, for frontend dropdowns
+#[get("/options")]
+async fn options() -> impl Responder {
+    HttpResponse::Ok().json(simulation_options())
+}
+
 #[actix_web::main]
 pub async fn main() -> std::io::Result<()> {
     // Initialize the logger
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
     info!("Starting the Prop Simulator Web Server");
 
-    HttpServer::new(|| {
+    // Hosted instances list their API keys and quotas in a TOML file; without one the
+    // server is open and only queues the jobs
+    let keys_file = match std::env::var("PROP_SIM_API_KEYS") {
+        Ok(path) => {
+            let keys_file = KeysFile::load(&path).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+            info!("Loaded {} API keys from {}", keys_file.keys.len(), path);
+            Some(keys_file)
+        }
+        Err(_) => None,
+    };
+    let jobs = web::Data::new(Jobs::new(keys_file));
+
+    // Resource limits of every request, e.g. `max_iterations = 100000`, from a TOML file
+    if let Ok(path) = std::env::var("PROP_SIM_LIMITS") {
+        let invalid = |e: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{}: {}", path, e));
+        let contents = std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+        let limits: SimulationLimits = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        info!("Simulation limits: {:?}", limits);
+        set_simulation_limits(limits);
+    }
+    let webhooks = web::Data::new(Webhooks::new(std::env::var("PROP_SIM_WEBHOOK_SECRET").ok()));
+
+    // Results and uploaded datasets outlive the container in object storage, if configured
+    let storage = Storage::from_env().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    if let Some(bucket) = storage.bucket() {
+        info!("Storing results and datasets in bucket {}", bucket);
+    }
+    let storage = web::Data::new(storage);
+
+    // Parsed trades of recent datasets, so repeated runs on the same upload skip the CSV parsing
+    let trade_cache_mb = match std::env::var("PROP_SIM_TRADE_CACHE_MB") {
+        Ok(mb) => mb.parse().map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "PROP_SIM_TRADE_CACHE_MB must be a number of megabytes"))?,
+        Err(_) => DEFAULT_TRADE_CACHE_MB,
+    };
+    info!("Caching up to {} MB of parsed trade data", trade_cache_mb);
+    let trade_cache = web::Data::new(TradeCache::new(trade_cache_mb * 1024 * 1024));
+
+    HttpServer::new(move || {
         App::new()
+            .app_data(jobs.clone())
+            .app_data(webhooks.clone())
+            .app_data(storage.clone())
+            .app_data(trade_cache.clone())
             .wrap(Logger::default())
+            .service(simulate_batch)
+            .service(preview)
+            .service(options)
+            .service(stored_results)
+            .service(stored_result)
+            .service(job_status)
+            .service(cancel_job)
             .service(simulate)
     })
     .bind("127.0.0.1:8080")?
@@ -0,0 +1,222 @@
+// src/web/jobs.rs
+// Simulation jobs of the web server: per-API-key quotas and a priority queue that
+// limits how many simulations run at once
+use chrono::{NaiveDate, Utc};
+use prop_simulator::prelude::SimulationHandle;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+
+// Simulations running at once unless the keys file says otherwise; each one already uses
+// every core through rayon
+const DEFAULT_RUNNING_JOBS: usize = 1;
+
+/// Limits of one API key; limits left out are unlimited
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct KeyQuota {
+    /// Simulation iterations allowed per UTC day
+    pub iterations_per_day: Option<u64>,
+    /// Jobs of this key queued or running at the same time
+    pub max_concurrent_jobs: Option<usize>,
+    /// Waiting jobs with a higher priority run first (e.g. moderators)
+    pub priority: i32,
+}
+
+/// Contents of the keys file named by `PROP_SIM_API_KEYS`
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeysFile {
+    pub max_running_jobs: Option<usize>,
+    pub keys: HashMap<String, KeyQuota>,
+}
+
+impl KeysFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        toml::from_str(&contents).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+#[derive(Debug)]
+pub enum JobError {
+    /// Missing or unknown API key
+    UnknownKey,
+    /// The key used its daily iterations
+    IterationQuota { limit: u64, used: u64 },
+    /// The key has too many jobs in flight
+    ConcurrencyQuota { limit: usize },
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::UnknownKey => write!(f, "missing or unknown API key"),
+            JobError::IterationQuota { limit, used } => {
+                write!(f, "daily iteration quota exceeded ({} of {} used today)", used, limit)
+            }
+            JobError::ConcurrencyQuota { limit } => write!(f, "at most {} concurrent jobs per API key", limit),
+        }
+    }
+}
+
+#[derive(Default)]
+struct KeyUsage {
+    day: Option<NaiveDate>,
+    iterations: u64,
+    jobs: usize,
+}
+
+#[derive(Default)]
+struct QueueState {
+    running: usize,
+    next_ticket: u64,
+    // Highest priority first, then first come first served
+    waiting: BinaryHeap<(i32, Reverse<u64>)>,
+}
+
+// Job submitted with a callback, queued or running, with the key that submitted it
+struct TrackedJob {
+    key: Option<String>,
+    handle: SimulationHandle,
+}
+
+/// Admits jobs against the key quotas and runs them in priority order
+pub struct Jobs {
+    keys: Option<HashMap<String, KeyQuota>>,
+    usage: Mutex<HashMap<String, KeyUsage>>,
+    max_running: usize,
+    queue: Mutex<QueueState>,
+    turn: Condvar,
+    tracked: Mutex<HashMap<u64, TrackedJob>>,
+}
+
+/// A job admitted for a key; releases the key's concurrency slot when dropped
+pub struct Admission {
+    jobs: Arc<Jobs>,
+    key: Option<String>,
+    priority: i32,
+}
+
+impl Jobs {
+    /// Without a keys file every request is accepted without limits
+    pub fn new(keys_file: Option<KeysFile>) -> Self {
+        let max_running = keys_file
+            .as_ref()
+            .and_then(|file| file.max_running_jobs)
+            .unwrap_or(DEFAULT_RUNNING_JOBS)
+            .max(1);
+        Jobs {
+            keys: keys_file.map(|file| file.keys),
+            usage: Mutex::new(HashMap::new()),
+            max_running,
+            queue: Mutex::new(QueueState::default()),
+            turn: Condvar::new(),
+            tracked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check the API key of a request that does not run a job
+    pub fn authorize(&self, key: Option<&str>) -> Result<(), JobError> {
+        match &self.keys {
+            Some(keys) if !key.is_some_and(|key| keys.contains_key(key)) => Err(JobError::UnknownKey),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the key's quotas for a job of `iterations` iterations and book it
+    pub fn admit(self: &Arc<Self>, key: Option<&str>, iterations: u64) -> Result<Admission, JobError> {
+        let keys = match &self.keys {
+            Some(keys) => keys,
+            None => return Ok(Admission { jobs: Arc::clone(self), key: None, priority: 0 }),
+        };
+        let (key, quota) = key
+            .and_then(|key| keys.get_key_value(key))
+            .ok_or(JobError::UnknownKey)?;
+
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(key.clone()).or_default();
+        let today = Utc::now().date_naive();
+        if usage.day != Some(today) {
+            usage.day = Some(today);
+            usage.iterations = 0;
+        }
+        if let Some(limit) = quota.iterations_per_day {
+            if usage.iterations + iterations > limit {
+                return Err(JobError::IterationQuota { limit, used: usage.iterations });
+            }
+        }
+        if let Some(limit) = quota.max_concurrent_jobs {
+            if usage.jobs >= limit {
+                return Err(JobError::ConcurrencyQuota { limit });
+            }
+        }
+        usage.iterations += iterations;
+        usage.jobs += 1;
+        Ok(Admission { jobs: Arc::clone(self), key: Some(key.clone()), priority: quota.priority })
+    }
+
+    /// Make an admitted job reachable by its id through `job` until `untrack`
+    pub fn track(&self, job_id: u64, admission: &Admission, handle: SimulationHandle) {
+        let job = TrackedJob { key: admission.key.clone(), handle };
+        self.tracked.lock().unwrap().insert(job_id, job);
+    }
+
+    pub fn untrack(&self, job_id: u64) {
+        self.tracked.lock().unwrap().remove(&job_id);
+    }
+
+    /// Handle of a queued or running job; with a keys file only the key that submitted it
+    /// gets it
+    pub fn job(&self, job_id: u64, key: Option<&str>) -> Result<Option<SimulationHandle>, JobError> {
+        self.authorize(key)?;
+        let tracked = self.tracked.lock().unwrap();
+        Ok(tracked
+            .get(&job_id)
+            .filter(|job| self.keys.is_none() || job.key.as_deref() == key)
+            .map(|job| job.handle.clone()))
+    }
+}
+
+impl Admission {
+    /// Wait for a free slot, then run the job. Blocks, so call it off the async runtime.
+    pub fn run<T>(self, job: impl FnOnce() -> T) -> T {
+        let jobs = &self.jobs;
+        let mut queue = jobs.queue.lock().unwrap();
+        let ticket = (self.priority, Reverse(queue.next_ticket));
+        queue.next_ticket += 1;
+        queue.waiting.push(ticket);
+        while queue.running >= jobs.max_running || queue.waiting.peek() != Some(&ticket) {
+            queue = jobs.turn.wait(queue).unwrap();
+        }
+        queue.waiting.pop();
+        queue.running += 1;
+        drop(queue);
+        // The next job in line may fit in another free slot
+        jobs.turn.notify_all();
+
+        let _slot = RunningSlot(jobs);
+        job()
+    }
+}
+
+// Frees the running slot even if the job panics
+struct RunningSlot<'a>(&'a Jobs);
+
+impl Drop for RunningSlot<'_> {
+    fn drop(&mut self) {
+        self.0.queue.lock().unwrap().running -= 1;
+        self.0.turn.notify_all();
+    }
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        if let Some(key) = &self.key {
+            if let Some(usage) = self.jobs.usage.lock().unwrap().get_mut(key) {
+                usage.jobs -= 1;
+            }
+        }
+    }
+}
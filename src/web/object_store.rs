@@ -0,0 +1,220 @@
+// src/web/object_store.rs
+// Minimal client for S3-compatible object storage (AWS S3, MinIO, Cloudflare R2, or Google
+// Cloud Storage through its XML API with HMAC keys), signing requests with AWS Signature V4
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+use super::webhook::{hex, hmac_sha256};
+
+const DEFAULT_REGION: &str = "us-east-1";
+// Largest object read back, so a wrong key cannot exhaust memory
+const MAX_OBJECT_BYTES: u64 = 512 * 1024 * 1024;
+
+/// A bucket addressed path-style (`<endpoint>/<bucket>/<key>`)
+pub struct ObjectStore {
+    endpoint: String,
+    host: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    prefix: String,
+}
+
+impl ObjectStore {
+    /// Configured by `PROP_SIM_STORAGE_ENDPOINT` (e.g. `https://s3.us-east-1.amazonaws.com`
+    /// or `https://storage.googleapis.com`), `PROP_SIM_STORAGE_BUCKET`,
+    /// `PROP_SIM_STORAGE_ACCESS_KEY` and `PROP_SIM_STORAGE_SECRET_KEY`, plus the optional
+    /// `PROP_SIM_STORAGE_REGION` (default us-east-1) and `PROP_SIM_STORAGE_PREFIX`.
+    /// Without an endpoint there is no store.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let endpoint = match std::env::var("PROP_SIM_STORAGE_ENDPOINT") {
+            Ok(endpoint) => endpoint.trim_end_matches('/').to_string(),
+            Err(_) => return Ok(None),
+        };
+        let required = |name: &str| std::env::var(name).map_err(|_| format!("{} is required with PROP_SIM_STORAGE_ENDPOINT", name));
+        let host = endpoint
+            .strip_prefix("https://")
+            .or_else(|| endpoint.strip_prefix("http://"))
+            .ok_or("PROP_SIM_STORAGE_ENDPOINT must be an http(s) URL")?
+            .to_string();
+        let prefix = std::env::var("PROP_SIM_STORAGE_PREFIX").unwrap_or_default();
+        Ok(Some(ObjectStore {
+            host,
+            bucket: required("PROP_SIM_STORAGE_BUCKET")?,
+            region: std::env::var("PROP_SIM_STORAGE_REGION").unwrap_or_else(|_| DEFAULT_REGION.to_string()),
+            access_key: required("PROP_SIM_STORAGE_ACCESS_KEY")?,
+            secret_key: required("PROP_SIM_STORAGE_SECRET_KEY")?,
+            prefix: prefix.trim_matches('/').to_string(),
+            endpoint,
+        }))
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// Upload `body` to `key`, replacing any existing object. Blocks.
+    pub fn put(&self, key: &str, body: &[u8], content_type: &str) -> Result<(), String> {
+        self.request("PUT", key, body)
+            .set("Content-Type", content_type)
+            .send_bytes(body)
+            .map(|_| ())
+            .map_err(|e| format!("storing {}: {}", key, e))
+    }
+
+    /// Download `key`, or `None` if there is no such object. Blocks.
+    pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = match self.request("GET", key, &[]).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(e) => return Err(format!("loading {}: {}", key, e)),
+        };
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_OBJECT_BYTES)
+            .read_to_end(&mut body)
+            .map_err(|e| format!("loading {}: {}", key, e))?;
+        Ok(Some(body))
+    }
+
+    /// Keys of all objects whose key starts with `prefix`, relative to the store prefix.
+    /// Blocks.
+    pub fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let store_prefix = if self.prefix.is_empty() { String::new() } else { format!("{}/", self.prefix) };
+        let full_prefix = format!("{}{}", store_prefix, prefix);
+        let path = format!("/{}", uri_encode(&self.bucket));
+        let mut keys = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            // ListObjectsV2, one page of up to 1000 keys at a time
+            let mut query = vec![("list-type", String::from("2")), ("prefix", full_prefix.clone())];
+            if let Some(token) = &continuation {
+                query.insert(0, ("continuation-token", token.clone()));
+            }
+            let query = query
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, uri_encode(value)))
+                .collect::<Vec<_>>()
+                .join("&");
+            let response = self
+                .signed("GET", &path, &query, &[])
+                .call()
+                .map_err(|e| format!("listing {}: {}", prefix, e))?;
+            let mut body = String::new();
+            response
+                .into_reader()
+                .take(MAX_OBJECT_BYTES)
+                .read_to_string(&mut body)
+                .map_err(|e| format!("listing {}: {}", prefix, e))?;
+            keys.extend(
+                xml_values(&body, "Key")
+                    .into_iter()
+                    .map(|key| key.strip_prefix(&store_prefix).unwrap_or(&key).to_string()),
+            );
+            continuation = match xml_values(&body, "IsTruncated").first().map(String::as_str) {
+                Some("true") => xml_values(&body, "NextContinuationToken").into_iter().next(),
+                _ => None,
+            };
+            if continuation.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+
+    // Signed request for `key`
+    fn request(&self, method: &str, key: &str, body: &[u8]) -> ureq::Request {
+        let path = format!("/{}/{}", uri_encode(&self.bucket), self.object_path(key));
+        self.signed(method, &path, "", body)
+    }
+
+    // Signed request for an encoded `path` and canonical (sorted, encoded) `query`
+    fn signed(&self, method: &str, path: &str, query: &str, body: &[u8]) -> ureq::Request {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex(&Sha256::digest(body));
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, query, self.host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(hmac_sha256(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes()), |key, part| {
+                hmac_sha256(&key, part.as_bytes())
+            });
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key,
+            scope,
+            signed_headers,
+            hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()))
+        );
+
+        let url = if query.is_empty() {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}{}?{}", self.endpoint, path, query)
+        };
+        ureq::request(method, &url)
+            .set("x-amz-content-sha256", &payload_hash)
+            .set("x-amz-date", &amz_date)
+            .set("Authorization", &authorization)
+    }
+
+    // URI-encoded object path of `key` under the prefix
+    fn object_path(&self, key: &str) -> String {
+        self.prefix
+            .split('/')
+            .chain(key.split('/'))
+            .filter(|segment| !segment.is_empty())
+            .map(uri_encode)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+// Text of every `<tag>` element of a listing, with the XML escapes undone
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        values.push(
+            rest[..end]
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&"),
+        );
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
+
+// Percent-encode everything but the unreserved characters, as Signature V4 requires
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
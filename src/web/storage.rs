@@ -0,0 +1,200 @@
+// src/web/storage.rs
+// Research history of the web server: results and the datasets uploaded with them, kept in
+// object storage (feature `cloud-storage`) so that it outlives ephemeral containers
+use chrono::Utc;
+use log::warn;
+use prop_simulator::prelude::{Currency, RunMetadata, SimulationConfig, SimulationResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+#[cfg(feature = "cloud-storage")]
+use super::object_store::ObjectStore;
+use super::webhook::hex;
+
+// Stored in the layout of `SavedResult`, so downloaded results can be compared with `diff`
+#[derive(Serialize)]
+struct StoredResult<'a> {
+    crate_version: &'static str,
+    config: &'a SimulationConfig,
+    result: &'a SimulationResult,
+    final_balances: &'a [f64],
+    /// Uploaded CSV the result was computed from, stored under `datasets/<sha256>.csv`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dataset_sha256: Option<&'a str>,
+}
+
+/// Entry of the results index under `index/<id>.json`, enough to find a result by its
+/// tags without downloading it
+#[derive(Serialize, Deserialize)]
+pub struct ResultEntry {
+    pub id: String,
+    #[serde(flatten)]
+    pub metadata: RunMetadata,
+    pub account_type: String,
+    pub iterations: usize,
+    pub mean_balance: f64,
+    pub currency: Currency,
+}
+
+pub struct Storage {
+    #[cfg(feature = "cloud-storage")]
+    store: Option<ObjectStore>,
+    // Datasets already stored by this server, which are not uploaded again
+    stored_datasets: Mutex<HashSet<String>>,
+}
+
+impl Storage {
+    #[cfg(feature = "cloud-storage")]
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Storage { store: ObjectStore::from_env()?, stored_datasets: Mutex::default() })
+    }
+
+    #[cfg(not(feature = "cloud-storage"))]
+    pub fn from_env() -> Result<Self, String> {
+        if std::env::var("PROP_SIM_STORAGE_ENDPOINT").is_ok() {
+            return Err("PROP_SIM_STORAGE_ENDPOINT needs a build with the cloud-storage feature".into());
+        }
+        Ok(Storage { stored_datasets: Mutex::default() })
+    }
+
+    /// Name of the bucket results are stored in, if any
+    #[cfg(feature = "cloud-storage")]
+    pub fn bucket(&self) -> Option<&str> {
+        self.store.as_ref().map(ObjectStore::bucket)
+    }
+
+    #[cfg(not(feature = "cloud-storage"))]
+    pub fn bucket(&self) -> Option<&str> {
+        None
+    }
+
+    #[cfg(feature = "cloud-storage")]
+    fn put(&self, key: &str, body: &[u8], content_type: &str) -> Option<Result<(), String>> {
+        self.store.as_ref().map(|store| store.put(key, body, content_type))
+    }
+
+    #[cfg(not(feature = "cloud-storage"))]
+    fn put(&self, _key: &str, _body: &[u8], _content_type: &str) -> Option<Result<(), String>> {
+        None
+    }
+
+    #[cfg(feature = "cloud-storage")]
+    fn get(&self, key: &str) -> Option<Result<Option<Vec<u8>>, String>> {
+        self.store.as_ref().map(|store| store.get(key))
+    }
+
+    #[cfg(not(feature = "cloud-storage"))]
+    fn get(&self, _key: &str) -> Option<Result<Option<Vec<u8>>, String>> {
+        None
+    }
+
+    #[cfg(feature = "cloud-storage")]
+    fn list(&self, prefix: &str) -> Option<Result<Vec<String>, String>> {
+        self.store.as_ref().map(|store| store.list(prefix))
+    }
+
+    #[cfg(not(feature = "cloud-storage"))]
+    fn list(&self, _prefix: &str) -> Option<Result<Vec<String>, String>> {
+        None
+    }
+
+    /// Store an uploaded CSV under its SHA-256 and return the hash. Blocks.
+    pub fn save_dataset(&self, csv_data: &str) -> Option<String> {
+        self.bucket()?;
+        let sha256 = hex(&Sha256::digest(csv_data.as_bytes()));
+        if self.stored_datasets.lock().unwrap().contains(&sha256) {
+            return Some(sha256);
+        }
+        match self.put(&format!("datasets/{}.csv", sha256), csv_data.as_bytes(), "text/csv")? {
+            Ok(()) => {
+                self.stored_datasets.lock().unwrap().insert(sha256.clone());
+                Some(sha256)
+            }
+            Err(e) => {
+                warn!("Dataset not stored: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Store a result with the config that produced it (its CSV data left out, see
+    /// `save_dataset`) and return the id to load it with. Blocks.
+    pub fn save_result(&self, config: SimulationConfig, result: &SimulationResult, dataset_sha256: Option<&str>) -> Option<String> {
+        self.bucket()?;
+        let config = SimulationConfig { csv_data: None, ..config };
+        let json = match serde_json::to_vec(&StoredResult {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            config: &config,
+            result,
+            final_balances: &result.final_balances,
+            dataset_sha256,
+        }) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Result not stored: {}", e);
+                return None;
+            }
+        };
+        // Time ordered, with part of the content hash to tell apart results stored at once
+        let id = format!("{}-{}", Utc::now().format("%Y%m%dT%H%M%S%3f"), &hex(&Sha256::digest(&json))[..8]);
+        if let Err(e) = self.put(&format!("results/{}.json", id), &json, "application/json")? {
+            warn!("Result not stored: {}", e);
+            return None;
+        }
+        let entry = ResultEntry {
+            id: id.clone(),
+            metadata: result.metadata.clone(),
+            account_type: result.account_type.clone(),
+            iterations: config.iterations,
+            mean_balance: result.mean_balance,
+            currency: result.currency,
+        };
+        // A result missing from the index can still be loaded by its id
+        match serde_json::to_vec(&entry).map_err(|e| e.to_string()) {
+            Ok(json) => {
+                if let Some(Err(e)) = self.put(&format!("index/{}.json", id), &json, "application/json") {
+                    warn!("Result {} not indexed: {}", id, e);
+                }
+            }
+            Err(e) => warn!("Result {} not indexed: {}", id, e),
+        }
+        Some(id)
+    }
+
+    /// Index entries of the stored results carrying all of `tags`, newest first, at most
+    /// `limit` of them. Blocks.
+    pub fn list_results(&self, tags: &[String], limit: usize) -> Result<Vec<ResultEntry>, String> {
+        let mut keys = self.list("index/").unwrap_or(Ok(Vec::new()))?;
+        // Ids start with the time they were stored at
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+        let mut entries = Vec::new();
+        for key in keys {
+            if entries.len() >= limit {
+                break;
+            }
+            let Some(json) = self.get(&key).unwrap_or(Ok(None))? else {
+                continue;
+            };
+            match serde_json::from_slice::<ResultEntry>(&json) {
+                Ok(entry) if entry.metadata.has_tags(tags) => entries.push(entry),
+                Ok(_) => (),
+                Err(e) => warn!("{}: {}", key, e),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// JSON of a stored result, `None` if there is no storage or no such result. Blocks.
+    pub fn load_result(&self, id: &str) -> Result<Option<Vec<u8>>, String> {
+        if !valid_result_id(id) {
+            return Ok(None);
+        }
+        self.get(&format!("results/{}.json", id)).unwrap_or(Ok(None))
+    }
+}
+
+fn valid_result_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
@@ -0,0 +1,96 @@
+// src/web/webhook.rs
+// Completion callbacks: jobs submitted with a `callback_url` get their outcome POSTed there
+use log::warn;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// Header with the HMAC-SHA256 of the body, when the server has a webhook secret
+const SIGNATURE_HEADER: &str = "X-Signature-256";
+const DELIVERY_ATTEMPTS: u32 = 3;
+
+/// Body POSTed to the callback URL
+#[derive(Debug, Serialize)]
+pub struct Callback<T: Serialize> {
+    pub job_id: u64,
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<T: Serialize> Callback<T> {
+    pub fn new(job_id: u64, outcome: Result<T, String>) -> Self {
+        match outcome {
+            Ok(result) => Callback { job_id, status: "completed", result: Some(result), error: None },
+            Err(error) => Callback { job_id, status: "failed", result: None, error: Some(error) },
+        }
+    }
+}
+
+pub struct Webhooks {
+    secret: Option<Vec<u8>>,
+    next_job_id: AtomicU64,
+}
+
+impl Webhooks {
+    /// Callbacks are signed with `secret` if there is one
+    pub fn new(secret: Option<String>) -> Self {
+        Webhooks {
+            secret: secret.map(String::into_bytes),
+            next_job_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn next_job_id(&self) -> u64 {
+        self.next_job_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// POST `body` to `url`, retrying with a growing delay. Blocks.
+    pub fn deliver(&self, url: &str, body: &str) -> Result<(), String> {
+        let mut last_error = String::new();
+        for attempt in 0..DELIVERY_ATTEMPTS {
+            if attempt > 0 {
+                thread::sleep(Duration::from_secs(1 << attempt));
+            }
+            let mut request = ureq::post(url).set("Content-Type", "application/json");
+            if let Some(secret) = &self.secret {
+                request = request.set(SIGNATURE_HEADER, &format!("sha256={}", hex(&hmac_sha256(secret, body.as_bytes()))));
+            }
+            match request.send_string(body) {
+                Ok(_) => return Ok(()),
+                Err(e) => {
+                    warn!("Callback to {} failed (attempt {}): {}", url, attempt + 1, e);
+                    last_error = e.to_string();
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// Whether `url` can be used as a callback
+pub fn valid_callback_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// HMAC-SHA256 (RFC 2104)
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.iter().map(|b| b ^ byte).collect::<Vec<u8>>();
+    let inner = Sha256::new().chain_update(pad(0x36)).chain_update(message).finalize();
+    Sha256::new().chain_update(pad(0x5c)).chain_update(inner).finalize().to_vec()
+}
@@ -0,0 +1,98 @@
+// tests/accounts.rs
+// Rules shared by the account implementations, checked trade by trade
+use prop_simulator::prelude::*;
+
+fn trade(return_value: f64, max_opposite_excursion: f64) -> Trade {
+    Trade { return_value, max_opposite_excursion }
+}
+
+// A losing trade that first ran 800 in our favor, then a day closing at -500
+fn trail(drawdown: &mut Drawdown) {
+    let losing = trade(-500.0, 800.0);
+    drawdown.before_trade(0.0, &losing);
+    drawdown.after_trade(-500.0, &AccountStatus::Active(-500.0));
+    drawdown.end_of_day(-500.0);
+}
+
+#[test]
+fn drawdown_modes_trail_their_own_highs() {
+    let mut eod = Drawdown::new(DrawdownType::EndOfDayTrailing, 2_000.0);
+    trail(&mut eod);
+    assert_eq!(eod.loss_balance(), -2_000.0);
+    eod.end_of_day(1_500.0);
+    assert_eq!(eod.loss_balance(), -500.0);
+
+    // The open-trade high of 800 moves the loss level before the loss is taken
+    let mut intraday = Drawdown::new(DrawdownType::IntradayTrailing, 2_000.0);
+    trail(&mut intraday);
+    assert_eq!(intraday.loss_balance(), -1_200.0);
+    assert_eq!(intraday.buffer(-500.0), 700.0);
+
+    let mut fixed = Drawdown::new(DrawdownType::Static, 2_000.0);
+    trail(&mut fixed);
+    fixed.end_of_day(5_000.0);
+    assert_eq!(fixed.loss_balance(), -2_000.0);
+
+    // Trailing stops at the lock
+    let mut locked = Drawdown::new(DrawdownType::EndOfDayTrailing, 2_000.0).with_lock(100.0);
+    locked.end_of_day(10_000.0);
+    assert_eq!(locked.loss_balance(), 100.0);
+    assert!(locked.is_breached(100.0));
+    locked.reset(0.0);
+    assert_eq!(locked.loss_balance(), -2_000.0);
+}
+
+#[test]
+fn intraday_accounts_blow_on_a_giveback_that_eod_accounts_survive() {
+    // Runs 2,400 in our favor, then closes flat: the 2,500 intraday drawdown trails to -100
+    let giveback = [trade(-0.0, 2_400.0), trade(-200.0, 0.0)];
+    let mut bulenox = create_account("bulenox:Fifty".parse().unwrap());
+    assert_eq!(bulenox.info().drawdown_type, DrawdownType::IntradayTrailing);
+    assert!(matches!(bulenox.process_trade(&giveback[0]), AccountStatus::Active(_)));
+    assert!(matches!(bulenox.process_trade(&giveback[1]), AccountStatus::Blown(_)));
+
+    let mut eod = create_account("mff:Starter50K".parse().unwrap());
+    for t in &giveback {
+        assert!(matches!(eod.process_trade(t), AccountStatus::Active(_)));
+    }
+}
+
+#[test]
+fn scaling_plans_raise_contracts_with_the_balance() {
+    let plan: ScalingPlan = "2,3@1500,5@2000".parse().unwrap();
+    assert_eq!(plan.max_contracts(-500.0), 2);
+    assert_eq!(plan.max_contracts(1_500.0), 3);
+    assert_eq!(plan.max_contracts(2_500.0), 5);
+    assert_eq!(plan.to_string(), "2, 3 from 1500, 5 from 2000");
+
+    // The Combine allows the full size; the funded account starts over at the lowest tier
+    let mut topstep = create_account("topstep:Fifty".parse().unwrap());
+    assert_eq!(topstep.max_contracts(), Some(5));
+    assert_eq!(create_account("mff:Starter50K".parse().unwrap()).max_contracts(), None);
+    assert!(matches!(topstep.process_trade(&trade(3_000.0, 0.0)), AccountStatus::PassedEval));
+    assert_eq!(topstep.max_contracts(), Some(2));
+}
+
+#[test]
+fn daily_loss_limit_is_a_distinct_outcome_of_the_trade() {
+    let overrides = RuleOverrides { daily_loss_limit: Some(DailyLossLimit::Amount(800.0)), ..Default::default() };
+    let mut hard = create_account("ftt:GT".parse().unwrap());
+    hard.apply_rules(&overrides);
+    assert!(matches!(hard.process_trade(&trade(-500.0, 0.0)), AccountStatus::Active(_)));
+    // A winner that went 400 against us on the way reaches the limit
+    assert!(matches!(hard.process_trade(&trade(200.0, -400.0)), AccountStatus::DailyLimitHit(ret) if ret == 200.0));
+    hard.update_end_of_day(-300.0);
+    assert!(matches!(hard.process_trade(&trade(-500.0, 0.0)), AccountStatus::Active(_)));
+
+    // A soft breach closes the trade at the limit and locks the account for the day
+    let mut soft = create_account("tpt:Fifty".parse().unwrap());
+    assert_eq!(soft.daily_loss_breach(), BreachSeverity::Soft);
+    assert!(matches!(soft.process_trade(&trade(-1_500.0, 0.0)), AccountStatus::LockedForDay(ret) if ret == -1_100.0));
+    assert_eq!(soft.get_current_balance(), -1_100.0);
+
+    let mut percent = create_account("ftt:GT".parse().unwrap());
+    percent.apply_rules(&RuleOverrides { daily_loss_limit: Some(DailyLossLimit::Percent(2.0)), ..Default::default() });
+    percent.set_account_size(50_000.0);
+    assert!(matches!(percent.process_trade(&trade(-999.0, 0.0)), AccountStatus::Active(_)));
+    assert!(matches!(percent.process_trade(&trade(-1.0, 0.0)), AccountStatus::DailyLimitHit(_)));
+}
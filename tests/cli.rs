@@ -0,0 +1,74 @@
+// tests/cli.rs
+// Options of the command line binary, combined with a config file
+#![cfg(feature = "cli")]
+use prop_simulator::prelude::*;
+use std::process::Command;
+
+const SAMPLE_TRADES: &str = include_str!("../sample_trades.csv");
+
+const CONFIG_FILE: &str = r#"
+[defaults]
+iterations = 20
+max_simulation_days = 30
+account_type = "mff:Starter50K"
+snapshot_days = [5]
+purchase_date = "2025-01-06"
+
+[defaults.holding_rules]
+no_overnight = true
+
+[defaults.insurance]
+cost = 50.0
+
+[defaults.min_profitable_days]
+days = 3
+min_profit = 100.0
+
+[defaults.convergence]
+tolerance = 1e9
+
+[defaults.eval_time_limit]
+calendar_days = 30
+
+[defaults.news_blackout]
+dates = ["2025-03-03", "2025-03-04"]
+builtin_calendar = false
+"#;
+
+// The config the binary ran with `flags` on top of `CONFIG_FILE`, as recorded in its bundle
+fn bundled_config(name: &str, flags: &[&str]) -> SimulationConfig {
+    let dir = std::env::temp_dir();
+    let config_file = dir.join(format!("prop_simulator_cli_{}.toml", name));
+    let trades = dir.join(format!("prop_simulator_cli_{}.csv", name));
+    let bundle = dir.join(format!("prop_simulator_cli_{}.zip", name));
+    std::fs::write(&config_file, CONFIG_FILE).unwrap();
+    std::fs::write(&trades, SAMPLE_TRADES).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_prop-simulator"))
+        .args(["--csv-file", trades.to_str().unwrap()])
+        .args(["--config", config_file.to_str().unwrap(), "--bundle", bundle.to_str().unwrap(), "--seed", "1", "--quiet"])
+        .args(flags)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let config = Bundle::load(bundle.to_str().unwrap()).unwrap().config;
+    let _ = std::fs::remove_file(config_file);
+    let _ = std::fs::remove_file(trades);
+    let _ = std::fs::remove_file(bundle);
+    config
+}
+
+#[test]
+fn flags_named_unlike_their_config_field_override_the_config_file() {
+    assert_eq!(bundled_config("defaults", &[]).snapshot_days, Some(vec![5]));
+    assert_eq!(bundled_config("snapshot_day", &["--snapshot-day", "10"]).snapshot_days, Some(vec![10]));
+    let holding_rules = bundled_config("no_weekend", &["--no-weekend"]).holding_rules.unwrap();
+    assert!(holding_rules.no_weekend && !holding_rules.no_overnight);
+    assert_eq!(bundled_config("news_dates", &["--news-dates", "2025-02-14"]).news_blackout.unwrap().dates.len(), 1);
+    assert!(bundled_config("news_calendar", &["--news-calendar"]).news_blackout.unwrap().builtin_calendar);
+    assert_eq!(bundled_config("insurance_cost", &["--insurance-cost", "80"]).insurance.unwrap().cost, 80.0);
+    let eval_time_limit = bundled_config("eval_time_limit_days", &["--eval-time-limit-days", "45"]).eval_time_limit.unwrap();
+    assert_eq!(eval_time_limit.calendar_days, 45);
+    let profitable_days = bundled_config("profitable_day_min", &["--profitable-day-min", "50"]).min_profitable_days.unwrap();
+    assert_eq!((profitable_days.days, profitable_days.min_profit), (3, 50.0));
+    assert_eq!(bundled_config("convergence_tolerance", &["--convergence-tolerance", "5"]).convergence.unwrap().tolerance, 5.0);
+}
@@ -0,0 +1,81 @@
+// tests/currency.rs
+// Results and money reported in a home currency other than USD
+use prop_simulator::prelude::*;
+
+const SAMPLE_TRADES: &str = include_str!("../sample_trades.csv");
+
+#[test]
+fn converted_currencies_need_a_rate() {
+    assert!(MoneyFormatter::new(Locale::EnUs, Currency::Eur, None).is_err());
+    assert!(MoneyFormatter::new(Locale::EnUs, Currency::Eur, Some(0.0)).is_err());
+    let euros = MoneyFormatter::new(Locale::EnUs, Currency::Eur, Some(0.5)).unwrap();
+    assert_eq!(euros.format(1_000.0), "€500.00");
+    let dollars = MoneyFormatter::new(Locale::EnUs, Currency::Usd, None).unwrap();
+    assert_eq!(dollars.format(1_000.0), "$1,000.00");
+}
+
+#[test]
+fn converted_results_need_a_rate() {
+    let config = || {
+        SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .iterations(50)
+            .max_simulation_days(20)
+            .account_type("mff:Starter50K")
+            .currency(Currency::Eur)
+            .seed(1)
+    };
+    assert!(matches!(config().build(), Err(SimulationError::InvalidConfig(_))));
+    let insurance = Insurance { cost: 50.0, ..Insurance::default() };
+    let converted = run_simulation(config().conversion_rate(0.9).insurance(insurance).build().unwrap()).unwrap();
+    assert_eq!(converted.currency, Currency::Eur);
+    assert!((converted.insurance.unwrap().cost - 45.0).abs() < 1e-9);
+}
+
+// Cells of the first row of the table a command of the binary prints, with the sample trades
+#[cfg(feature = "cli")]
+fn first_table_row(command: &str, flags: &[&str]) -> Vec<String> {
+    let trades = std::env::temp_dir().join(format!("prop_simulator_currency_{}.csv", command));
+    std::fs::write(&trades, SAMPLE_TRADES).unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_prop-simulator"))
+        .arg(command)
+        .args(["--csv-file", trades.to_str().unwrap(), "-i", "300", "--seed", "1", "--monthly-budget", "200", "--company", "mff"])
+        .args(flags)
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(trades);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    stdout.lines().nth(1).unwrap().split_whitespace().map(String::from).collect()
+}
+
+#[cfg(feature = "cli")]
+fn amount(cell: &str) -> f64 {
+    cell.replace(['$', '€', ','], "").parse().unwrap()
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn recommended_balances_are_converted_once() {
+    let flags = ["--top", "1", "--contracts", "1"];
+    let dollars = first_table_row("recommend", &flags);
+    let euros = first_table_row("recommend", &[&flags[..], &["--currency", "EUR", "--conversion-rate", "0.5"]].concat());
+    // Rank, account, contracts, monthly cost, mean, median, standard deviation
+    assert!(euros[4].contains('€'));
+    for column in 3..7 {
+        assert!((amount(&euros[column]) - amount(&dollars[column]) * 0.5).abs() < 0.01, "{} vs {}", euros[column], dollars[column]);
+    }
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn allocated_balances_are_converted_once() {
+    let flags = ["--top", "1", "--max-accounts", "1"];
+    let dollars = first_table_row("allocate", &flags);
+    let euros = first_table_row("allocate", &[&flags[..], &["--currency", "EUR", "--conversion-rate", "0.5"]].concat());
+    // Rank, accounts ("1x" and the account type), monthly cost, mean, median, CVaR
+    assert!(euros[4].contains('€'));
+    for column in 3..7 {
+        assert!((amount(&euros[column]) - amount(&dollars[column]) * 0.5).abs() < 0.01, "{} vs {}", euros[column], dollars[column]);
+    }
+}
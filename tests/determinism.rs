@@ -0,0 +1,92 @@
+// tests/determinism.rs
+// Seeded runs must not depend on the number of threads the iterations run on
+use proptest::prelude::*;
+use prop_simulator::prelude::*;
+
+const SAMPLE_TRADES: &str = include_str!("../sample_trades.csv");
+
+fn config(seed: u64, iterations: usize, rng_backend: RngBackend, account_type: &str) -> SimulationConfig {
+    SimulationConfig::builder()
+        .csv_data(SAMPLE_TRADES.to_string())
+        .multiplier(20.0)
+        .iterations(iterations)
+        .max_simulation_days(60)
+        .account_type(account_type)
+        .rng_backend(rng_backend)
+        .seed(seed)
+        .build()
+        .unwrap()
+}
+
+// Results compare as JSON values, since map fields have no fixed order
+fn run_on_threads(threads: usize, config: SimulationConfig) -> (Vec<f64>, serde_json::Value) {
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+    let result = pool.install(|| run_simulation(config).map_err(|e| e.to_string())).unwrap();
+    (result.final_balances.clone(), serde_json::to_value(&result).unwrap())
+}
+
+fn rng_backend() -> impl Strategy<Value = RngBackend> {
+    prop_oneof![Just(RngBackend::Std), Just(RngBackend::Small), Just(RngBackend::Chacha)]
+}
+
+fn account_type() -> impl Strategy<Value = &'static str> {
+    prop_oneof![Just("ftt:GT"), Just("ftt:Rally"), Just("topstep:Fifty"), Just("topstep:OneFifty"), Just("mff:Starter100K"), Just("mff:Expert50K"), Just("tpt:Fifty"), Just("bulenox:Fifty")]
+}
+
+#[test]
+fn cached_trade_data_gives_the_same_results() {
+    let cache = TradeCache::new(64 * 1024 * 1024);
+    let handle = SimulationHandle::new();
+    let uncached = run_simulation(config(5, 100, RngBackend::Std, "ftt:GT")).unwrap();
+    for _ in 0..3 {
+        let cached = run_simulation_cached(config(5, 100, RngBackend::Std, "ftt:GT"), &cache, &handle).unwrap();
+        assert_eq!(serde_json::to_value(&cached).unwrap(), serde_json::to_value(&uncached).unwrap());
+    }
+    let stats = cache.stats();
+    assert_eq!((stats.datasets, stats.misses, stats.hits), (1, 1, 2));
+    assert!(stats.bytes > 0 && stats.bytes <= stats.max_bytes);
+
+    // Data larger than the cache is loaded every time
+    let tiny = TradeCache::new(16);
+    run_simulation_cached(config(5, 10, RngBackend::Std, "ftt:GT"), &tiny, &handle).unwrap();
+    run_simulation_cached(config(5, 10, RngBackend::Std, "ftt:GT"), &tiny, &handle).unwrap();
+    assert_eq!((tiny.stats().datasets, tiny.stats().misses), (0, 2));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn seeded_results_do_not_depend_on_thread_count(
+        seed in any::<u64>(),
+        iterations in 1usize..200,
+        threads in 2usize..8,
+        rng_backend in rng_backend(),
+        account_type in account_type(),
+    ) {
+        let single = run_on_threads(1, config(seed, iterations, rng_backend, account_type));
+        let multi = run_on_threads(threads, config(seed, iterations, rng_backend, account_type));
+        prop_assert_eq!(single, multi);
+    }
+
+    #[test]
+    fn batch_runs_match_single_runs(seed in any::<u64>(), iterations in 1usize..100) {
+        let configs = vec![
+            config(seed, iterations, RngBackend::Std, "ftt:GT"),
+            config(seed.wrapping_add(1), iterations, RngBackend::Small, "topstep:Fifty"),
+        ];
+        let batch = run_simulations(configs.clone()).unwrap();
+        for (config, batch_result) in configs.into_iter().zip(batch) {
+            let single = run_simulation(config).unwrap();
+            prop_assert_eq!(&single.final_balances, &batch_result.final_balances);
+            prop_assert_eq!(serde_json::to_value(&single).unwrap(), serde_json::to_value(&batch_result).unwrap());
+        }
+    }
+
+    #[test]
+    fn iterations_keep_their_stream_when_more_are_added(seed in any::<u64>(), iterations in 1usize..100) {
+        let fewer = run_simulation(config(seed, iterations, RngBackend::Std, "ftt:GT")).unwrap();
+        let more = run_simulation(config(seed, iterations + 50, RngBackend::Std, "ftt:GT")).unwrap();
+        prop_assert_eq!(&fewer.final_balances[..], &more.final_balances[..iterations]);
+    }
+}
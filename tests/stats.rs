@@ -0,0 +1,456 @@
+// tests/stats.rs
+// Estimators of the public stats module, on hand-checked data and edge cases
+use proptest::prelude::*;
+use prop_simulator::prelude::*;
+use prop_simulator::prelude::stats::{self, ConfidenceInterval, Summary, TDigest};
+
+const SAMPLE_TRADES: &str = include_str!("../sample_trades.csv");
+
+#[test]
+fn empty_and_nan_only_data_have_no_statistics() {
+    for data in [&[][..], &[f64::NAN, f64::NAN][..]] {
+        let sorted = stats::sorted(data);
+        assert!(sorted.is_empty());
+        assert_eq!(stats::mean(data), None);
+        assert_eq!(stats::std_dev(data), None);
+        assert_eq!(stats::mean_absolute_deviation(data), None);
+        assert_eq!(stats::median(&sorted), None);
+        assert_eq!(stats::percentile(&sorted, 50.0), None);
+        assert_eq!(stats::iqr(&sorted), None);
+        assert_eq!(stats::median_absolute_deviation(&sorted), None);
+        assert_eq!(Summary::of(data), None);
+        assert_eq!(TDigest::from_values(data).quantile(0.5), None);
+        assert_eq!(stats::bootstrap_ci(data, |d| stats::mean(d).unwrap(), 100, 95.0, 1), None);
+    }
+}
+
+#[test]
+fn single_value_is_every_statistic() {
+    let summary = Summary::of(&[42.0]).unwrap();
+    assert_eq!(
+        summary,
+        Summary { count: 1, mean: 42.0, median: 42.0, std_dev: 0.0, mad: 0.0, iqr: 0.0, mad_median: 0.0 }
+    );
+    for p in [0.0, 1.0, 50.0, 99.0, 100.0] {
+        assert_eq!(stats::percentile(&[42.0], p), Some(42.0));
+    }
+    assert_eq!(TDigest::from_values(&[42.0]).quantile(0.3), Some(42.0));
+}
+
+#[test]
+fn nan_values_are_left_out() {
+    let data = [3.0, f64::NAN, 1.0, 2.0];
+    assert_eq!(stats::sorted(&data), vec![1.0, 2.0, 3.0]);
+    assert_eq!(stats::mean(&data), Some(2.0));
+    assert_eq!(Summary::of(&data), Summary::of(&[1.0, 2.0, 3.0]));
+    assert_eq!(TDigest::from_values(&data).count(), 3);
+}
+
+#[test]
+fn summary_of_known_data() {
+    let summary = Summary::of(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap();
+    assert_eq!(summary.count, 8);
+    assert_eq!(summary.mean, 5.0);
+    assert_eq!(summary.median, 4.5);
+    assert_eq!(summary.std_dev, 2.0);
+    assert_eq!(summary.mad, 1.5);
+    // 25th percentile is the third value, 75th the seventh
+    assert_eq!(summary.iqr, 3.0);
+    assert_eq!(summary.mad_median, 0.5);
+
+    let odd = stats::sorted(&[5.0, 1.0, 3.0]);
+    assert_eq!(stats::median(&odd), Some(3.0));
+    assert_eq!(stats::percentile(&odd, 0.0), Some(1.0));
+    assert_eq!(stats::percentile(&odd, 100.0), Some(5.0));
+}
+
+#[test]
+fn t_digest_quantiles_are_close_on_large_data() {
+    let data: Vec<f64> = (0..100_000).map(|i| ((i * 7919) % 100_000) as f64).collect();
+    let digest = TDigest::from_values(&data);
+    assert_eq!(digest.count(), data.len());
+    let sorted = stats::sorted(&data);
+    for q in [0.001, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 0.999] {
+        let exact = stats::percentile(&sorted, q * 100.0).unwrap();
+        let estimate = digest.quantile(q).unwrap();
+        assert!((estimate - exact).abs() <= 0.005 * data.len() as f64, "q {}: {} vs {}", q, estimate, exact);
+    }
+    assert_eq!(digest.quantile(0.0), Some(0.0));
+    assert_eq!(digest.quantile(1.0), Some(99_999.0));
+}
+
+#[test]
+fn merged_t_digests_match_one_digest_of_all_data() {
+    let data: Vec<f64> = (0..20_000).map(|i| (i as f64 * 0.37).sin() * 1000.0).collect();
+    let mut merged = TDigest::default();
+    for chunk in data.chunks(3_000) {
+        merged.merge(&TDigest::from_values(chunk));
+    }
+    let whole = TDigest::from_values(&data);
+    assert_eq!(merged.count(), whole.count());
+    for q in [0.05, 0.5, 0.95] {
+        assert!((merged.quantile(q).unwrap() - whole.quantile(q).unwrap()).abs() < 20.0);
+    }
+}
+
+#[test]
+fn bootstrap_interval_covers_the_estimate_and_is_reproducible() {
+    let data: Vec<f64> = (1..=200).map(|i| i as f64).collect();
+    let mean = |d: &[f64]| stats::mean(d).unwrap();
+    let ci: ConfidenceInterval = stats::bootstrap_ci(&data, mean, 2_000, 95.0, 7).unwrap();
+    assert_eq!(ci.estimate, 100.5);
+    assert!(ci.lower < ci.estimate && ci.estimate < ci.upper);
+    // Standard error of the mean is about 4.1, so the interval spans about 16
+    assert!(ci.upper - ci.lower > 10.0 && ci.upper - ci.lower < 22.0, "{:?}", ci);
+    assert_eq!(stats::bootstrap_ci(&data, mean, 2_000, 95.0, 7), Some(ci));
+}
+
+#[test]
+fn exported_iterations_reproduce_the_reported_statistics() {
+    let config = SimulationConfig::builder()
+        .csv_data(SAMPLE_TRADES.to_string())
+        .multiplier(20.0)
+        .iterations(500)
+        .max_simulation_days(60)
+        .account_type("ftt:GT")
+        .output_file(String::from("iterations.csv"))
+        .seed(3)
+        .build()
+        .unwrap();
+    let result = run_simulation(config).unwrap();
+    let balances: Vec<f64> = result.iterations.iter().map(|iteration| iteration.final_balance).collect();
+    let summary = Summary::of(&balances).unwrap();
+    assert_eq!(summary.mean, result.mean_balance);
+    assert_eq!(summary.median, result.median_balance);
+    assert_eq!(summary.std_dev, result.std_dev);
+    assert_eq!(summary.mad, result.mad);
+    assert_eq!(summary.iqr, result.iqr);
+    assert_eq!(summary.mad_median, result.mad_median);
+}
+
+#[test]
+fn time_to_money_covers_the_runs_that_got_there() {
+    let config = SimulationConfig::builder()
+        .csv_data(SAMPLE_TRADES.to_string())
+        .multiplier(2.0)
+        .iterations(500)
+        .max_simulation_days(400)
+        .account_type("mff:Starter50K")
+        .output_file(String::from("iterations.csv"))
+        .seed(1)
+        .build()
+        .unwrap();
+    let result = run_simulation(config).unwrap();
+    for iteration in &result.iterations {
+        if let Some(payout_day) = iteration.first_payout_day {
+            assert!(iteration.funded_day.is_some_and(|funded_day| funded_day <= payout_day));
+        }
+    }
+    let funded: Vec<f64> = result.iterations.iter().filter_map(|iteration| iteration.funded_day).map(|day| day as f64).collect();
+    let stats = result.time_to_money.funded.unwrap();
+    assert_eq!(stats.runs, funded.len());
+    assert_eq!(stats.median, stats::median(&stats::sorted(&funded)).unwrap());
+    assert!(stats.p10 <= stats.median && stats.median <= stats.p90);
+    assert!(stats.histogram.is_none());
+    assert!(result.time_to_money.first_payout.is_some_and(|payout| payout.runs <= stats.runs));
+}
+
+#[test]
+fn delayed_payouts_arrive_later_but_in_full() {
+    let run = |delay: Option<u64>| {
+        let mut config = SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .multiplier(2.0)
+            .iterations(300)
+            .max_simulation_days(400)
+            .account_type("topstep:Fifty")
+            .seed(2)
+            .build()
+            .unwrap();
+        config.payout_delay_days = delay;
+        run_simulation(config).unwrap()
+    };
+    let (instant, delayed) = (run(None), run(Some(10)));
+    assert_eq!(delayed.mean_balance, instant.mean_balance);
+    assert_eq!(delayed.ev_decomposition.gross_payouts, instant.ev_decomposition.gross_payouts);
+    let first_payout = |result: &SimulationResult| result.time_to_money.first_payout.as_ref().unwrap().mean;
+    assert!((first_payout(&delayed) - first_payout(&instant) - 10.0).abs() < 1e-9);
+}
+
+#[test]
+fn denied_payouts_stay_on_the_account() {
+    let run = |probability: Option<f64>| {
+        let mut config = SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .multiplier(2.0)
+            .iterations(300)
+            .max_simulation_days(400)
+            .account_type("topstep:Fifty")
+            .seed(2)
+            .build()
+            .unwrap();
+        config.payout_denial_probability = probability;
+        run_simulation(config).unwrap()
+    };
+    let (paid, never_denied, always_denied) = (run(None), run(Some(0.0)), run(Some(1.0)));
+    assert_eq!(never_denied.mean_balance, paid.mean_balance);
+    assert_eq!(never_denied.rule_triggers.payout_denials, 0.0);
+    assert_eq!(always_denied.ev_decomposition.gross_payouts, 0.0);
+    assert!(always_denied.time_to_money.first_payout.is_none());
+    assert!(always_denied.rule_triggers.payout_denials > 0.0);
+}
+
+#[test]
+fn copied_accounts_move_in_lockstep() {
+    let run = |accounts: u32| {
+        let config = SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .multiplier(2.0)
+            .iterations(200)
+            .max_simulation_days(300)
+            .account_type("topstep:Fifty")
+            .copied_accounts(accounts)
+            .seed(4)
+            .build()
+            .unwrap();
+        run_simulation(config).unwrap().copy_trading.unwrap()
+    };
+    let (single, copies) = (run(1), run(3));
+    assert_eq!(copies.accounts, 3);
+    assert!((copies.mean_combined_balance - 3.0 * single.mean_combined_balance).abs() < 1e-6);
+    assert!((copies.mean_busted_accounts - 3.0 * single.mean_busted_accounts).abs() < 1e-9);
+    assert_eq!(copies.all_busted_percentage, single.all_busted_percentage);
+    assert!(copies.mean_lowest_combined_balance <= 0.0);
+}
+
+#[test]
+fn independent_copies_diversify() {
+    let run = |correlation: f64| {
+        let config = SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .multiplier(2.0)
+            .iterations(300)
+            .max_simulation_days(300)
+            .account_type("topstep:Fifty")
+            .copied_accounts(4)
+            .copy_correlation(correlation)
+            .seed(4)
+            .build()
+            .unwrap();
+        run_simulation(config).unwrap().copy_trading.unwrap()
+    };
+    let (lockstep, independent) = (run(1.0), run(0.0));
+    assert!((lockstep.combined_balance_std_dev - 4.0 * lockstep.account_balance_std_dev).abs() < 1e-6);
+    assert!(independent.combined_balance_std_dev < 3.0 * independent.account_balance_std_dev);
+    assert!(independent.p10_combined_balance <= independent.median_combined_balance);
+    assert!(independent.median_combined_balance <= independent.p90_combined_balance);
+}
+
+#[test]
+fn streaming_stats_match_the_kept_runs() {
+    let config = || {
+        SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .multiplier(2.0)
+            .iterations(2_000)
+            .max_simulation_days(200)
+            .account_type("mff:Starter50K")
+            .seed(8)
+    };
+    let kept = run_simulation(config().build().unwrap()).unwrap();
+    let streamed = run_simulation(config().streaming_stats(true).build().unwrap()).unwrap();
+    assert!(streamed.final_balances.is_empty());
+    assert!((streamed.mean_balance - kept.mean_balance).abs() < 1e-6);
+    assert!((streamed.std_dev - kept.std_dev).abs() < 1e-6);
+    assert!((streamed.ev_decomposition.gross_payouts - kept.ev_decomposition.gross_payouts).abs() < 1e-6);
+    assert_eq!(streamed.end_state_percentages, kept.end_state_percentages);
+    assert_eq!(streamed.positive_balance_percentage, kept.positive_balance_percentage);
+    assert!((streamed.rule_triggers.max_drawdown - kept.rule_triggers.max_drawdown).abs() < 1e-9);
+    assert!(streamed.tail_risk.value_at_risk.is_finite());
+    assert_eq!(streamed.period_pnl.weekly.periods, kept.period_pnl.weekly.periods);
+    assert!((streamed.period_pnl.monthly.mean - kept.period_pnl.monthly.mean).abs() < 1e-6);
+    assert_eq!(streamed.period_pnl.weekly.worst, kept.period_pnl.weekly.worst);
+    assert_eq!(streamed.worst_outcomes.worst_day.worst, kept.worst_outcomes.worst_day.worst);
+    assert!((streamed.worst_outcomes.worst_trade.mean - kept.worst_outcomes.worst_trade.mean).abs() < 1e-6);
+    let (streamed_funded, kept_funded) = (streamed.time_to_money.funded.unwrap(), kept.time_to_money.funded.unwrap());
+    assert_eq!(streamed_funded.runs, kept_funded.runs);
+    assert!((streamed_funded.mean - kept_funded.mean).abs() < 1e-6);
+    let (streamed_income, kept_income) = (streamed.stationary_income.unwrap(), kept.stationary_income.unwrap());
+    assert!((streamed_income.mean_monthly_income - kept_income.mean_monthly_income).abs() < 1e-6);
+    assert!((streamed_income.monthly_std_dev - kept_income.monthly_std_dev).abs() < 1e-6 * kept_income.monthly_std_dev);
+    // An account whose daily loss limit is a soft breach
+    let soft = || config().account_type("tpt:Fifty").multiplier(20.0).iterations(500);
+    let kept_soft = run_simulation(soft().build().unwrap()).unwrap().soft_breaches.unwrap();
+    let streamed_soft = run_simulation(soft().streaming_stats(true).build().unwrap()).unwrap().soft_breaches.unwrap();
+    assert_eq!(streamed_soft.max_per_run, kept_soft.max_per_run);
+    assert!((streamed_soft.mean_per_run - kept_soft.mean_per_run).abs() < 1e-9);
+    assert!((streamed_soft.breached_percentage - kept_soft.breached_percentage).abs() < 1e-9);
+    let (streamed_buffer, kept_buffer) = (streamed.drawdown_buffer.unwrap(), kept.drawdown_buffer.unwrap());
+    assert_eq!(streamed_buffer.surviving_runs, kept_buffer.surviving_runs);
+    assert!((streamed_buffer.mean_min_buffer - kept_buffer.mean_min_buffer).abs() < 1e-6);
+
+    let mut moments = stats::RunningMoments::default();
+    let mut other = stats::RunningMoments::default();
+    kept.final_balances[..500].iter().for_each(|&balance| moments.add(balance));
+    kept.final_balances[500..].iter().for_each(|&balance| other.add(balance));
+    moments.merge(&other);
+    assert!((moments.mean().unwrap() - kept.mean_balance).abs() < 1e-6);
+    assert!((moments.std_dev().unwrap() - kept.std_dev).abs() < 1e-6);
+
+    for with_runs in [
+        config().equity_curve(true),
+        config().monthly_income_target(1_000.0),
+        config().continue_after_max_payouts(true),
+        config().copied_accounts(2),
+    ] {
+        let error = run_simulation(with_runs.streaming_stats(true).build().unwrap()).unwrap_err();
+        assert!(matches!(error.downcast_ref::<SimulationError>(), Some(SimulationError::InvalidConfig(_))), "{}", error);
+    }
+}
+
+#[test]
+fn convergence_stops_once_the_standard_error_is_within_tolerance() {
+    let config = |iterations: usize| {
+        SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .multiplier(2.0)
+            .iterations(iterations)
+            .max_simulation_days(200)
+            .account_type("mff:Starter50K")
+            .seed(8)
+    };
+    let loose = Convergence { tolerance: 1e9, metric: ConvergenceMetric::MeanBalance };
+    let converged = run_simulation(config(10_000).convergence(loose).copied_accounts(2).build().unwrap()).unwrap();
+    let report = converged.convergence.clone().unwrap();
+    assert!(report.converged);
+    assert_eq!(report.iterations, CONVERGENCE_BATCH);
+    // The same runs as a fixed number of iterations
+    let fixed = run_simulation(config(CONVERGENCE_BATCH).copied_accounts(2).build().unwrap()).unwrap();
+    assert!(fixed.convergence.is_none());
+    assert_eq!(converged.final_balances, fixed.final_balances);
+    // Side analyses stop with the main runs
+    let (converged_copies, fixed_copies) = (converged.copy_trading.as_ref().unwrap(), fixed.copy_trading.as_ref().unwrap());
+    assert_eq!(converged_copies.median_combined_balance, fixed_copies.median_combined_balance);
+    assert_eq!(converged_copies.combined_balance_std_dev, fixed_copies.combined_balance_std_dev);
+    assert_eq!(converged.end_state_percentages, fixed.end_state_percentages);
+    let standard_error = fixed.std_dev / (CONVERGENCE_BATCH as f64).sqrt();
+    assert!((report.standard_error - standard_error).abs() < 1e-6);
+
+    // Out of iterations before the tolerance is met
+    let tight = Convergence { tolerance: 1e-9, metric: ConvergenceMetric::BustPercentage };
+    let capped = run_simulation(config(1_500).convergence(tight).build().unwrap()).unwrap();
+    let report = capped.convergence.unwrap();
+    assert!(!report.converged);
+    assert_eq!(report.iterations, 1_500);
+    let busted = capped.end_state_percentages.get(&EndOfGame::Busted).copied().unwrap_or(0.0) / 100.0;
+    assert!((report.standard_error - (busted * (1.0 - busted) / 1_500.0).sqrt() * 100.0).abs() < 1e-9);
+
+    let streamed = run_simulation(config(10_000).convergence(loose).streaming_stats(true).build().unwrap()).unwrap();
+    assert_eq!(streamed.convergence.unwrap().iterations, CONVERGENCE_BATCH);
+    assert!((streamed.mean_balance - fixed.mean_balance).abs() < 1e-6);
+
+    let invalid = config(1_000).convergence(Convergence { tolerance: 0.0, metric: ConvergenceMetric::MeanBalance });
+    assert!(run_simulation(invalid.build().unwrap()).is_err());
+}
+
+#[test]
+fn invalid_side_analyses_fail_before_the_runs() {
+    // Far too many iterations to finish, were they run before the check
+    let config = || {
+        SimulationConfig::builder()
+            .csv_data(SAMPLE_TRADES.to_string())
+            .iterations(100_000_000)
+            .account_type("mff:Starter50K")
+    };
+    for invalid in [
+        config().cohort_interval_days(0),
+        config().parallel_evals(0),
+        config().hedge_account_type("mff:Starter50K".to_string()).hedge_correlation(1.5),
+        config().hedge_account_type("mff:NoSuchAccount".to_string()),
+        config().copied_accounts(0),
+        config().copied_accounts(2).copy_correlation(-0.5),
+    ] {
+        let error = run_simulation(invalid.build().unwrap()).unwrap_err();
+        assert!(matches!(error.downcast_ref::<SimulationError>(), Some(SimulationError::InvalidConfig(_))), "{}", error);
+    }
+}
+
+#[test]
+fn histograms_of_degenerate_data_are_well_defined() {
+    assert!(compute_histogram(&[f64::NAN, f64::INFINITY], 10).counts.is_empty());
+    let identical = compute_histogram(&[7.0, 7.0, f64::NAN, 7.0], 10);
+    assert_eq!(identical.counts, vec![3]);
+    assert!(identical.edges[0] < 7.0 && identical.edges[1] > 7.0);
+    // Too large for a unit-wide bin to be a range
+    let huge = compute_histogram(&[1e300, 1e300], 10);
+    assert!(huge.edges[0] < huge.edges[1]);
+
+    let dir = std::env::temp_dir();
+    for (name, data) in [("empty", &[][..]), ("identical", &[1e300, 1e300][..])] {
+        let file = dir.join(format!("prop_simulator_{}_histogram.png", name));
+        plot_histogram(data, file.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+#[test]
+fn degenerate_runs_give_results_or_typed_errors() {
+    let config = || SimulationConfig::builder().csv_data(SAMPLE_TRADES.to_string()).account_type("ftt:GT").seed(5);
+
+    let single = run_simulation(config().iterations(1).build().unwrap()).unwrap();
+    assert_eq!(single.std_dev, 0.0);
+    assert_eq!(single.mean_balance, single.median_balance);
+
+    // Configs deserialized from JSON or TOML skip the checks of `build`
+    let none = run_simulation(SimulationConfig { iterations: 0, ..config().build().unwrap() }).unwrap_err();
+    assert!(matches!(none.downcast_ref::<SimulationError>(), Some(SimulationError::InvalidConfig(_))), "{}", none);
+
+    let nan_trades = String::from("DateTime,Return,Max Opposite Excursion\n20240912 19:20:00,NaN,-9\n");
+    let poisoned = SimulationConfig::builder().csv_data(nan_trades).iterations(10).build().unwrap();
+    let error = run_simulation(poisoned).unwrap_err();
+    assert!(matches!(error.downcast_ref::<SimulationError>(), Some(SimulationError::Data(_))), "{}", error);
+}
+
+proptest! {
+    #[test]
+    fn statistics_do_not_depend_on_the_order_of_the_data(mut data in prop::collection::vec(-1e6f64..1e6, 1..200)) {
+        let summary = Summary::of(&data);
+        data.reverse();
+        prop_assert_eq!(Summary::of(&data), summary);
+    }
+
+    #[test]
+    fn percentiles_stay_within_the_data(data in prop::collection::vec(-1e6f64..1e6, 1..200), p in 0.0f64..=100.0) {
+        let sorted = stats::sorted(&data);
+        let value = stats::percentile(&sorted, p).unwrap();
+        prop_assert!(sorted[0] <= value && value <= sorted[sorted.len() - 1]);
+        let estimate = TDigest::from_values(&data).quantile(p / 100.0).unwrap();
+        prop_assert!(sorted[0] <= estimate && estimate <= sorted[sorted.len() - 1]);
+    }
+}
+
+#[test]
+fn trade_blocks_carry_over_to_the_next_day() {
+    // One trade a day, alternating a win and a loss of the same size
+    let csv: String = std::iter::once(String::from("DateTime, Return, Max Opposite Excursion\n"))
+        .chain((0..200).map(|day| {
+            let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Days::new(day);
+            let return_value = if day % 2 == 0 { 100.0 } else { -100.0 };
+            format!("{} 10:00:00,{},-100\n", date.format("%Y%m%d"), return_value)
+        }))
+        .collect();
+    let config = SimulationConfig::builder()
+        .csv_data(csv)
+        .multiplier(1.0)
+        .iterations(200)
+        .max_simulation_days(60)
+        .account_type("mff:Starter50K")
+        .trade_sampling(TradeSampling::Block)
+        .block_length(1_000)
+        .seed(3)
+        .build()
+        .unwrap();
+    // A block runs on through the days, so every two days add up to nothing
+    let weekly = run_simulation(config).unwrap().period_pnl.weekly;
+    assert!(weekly.worst >= -100.0 - 1e-9 && weekly.best <= 100.0 + 1e-9, "{:?}", weekly);
+}